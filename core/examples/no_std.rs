@@ -0,0 +1,32 @@
+//! A `no_std` library that hashes points on the hilbert curve, to check that
+//! coupe-core builds without the standard library, for example on a bare
+//! metal target:
+//!
+//!     cargo build -p coupe-core --examples --target thumbv7em-none-eabihf
+
+#![no_std]
+
+use coupe_core::geometry::BoundingBox;
+use coupe_core::geometry::Point2D;
+
+/// The hilbert index of a point of the unit square, on a curve of order 16.
+pub fn hash(x: f32, y: f32) -> u64 {
+    const ORDER: usize = 16;
+    let cells = (1 << ORDER) as f32;
+    let cell = |c: f32| (c * cells).clamp(0.0, cells - 1.0) as u64;
+    coupe_core::hilbert::encode_2d(cell(x), cell(y), ORDER)
+}
+
+/// The hilbert indices of the given points, on a curve that spans their
+/// bounding box.
+pub fn hash_all(points: &[Point2D], indices: &mut [u64]) {
+    let bb = match BoundingBox::from_points(points.iter().cloned()) {
+        Some(bb) => bb,
+        None => return,
+    };
+    let extents = bb.extents();
+    for (point, index) in points.iter().zip(indices) {
+        let relative = (point - bb.p_min).component_div(&extents);
+        *index = hash(relative.x as f32, relative.y as f32);
+    }
+}
@@ -0,0 +1,184 @@
+//! Points and axis-aligned bounding boxes.
+//!
+//! Points are nalgebra vectors, so that coupe and its users can share them.
+//! nalgebra is used without its `std` feature, and its `libm` feature
+//! provides the floating-point functions that `core` lacks.
+
+use nalgebra::SVector;
+
+// With the `serde` feature of nalgebra, points implement `Serialize` and
+// `Deserialize` as flat arrays of coordinates.
+pub type Point2D = SVector<f64, 2>;
+pub type Point3D = SVector<f64, 3>;
+pub type PointND<const D: usize> = SVector<f64, D>;
+
+/// Axis-aligned bounding box.
+#[derive(Debug, Clone)]
+pub struct BoundingBox<const D: usize> {
+    pub p_min: PointND<D>,
+    pub p_max: PointND<D>,
+}
+
+impl<const D: usize> BoundingBox<D> {
+    /// The axis-aligned *minimum* bounding box.
+    ///
+    /// This is the smallest rectangle (rectangular cuboid in 3D) that both
+    /// contains all given points and is aligned with the axises.
+    ///
+    /// Returns `None` iff the given iterator is empty.
+    pub fn from_points<P>(points: P) -> Option<Self>
+    where
+        P: IntoIterator<Item = PointND<D>>,
+    {
+        let mut points = points.into_iter().peekable();
+        points.peek()?;
+        let (p_min, p_max) = points.fold(
+            (
+                PointND::<D>::from_element(f64::MAX),
+                PointND::<D>::from_element(f64::MIN),
+            ),
+            |(mut mins, mut maxs), vals| {
+                for ((min, max), val) in mins.iter_mut().zip(maxs.iter_mut()).zip(&vals) {
+                    if *val < *min {
+                        *min = *val;
+                    }
+                    if *max < *val {
+                        *max = *val;
+                    }
+                }
+                (mins, maxs)
+            },
+        );
+        Some(Self { p_min, p_max })
+    }
+
+    pub fn center(&self) -> PointND<D> {
+        (self.p_min + self.p_max) / 2.0
+    }
+
+    /// The length of the box along each axis.
+    pub fn extents(&self) -> PointND<D> {
+        self.p_max - self.p_min
+    }
+
+    /// The volume of the box (its area in 2D).
+    pub fn volume(&self) -> f64 {
+        self.extents().iter().product()
+    }
+
+    /// The total measure of the faces of the box (its perimeter in 2D, its
+    /// surface area in 3D).
+    pub fn surface_area(&self) -> f64 {
+        let extents = self.extents();
+        // Each axis is orthogonal to two opposite faces, whose measure is the
+        // product of the other extents.
+        2.0 * (0..D)
+            .map(|axis| {
+                extents
+                    .iter()
+                    .enumerate()
+                    .filter(|(other_axis, _)| *other_axis != axis)
+                    .map(|(_, extent)| extent)
+                    .product::<f64>()
+            })
+            .sum::<f64>()
+    }
+
+    // region = bdim...b2b1b0 where bi are bits (0 or 1)
+    // if bi is set (i.e. bi == 1) then the matching region has a i-th coordinates from center[i] to p_max[i]
+    // otherwise, the matching region has a i-th coordinates from p_min[i] to center[i]
+    pub fn sub_aabb(&self, region: u32) -> Self {
+        assert!(
+            region < 2u32.pow(D as u32),
+            "Wrong region. Region should be composed of dim bits."
+        );
+
+        let center = self.center();
+
+        let p_min =
+            PointND::<D>::from_iterator(self.p_min.iter().zip(center.iter()).enumerate().map(
+                |(i, (min, center))| {
+                    if (region >> i) & 1 == 0 {
+                        *min
+                    } else {
+                        *center
+                    }
+                },
+            ));
+
+        let p_max =
+            PointND::<D>::from_iterator(center.iter().zip(self.p_max.iter()).enumerate().map(
+                |(i, (center, max))| {
+                    if (region >> i) & 1 == 0 {
+                        *center
+                    } else {
+                        *max
+                    }
+                },
+            ));
+
+        Self { p_min, p_max }
+    }
+
+    pub fn contains(&self, point: &PointND<D>) -> bool {
+        let eps = 10. * f64::EPSILON;
+        self.p_min
+            .iter()
+            .zip(self.p_max.iter())
+            .zip(point.iter())
+            .all(|((min, max), point)| *point < *max + eps && *point > *min - eps)
+    }
+
+    pub fn distance_to_point(&self, point: &PointND<D>) -> f64 {
+        if !self.contains(point) {
+            let clamped = PointND::<D>::from_iterator(
+                self.p_min
+                    .iter()
+                    .zip(self.p_max.iter())
+                    .zip(point.iter())
+                    .map(|((min, max), point)| {
+                        if point > max {
+                            *max
+                        } else if point < min {
+                            *min
+                        } else {
+                            *point
+                        }
+                    }),
+            );
+            clamped.norm()
+        } else {
+            let center = self.center();
+
+            self.p_min
+                .iter()
+                .zip(self.p_max.iter())
+                .zip(point.iter())
+                .zip(&center)
+                .map(|(((min, max), point), center)| {
+                    if point > center {
+                        (max - point).abs()
+                    } else {
+                        (min - point).abs()
+                    }
+                })
+                .max_by(|a, b| a.partial_cmp(b).unwrap())
+                .unwrap()
+        }
+    }
+
+    pub fn region(&self, point: &PointND<D>) -> Option<u32> {
+        if !self.contains(point) {
+            return None;
+        }
+
+        let mut ret: u32 = 0;
+        let center = self.center();
+        for (i, (point, center)) in point.iter().zip(&center).enumerate() {
+            if point > center {
+                ret |= 1 << i;
+            }
+        }
+        Some(ret)
+    }
+}
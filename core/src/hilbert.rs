@@ -0,0 +1,438 @@
+//! Encoding of grid cells on the Hilbert space filling curve.
+//!
+//! How cells are mapped to indices follows "Encoding and Decoding the Hilbert
+//! Order" by Xian Liu and Günther Schrack.  The complexity of encoding a cell
+//! is `O(order)`.
+
+// BASE_PATTERN[i][j] is the hilbert index given:
+// - i: the current configuration,
+// - j: the quadrant in row-major order.
+const BASE_PATTERN: [[u64; 4]; 4] = [
+    [0, 1, 3, 2], // config 0 = [1,2]   )
+    [0, 3, 1, 2], // config 1 = [2,1]   n
+    [2, 3, 1, 0], // config 2 = [-1,-2] (
+    [2, 1, 3, 0], // config 3 = [-2,-1] U
+];
+
+// CONFIGURATION[i][j] is the next configuration given:
+// - i: the current configuration,
+// - j: the quadrant in row-major order.
+const CONFIGURATION: [[usize; 4]; 4] = [
+    [1, 0, 3, 0], // ) => U)
+    //                    n)
+    [0, 2, 1, 1], // n => nn
+    //                    )(
+    [2, 1, 2, 3], // ( => (U
+    //                    (n
+    [3, 3, 0, 2], // U => )(
+                  //      UU
+];
+
+/// Slower version of [encode_2d], to build the lookup table for [encode_2d].
+///
+/// This version takes the initial configuration as argument and also returns
+/// the final configuration.
+///
+/// Taken from Marot, Célestin. "Parallel tetrahedral mesh generation." Prom:
+/// Remacle, Jean-François <http://hdl.handle.net/2078.1/240626>.
+///
+/// TODO: once const-fn are more mature, take the "order" argument into account,
+/// though it is only set to 6 for the purpose of building the lookup table.
+const fn encode_2d_slow(zorder: u64, _order: usize, mut config: usize) -> (u64, usize) {
+    let mut hilbert = 0;
+
+    // TODO replace unrolled loop by "for i in (0..order).rev()" once for loops
+    // are allowed in const fns.
+    let mut i = 5;
+    let quadrant = (zorder >> (2 * i)) as usize & 3;
+    hilbert = (hilbert << 2) | BASE_PATTERN[config][quadrant];
+    config = CONFIGURATION[config][quadrant];
+    i -= 1;
+    let quadrant = (zorder >> (2 * i)) as usize & 3;
+    hilbert = (hilbert << 2) | BASE_PATTERN[config][quadrant];
+    config = CONFIGURATION[config][quadrant];
+    i -= 1;
+    let quadrant = (zorder >> (2 * i)) as usize & 3;
+    hilbert = (hilbert << 2) | BASE_PATTERN[config][quadrant];
+    config = CONFIGURATION[config][quadrant];
+    i -= 1;
+    let quadrant = (zorder >> (2 * i)) as usize & 3;
+    hilbert = (hilbert << 2) | BASE_PATTERN[config][quadrant];
+    config = CONFIGURATION[config][quadrant];
+    i -= 1;
+    let quadrant = (zorder >> (2 * i)) as usize & 3;
+    hilbert = (hilbert << 2) | BASE_PATTERN[config][quadrant];
+    config = CONFIGURATION[config][quadrant];
+    i -= 1;
+    let quadrant = (zorder >> (2 * i)) as usize & 3;
+    hilbert = (hilbert << 2) | BASE_PATTERN[config][quadrant];
+    config = CONFIGURATION[config][quadrant];
+
+    (hilbert, config)
+}
+
+/// Interleaves the 32 least significant bits of `x` and `y`, the bit of `x`
+/// being the most significant of each pair.
+///
+/// This is a single `pdep` instruction on targets that have BMI2, for example
+/// with `-C target-cpu=native` on recent x86_64 CPUs.
+fn interleave_2d(x: u64, y: u64) -> u64 {
+    #[cfg(all(target_arch = "x86_64", target_feature = "bmi2"))]
+    // SAFETY: the target has BMI2.
+    unsafe {
+        core::arch::x86_64::_pdep_u64(x, 0x5555_5555_5555_5555 << 1)
+            | core::arch::x86_64::_pdep_u64(y, 0x5555_5555_5555_5555)
+    }
+
+    #[cfg(not(all(target_arch = "x86_64", target_feature = "bmi2")))]
+    {
+        (spread_2d(x) << 1) | spread_2d(y)
+    }
+}
+
+/// Moves the `i`-th of the 32 least significant bits of `x` to bit `2i`.
+#[cfg(not(all(target_arch = "x86_64", target_feature = "bmi2")))]
+fn spread_2d(x: u64) -> u64 {
+    let x = x & 0x0000_0000_ffff_ffff;
+    let x = (x | x << 16) & 0x0000_ffff_0000_ffff;
+    let x = (x | x << 8) & 0x00ff_00ff_00ff_00ff;
+    let x = (x | x << 4) & 0x0f0f_0f0f_0f0f_0f0f;
+    let x = (x | x << 2) & 0x3333_3333_3333_3333;
+    (x | x << 1) & 0x5555_5555_5555_5555
+}
+
+/// Interleaves the bits of `x`, `y` and `z`, the bit of `x` being the most
+/// significant of each triple.  `z` gives 22 bits, the other ones 21 bits.
+///
+/// Like [interleave_2d], this uses `pdep` on targets that have BMI2.
+fn interleave_3d(x: u64, y: u64, z: u64) -> u64 {
+    #[cfg(all(target_arch = "x86_64", target_feature = "bmi2"))]
+    // SAFETY: the target has BMI2.
+    unsafe {
+        core::arch::x86_64::_pdep_u64(x, 0x9249_2492_4924_9249 << 2)
+            | core::arch::x86_64::_pdep_u64(y, 0x9249_2492_4924_9249 << 1)
+            | core::arch::x86_64::_pdep_u64(z, 0x9249_2492_4924_9249)
+    }
+
+    #[cfg(not(all(target_arch = "x86_64", target_feature = "bmi2")))]
+    {
+        (spread_3d(x) << 2) | (spread_3d(y) << 1) | spread_3d(z)
+    }
+}
+
+/// Moves the `i`-th of the 22 least significant bits of `x` to bit `3i`.
+#[cfg(not(all(target_arch = "x86_64", target_feature = "bmi2")))]
+fn spread_3d(x: u64) -> u64 {
+    let top = (x >> 21 & 1) << 63;
+    let x = x & 0x1f_ffff;
+    let x = (x | x << 32) & 0x001f_0000_0000_ffff;
+    let x = (x | x << 16) & 0x001f_0000_ff00_00ff;
+    let x = (x | x << 8) & 0x100f_00f0_0f00_f00f;
+    let x = (x | x << 4) & 0x10c3_0c30_c30c_30c3;
+    let x = (x | x << 2) & 0x1249_2492_4924_9249;
+    top | x
+}
+
+/// The index of the cell `(x, y)` on the hilbert curve of the given order,
+/// which covers a `2^order * 2^order` grid.
+///
+/// In debug builds, panics if a coordinate is greater or equal to
+/// `2^order`.
+///
+/// # Example
+///
+/// ```rust
+/// use coupe_core::hilbert::encode_2d;
+///
+/// assert_eq!(encode_2d(0, 0, 1), 0);
+/// assert_eq!(encode_2d(0, 1, 1), 1);
+/// assert_eq!(encode_2d(1, 1, 1), 2);
+/// assert_eq!(encode_2d(1, 0, 1), 3);
+/// ```
+pub fn encode_2d(x: u64, y: u64, order: usize) -> u64 {
+    debug_assert!(order < 64);
+    debug_assert!(
+        x < (1 << order),
+        "Cannot encode the point {:?} on an hilbert curve of order {} because x >= 2^order.",
+        (x, y),
+        order,
+    );
+    debug_assert!(
+        y < (1 << order),
+        "Cannot encode the point {:?} on an hilbert curve of order {} because y >= 2^order.",
+        (x, y),
+        order,
+    );
+
+    static LUT: [u16; 16_384] = {
+        let mut lut = [0; 16_384];
+        let mut i: usize = 0;
+        while i < 16_384 {
+            let zorder = (i & 0xfff) as u64;
+            let config = i >> 12;
+            let (hilbert_order, config) = encode_2d_slow(zorder, 6, config);
+            lut[i] = (config << 12) as u16 | hilbert_order as u16;
+            i += 1;
+        }
+        lut
+    };
+
+    let zorder = interleave_2d(x, y);
+
+    // The index is computed 12 bits at a time, so up to 11 bits of padding are
+    // added to the 2*order bits of the result.  Use 128 bits so that they do
+    // not push the most significant bits out when order > 30.
+    let mut config: u16 = 0;
+    let mut hilbert: u128 = 0;
+    let mut shift: i64 = 2 * order as i64 - 12;
+    while shift > 0 {
+        config = LUT[((config & !0xfff) | ((zorder >> shift) & 0xfff) as u16) as usize];
+        hilbert = (hilbert << 12) | (config & 0xfff) as u128;
+        shift -= 12;
+    }
+
+    config = LUT[((config & !0xfff) | ((zorder << (-shift) as u64) & 0xfff) as u16) as usize];
+    hilbert = (hilbert << 12) | (config & 0xfff) as u128;
+
+    (hilbert >> -shift) as u64
+}
+
+/// The inverse of [encode_2d]: the cell `(x, y)` of the given hilbert index.
+///
+/// The index is read two bits at a time, from the most significant ones.  Each
+/// pair of bits selects a quadrant, which gives one bit of `x` and one bit of
+/// `y`, and the configuration of the curve in this quadrant.
+pub fn decode_2d(hilbert: u64, order: usize) -> (u64, u64) {
+    debug_assert!(order <= 32);
+    debug_assert!(
+        order == 32 || hilbert < (1 << (2 * order)),
+        "Cannot decode the index {} of an hilbert curve of order {} because index >= 4^order.",
+        hilbert,
+        order,
+    );
+
+    let mut config = 0;
+    let mut x = 0;
+    let mut y = 0;
+    for i in (0..order).rev() {
+        let digit = (hilbert >> (2 * i)) & 3;
+        let quadrant = BASE_PATTERN[config]
+            .iter()
+            .position(|d| *d == digit)
+            .unwrap();
+        x = (x << 1) | (quadrant >> 1) as u64;
+        y = (y << 1) | (quadrant & 1) as u64;
+        config = CONFIGURATION[config][quadrant];
+    }
+
+    (x, y)
+}
+
+/// The index of the cell `(x, y, z)` on the hilbert curve of the given
+/// order, which covers a `2^order * 2^order * 2^order` grid.
+///
+/// In debug builds, panics if a coordinate is greater or equal to
+/// `2^order`.
+pub fn encode_3d(x: u64, y: u64, z: u64, order: usize) -> u64 {
+    debug_assert!(order < 64);
+    debug_assert!(
+        x < (1 << order),
+        "Cannot encode the point {:?} on an hilbert curve of order {} because x >= 2^order.",
+        (x, y, z),
+        order,
+    );
+    debug_assert!(
+        y < (1 << order),
+        "Cannot encode the point {:?} on an hilbert curve of order {} because y >= 2^order.",
+        (x, y, z),
+        order,
+    );
+    debug_assert!(
+        z < (1 << order),
+        "Cannot encode the point {:?} on an hilbert curve of order {} because z >= 2^order.",
+        (x, y, z),
+        order,
+    );
+
+    #[allow(clippy::unusual_byte_groupings)]
+    #[rustfmt::skip]
+    const LUT: [u8; 96] = [
+        0b0110_000, 0b0100_001, 0b0011_011, 0b0100_010, 0b0101_111, 0b1001_110, 0b0011_100, 0b1001_101,
+        0b1000_010, 0b0011_101, 0b0110_011, 0b0110_100, 0b1000_001, 0b0011_110, 0b1001_000, 0b0111_111,
+        0b1001_100, 0b1011_111, 0b1001_011, 0b0011_000, 0b0110_101, 0b0110_110, 0b1010_010, 0b1010_001,
+        0b0010_010, 0b0000_011, 0b0010_001, 0b1010_000, 0b0111_101, 0b0000_100, 0b0111_110, 0b0001_111,
+        0b0000_000, 0b0111_011, 0b1000_111, 0b0111_100, 0b0110_001, 0b0110_010, 0b1010_110, 0b1010_101,
+        0b1010_100, 0b1010_011, 0b0000_101, 0b1011_010, 0b1001_111, 0b0111_000, 0b0000_110, 0b1011_001,
+        0b0100_000, 0b0010_111, 0b0000_001, 0b1011_110, 0b0001_011, 0b0001_100, 0b0000_010, 0b1011_101,
+        0b0101_010, 0b0101_001, 0b0001_101, 0b0001_110, 0b0100_011, 0b1011_000, 0b0100_100, 0b0011_111,
+        0b1011_100, 0b0100_101, 0b1010_111, 0b0100_110, 0b1011_011, 0b1001_010, 0b0001_000, 0b1001_001,
+        0b0101_110, 0b0101_101, 0b0001_001, 0b0001_010, 0b0000_111, 0b0010_100, 0b1000_000, 0b0010_011,
+        0b1000_110, 0b0011_001, 0b0100_111, 0b0010_000, 0b1000_101, 0b0011_010, 0b0101_100, 0b0101_011,
+        0b0010_110, 0b0110_111, 0b0010_101, 0b1000_100, 0b0111_001, 0b0101_000, 0b0111_010, 0b1000_011,
+    ];
+
+    let zorder = interleave_3d(x, y, z);
+
+    let mut config = 0;
+    let mut hilbert = 0;
+
+    for i in (0..order).rev() {
+        config = LUT[(config | ((zorder >> (3 * i)) & 7)) as usize] as u64;
+        hilbert = (hilbert << 3) | (config & 7);
+        config &= !7;
+    }
+
+    hilbert
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_encode_2d() {
+        let points = vec![(0, 0), (1, 1), (1, 0), (0, 1)];
+
+        let indices: Vec<_> = points
+            .into_iter()
+            .map(|(x, y)| encode_2d(x, y, 1))
+            .collect();
+        assert_eq!(indices, vec![0, 2, 3, 1]);
+
+        let points = vec![
+            (0, 0),
+            (1, 0),
+            (1, 1),
+            (0, 1),
+            (0, 2),
+            (0, 3),
+            (1, 3),
+            (1, 2),
+            (2, 2),
+            (2, 3),
+            (3, 3),
+            (3, 2),
+            (3, 1),
+            (2, 1),
+            (2, 0),
+            (3, 0),
+        ];
+
+        let expected: Vec<_> = (0..16).collect();
+        let indices: Vec<_> = points
+            .into_iter()
+            .map(|(x, y)| encode_2d(x, y, 2))
+            .collect();
+        assert_eq!(indices, expected);
+    }
+
+    #[test]
+    fn test_encode_2d_injective() {
+        for order in 1..=6 {
+            let side = 1 << order;
+            let mut indices: Vec<u64> = (0..side)
+                .flat_map(|x| (0..side).map(move |y| encode_2d(x, y, order)))
+                .collect();
+            indices.sort_unstable();
+            let expected: Vec<u64> = (0..side * side).collect();
+            assert_eq!(indices, expected, "order {order}");
+        }
+    }
+
+    #[test]
+    fn test_encode_2d_high_order() {
+        // The most significant digits of an index do not depend on the order.
+        let coords = [0, 1, 2, 0x1234_5678, 0x8765_4321, u32::MAX as u64];
+        for x in coords {
+            for y in coords {
+                let expected = encode_2d(x >> 2, y >> 2, 30);
+                assert_eq!(encode_2d(x, y, 32) >> 4, expected, "{:?}", (x, y));
+                assert_eq!(encode_2d(x >> 1, y >> 1, 31) >> 2, expected, "{:?}", (x, y));
+            }
+        }
+        assert_eq!(encode_2d(0, 0, 32), 0);
+        assert_eq!(encode_2d(u32::MAX as u64, 0, 32), u64::MAX);
+    }
+
+    #[test]
+    fn test_decode_2d() {
+        for order in 1..=6 {
+            let side = 1 << order;
+            for hilbert in 0..side * side {
+                let (x, y) = decode_2d(hilbert, order);
+                assert!(x < side && y < side, "order {order}, index {hilbert}");
+                assert_eq!(encode_2d(x, y, order), hilbert, "order {order}");
+            }
+        }
+    }
+
+    /// A bit by bit `pdep`, to check the interleaving functions.
+    fn deposit_bits(mut value: u64, mut mask: u64) -> u64 {
+        let mut result = 0;
+        while mask != 0 {
+            let lowest = mask & mask.wrapping_neg();
+            if value & 1 == 1 {
+                result |= lowest;
+            }
+            value >>= 1;
+            mask &= mask - 1;
+        }
+        result
+    }
+
+    proptest!(
+        #[test]
+        fn test_interleave(x: u64, y: u64, z: u64) {
+            prop_assert_eq!(
+                interleave_2d(x, y),
+                deposit_bits(x, 0x5555_5555_5555_5555 << 1)
+                    | deposit_bits(y, 0x5555_5555_5555_5555)
+            );
+            prop_assert_eq!(
+                interleave_3d(x, y, z),
+                deposit_bits(x, 0x9249_2492_4924_9249 << 2)
+                    | deposit_bits(y, 0x9249_2492_4924_9249 << 1)
+                    | deposit_bits(z, 0x9249_2492_4924_9249)
+            );
+        }
+    );
+
+    proptest!(
+        #[test]
+        fn test_decode_2d_high_order(x in 0..1_u64 << 32, y in 0..1_u64 << 32) {
+            prop_assert_eq!(decode_2d(encode_2d(x, y, 32), 32), (x, y));
+        }
+    );
+
+    #[test]
+    fn test_encode_3d_injective() {
+        for order in 1..=4 {
+            let side = 1 << order;
+            let mut indices: Vec<u64> = (0..side)
+                .flat_map(|x| {
+                    (0..side).flat_map(move |y| (0..side).map(move |z| encode_3d(x, y, z, order)))
+                })
+                .collect();
+            indices.sort_unstable();
+            let expected: Vec<u64> = (0..side * side * side).collect();
+            assert_eq!(indices, expected, "order {order}");
+        }
+    }
+
+    #[test]
+    fn test_encode_2d_slow() {
+        const ORDER: usize = 6; // encode_2d_slow requires ORDER==6.
+        for x in 0..(1 << 6) {
+            for y in 0..(1 << 6) {
+                let fast_encode = encode_2d(x, y, ORDER);
+                let slow_encode = {
+                    let zorder = interleave_2d(x, y);
+                    let config = 0;
+                    encode_2d_slow(zorder, ORDER, config).0
+                };
+                assert_eq!(fast_encode, slow_encode);
+            }
+        }
+    }
+}
@@ -0,0 +1,20 @@
+//! The building blocks of [coupe] that do not need the standard library, such
+//! as points, axis-aligned bounding boxes and the encoding of cells on space
+//! filling curves.
+//!
+//! This crate is `no_std`, so that spatial hashing can be done on devices
+//! that cannot run coupe's parallel algorithms.  Its only dependency is
+//! nalgebra, without its `std` feature.
+//!
+//! [coupe]: https://docs.rs/coupe
+
+#![cfg_attr(not(test), no_std)]
+#![warn(
+    missing_copy_implementations,
+    missing_debug_implementations,
+    rust_2018_idioms
+)]
+
+pub mod geometry;
+pub mod hilbert;
+pub mod peano;
@@ -0,0 +1,119 @@
+//! Encoding of grid cells on the Peano space filling curve.
+//!
+//! Cell indices are computed digit by digit, following Peano's original
+//! definition: the base-3 digits of the index alternate between the digits of
+//! `x` and `y`, each of them being reflected (`d` becomes `2 - d`) when the
+//! sum of the previous digits of the other coordinate is odd.
+
+/// The maximum order, such that `3^(2*order)` fits in a `u64`.
+pub const MAX_ORDER: u32 = 20;
+
+/// The index of the cell `(x, y)` on the peano curve of the given order,
+/// which covers a `3^order * 3^order` grid.
+///
+/// In debug builds, panics if `order` is greater than [`MAX_ORDER`] or if a
+/// coordinate is greater or equal to `3^order`.
+///
+/// # Example
+///
+/// ```rust
+/// use coupe_core::peano::encode_2d;
+///
+/// // The first column is visited upwards, the second one downwards.
+/// assert_eq!(encode_2d(0, 2, 1), 2);
+/// assert_eq!(encode_2d(1, 2, 1), 3);
+/// ```
+pub fn encode_2d(x: u64, y: u64, order: u32) -> u64 {
+    debug_assert!(order <= MAX_ORDER);
+    debug_assert!(
+        x < 3_u64.pow(order),
+        "Cannot encode the point {:?} on a peano curve of order {} because x >= 3^order.",
+        (x, y),
+        order,
+    );
+    debug_assert!(
+        y < 3_u64.pow(order),
+        "Cannot encode the point {:?} on a peano curve of order {} because y >= 3^order.",
+        (x, y),
+        order,
+    );
+
+    let mut peano = 0;
+
+    // Parities of the sums of the digits of x and y seen so far.
+    let mut x_parity = 0;
+    let mut y_parity = 0;
+
+    let mut pow = 3_u64.pow(order) / 3;
+    while pow > 0 {
+        let x_digit = x / pow % 3;
+        let y_digit = y / pow % 3;
+
+        let t_x = if y_parity == 1 { 2 - x_digit } else { x_digit };
+        x_parity ^= x_digit & 1;
+        let t_y = if x_parity == 1 { 2 - y_digit } else { y_digit };
+        y_parity ^= y_digit & 1;
+
+        peano = 9 * peano + 3 * t_x + t_y;
+        pow /= 3;
+    }
+
+    peano
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_2d() {
+        let points = [
+            (0, 0),
+            (0, 1),
+            (0, 2),
+            (1, 2),
+            (1, 1),
+            (1, 0),
+            (2, 0),
+            (2, 1),
+            (2, 2),
+        ];
+
+        let expected: Vec<_> = (0..9).collect();
+        let indices: Vec<_> = points
+            .into_iter()
+            .map(|(x, y)| encode_2d(x, y, 1))
+            .collect();
+        assert_eq!(indices, expected);
+    }
+
+    #[test]
+    fn test_encode_2d_continuous() {
+        for order in 0..5 {
+            let side = 3_u64.pow(order);
+            let mut cells = vec![None; (side * side) as usize];
+            for x in 0..side {
+                for y in 0..side {
+                    let index = encode_2d(x, y, order) as usize;
+                    assert!(cells[index].is_none(), "index {index} is used twice");
+                    cells[index] = Some((x, y));
+                }
+            }
+            let cells: Vec<(u64, u64)> = cells.into_iter().map(Option::unwrap).collect();
+
+            assert_eq!(cells[0], (0, 0));
+            assert_eq!(*cells.last().unwrap(), (side - 1, side - 1));
+            for pair in cells.windows(2) {
+                let (x0, y0) = pair[0];
+                let (x1, y1) = pair[1];
+                assert_eq!(
+                    x0.abs_diff(x1) + y0.abs_diff(y1),
+                    1,
+                    "cells {:?} and {:?} are consecutive but not adjacent",
+                    pair[0],
+                    pair[1],
+                );
+            }
+        }
+    }
+}
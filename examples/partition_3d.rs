@@ -0,0 +1,58 @@
+use std::error::Error;
+use std::io;
+use std::io::Write as _;
+
+use coupe::Partition as _;
+use coupe::Point3D;
+use rand::Rng as _;
+use rand::SeedableRng as _;
+use rayon::iter::IntoParallelRefIterator as _;
+use rayon::iter::ParallelIterator as _;
+
+/// Writes one line per point, with its coordinates and its part ID.
+///
+/// The output can be plotted with gnuplot, where points are colored by part:
+///
+/// ```text
+/// splot 'partition.dat' using 1:2:3:4 with points palette
+/// ```
+fn write_partition_3d(
+    mut w: impl io::Write,
+    points: &[Point3D],
+    partition: &[usize],
+) -> io::Result<()> {
+    for (point, part) in points.iter().zip(partition) {
+        writeln!(w, "{} {} {} {part}", point.x, point.y, point.z)?;
+    }
+    Ok(())
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    // Points drawn uniformly in the box [0, 4] x [0, 2] x [0, 1], with a fixed
+    // seed so that the output is reproducible.
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+    let points: Vec<Point3D> = (0..2000)
+        .map(|_| {
+            Point3D::new(
+                rng.gen_range(0.0..4.0),
+                rng.gen_range(0.0..2.0),
+                rng.gen_range(0.0..1.0),
+            )
+        })
+        .collect();
+    let weights = vec![1.0; points.len()];
+    let mut partition = vec![0; points.len()];
+
+    coupe::Rcb {
+        iter_count: 3,
+        ..Default::default()
+    }
+    .partition(&mut partition, (points.par_iter().cloned(), weights))?;
+
+    let stdout = io::stdout();
+    let mut stdout = io::BufWriter::new(stdout.lock());
+    write_partition_3d(&mut stdout, &points, &partition)?;
+    stdout.flush()?;
+
+    Ok(())
+}
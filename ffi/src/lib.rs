@@ -279,6 +279,7 @@ pub unsafe extern "C" fn coupe_rcb(
     let algo = coupe::Rcb {
         iter_count,
         tolerance,
+        ..Default::default()
     };
 
     catch_unwind(|| {
@@ -335,6 +336,7 @@ pub unsafe extern "C" fn coupe_rib(
     let algo = coupe::Rib {
         iter_count,
         tolerance,
+        ..Default::default()
     };
 
     catch_unwind(|| {
@@ -378,8 +380,12 @@ pub unsafe extern "C" fn coupe_hilbert(
             Err(_) => return Error::Alloc,
         };
 
-        let res =
-            coupe::HilbertCurve { part_count, order }.partition(partition, (&*points, weights));
+        let res = coupe::HilbertCurve {
+            part_count,
+            order,
+            ..Default::default()
+        }
+        .partition(partition, (&*points, weights));
 
         match res {
             Ok(()) => Error::Ok,
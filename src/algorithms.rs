@@ -1,7 +1,15 @@
+use crate::geometry::PointND;
+use rayon::iter::IndexedParallelIterator;
+use rayon::iter::IntoParallelIterator;
+use std::borrow::Borrow;
+use std::borrow::Cow;
 use std::fmt;
 
+mod anti_affinity;
 mod arc_swap;
 mod ckk;
+mod diffusion;
+mod directional_bisection;
 mod fiduccia_mattheyses;
 mod graph_growth;
 mod greedy;
@@ -9,16 +17,31 @@ mod hilbert_curve;
 mod k_means;
 mod kernighan_lin;
 mod kk;
+mod label_propagation;
+mod masked;
 mod multi_jagged;
+mod partition_tree;
+mod peano_curve;
 mod recursive_bisection;
+mod repartition;
+mod simplified_k_means;
+mod simulated_annealing;
+mod spectral_bisection;
+mod subdivide;
+mod utils;
 mod vn;
 mod z_curve;
 
+pub use anti_affinity::AntiAffinity;
+pub use anti_affinity::Metadata as AntiAffinityMetadata;
 pub use arc_swap::ArcSwap;
 pub use arc_swap::AsWeight;
 pub use arc_swap::Metadata as AsMetadata;
 pub use ckk::CkkWeight;
 pub use ckk::CompleteKarmarkarKarp;
+pub use diffusion::Diffusion;
+pub use diffusion::Metadata as DiffusionMetadata;
+pub use directional_bisection::DirectionalBisection;
 pub use fiduccia_mattheyses::FiducciaMattheyses;
 pub use fiduccia_mattheyses::FmWeight;
 pub use fiduccia_mattheyses::Metadata as FmMetadata;
@@ -27,18 +50,41 @@ pub use greedy::Greedy;
 pub use greedy::GreedyWeight;
 pub use hilbert_curve::Error as HilbertCurveError;
 pub use hilbert_curve::HilbertCurve;
+pub use hilbert_curve::HilbertPoint;
+pub use k_means::CutAwareKMeans;
+pub use k_means::IterationReport;
 pub use k_means::KMeans;
+pub use k_means::KMeansBuilder;
+pub use k_means::KMeansReport;
+pub use k_means::Metric;
 pub use kernighan_lin::KernighanLin;
 pub use kk::KarmarkarKarp;
 pub use kk::KkWeight;
+pub use label_propagation::LabelPropagation;
+pub use masked::Masked;
 pub use multi_jagged::MultiJagged;
+pub use partition_tree::PartitionTree;
+pub use peano_curve::Error as PeanoCurveError;
+pub use peano_curve::PeanoCurve;
+pub use recursive_bisection::BisectionTree;
+pub use recursive_bisection::PartNumbering;
 pub use recursive_bisection::Rcb;
 pub use recursive_bisection::RcbWeight;
 pub use recursive_bisection::Rib;
+pub use recursive_bisection::RibMetadata;
+pub use repartition::Metadata as RepartitionMetadata;
+pub use repartition::Repartition;
+pub use simplified_k_means::SimplifiedKMeans;
+pub use simulated_annealing::Metadata as SaMetadata;
+pub use simulated_annealing::SimulatedAnnealing;
+pub use spectral_bisection::SpectralBisection;
+pub use subdivide::Subdivide;
 pub use vn::VnBest;
 pub use vn::VnBestWeight;
 pub use vn::VnFirst;
 pub use vn::VnFirstWeight;
+pub use z_curve::Error as ZCurveError;
+pub use z_curve::Quadrant;
 pub use z_curve::ZCurve;
 
 /// Common errors thrown by algorithms.
@@ -56,6 +102,24 @@ pub enum Error {
 
     /// When a partition improving algorithm is given more than 2 parts.
     BiPartitioningOnly,
+
+    /// A point has a NaN or infinite coordinate.
+    NonFiniteCoordinate { index: usize },
+
+    /// An input refers to an element that does not exist.
+    IndexOutOfBounds { index: usize, len: usize },
+
+    /// A directional algorithm has been given no direction to cut along.
+    NoDirection,
+
+    /// The period of an axis is empty or not finite.
+    InvalidPeriod { axis: usize },
+
+    /// The requested number of parts does not fit in a `usize`.
+    TooManyParts,
+
+    /// A multi-criteria algorithm has been given weights with no criterion.
+    NoCriterion,
 }
 
 impl fmt::Display for Error {
@@ -68,12 +132,58 @@ impl fmt::Display for Error {
             ),
             Error::NegativeValues => write!(f, "input contains negative values"),
             Error::BiPartitioningOnly => write!(f, "expected no more than two parts"),
+            Error::NonFiniteCoordinate { index } => {
+                write!(f, "point {index} has a non-finite coordinate")
+            }
+            Error::IndexOutOfBounds { index, len } => {
+                write!(f, "element {index} is out of bounds (got {len} elements)")
+            }
+            Error::NoDirection => write!(f, "expected at least one direction"),
+            Error::InvalidPeriod { axis } => {
+                write!(f, "the period of axis {axis} is empty or not finite")
+            }
+            Error::TooManyParts => write!(f, "too many parts requested"),
+            Error::NoCriterion => write!(f, "expected at least one weight criterion"),
         }
     }
 }
 
 impl std::error::Error for Error {}
 
+impl From<std::convert::Infallible> for Error {
+    fn from(err: std::convert::Infallible) -> Self {
+        match err {}
+    }
+}
+
+/// The index of the first point that has a NaN or infinite coordinate.
+pub(crate) fn first_non_finite<const D: usize, P>(points: P) -> Option<usize>
+where
+    P: IntoParallelIterator,
+    P::Iter: IndexedParallelIterator,
+    P::Item: Borrow<PointND<D>>,
+{
+    points
+        .into_par_iter()
+        .position_first(|point| !point.borrow().iter().all(|c| c.is_finite()))
+}
+
+/// Checks that all coordinates of the given points are finite.
+///
+/// Geometric algorithms sort and sum coordinates, so a single NaN silently
+/// yields a meaningless partition.  They call this first to report it instead.
+pub(crate) fn validate_points<const D: usize, P>(points: P) -> Result<(), Error>
+where
+    P: IntoParallelIterator,
+    P::Iter: IndexedParallelIterator,
+    P::Item: Borrow<PointND<D>>,
+{
+    match first_non_finite(points) {
+        Some(index) => Err(Error::NonFiniteCoordinate { index }),
+        None => Ok(()),
+    }
+}
+
 /// Map elements to parts randomly.
 ///
 /// # Example
@@ -96,6 +206,18 @@ pub struct Random<R> {
     pub part_count: usize,
 }
 
+impl<R> crate::PartCount for Random<R> {
+    fn expected_part_count(&self) -> Option<usize> {
+        Some(self.part_count)
+    }
+}
+
+impl<R> crate::Name for Random<R> {
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed("random")
+    }
+}
+
 impl<R> crate::Partition<()> for Random<R>
 where
     R: rand::Rng,
@@ -110,3 +232,282 @@ where
         Ok(())
     }
 }
+
+/// Map elements to parts in a cyclic fashion, in input order.
+///
+/// Element `i` is assigned to part `i % part_count`.  Like [Random], this is
+/// a baseline to compare other algorithms against.  The partition is left
+/// untouched when `part_count` is zero.
+///
+/// # Example
+///
+/// ```rust
+/// # fn main() -> Result<(), std::convert::Infallible> {
+/// use coupe::Partition as _;
+///
+/// let mut partition = [0; 7];
+///
+/// coupe::RoundRobin { part_count: 3 }
+///     .partition(&mut partition, ())?;
+///
+/// assert_eq!(partition, [0, 1, 2, 0, 1, 2, 0]);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct RoundRobin {
+    pub part_count: usize,
+}
+
+impl crate::PartCount for RoundRobin {
+    fn expected_part_count(&self) -> Option<usize> {
+        Some(self.part_count)
+    }
+}
+
+impl crate::Name for RoundRobin {
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed("round_robin")
+    }
+}
+
+impl crate::Partition<()> for RoundRobin {
+    type Metadata = ();
+    type Error = std::convert::Infallible;
+
+    fn partition(&mut self, part_ids: &mut [usize], _: ()) -> Result<Self::Metadata, Self::Error> {
+        if self.part_count == 0 {
+            return Ok(());
+        }
+        for (i, part_id) in part_ids.iter_mut().enumerate() {
+            *part_id = i % self.part_count;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Partition as _;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng as _;
+
+    #[test]
+    fn test_random_reproducible() {
+        let mut partition1 = vec![0; 1000];
+        Random {
+            rng: StdRng::seed_from_u64(42),
+            part_count: 4,
+        }
+        .partition(&mut partition1, ())
+        .unwrap();
+
+        let mut partition2 = vec![0; 1000];
+        Random {
+            rng: StdRng::seed_from_u64(42),
+            part_count: 4,
+        }
+        .partition(&mut partition2, ())
+        .unwrap();
+
+        assert_eq!(partition1, partition2);
+    }
+
+    #[test]
+    fn test_random_balanced() {
+        let element_count = 100_000;
+        let part_count = 4;
+        let mut partition = vec![0; element_count];
+        Random {
+            rng: StdRng::seed_from_u64(0),
+            part_count,
+        }
+        .partition(&mut partition, ())
+        .unwrap();
+
+        // Each part size follows a binomial distribution of mean 25000 and
+        // standard deviation ~137, so this bound is about 5 sigmas.
+        let mut sizes = vec![0_usize; part_count];
+        for part in partition {
+            sizes[part] += 1;
+        }
+        let ideal = element_count / part_count;
+        for size in sizes {
+            assert!(size.abs_diff(ideal) < 700, "{size} vs {ideal}");
+        }
+    }
+
+    #[test]
+    fn test_round_robin() {
+        let mut partition = [usize::MAX; 10];
+        RoundRobin { part_count: 4 }
+            .partition(&mut partition, ())
+            .unwrap();
+        assert_eq!(partition, [0, 1, 2, 3, 0, 1, 2, 3, 0, 1]);
+
+        let mut partition = [7; 3];
+        RoundRobin { part_count: 0 }
+            .partition(&mut partition, ())
+            .unwrap();
+        assert_eq!(partition, [7; 3]);
+    }
+
+    #[test]
+    fn test_expected_part_count() {
+        use crate::PartCount as _;
+
+        assert_eq!(
+            Random {
+                rng: StdRng::seed_from_u64(0),
+                part_count: 5,
+            }
+            .expected_part_count(),
+            Some(5),
+        );
+        assert_eq!(RoundRobin { part_count: 5 }.expected_part_count(), Some(5));
+        assert_eq!(Greedy { part_count: 5 }.expected_part_count(), Some(5));
+        assert_eq!(
+            KarmarkarKarp { part_count: 5 }.expected_part_count(),
+            Some(5)
+        );
+        assert_eq!(
+            CompleteKarmarkarKarp { tolerance: 0.1 }.expected_part_count(),
+            Some(2),
+        );
+        assert_eq!(
+            GraphGrowth {
+                part_count: 5,
+                ..Default::default()
+            }
+            .expected_part_count(),
+            Some(5),
+        );
+        assert_eq!(
+            LabelPropagation {
+                part_count: 5,
+                ..Default::default()
+            }
+            .expected_part_count(),
+            Some(5),
+        );
+        assert_eq!(
+            MultiJagged {
+                part_count: 5,
+                max_iter: 2,
+                target_weights: None,
+            }
+            .expected_part_count(),
+            Some(5),
+        );
+        assert_eq!(
+            ZCurve {
+                part_count: 5,
+                order: 5,
+                ..Default::default()
+            }
+            .expected_part_count(),
+            Some(5),
+        );
+        assert_eq!(
+            HilbertCurve {
+                part_count: 5,
+                ..Default::default()
+            }
+            .expected_part_count(),
+            Some(5),
+        );
+        assert_eq!(
+            PeanoCurve {
+                part_count: 5,
+                ..Default::default()
+            }
+            .expected_part_count(),
+            Some(5),
+        );
+        assert_eq!(
+            Rcb {
+                iter_count: 3,
+                ..Default::default()
+            }
+            .expected_part_count(),
+            Some(8),
+        );
+        assert_eq!(
+            Rib {
+                iter_count: 0,
+                ..Default::default()
+            }
+            .expected_part_count(),
+            Some(1),
+        );
+        assert_eq!(
+            DirectionalBisection::<2> {
+                directions: Vec::new(),
+                iter_count: 2,
+            }
+            .expected_part_count(),
+            Some(4),
+        );
+        assert_eq!(
+            Rcb {
+                iter_count: 64,
+                ..Default::default()
+            }
+            .expected_part_count(),
+            None,
+        );
+        assert_eq!(
+            Subdivide(RoundRobin { part_count: 2 }).expected_part_count(),
+            None,
+        );
+    }
+
+    #[test]
+    fn test_non_finite_coordinate() {
+        use crate::Point2D;
+
+        let mut points: Vec<Point2D> = (0..8).map(|i| Point2D::new(i as f64, 0.0)).collect();
+        points[5].y = f64::NAN;
+        let weights = [1.0; 8];
+        let mut partition = [0; 8];
+
+        let result = MultiJagged {
+            part_count: 2,
+            max_iter: 2,
+            target_weights: None,
+        }
+        .partition(&mut partition, (&points, &weights));
+        assert!(matches!(
+            result,
+            Err(Error::NonFiniteCoordinate { index: 5 })
+        ));
+
+        let result = Rcb {
+            iter_count: 1,
+            ..Default::default()
+        }
+        .partition(&mut partition, (points.clone(), weights));
+        assert!(matches!(
+            result,
+            Err(Error::NonFiniteCoordinate { index: 5 })
+        ));
+
+        let result = KMeans::default().partition(&mut partition, (&points[..], &weights[..]));
+        assert!(matches!(
+            result,
+            Err(Error::NonFiniteCoordinate { index: 5 })
+        ));
+
+        points[5].y = f64::INFINITY;
+        let result = HilbertCurve {
+            part_count: 2,
+            ..Default::default()
+        }
+        .partition(&mut partition, (&points[..], &weights[..]));
+        assert!(matches!(
+            result,
+            Err(HilbertCurveError::NonFiniteCoordinate { index: 5 })
+        ));
+    }
+}
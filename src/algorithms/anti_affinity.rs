@@ -0,0 +1,222 @@
+use super::utils::part_centers;
+use crate::geometry::PointND;
+
+use std::borrow::Cow;
+
+/// Diagnostic data for an [AntiAffinity] run.
+#[non_exhaustive]
+#[derive(Debug, Default, Clone)]
+pub struct Metadata {
+    /// Number of elements that changed part.
+    pub moved: usize,
+
+    /// Number of pairs that still share a part, because all other parts hold
+    /// a forbidden partner of both of their elements.
+    pub unresolved: usize,
+}
+
+fn anti_affinity<const D: usize>(
+    partition: &mut [usize],
+    points: &[PointND<D>],
+    weights: &[f64],
+    pairs: &[(usize, usize)],
+    part_count: usize,
+) -> Metadata {
+    let mut partners = vec![Vec::new(); partition.len()];
+    for (a, b) in pairs {
+        partners[*a].push(*b);
+        partners[*b].push(*a);
+    }
+    let centers = part_centers(partition, points, part_count);
+
+    // The part closest to the given element, among those that hold none of its
+    // partners.  Empty parts come last.
+    let nearest_allowed_part = |partition: &[usize], element: usize| {
+        let point = points[element];
+        (0..part_count)
+            .filter(|part| partners[element].iter().all(|p| partition[*p] != *part))
+            .min_by(|p1, p2| {
+                let distance = |part: &usize| match centers[*part] {
+                    Some(center) => (point - center).norm(),
+                    None => f64::INFINITY,
+                };
+                f64::total_cmp(&distance(p1), &distance(p2)).then(p1.cmp(p2))
+            })
+    };
+
+    let mut metadata = Metadata::default();
+    for (a, b) in pairs {
+        if a == b || partition[*a] != partition[*b] {
+            continue;
+        }
+        // Move the lighter element, to disrupt balance as little as possible.
+        let (a, b) = if weights[*b] < weights[*a] {
+            (*b, *a)
+        } else {
+            (*a, *b)
+        };
+        let target = nearest_allowed_part(partition, a)
+            .map(|part| (a, part))
+            .or_else(|| nearest_allowed_part(partition, b).map(|part| (b, part)));
+        match target {
+            Some((element, part)) => {
+                partition[element] = part;
+                metadata.moved += 1;
+            }
+            None => metadata.unresolved += 1,
+        }
+    }
+    metadata
+}
+
+/// # Anti-affinity enforcement
+///
+/// A partition improving algorithm that separates pairs of elements that must
+/// not share a part, like the redundant replicas of some data.
+///
+/// For each given pair whose elements share a part, the lighter element is
+/// moved to the part closest to it, among those that hold none of its
+/// forbidden partners, or the heavier one if no such part exists for the
+/// lighter one.  Distances are measured to the centroid of the parts of the
+/// input partition.  Elements that already are in different parts are not
+/// moved, so the partition is otherwise left untouched.
+///
+/// The part count is not changed, so pairs can stay together when all other
+/// parts hold a partner of both elements.  They are counted in
+/// [`Metadata::unresolved`][crate::AntiAffinityMetadata::unresolved].
+///
+/// Partitioning fails with [`Error::IndexOutOfBounds`][crate::Error] when a
+/// pair refers to an element that is out of bounds.
+///
+/// # Example
+///
+/// ```rust
+/// # fn main() -> Result<(), coupe::Error> {
+/// use coupe::Partition as _;
+/// use coupe::Point2D;
+///
+/// let points: Vec<Point2D> = (0..6).map(|i| Point2D::new(i as f64, 0.0)).collect();
+/// let weights = [1.0, 1.0, 1.0, 1.0, 1.0, 2.0];
+/// let mut partition = [0, 0, 0, 1, 1, 1];
+///
+/// // Elements 4 and 5 must not share a part.
+/// let metadata = coupe::AntiAffinity
+///     .partition(&mut partition, (&points, &weights, &[(4, 5)]))?;
+///
+/// assert_eq!(partition, [0, 0, 0, 1, 0, 1]);
+/// assert_eq!(metadata.moved, 1);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default, Clone, Copy)]
+pub struct AntiAffinity;
+
+impl crate::Name for AntiAffinity {
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed("anti_affinity")
+    }
+}
+
+impl<'a, const D: usize> crate::Partition<(&'a [PointND<D>], &'a [f64], &'a [(usize, usize)])>
+    for AntiAffinity
+{
+    type Metadata = Metadata;
+    type Error = crate::Error;
+
+    fn partition(
+        &mut self,
+        part_ids: &mut [usize],
+        (points, weights, pairs): (&'a [PointND<D>], &'a [f64], &'a [(usize, usize)]),
+    ) -> Result<Self::Metadata, Self::Error> {
+        if part_ids.len() != points.len() {
+            return Err(crate::Error::InputLenMismatch {
+                expected: part_ids.len(),
+                actual: points.len(),
+            });
+        }
+        if part_ids.len() != weights.len() {
+            return Err(crate::Error::InputLenMismatch {
+                expected: part_ids.len(),
+                actual: weights.len(),
+            });
+        }
+        super::validate_points(points)?;
+        if let Some(index) = pairs
+            .iter()
+            .map(|(a, b)| usize::max(*a, *b))
+            .find(|index| part_ids.len() <= *index)
+        {
+            return Err(crate::Error::IndexOutOfBounds {
+                index,
+                len: part_ids.len(),
+            });
+        }
+        let part_count = match part_ids.iter().max() {
+            Some(max) => max + 1,
+            None => return Ok(Metadata::default()),
+        };
+        Ok(anti_affinity(part_ids, points, weights, pairs, part_count))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Partition as _;
+    use crate::Point2D;
+
+    #[test]
+    fn test_separate_pair() {
+        // Three strips of a 6x2 grid, with a forbidden pair inside the middle
+        // one, and a pair that already is separated.
+        let points: Vec<Point2D> = (0..12)
+            .map(|i| Point2D::new((i % 6) as f64, (i / 6) as f64))
+            .collect();
+        let weights = [1.0; 12];
+        let mut partition: Vec<usize> = (0..12).map(|i| (i % 6) / 2).collect();
+        let pairs = [(2, 9), (0, 5)];
+
+        let metadata = AntiAffinity
+            .partition(&mut partition, (&points, &weights, &pairs))
+            .unwrap();
+
+        assert_ne!(partition[2], partition[9]);
+        assert_ne!(partition[0], partition[5]);
+        assert_eq!(metadata.moved, 1);
+        assert_eq!(metadata.unresolved, 0);
+        // Element 2 is at the edge of its strip, next to the first one.
+        assert_eq!(partition[2], 0);
+    }
+
+    #[test]
+    fn test_unresolved() {
+        // Two parts, and an element that is forbidden from both.
+        let points: Vec<Point2D> = (0..3).map(|i| Point2D::new(i as f64, 0.0)).collect();
+        let weights = [1.0; 3];
+        let mut partition = [0, 0, 1];
+        let pairs = [(0, 1), (0, 2), (1, 2)];
+
+        let metadata = AntiAffinity
+            .partition(&mut partition, (&points, &weights, &pairs))
+            .unwrap();
+
+        assert_eq!(partition, [0, 0, 1]);
+        assert_eq!(metadata.unresolved, 1);
+    }
+
+    #[test]
+    fn test_pair_out_of_bounds() {
+        let points: Vec<Point2D> = (0..3).map(|i| Point2D::new(i as f64, 0.0)).collect();
+        let weights = [1.0; 3];
+        let mut partition = [0, 0, 1];
+
+        let err = AntiAffinity
+            .partition(&mut partition, (&points, &weights, &[(0, 1), (5, 2)]))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            crate::Error::IndexOutOfBounds { index: 5, len: 3 },
+        ));
+        assert_eq!(partition, [0, 0, 1]);
+    }
+}
@@ -11,6 +11,7 @@ use rayon::iter::IndexedParallelIterator as _;
 use rayon::iter::IntoParallelRefIterator as _;
 use rayon::iter::ParallelIterator as _;
 use rayon::slice::ParallelSlice;
+use std::borrow::Cow;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering;
 
@@ -370,6 +371,12 @@ pub struct ArcSwap {
     pub max_imbalance: Option<f64>,
 }
 
+impl crate::Name for ArcSwap {
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed("arc_swap")
+    }
+}
+
 impl<'a, T, W> Partition<(T, &'a [W])> for ArcSwap
 where
     W: AsWeight,
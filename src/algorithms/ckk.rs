@@ -2,6 +2,7 @@ use num_traits::FromPrimitive;
 use num_traits::ToPrimitive;
 
 use super::Error;
+use std::borrow::Cow;
 use std::iter::Sum;
 use std::ops::Add;
 use std::ops::Sub;
@@ -173,6 +174,18 @@ where
 {
 }
 
+impl crate::PartCount for CompleteKarmarkarKarp {
+    fn expected_part_count(&self) -> Option<usize> {
+        Some(2)
+    }
+}
+
+impl crate::Name for CompleteKarmarkarKarp {
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed("complete_karmarkar_karp")
+    }
+}
+
 impl<W> crate::Partition<W> for CompleteKarmarkarKarp
 where
     W: IntoIterator,
@@ -0,0 +1,355 @@
+use super::utils::load_imbalance;
+use super::utils::part_loads;
+use crate::topology::Topology;
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+
+/// Diagnostic data for a [Diffusion] run.
+#[non_exhaustive]
+#[derive(Debug, Default, Clone)]
+pub struct Metadata {
+    /// Number of diffusion rounds that have been run.
+    pub iterations: usize,
+
+    /// Number of vertices moved during each round.
+    pub moves_per_iteration: Vec<usize>,
+
+    /// Imbalance of the output partition.
+    pub imbalance: f64,
+}
+
+/// The gain in edge cut of moving `vertex` to `target`.
+fn move_gain<T>(adjacency: &T, partition: &[usize], vertex: usize, target: usize) -> i64
+where
+    T: Topology<i64>,
+{
+    let source = partition[vertex];
+    adjacency
+        .neighbors(vertex)
+        .map(|(neighbor, edge_weight)| {
+            if partition[neighbor] == target {
+                edge_weight
+            } else if partition[neighbor] == source {
+                -edge_weight
+            } else {
+                0
+            }
+        })
+        .sum()
+}
+
+/// Computes a flow on the quotient graph that balances part loads.
+///
+/// Loads are diffused with a first-order scheme: at each step, each pair of
+/// adjacent parts exchanges a fraction of their load difference, scaled so
+/// that no part sends more than it has.  Steps are repeated until all loads
+/// are within `tolerance` of the average.
+///
+/// Returns the positive net flows, as `(from, to, amount)`, largest first.
+fn balancing_flow(
+    quotient: &[BTreeSet<usize>],
+    loads: &[f64],
+    tolerance: f64,
+) -> Vec<(usize, usize, f64)> {
+    const MAX_STEPS: usize = 1000;
+
+    let part_count = loads.len();
+    let ideal = loads.iter().sum::<f64>() / part_count as f64;
+    let mut loads = loads.to_vec();
+    let mut flows = vec![0.0; part_count * part_count];
+    let mut step_flows = Vec::new();
+
+    for _ in 0..MAX_STEPS {
+        let max_deviation = loads
+            .iter()
+            .map(|load| f64::abs(load - ideal))
+            .fold(0.0, f64::max);
+        if max_deviation <= tolerance * ideal / 2.0 {
+            break;
+        }
+        step_flows.clear();
+        for (p, neighbors) in quotient.iter().enumerate() {
+            for &q in neighbors {
+                let alpha = 1.0 / (1 + usize::max(neighbors.len(), quotient[q].len())) as f64;
+                let flow = alpha * (loads[p] - loads[q]);
+                if flow > 0.0 {
+                    step_flows.push((p, q, flow));
+                }
+            }
+        }
+        for &(p, q, flow) in &step_flows {
+            loads[p] -= flow;
+            loads[q] += flow;
+            flows[p * part_count + q] += flow;
+            flows[q * part_count + p] -= flow;
+        }
+    }
+
+    let mut flows: Vec<(usize, usize, f64)> = flows
+        .into_iter()
+        .enumerate()
+        .map(|(i, flow)| (i / part_count, i % part_count, flow))
+        .filter(|(_, _, flow)| *flow > 0.0)
+        .collect();
+    flows.sort_by(|(_, _, f1), (_, _, f2)| f64::total_cmp(f2, f1));
+    flows
+}
+
+fn diffusion<T>(
+    partition: &mut [usize],
+    weights: &[f64],
+    adjacency: T,
+    part_count: usize,
+    max_iter: usize,
+    flow_tolerance: f64,
+) -> Metadata
+where
+    T: Topology<i64>,
+{
+    let mut metadata = Metadata::default();
+    let mut loads = part_loads(partition, weights, part_count);
+
+    for _ in 0..max_iter {
+        if load_imbalance(&loads) <= flow_tolerance {
+            break;
+        }
+        metadata.iterations += 1;
+
+        // Quotient graph: parts are adjacent iff they share an edge.  Boundary
+        // vertices are bucketed by their part and the adjacent one.
+        let mut quotient = vec![BTreeSet::new(); part_count];
+        let mut boundaries: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+        for vertex in 0..adjacency.len() {
+            let part = partition[vertex];
+            for (neighbor, _edge_weight) in adjacency.neighbors(vertex) {
+                let neighbor_part = partition[neighbor];
+                if neighbor_part != part {
+                    quotient[part].insert(neighbor_part);
+                    let boundary = boundaries.entry((part, neighbor_part)).or_default();
+                    if boundary.last() != Some(&vertex) {
+                        boundary.push(vertex);
+                    }
+                }
+            }
+        }
+
+        let flows = balancing_flow(&quotient, &loads, flow_tolerance);
+
+        let mut moves = 0;
+        for (p, q, flow) in flows {
+            // Boundary vertices of p that are adjacent to q, best gains first.
+            // Previous flows of this round may have moved some of them, or
+            // their neighbors.
+            let boundary = boundaries.remove(&(p, q)).unwrap_or_default();
+            let mut candidates: Vec<(i64, usize)> = boundary
+                .into_iter()
+                .filter(|&vertex| {
+                    partition[vertex] == p
+                        && adjacency
+                            .neighbors(vertex)
+                            .any(|(neighbor, _)| partition[neighbor] == q)
+                })
+                .map(|vertex| (move_gain(&adjacency, partition, vertex, q), vertex))
+                .collect();
+            candidates.sort_by(|(g1, v1), (g2, v2)| g2.cmp(g1).then(v1.cmp(v2)));
+
+            // Move vertices until the flow is met, rounding to the nearest
+            // vertex weight.
+            let mut moved_weight = 0.0;
+            for (_gain, vertex) in candidates {
+                let weight = weights[vertex];
+                if flow < moved_weight + weight / 2.0 {
+                    continue;
+                }
+                moved_weight += weight;
+                partition[vertex] = q;
+                loads[p] -= weight;
+                loads[q] += weight;
+                moves += 1;
+            }
+        }
+        metadata.moves_per_iteration.push(moves);
+        if moves == 0 {
+            break;
+        }
+    }
+
+    metadata.imbalance = load_imbalance(&loads);
+    metadata
+}
+
+/// # Diffusion load balancing
+///
+/// An improvement algorithm that balances the parts of an existing partition
+/// by moving vertices from heavy parts to lighter adjacent parts.
+///
+/// Each round, the algorithm computes a balancing flow on the quotient graph
+/// (the graph whose vertices are the parts, and where two parts are adjacent
+/// when they share an edge) using a first-order diffusion scheme.  It then
+/// migrates boundary vertices along this flow, preferring those whose move
+/// cuts the fewest edges.  Because only boundary vertices move, the edge cut
+/// stays close to the one of the input partition.
+///
+/// # Example
+///
+/// ```rust
+/// # fn main() -> Result<(), coupe::Error> {
+/// use coupe::Grid;
+/// use coupe::Partition as _;
+/// use std::num::NonZeroUsize;
+///
+/// // 0  0  0  0  0  1
+/// // 0  0  0  0  0  1
+/// let width = NonZeroUsize::new(6).unwrap();
+/// let height = NonZeroUsize::new(2).unwrap();
+/// let grid = Grid::new_2d(width, height);
+/// let weights = [1.0; 12];
+/// let mut partition = [0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 1];
+///
+/// coupe::Diffusion { flow_tolerance: 0.0, ..Default::default() }
+///     .partition(&mut partition, (grid, &weights))?;
+///
+/// assert_eq!(partition, [0, 0, 0, 1, 1, 1, 0, 0, 0, 1, 1, 1]);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Reference
+///
+/// Cybenko, G., 1989. Dynamic load balancing for distributed memory
+/// multiprocessors. *Journal of Parallel and Distributed Computing*, 7(2),
+/// pp. 279–301. <https://doi.org/10.1016/0743-7315(89)90021-X>
+#[derive(Debug, Clone, Copy)]
+pub struct Diffusion {
+    /// Maximum number of diffusion rounds.
+    pub max_iter: usize,
+
+    /// The algorithm stops as soon as the imbalance of the partition is below
+    /// this value.
+    pub flow_tolerance: f64,
+}
+
+impl Default for Diffusion {
+    fn default() -> Self {
+        Self {
+            max_iter: 64,
+            flow_tolerance: 0.05,
+        }
+    }
+}
+
+impl crate::Name for Diffusion {
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed("diffusion")
+    }
+}
+
+impl<'a, T> crate::Partition<(T, &'a [f64])> for Diffusion
+where
+    T: Topology<i64>,
+{
+    type Metadata = Metadata;
+    type Error = crate::Error;
+
+    fn partition(
+        &mut self,
+        part_ids: &mut [usize],
+        (adjacency, weights): (T, &'a [f64]),
+    ) -> Result<Self::Metadata, Self::Error> {
+        if part_ids.is_empty() {
+            return Ok(Metadata::default());
+        }
+        if part_ids.len() != weights.len() {
+            return Err(crate::Error::InputLenMismatch {
+                expected: part_ids.len(),
+                actual: weights.len(),
+            });
+        }
+        if part_ids.len() != adjacency.len() {
+            return Err(crate::Error::InputLenMismatch {
+                expected: part_ids.len(),
+                actual: adjacency.len(),
+            });
+        }
+        if weights.iter().any(|weight| *weight < 0.0) {
+            return Err(crate::Error::NegativeValues);
+        }
+        let part_count = 1 + *part_ids.iter().max().unwrap_or(&0);
+        Ok(diffusion(
+            part_ids,
+            weights,
+            adjacency,
+            part_count,
+            self.max_iter,
+            self.flow_tolerance,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Grid;
+    use crate::Partition as _;
+    use rayon::iter::IntoParallelRefIterator as _;
+    use rayon::iter::ParallelIterator as _;
+    use std::num::NonZeroUsize;
+
+    #[test]
+    fn test_imbalanced_three_parts() {
+        // Three vertical strips of 8, 2 and 2 columns.
+        let width = 12;
+        let height = 4;
+        let grid = Grid::new_2d(
+            NonZeroUsize::new(width).unwrap(),
+            NonZeroUsize::new(height).unwrap(),
+        );
+        let mut partition: Vec<usize> = (0..width * height)
+            .map(|i| match i % width {
+                0..=7 => 0,
+                8..=9 => 1,
+                _ => 2,
+            })
+            .collect();
+        let weights = vec![1.0; width * height];
+
+        let old_imbalance = crate::imbalance::imbalance(3, &partition, weights.par_iter().cloned());
+        let old_edge_cut: i64 = grid.edge_cut(&partition);
+        assert_eq!(old_imbalance, 1.0);
+
+        let mut diffusion = Diffusion {
+            max_iter: 100,
+            flow_tolerance: 0.05,
+        };
+        let metadata = diffusion
+            .partition(&mut partition, (grid, &weights))
+            .unwrap();
+
+        let new_imbalance = crate::imbalance::imbalance(3, &partition, weights.par_iter().cloned());
+        let new_edge_cut: i64 = grid.edge_cut(&partition);
+        assert!(new_imbalance <= diffusion.flow_tolerance, "{metadata:?}");
+        assert_eq!(metadata.imbalance, new_imbalance);
+        assert!(
+            new_edge_cut <= 2 * old_edge_cut,
+            "{new_edge_cut} > 2 * {old_edge_cut}"
+        );
+        assert_eq!(
+            Topology::<i64>::connected_components_per_part(&grid, &partition),
+            [1, 1, 1],
+            "{partition:?}",
+        );
+    }
+
+    #[test]
+    fn test_balanced_input_is_untouched() {
+        let grid = Grid::new_2d(NonZeroUsize::new(4).unwrap(), NonZeroUsize::new(4).unwrap());
+        let mut partition: Vec<usize> = (0..16).map(|i| i % 4 / 2).collect();
+        let expected = partition.clone();
+        let metadata = Diffusion::default()
+            .partition(&mut partition, (grid, &[1.0; 16][..]))
+            .unwrap();
+        assert_eq!(partition, expected);
+        assert_eq!(metadata.iterations, 0);
+    }
+}
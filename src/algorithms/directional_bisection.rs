@@ -0,0 +1,288 @@
+use super::Error;
+use crate::geometry::PointND;
+use nalgebra::Unit;
+use std::borrow::Cow;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+/// Recursion of [directional_bisection] on the elements listed in `indices`.
+///
+/// Leaves get the ID `2^level - 1 + i`, where `i` is the index of the leaf in
+/// its level, like in a binary heap.
+#[allow(clippy::too_many_arguments)]
+fn directional_bisection_recurse<const D: usize>(
+    parts: &[AtomicUsize],
+    points: &[PointND<D>],
+    weights: &[f64],
+    directions: &[Unit<PointND<D>>],
+    indices: &mut [usize],
+    iter_count: usize,
+    level: usize,
+    part_id: usize,
+) {
+    if indices.is_empty() {
+        return;
+    }
+    if iter_count == level {
+        for i in indices {
+            parts[*i].store(part_id, Ordering::Relaxed);
+        }
+        return;
+    }
+
+    let direction = &directions[level % directions.len()];
+    let projection = |i: usize| points[i].dot(direction);
+    indices
+        .sort_unstable_by(|i, j| f64::total_cmp(&projection(*i), &projection(*j)).then(i.cmp(j)));
+
+    // Cut at the weighted median, between two points that have different
+    // projections, so that points are located consistently.
+    let total_weight: f64 = indices.iter().map(|i| weights[*i]).sum();
+    let mut left_weight = 0.0;
+    let mut best_split = 0;
+    let mut best_distance = f64::abs(total_weight / 2.0);
+    for k in 1..=indices.len() {
+        left_weight += weights[indices[k - 1]];
+        if k < indices.len() && projection(indices[k - 1]) == projection(indices[k]) {
+            continue;
+        }
+        let distance = f64::abs(left_weight - total_weight / 2.0);
+        if distance < best_distance {
+            best_split = k;
+            best_distance = distance;
+        }
+    }
+
+    let (left, right) = indices.split_at_mut(best_split);
+    rayon::join(
+        || {
+            directional_bisection_recurse(
+                parts,
+                points,
+                weights,
+                directions,
+                left,
+                iter_count,
+                level + 1,
+                2 * part_id + 1,
+            )
+        },
+        || {
+            directional_bisection_recurse(
+                parts,
+                points,
+                weights,
+                directions,
+                right,
+                iter_count,
+                level + 1,
+                2 * part_id + 2,
+            )
+        },
+    );
+}
+
+fn directional_bisection<const D: usize>(
+    partition: &mut [usize],
+    points: &[PointND<D>],
+    weights: &[f64],
+    directions: &[Unit<PointND<D>>],
+    iter_count: usize,
+) {
+    let mut indices: Vec<usize> = (0..points.len()).collect();
+    directional_bisection_recurse(
+        crate::as_atomic(partition),
+        points,
+        weights,
+        directions,
+        &mut indices,
+        iter_count,
+        0,
+        0,
+    );
+
+    // Part IDs must be contiguous and start from zero.
+    let mut part_ids = partition.to_vec();
+    part_ids.sort_unstable();
+    part_ids.dedup();
+    for part in partition {
+        *part = part_ids.binary_search(part).unwrap();
+    }
+}
+
+/// # Directional bisection
+///
+/// A generalization of [Recursive Coordinate Bisection][crate::Rcb], where
+/// cuts are orthogonal to user-supplied directions instead of the axes of the
+/// canonical basis.
+///
+/// At the `i`-th level of recursion, points are projected onto
+/// `directions[i % directions.len()]`, and split in two sets of similar
+/// weights, at the weighted median of the projections.  Thus, giving the
+/// canonical basis as `directions` behaves like RCB.
+///
+/// This is useful when the best direction of the cuts is known beforehand,
+/// for example the direction of a flow.
+///
+/// # Example
+///
+/// ```rust
+/// # fn main() -> Result<(), coupe::Error> {
+/// use coupe::nalgebra::Unit;
+/// use coupe::Partition as _;
+/// use coupe::Point2D;
+///
+/// let points = [
+///     Point2D::new(0.0, 0.0),
+///     Point2D::new(1.0, 0.0),
+///     Point2D::new(0.0, 1.0),
+///     Point2D::new(1.0, 1.0),
+/// ];
+/// let weights = [1.0; 4];
+/// let mut partition = [0; 4];
+///
+/// // Cut along the anti-diagonal.
+/// let direction = Unit::new_normalize(Point2D::new(1.0, -1.0));
+/// coupe::DirectionalBisection { directions: vec![direction], iter_count: 1 }
+///     .partition(&mut partition, (&points, &weights))?;
+///
+/// assert_eq!(partition[0], partition[3]);
+/// assert_ne!(partition[1], partition[2]);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Partitioning fails with [`Error::NoDirection`][crate::Error] if
+/// `directions` is empty and `iter_count` is not zero.
+#[derive(Clone, Debug)]
+pub struct DirectionalBisection<const D: usize> {
+    /// The normals of the cuts, one per level of recursion, repeated
+    /// cyclically.
+    pub directions: Vec<Unit<PointND<D>>>,
+
+    /// The number of iterations of the algorithm. This will yield a partition
+    /// of at most `2^iter_count` parts.
+    pub iter_count: usize,
+}
+
+impl<const D: usize> crate::PartCount for DirectionalBisection<D> {
+    fn expected_part_count(&self) -> Option<usize> {
+        u32::try_from(self.iter_count)
+            .ok()
+            .and_then(|iter_count| 1_usize.checked_shl(iter_count))
+    }
+}
+
+impl<const D: usize> crate::Name for DirectionalBisection<D> {
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed("directional_bisection")
+    }
+}
+
+impl<'a, const D: usize> crate::Partition<(&'a [PointND<D>], &'a [f64])>
+    for DirectionalBisection<D>
+{
+    type Metadata = ();
+    type Error = Error;
+
+    fn partition(
+        &mut self,
+        part_ids: &mut [usize],
+        (points, weights): (&'a [PointND<D>], &'a [f64]),
+    ) -> Result<Self::Metadata, Self::Error> {
+        if part_ids.len() != points.len() {
+            return Err(Error::InputLenMismatch {
+                expected: part_ids.len(),
+                actual: points.len(),
+            });
+        }
+        if part_ids.len() != weights.len() {
+            return Err(Error::InputLenMismatch {
+                expected: part_ids.len(),
+                actual: weights.len(),
+            });
+        }
+        super::validate_points(points)?;
+        if self.directions.is_empty() && self.iter_count != 0 {
+            return Err(Error::NoDirection);
+        }
+        directional_bisection(part_ids, points, weights, &self.directions, self.iter_count);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Partition as _;
+    use crate::Point2D;
+
+    #[test]
+    fn test_diagonal_cut() {
+        // A 10x10 square, cut along its diagonal.
+        let points: Vec<Point2D> = (0..100)
+            .map(|i| Point2D::new((i % 10) as f64, (i / 10) as f64))
+            .collect();
+        let weights = vec![1.0; 100];
+        let mut partition = vec![0; 100];
+
+        DirectionalBisection {
+            directions: vec![Unit::new_normalize(Point2D::new(1.0, 1.0))],
+            iter_count: 1,
+        }
+        .partition(&mut partition, (&points, &weights))
+        .unwrap();
+
+        // Points on the diagonal (x + y == 9) may be on either side.
+        let low_part = partition[0];
+        for (point, part) in points.iter().zip(&partition) {
+            let sum = point.x + point.y;
+            if sum < 9.0 {
+                assert_eq!(*part, low_part, "{partition:?}");
+            } else if 9.0 < sum {
+                assert_ne!(*part, low_part, "{partition:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_canonical_directions_are_rcb() {
+        let points: Vec<Point2D> = (0..64)
+            .map(|i| Point2D::new((i % 8) as f64, (i / 8) as f64))
+            .collect();
+        let weights = vec![1.0; 64];
+        let mut partition = vec![0; 64];
+
+        DirectionalBisection {
+            directions: vec![Point2D::x_axis(), Point2D::y_axis()],
+            iter_count: 2,
+        }
+        .partition(&mut partition, (&points, &weights))
+        .unwrap();
+
+        for (point, part) in points.iter().zip(&partition) {
+            let expected = 2 * (point.x >= 4.0) as usize + (point.y >= 4.0) as usize;
+            assert_eq!(*part, expected, "{partition:?}");
+        }
+    }
+
+    #[test]
+    fn test_no_direction() {
+        let points = [Point2D::new(0.0, 0.0), Point2D::new(1.0, 0.0)];
+        let weights = [1.0; 2];
+        let mut partition = [0; 2];
+        let mut algo = DirectionalBisection {
+            directions: Vec::new(),
+            iter_count: 1,
+        };
+
+        let err = algo
+            .partition(&mut partition, (&points, &weights))
+            .unwrap_err();
+        assert!(matches!(err, Error::NoDirection));
+
+        algo.iter_count = 0;
+        algo.partition(&mut partition, (&points, &weights)).unwrap();
+        assert_eq!(partition, [0, 0]);
+    }
+}
@@ -5,6 +5,7 @@ use num_traits::ToPrimitive;
 use num_traits::Zero;
 use rayon::iter::IntoParallelRefIterator as _;
 use rayon::iter::ParallelIterator as _;
+use std::borrow::Cow;
 use std::collections::HashSet;
 use std::iter::Sum;
 use std::ops::AddAssign;
@@ -34,6 +35,7 @@ struct Move {
     initial_part: usize,
 }
 
+#[allow(clippy::too_many_arguments)]
 fn fiduccia_mattheyses<W, T>(
     partition: &mut [usize],
     weights: &[W],
@@ -42,6 +44,7 @@ fn fiduccia_mattheyses<W, T>(
     max_moves_per_pass: usize,
     max_imbalance: Option<f64>,
     max_bad_moves_in_a_row: usize,
+    fixed: Option<&[bool]>,
 ) -> Metadata
 where
     W: FmWeight,
@@ -111,6 +114,11 @@ where
             set.clear();
         }
         for (vertex, initial_part) in partition.iter().enumerate() {
+            if fixed.is_some_and(|fixed| fixed[vertex]) {
+                // Fixed vertices stay locked for the whole pass.
+                vertex_to_gain[vertex] = None;
+                continue;
+            }
             let gain = adjacency
                 .neighbors(vertex)
                 .map(|(neighbor, edge_weight)| {
@@ -350,19 +358,70 @@ pub struct FiducciaMattheyses {
     pub max_bad_move_in_a_row: usize,
 }
 
-impl<'a, T, W> crate::Partition<(T, &'a [W])> for FiducciaMattheyses
-where
-    T: Topology<i64> + Sync,
-    W: FmWeight,
-{
-    type Metadata = Metadata;
-    type Error = Error;
+impl crate::Name for FiducciaMattheyses {
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed("fiduccia_mattheyses")
+    }
+}
 
-    fn partition(
+impl FiducciaMattheyses {
+    /// Same as [`Partition::partition`][crate::Partition::partition], except
+    /// that vertices for which `fixed` is `true` keep the part they have in
+    /// `part_ids`.
+    ///
+    /// Fixed vertices still count toward the weight of their part and the
+    /// edge cut, but are never moved to another part.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), coupe::Error> {
+    /// use coupe::Grid;
+    /// use std::num::NonZeroUsize;
+    ///
+    /// let grid = Grid::new_2d(NonZeroUsize::new(4).unwrap(), NonZeroUsize::new(2).unwrap());
+    /// let weights = [1; 8];
+    /// let mut partition = [0, 0, 1, 1, 0, 1, 0, 1];
+    /// let mut fixed = [false; 8];
+    /// fixed[5] = true;
+    ///
+    /// coupe::FiducciaMattheyses { max_imbalance: Some(0.25), ..Default::default() }
+    ///     .partition_with_fixed(&mut partition, (grid, &weights), &fixed)?;
+    ///
+    /// assert_eq!(partition[5], 1);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn partition_with_fixed<T, W>(
         &mut self,
         part_ids: &mut [usize],
-        (adjacency, weights): (T, &'a [W]),
-    ) -> Result<Self::Metadata, Self::Error> {
+        (adjacency, weights): (T, &[W]),
+        fixed: &[bool],
+    ) -> Result<Metadata, Error>
+    where
+        T: Topology<i64> + Sync,
+        W: FmWeight,
+    {
+        if part_ids.len() != fixed.len() {
+            return Err(Error::InputLenMismatch {
+                expected: part_ids.len(),
+                actual: fixed.len(),
+            });
+        }
+        self.run(part_ids, adjacency, weights, Some(fixed))
+    }
+
+    fn run<T, W>(
+        &self,
+        part_ids: &mut [usize],
+        adjacency: T,
+        weights: &[W],
+        fixed: Option<&[bool]>,
+    ) -> Result<Metadata, Error>
+    where
+        T: Topology<i64> + Sync,
+        W: FmWeight,
+    {
         if part_ids.is_empty() {
             return Ok(Metadata::default());
         }
@@ -389,7 +448,81 @@ where
             self.max_moves_per_pass.unwrap_or(usize::MAX),
             self.max_imbalance,
             self.max_bad_move_in_a_row,
+            fixed,
         );
         Ok(metadata)
     }
 }
+
+impl<'a, T, W> crate::Partition<(T, &'a [W])> for FiducciaMattheyses
+where
+    T: Topology<i64> + Sync,
+    W: FmWeight,
+{
+    type Metadata = Metadata;
+    type Error = Error;
+
+    fn partition(
+        &mut self,
+        part_ids: &mut [usize],
+        (adjacency, weights): (T, &'a [W]),
+    ) -> Result<Self::Metadata, Self::Error> {
+        self.run(part_ids, adjacency, weights, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Grid;
+    use crate::Partition as _;
+    use std::num::NonZeroUsize;
+
+    #[test]
+    fn test_fixed() {
+        // The 4x2 grid of the example of FiducciaMattheyses, where the best
+        // move is to swap vertices 5 and 6.
+        let grid = Grid::new_2d(NonZeroUsize::new(4).unwrap(), NonZeroUsize::new(2).unwrap());
+        let weights = [1; 8];
+        let initial = [0, 0, 1, 1, 0, 1, 0, 1];
+        let mut algorithm = FiducciaMattheyses {
+            max_imbalance: Some(0.25),
+            ..Default::default()
+        };
+
+        let mut partition = initial;
+        algorithm
+            .partition(&mut partition, (grid, &weights[..]))
+            .unwrap();
+        assert_eq!(partition, [0, 0, 1, 1, 0, 0, 1, 1]);
+
+        let mut fixed = [false; 8];
+        fixed[5] = true;
+        fixed[6] = true;
+        let mut partition = initial;
+        algorithm
+            .partition_with_fixed(&mut partition, (grid, &weights), &fixed)
+            .unwrap();
+        assert_eq!(partition[5], 1);
+        assert_eq!(partition[6], 0);
+        let edge_cut = |partition| Topology::<i64>::edge_cut(&grid, partition);
+        assert!(edge_cut(&partition) <= edge_cut(&initial));
+
+        let mut partition = initial;
+        algorithm
+            .partition_with_fixed(&mut partition, (grid, &weights), &[true; 8])
+            .unwrap();
+        assert_eq!(partition, initial);
+
+        let err = algorithm
+            .partition_with_fixed(&mut partition, (grid, &weights), &[false; 7])
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::InputLenMismatch {
+                expected: 8,
+                actual: 7,
+            },
+        ));
+    }
+}
@@ -1,11 +1,16 @@
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
+use rand::SeedableRng as _;
 use sprs::CsMatView;
 
+use std::borrow::Cow;
+
 fn graph_growth(
     initial_ids: &mut [usize],
     weights: &[f64],
     adjacency: CsMatView<'_, f64>,
     num_parts: usize,
+    seed: Option<u64>,
 ) {
     let (shape_x, shape_y) = adjacency.shape();
     assert_eq!(shape_x, shape_y);
@@ -15,7 +20,10 @@ fn graph_growth(
     // let weight_per_part = total_weight / num_parts as f64;
     let max_expansion_per_pass = 20;
 
-    let mut rng = rand::thread_rng();
+    let mut rng = match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
 
     // select two random nodes to grow from
     let indices = (0..weights.len()).collect::<Vec<_>>();
@@ -67,6 +75,10 @@ fn graph_growth(
 /// Given a number k of parts, the algorithm selects k nodes randomly and assigns them to a different part.
 /// Then, at each iteration, each part is expanded to neighbor nodes that are not yet assigned to a part
 ///
+/// The initial nodes are drawn from a random number generator seeded with
+/// `seed`, so that runs with the same seed give the same partition.  A `seed`
+/// of `None` seeds the generator from the operating system's entropy source.
+///
 /// # Example
 ///
 /// ```rust
@@ -108,7 +120,7 @@ fn graph_growth(
 ///  adjacency.insert(6, 2, 1.);
 ///  adjacency.insert(7, 3, 1.);
 ///
-/// coupe::GraphGrowth { part_count: 2 }
+/// coupe::GraphGrowth { part_count: 2, ..Default::default() }
 ///     .partition(&mut partition, (adjacency.view(), &weights))?;
 /// # Ok(())
 /// # }
@@ -116,6 +128,30 @@ fn graph_growth(
 #[derive(Debug, Clone, Copy)]
 pub struct GraphGrowth {
     pub part_count: usize,
+
+    /// Seed of the random number generator, or `None` for a random seed.
+    pub seed: Option<u64>,
+}
+
+impl Default for GraphGrowth {
+    fn default() -> Self {
+        Self {
+            part_count: 2,
+            seed: Some(0),
+        }
+    }
+}
+
+impl crate::PartCount for GraphGrowth {
+    fn expected_part_count(&self) -> Option<usize> {
+        Some(self.part_count)
+    }
+}
+
+impl crate::Name for GraphGrowth {
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed("graph_growth")
+    }
 }
 
 impl<'a, W> crate::Partition<(CsMatView<'a, f64>, W)> for GraphGrowth
@@ -135,7 +171,107 @@ where
             weights.as_ref(),
             adjacency.view(),
             self.part_count,
+            self.seed,
         );
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Partition as _;
+    use sprs::CsMat;
+
+    /// A path of `len` vertices.
+    fn path(len: usize) -> CsMat<f64> {
+        let mut adjacency = CsMat::empty(sprs::CSR, len);
+        for i in 1..len {
+            adjacency.insert(i - 1, i, 1.0);
+            adjacency.insert(i, i - 1, 1.0);
+        }
+        adjacency
+    }
+
+    /// Two cliques of `size` vertices, linked by a single edge.
+    fn two_cliques(size: usize) -> CsMat<f64> {
+        let mut adjacency = CsMat::empty(sprs::CSR, 2 * size);
+        for offset in [0, size] {
+            for a in offset..offset + size {
+                for b in offset..offset + size {
+                    if a != b {
+                        adjacency.insert(a, b, 1.0);
+                    }
+                }
+            }
+        }
+        adjacency.insert(size - 1, size, 1.0);
+        adjacency.insert(size, size - 1, 1.0);
+        adjacency
+    }
+
+    #[test]
+    fn test_two_cliques() {
+        // Only the adjacency matrix is given, without any coordinates.
+        let adjacency = two_cliques(5);
+        let weights = [1.0; 10];
+
+        for seed in 0..20 {
+            let mut partition = [usize::MAX; 10];
+            GraphGrowth {
+                part_count: 2,
+                seed: Some(seed),
+            }
+            .partition(&mut partition, (adjacency.view(), &weights))
+            .unwrap();
+
+            let mut parts = partition.to_vec();
+            parts.sort();
+            parts.dedup();
+            assert_eq!(parts.len(), 2, "seed {seed}: {partition:?}");
+
+            // Parts are grown from their seed vertex, so they are connected.
+            for part in parts {
+                let start = partition.iter().position(|p| *p == part).unwrap();
+                let mut reached = vec![false; partition.len()];
+                reached[start] = true;
+                let mut stack = vec![start];
+                while let Some(vertex) = stack.pop() {
+                    for (neighbor, _) in adjacency.outer_view(vertex).unwrap().iter() {
+                        if partition[neighbor] == part && !reached[neighbor] {
+                            reached[neighbor] = true;
+                            stack.push(neighbor);
+                        }
+                    }
+                }
+                let size = partition.iter().filter(|p| **p == part).count();
+                let reached = reached.iter().filter(|r| **r).count();
+                assert_eq!(reached, size, "seed {seed}: {partition:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_same_seed_same_partition() {
+        let adjacency = path(100);
+        let weights = [1.0; 100];
+
+        let mut partition1 = [0; 100];
+        GraphGrowth {
+            part_count: 4,
+            seed: Some(42),
+        }
+        .partition(&mut partition1, (adjacency.view(), &weights))
+        .unwrap();
+
+        let mut partition2 = [0; 100];
+        GraphGrowth {
+            part_count: 4,
+            seed: Some(42),
+        }
+        .partition(&mut partition2, (adjacency.view(), &weights))
+        .unwrap();
+
+        assert_eq!(partition1, partition2);
+    }
+}
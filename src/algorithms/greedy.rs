@@ -1,5 +1,6 @@
 use super::Error;
 use num_traits::Zero;
+use std::borrow::Cow;
 use std::ops::AddAssign;
 
 /// Implementation of the greedy algorithm.
@@ -86,6 +87,18 @@ pub struct Greedy {
     pub part_count: usize,
 }
 
+impl crate::PartCount for Greedy {
+    fn expected_part_count(&self) -> Option<usize> {
+        Some(self.part_count)
+    }
+}
+
+impl crate::Name for Greedy {
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed("greedy")
+    }
+}
+
 impl<W> crate::Partition<W> for Greedy
 where
     W: IntoIterator,
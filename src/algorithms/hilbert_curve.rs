@@ -10,22 +10,34 @@
 //!
 //! The complexity of encoding a point is O(order)
 
+use crate::geometry::invalid_period;
+use crate::geometry::wrap_periodic;
+use crate::geometry::wrap_periodic_points;
+use crate::geometry::BoundingBox;
 use crate::geometry::OrientedBoundingBox;
 use crate::Average;
 use crate::Point2D;
 use crate::Point3D;
 use crate::PointND;
+use coupe_core::hilbert::encode_2d;
+use coupe_core::hilbert::encode_3d;
 use num_traits::AsPrimitive;
 use num_traits::NumAssign;
 use rayon::prelude::*;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::collections::HashSet;
 use std::fmt;
 use std::iter::Sum;
 
 /// Divide `points` into `n` parts of similar weights.
 ///
+/// A split is settled once the average part weights on both of its sides are
+/// within `tolerance` (relative to the total weight) of each other.
+///
 /// The result is an array of `n` elements, the ith element is the the ???est
 /// value of the ith part.
-fn weighted_quantiles<P, W>(points: &[P], weights: &[W], n: usize) -> Vec<P>
+fn weighted_quantiles<P, W>(points: &[P], weights: &[W], n: usize, tolerance: f64) -> Vec<P>
 where
     P: 'static + Copy + PartialOrd + Send + Sync,
     P: NumAssign + Average,
@@ -36,8 +48,6 @@ where
 {
     debug_assert!(n > 0);
 
-    const SPLIT_TOLERANCE: f64 = 0.05;
-
     let (min, max) = rayon::join(
         || *points.par_iter().min_by(crate::partial_cmp).unwrap(),
         || *points.par_iter().max_by(crate::partial_cmp).unwrap(),
@@ -104,8 +114,7 @@ where
                 }
                 let left_weight_ratio = left_weight.as_() / (p + 1) as f64;
                 let right_weight_ratio = (total_weight - left_weight).as_() / (n - p - 1) as f64;
-                if f64::abs(left_weight_ratio - right_weight_ratio) / total_weight.as_()
-                    < SPLIT_TOLERANCE
+                if f64::abs(left_weight_ratio - right_weight_ratio) / total_weight.as_() < tolerance
                 {
                     split.settled = true;
                     todo_split_count -= 1;
@@ -164,23 +173,35 @@ where
     splits.into_iter().map(|split| split.position).collect()
 }
 
-fn partition_indexed<const D: usize>(
+/// Split points into parts of similar weights, based on their index on a
+/// space-filling curve.
+///
+/// Points that share the same index cannot be separated, so this fails with
+/// the number of distinct indices when it is lower than `part_count`.
+/// Otherwise, the index of each point is returned.
+pub(super) fn partition_indexed<const D: usize>(
     partition: &mut [usize],
     points: &[PointND<D>],
     weights: &[f64],
     part_count: usize,
+    tolerance: f64,
     index_fn: impl Fn(&PointND<D>) -> u64 + Send + Sync,
-) {
+) -> Result<Vec<u64>, usize> {
     let span = tracing::info_span!("compute indices");
     let enter = span.enter();
 
     let hilbert_indices: Vec<u64> = points.par_iter().map(index_fn).collect();
 
+    let distinct_count = count_distinct(&hilbert_indices, part_count);
+    if distinct_count < part_count {
+        return Err(distinct_count);
+    }
+
     drop(enter);
     let span = tracing::info_span!("computing split positions");
     let enter = span.enter();
 
-    let split_positions = weighted_quantiles(&hilbert_indices, weights, part_count);
+    let split_positions = weighted_quantiles(&hilbert_indices, weights, part_count, tolerance);
 
     drop(enter);
     let span = tracing::info_span!("apply part ids");
@@ -188,22 +209,50 @@ fn partition_indexed<const D: usize>(
 
     partition
         .par_iter_mut()
-        .zip(hilbert_indices)
+        .zip(&hilbert_indices)
         .for_each(|(part, index)| {
-            let (Ok(part_id) | Err(part_id)) = split_positions.binary_search(&index);
+            let (Ok(part_id) | Err(part_id)) = split_positions.binary_search(index);
             *part = part_id;
         });
+
+    Ok(hilbert_indices)
 }
 
-/// Compute a mapping from [min; max] to [0; 2**order-1]
-fn segment_to_segment(min: f64, max: f64, order: usize) -> impl Fn(f64) -> u64 {
+/// The number of distinct values of `indices`, or `limit` if there are at
+/// least that many.
+///
+/// Counting stops as soon as `limit` values have been seen, so this is cheap
+/// when indices are diverse.
+fn count_distinct(indices: &[u64], limit: usize) -> usize {
+    let mut distinct = HashSet::new();
+    for index in indices {
+        if limit <= distinct.len() {
+            break;
+        }
+        distinct.insert(*index);
+    }
+    distinct.len()
+}
+
+/// Sort point indices along a space-filling curve, given the curve index of
+/// each point.
+///
+/// Points that share the same curve index are ordered by their own index.
+pub(super) fn curve_permutation(indices: &[u64]) -> Vec<usize> {
+    let mut permutation: Vec<usize> = (0..indices.len()).collect();
+    permutation.par_sort_unstable_by_key(|&i| (indices[i], i));
+    permutation
+}
+
+/// Compute a mapping from [min; max] to [0; cell_count-1]
+pub(super) fn segment_to_segment(min: f64, max: f64, cell_count: u64) -> impl Fn(f64) -> u64 {
     debug_assert!(min <= max);
 
     let width = max - min;
-    let n = (1_u64 << order) as f64;
+    let n = cell_count as f64;
     let mut f = n / width;
 
-    // Map max to (2**order-1) and avoid u64 overflow.
+    // Map max to (cell_count-1) and avoid u64 overflow.
     while n <= width * f {
         f = crate::nextafter(f, 0.0);
     }
@@ -214,17 +263,64 @@ fn segment_to_segment(min: f64, max: f64, order: usize) -> impl Fn(f64) -> u64 {
     }
 }
 
+/// Which axes of the given bounding box are flat, ie. thinner than a cell of
+/// its widest axis.
+///
+/// Points are not spread along flat axes, apart from rounding noise (e.g. when
+/// they are collinear), so these axes are left out of the encoding.
+pub(super) fn flat_axes<const D: usize>(aabb: &BoundingBox<D>, cell_count: u64) -> [bool; D] {
+    let extent = aabb.p_max - aabb.p_min;
+    let cell_width = extent.max() / cell_count as f64;
+    std::array::from_fn(|axis| extent[axis] <= cell_width)
+}
+
+/// Points that can be mapped onto the hilbert curve, that is 2D and 3D points.
+pub trait HilbertPoint: Sized {
+    /// The highest order for which curve indices fit in a `u64`.
+    const MAX_ORDER: u32;
+
+    /// Returns a function that maps points to their hilbert curve index.
+    ///
+    /// Panics if `points` is empty.
+    fn index_fn(points: &[Self], order: usize) -> impl Fn(&Self) -> u64 + Send + Sync;
+}
+
+impl HilbertPoint for Point2D {
+    const MAX_ORDER: u32 = 32;
+
+    fn index_fn(points: &[Self], order: usize) -> impl Fn(&Self) -> u64 + Send + Sync {
+        index_fn_2d(points, order)
+    }
+}
+
+impl HilbertPoint for Point3D {
+    const MAX_ORDER: u32 = 21;
+
+    fn index_fn(points: &[Self], order: usize) -> impl Fn(&Self) -> u64 + Send + Sync {
+        index_fn_3d(points, order)
+    }
+}
+
 /// Returns a function that maps 2D points to their hilbert curve index.
 ///
 /// Panics if `points` is empty.
 fn index_fn_2d(points: &[Point2D], order: usize) -> impl Fn(&Point2D) -> u64 {
     let mbr = OrientedBoundingBox::from_points(points).unwrap();
-    let aabb = mbr.aabb();
-    let x_mapping = segment_to_segment(aabb.p_min.x, aabb.p_max.x, order);
-    let y_mapping = segment_to_segment(aabb.p_min.y, aabb.p_max.y, order);
-    move |p| {
-        let p = mbr.obb_to_aabb(p);
-        encode_2d(x_mapping(p.x), y_mapping(p.y), order)
+    let aabb_index_fn = aabb_index_fn_2d(mbr.aabb(), order);
+    move |p| aabb_index_fn(&mbr.obb_to_aabb(p))
+}
+
+/// Returns a function that maps 2D points that lie in `aabb` to their hilbert
+/// curve index.
+fn aabb_index_fn_2d(aabb: &BoundingBox<2>, order: usize) -> impl Fn(&Point2D) -> u64 {
+    let flat = flat_axes(aabb, 1 << order);
+    let x_mapping = segment_to_segment(aabb.p_min.x, aabb.p_max.x, 1 << order);
+    let y_mapping = segment_to_segment(aabb.p_min.y, aabb.p_max.y, 1 << order);
+    move |p| match flat {
+        [false, false] => encode_2d(x_mapping(p.x), y_mapping(p.y), order),
+        [false, true] => x_mapping(p.x),
+        [true, false] => y_mapping(p.y),
+        [true, true] => 0,
     }
 }
 
@@ -234,183 +330,25 @@ fn index_fn_2d(points: &[Point2D], order: usize) -> impl Fn(&Point2D) -> u64 {
 fn index_fn_3d(points: &[Point3D], order: usize) -> impl Fn(&Point3D) -> u64 {
     let mbr = OrientedBoundingBox::from_points(points).unwrap();
     let aabb = mbr.aabb();
-    let x_mapping = segment_to_segment(aabb.p_min.x, aabb.p_max.x, order);
-    let y_mapping = segment_to_segment(aabb.p_min.y, aabb.p_max.y, order);
-    let z_mapping = segment_to_segment(aabb.p_min.z, aabb.p_max.z, order);
+    let flat = flat_axes(aabb, 1 << order);
+    let x_mapping = segment_to_segment(aabb.p_min.x, aabb.p_max.x, 1 << order);
+    let y_mapping = segment_to_segment(aabb.p_min.y, aabb.p_max.y, 1 << order);
+    let z_mapping = segment_to_segment(aabb.p_min.z, aabb.p_max.z, 1 << order);
     move |p| {
         let p = mbr.obb_to_aabb(p);
-        encode_3d(x_mapping(p.x), y_mapping(p.y), z_mapping(p.z), order)
-    }
-}
-
-/// Slower version of [encode_2d], to build the lookup table for [encode_2d].
-///
-/// This version takes the initial configuration as argument and also returns
-/// the final configuration.
-///
-/// Taken from Marot, Célestin. "Parallel tetrahedral mesh generation." Prom:
-/// Remacle, Jean-François <http://hdl.handle.net/2078.1/240626>.
-///
-/// TODO: once const-fn are more mature, take the "order" argument into account,
-/// though it is only set to 6 for the purpose of building the lookup table.
-const fn encode_2d_slow(zorder: u64, _order: usize, mut config: usize) -> (u64, usize) {
-    // BASE_PATTERN[i][j] is the hilbert index given:
-    // - i: the current configuration,
-    // - j: the quadrant in row-major order.
-    const BASE_PATTERN: [[u64; 4]; 4] = [
-        [0, 1, 3, 2], // config 0 = [1,2]   )
-        [0, 3, 1, 2], // config 1 = [2,1]   n
-        [2, 3, 1, 0], // config 2 = [-1,-2] (
-        [2, 1, 3, 0], // config 3 = [-2,-1] U
-    ];
-    // CONFIGURATION[i][j] is the next configuration given:
-    // - i: the current configuration,
-    // - j: the quadrant in row-major order.
-    const CONFIGURATION: [[usize; 4]; 4] = [
-        [1, 0, 3, 0], // ) => U)
-        //                    n)
-        [0, 2, 1, 1], // n => nn
-        //                    )(
-        [2, 1, 2, 3], // ( => (U
-        //                    (n
-        [3, 3, 0, 2], // U => )(
-                      //      UU
-    ];
-
-    let mut hilbert = 0;
-
-    // TODO replace unrolled loop by "for i in (0..order).rev()" once for loops
-    // are allowed in const fns.
-    let mut i = 5;
-    let quadrant = (zorder >> (2 * i)) as usize & 3;
-    hilbert = (hilbert << 2) | BASE_PATTERN[config][quadrant];
-    config = CONFIGURATION[config][quadrant];
-    i -= 1;
-    let quadrant = (zorder >> (2 * i)) as usize & 3;
-    hilbert = (hilbert << 2) | BASE_PATTERN[config][quadrant];
-    config = CONFIGURATION[config][quadrant];
-    i -= 1;
-    let quadrant = (zorder >> (2 * i)) as usize & 3;
-    hilbert = (hilbert << 2) | BASE_PATTERN[config][quadrant];
-    config = CONFIGURATION[config][quadrant];
-    i -= 1;
-    let quadrant = (zorder >> (2 * i)) as usize & 3;
-    hilbert = (hilbert << 2) | BASE_PATTERN[config][quadrant];
-    config = CONFIGURATION[config][quadrant];
-    i -= 1;
-    let quadrant = (zorder >> (2 * i)) as usize & 3;
-    hilbert = (hilbert << 2) | BASE_PATTERN[config][quadrant];
-    config = CONFIGURATION[config][quadrant];
-    i -= 1;
-    let quadrant = (zorder >> (2 * i)) as usize & 3;
-    hilbert = (hilbert << 2) | BASE_PATTERN[config][quadrant];
-    config = CONFIGURATION[config][quadrant];
-
-    (hilbert, config)
-}
-
-fn encode_2d(x: u64, y: u64, order: usize) -> u64 {
-    debug_assert!(order < 64);
-    debug_assert!(
-        x < (1 << order),
-        "Cannot encode the point {:?} on an hilbert curve of order {} because x >= 2^order.",
-        (x, y),
-        order,
-    );
-    debug_assert!(
-        y < (1 << order),
-        "Cannot encode the point {:?} on an hilbert curve of order {} because y >= 2^order.",
-        (x, y),
-        order,
-    );
-
-    const LUT: [u16; 16_384] = {
-        let mut lut = [0; 16_384];
-        let mut i: usize = 0;
-        while i < 16_384 {
-            let zorder = (i & 0xfff) as u64;
-            let config = i >> 12;
-            let (hilbert_order, config) = encode_2d_slow(zorder, 6, config);
-            lut[i] = (config << 12) as u16 | hilbert_order as u16;
-            i += 1;
+        match flat {
+            [false, false, false] => {
+                encode_3d(x_mapping(p.x), y_mapping(p.y), z_mapping(p.z), order)
+            }
+            [true, false, false] => encode_2d(y_mapping(p.y), z_mapping(p.z), order),
+            [false, true, false] => encode_2d(x_mapping(p.x), z_mapping(p.z), order),
+            [false, false, true] => encode_2d(x_mapping(p.x), y_mapping(p.y), order),
+            [false, true, true] => x_mapping(p.x),
+            [true, false, true] => y_mapping(p.y),
+            [true, true, false] => z_mapping(p.z),
+            [true, true, true] => 0,
         }
-        lut
-    };
-
-    let zorder = unsafe {
-        std::arch::x86_64::_pdep_u64(x, 0x5555_5555_5555_5555 << 1)
-            | std::arch::x86_64::_pdep_u64(y, 0x5555_5555_5555_5555)
-    };
-
-    let mut config: u16 = 0;
-    let mut hilbert: u64 = 0;
-    let mut shift: i64 = 2 * order as i64 - 12;
-    while shift > 0 {
-        config = LUT[((config & !0xfff) | ((zorder >> shift) & 0xfff) as u16) as usize];
-        hilbert = (hilbert << 12) | (config & 0xfff) as u64;
-        shift -= 12;
     }
-
-    config = LUT[((config & !0xfff) | ((zorder << (-shift) as u64) & 0xfff) as u16) as usize];
-    hilbert = (hilbert << 12) | (config & 0xfff) as u64;
-
-    hilbert >> -shift
-}
-
-fn encode_3d(x: u64, y: u64, z: u64, order: usize) -> u64 {
-    debug_assert!(order < 64);
-    debug_assert!(
-        x < (1 << order),
-        "Cannot encode the point {:?} on an hilbert curve of order {} because x >= 2^order.",
-        (x, y, z),
-        order,
-    );
-    debug_assert!(
-        y < (1 << order),
-        "Cannot encode the point {:?} on an hilbert curve of order {} because y >= 2^order.",
-        (x, y, z),
-        order,
-    );
-    debug_assert!(
-        z < (1 << order),
-        "Cannot encode the point {:?} on an hilbert curve of order {} because z >= 2^order.",
-        (x, y, z),
-        order,
-    );
-
-    #[allow(clippy::unusual_byte_groupings)]
-    #[rustfmt::skip]
-    const LUT: [u8; 96] = [
-        0b0110_000, 0b0100_001, 0b0011_011, 0b0100_010, 0b0101_111, 0b1001_110, 0b0011_100, 0b1001_101,
-        0b1000_010, 0b0011_101, 0b0110_011, 0b0110_100, 0b1000_001, 0b0011_110, 0b1001_000, 0b0111_111,
-        0b1001_100, 0b1011_111, 0b1001_011, 0b0011_000, 0b0110_101, 0b0110_110, 0b1010_010, 0b1010_001,
-        0b0010_010, 0b0000_011, 0b0010_001, 0b1010_000, 0b0111_101, 0b0000_100, 0b0111_110, 0b0001_111,
-        0b0000_000, 0b0111_011, 0b1000_111, 0b0111_100, 0b0110_001, 0b0110_010, 0b1010_110, 0b1010_101,
-        0b1010_100, 0b1010_011, 0b0000_101, 0b1011_010, 0b1001_111, 0b0111_000, 0b0000_110, 0b1011_001,
-        0b0100_000, 0b0010_111, 0b0000_001, 0b1011_110, 0b0001_011, 0b0001_100, 0b0000_010, 0b1011_101,
-        0b0101_010, 0b0101_001, 0b0001_101, 0b0001_110, 0b0100_011, 0b1011_000, 0b0100_100, 0b0011_111,
-        0b1011_100, 0b0100_101, 0b1010_111, 0b0100_110, 0b1011_011, 0b1001_010, 0b0001_000, 0b1001_001,
-        0b0101_110, 0b0101_101, 0b0001_001, 0b0001_010, 0b0000_111, 0b0010_100, 0b1000_000, 0b0010_011,
-        0b1000_110, 0b0011_001, 0b0100_111, 0b0010_000, 0b1000_101, 0b0011_010, 0b0101_100, 0b0101_011,
-        0b0010_110, 0b0110_111, 0b0010_101, 0b1000_100, 0b0111_001, 0b0101_000, 0b0111_010, 0b1000_011,
-    ];
-
-    let zorder = unsafe {
-        std::arch::x86_64::_pdep_u64(x, 0x9249_2492_4924_9249 << 2)
-            | std::arch::x86_64::_pdep_u64(y, 0x9249_2492_4924_9249 << 1)
-            | std::arch::x86_64::_pdep_u64(z, 0x9249_2492_4924_9249)
-    };
-
-    let mut config = 0;
-    let mut hilbert = 0;
-
-    for i in (0..order).rev() {
-        config = LUT[(config | ((zorder >> (3 * i)) & 7)) as usize] as u64;
-        hilbert = (hilbert << 3) | (config & 7);
-        config &= !7;
-    }
-
-    hilbert
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -418,6 +356,18 @@ fn encode_3d(x: u64, y: u64, z: u64, order: usize) -> u64 {
 pub enum Error {
     /// Invalid space filling curve order.
     InvalidOrder { max: u32, actual: u32 },
+
+    /// Points span fewer distinct cells of the curve than there are parts.
+    TooManyParts {
+        part_count: usize,
+        cell_count: usize,
+    },
+
+    /// A point has a NaN or infinite coordinate.
+    NonFiniteCoordinate { index: usize },
+
+    /// The period of an axis is empty or not finite.
+    InvalidPeriod { axis: usize },
 }
 
 impl fmt::Display for Error {
@@ -430,6 +380,19 @@ impl fmt::Display for Error {
                     actual, max
                 )
             }
+            Error::TooManyParts {
+                part_count,
+                cell_count,
+            } => write!(
+                f,
+                "cannot make {part_count} parts out of {cell_count} distinct hilbert curve cells, try a higher order",
+            ),
+            Error::NonFiniteCoordinate { index } => {
+                write!(f, "point {index} has a non-finite coordinate")
+            }
+            Error::InvalidPeriod { axis } => {
+                write!(f, "the period of axis {axis} is empty or not finite")
+            }
         }
     }
 }
@@ -448,6 +411,13 @@ impl std::error::Error for Error {}
 ///
 /// The complexity of encoding a point is `O(order)`.
 ///
+/// The curve is cut so that part weights are balanced: cuts are moved along
+/// the curve until the weights of the parts on both sides of each cut are
+/// within `tolerance` of each other, relative to the total weight.  Points
+/// that share the same cell cannot be separated, hence a coarse `order` may
+/// prevent reaching `tolerance`, and partitioning fails with
+/// [`Error::TooManyParts`] when points span fewer cells than `part_count`.
+///
 /// # Example
 ///
 /// ```rust
@@ -496,10 +466,30 @@ impl std::error::Error for Error {}
 /// Deveci, M. et al., 2016. Multi-Jagged: A Scalable Parallel Spatial
 /// Partitioning Algorithm. *IEEE Transactions on Parallel and Distributed
 /// Systems*, 27(3), pp. 803–817. <https://doi.org/10.1109/TPDS.2015.2412545>
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub struct HilbertCurve {
     pub part_count: usize,
+
+    /// The resolution of the curve, or 0 to derive it from the number of
+    /// points and parts, see [`HilbertCurve::auto_order`].
     pub order: u32,
+
+    /// Tolerance on the relative weight difference between the two sides of
+    /// each cut.
+    pub tolerance: f64,
+
+    /// The period of each axis along which space wraps around, indexed by
+    /// axis, for meshes with periodic boundary conditions.
+    ///
+    /// Coordinates along an axis with a `Some((min, max))` period are mapped
+    /// into one period before being hashed, so that points that are actually
+    /// neighbors end up in the same parts.  The period is shifted to start
+    /// where points are the sparsest, so that parts may span the boundary of
+    /// the domain.  By default, no axis is periodic.
+    ///
+    /// Periods must be non-empty and finite, otherwise partitioning fails
+    /// with [`Error::InvalidPeriod`].
+    pub periodic: Vec<Option<(f64, f64)>>,
 }
 
 impl Default for HilbertCurve {
@@ -507,22 +497,108 @@ impl Default for HilbertCurve {
         Self {
             part_count: 2,
             order: 12,
+            tolerance: 0.05,
+            periodic: Vec::new(),
         }
     }
 }
 
-impl<W> crate::Partition<(&[Point2D], W)> for HilbertCurve
-where
-    W: AsRef<[f64]>,
-{
-    type Metadata = ();
-    type Error = Error;
+/// The curve order used when [`HilbertCurve::order`] is 0: the lowest one that
+/// gives at least 16 cells per point, and per part, capped to `max_order`.
+fn order_for(dimension: usize, point_count: usize, part_count: usize, max_order: u32) -> u32 {
+    let min_cell_count = 16 * usize::max(1, usize::max(point_count, part_count));
+    let bit_count = usize::BITS - (min_cell_count - 1).leading_zeros();
+    u32::clamp(bit_count.div_ceil(dimension as u32), 1, max_order)
+}
 
-    fn partition(
-        &mut self,
-        part_ids: &mut [usize],
-        (points, weights): (&[Point2D], W),
-    ) -> Result<Self::Metadata, Self::Error> {
+impl HilbertCurve {
+    /// A partitioner into `part_count` parts that chooses its
+    /// [`order`][HilbertCurve::order] from the input.
+    ///
+    /// Too low an order puts distinct points in the same cell, which prevents
+    /// balancing parts, and too high an order wastes work.  With this
+    /// constructor, the order is chosen when partitioning, so that the curve
+    /// has a few cells per point, which is enough to separate them unless
+    /// they are very unevenly spread.
+    ///
+    /// [`partition_streaming`][HilbertCurve::partition_streaming] does not
+    /// know the number of points in advance, and uses the default order of
+    /// 12 instead.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), coupe::HilbertCurveError> {
+    /// use coupe::Partition as _;
+    ///
+    /// let points = coupe::mesh::grid_2d(10, 10, 1.0);
+    /// let weights = vec![1.0; points.len()];
+    /// let mut partition = vec![0; points.len()];
+    ///
+    /// coupe::HilbertCurve::auto_order(4).partition(&mut partition, (&*points, &weights))?;
+    ///
+    /// for part in 0..4 {
+    ///     assert_eq!(partition.iter().filter(|p| **p == part).count(), 25);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn auto_order(part_count: usize) -> Self {
+        Self {
+            part_count,
+            order: 0,
+            ..Self::default()
+        }
+    }
+
+    /// Partitions points that are streamed rather than held in memory.
+    ///
+    /// `points` is called twice, and must yield the same `(point, weight)`
+    /// pairs each time, for example by reading them from a file.  The first
+    /// pass computes the weight of each cell of the curve, from which the curve
+    /// is cut.  The second pass is done lazily by the returned iterator, which
+    /// yields the index of each point in the stream along with its part ID.
+    /// Hence, memory usage is bounded by the number of non-empty cells, which
+    /// is at most `4^order`, rather than by the number of points.
+    ///
+    /// Unlike [`partition`][crate::Partition::partition], which aligns the
+    /// curve with the inertia axes of the points, the curve is aligned with
+    /// `bounds`, which must contain all the points.  Points outside of
+    /// `bounds` are moved to its closest side.  Likewise, periodic axes keep
+    /// their period as given, since finding a better place for its start
+    /// would need all points at once.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), coupe::HilbertCurveError> {
+    /// use coupe::BoundingBox;
+    /// use coupe::Point2D;
+    ///
+    /// let stream = || (0..100).map(|i| (Point2D::new(i as f64, 0.0), 1.0));
+    /// let bounds = BoundingBox {
+    ///     p_min: Point2D::new(0.0, 0.0),
+    ///     p_max: Point2D::new(99.0, 1.0),
+    /// };
+    ///
+    /// let partition: Vec<(usize, usize)> = coupe::HilbertCurve { part_count: 2, ..Default::default() }
+    ///     .partition_streaming(stream, &bounds)?
+    ///     .collect();
+    ///
+    /// assert_eq!(partition[0], (0, 0));
+    /// assert_eq!(partition[99], (99, 1));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn partition_streaming<F, I>(
+        &self,
+        mut points: F,
+        bounds: &BoundingBox<2>,
+    ) -> Result<impl Iterator<Item = (usize, usize)>, Error>
+    where
+        F: FnMut() -> I,
+        I: IntoIterator<Item = (Point2D, f64)>,
+    {
         const MAX_ORDER: u32 = 32;
         if self.order > MAX_ORDER {
             return Err(Error::InvalidOrder {
@@ -530,23 +606,153 @@ where
                 actual: self.order,
             });
         }
+        if let Some(axis) = invalid_period(&self.periodic) {
+            return Err(Error::InvalidPeriod { axis });
+        }
+        let order = if self.order == 0 {
+            Self::default().order
+        } else {
+            self.order
+        };
+
+        let aabb_index_fn = aabb_index_fn_2d(bounds, order as usize);
+        let (p_min, p_max) = (bounds.p_min, bounds.p_max);
+        let periodic = self.periodic.clone();
+        let index_fn = move |p: &Point2D| {
+            let p = wrap_periodic(*p, &periodic);
+            aabb_index_fn(&p.sup(&p_min).inf(&p_max))
+        };
+
+        let mut cell_weights: BTreeMap<u64, f64> = BTreeMap::new();
+        for (point, weight) in points() {
+            *cell_weights.entry(index_fn(&point)).or_default() += weight;
+        }
+
+        let split_positions = if cell_weights.is_empty() {
+            Vec::new()
+        } else if cell_weights.len() < self.part_count {
+            return Err(Error::TooManyParts {
+                part_count: self.part_count,
+                cell_count: cell_weights.len(),
+            });
+        } else {
+            let (cells, weights): (Vec<u64>, Vec<f64>) = cell_weights.into_iter().unzip();
+            weighted_quantiles(&cells, &weights, self.part_count, self.tolerance)
+        };
+
+        Ok(points()
+            .into_iter()
+            .enumerate()
+            .map(move |(i, (point, _weight))| {
+                let (Ok(part) | Err(part)) = split_positions.binary_search(&index_fn(&point));
+                (i, part)
+            }))
+    }
+
+    /// Partitions points like [`partition`][crate::Partition::partition],
+    /// and also returns the order of the points along the curve.
+    ///
+    /// The first vector holds the part ID of each point.  The second one
+    /// maps positions along the curve to indices in `points`, such that the
+    /// points of each part are contiguous in it.  Reordering point data with
+    /// it once improves memory locality, both within and across parts.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), coupe::HilbertCurveError> {
+    /// use coupe::Point2D;
+    ///
+    /// let points: Vec<Point2D> = (0..16)
+    ///     .map(|i| Point2D::new((i % 4) as f64, (i / 4) as f64))
+    ///     .collect();
+    /// let weights = vec![1.0; points.len()];
+    ///
+    /// let (partition, permutation) = coupe::HilbertCurve { part_count: 4, ..Default::default() }
+    ///     .partition_with_permutation(&points, &weights)?;
+    ///
+    /// let reordered: Vec<usize> = permutation.iter().map(|&i| partition[i]).collect();
+    /// assert!(reordered.windows(2).all(|w| w[0] <= w[1]));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn partition_with_permutation<const D: usize, W>(
+        &self,
+        points: &[PointND<D>],
+        weights: W,
+    ) -> Result<(Vec<usize>, Vec<usize>), Error>
+    where
+        PointND<D>: HilbertPoint,
+        W: AsRef<[f64]>,
+    {
+        let mut partition = vec![0; points.len()];
+        let indices = self.partition_indices(&mut partition, points, weights.as_ref())?;
+        Ok((partition, curve_permutation(&indices)))
+    }
+
+    /// Fills `part_ids` and returns the curve index of each point.
+    fn partition_indices<const D: usize>(
+        &self,
+        part_ids: &mut [usize],
+        points: &[PointND<D>],
+        weights: &[f64],
+    ) -> Result<Vec<u64>, Error>
+    where
+        PointND<D>: HilbertPoint,
+    {
+        let max_order = <PointND<D> as HilbertPoint>::MAX_ORDER;
+        if self.order > max_order {
+            return Err(Error::InvalidOrder {
+                max: max_order,
+                actual: self.order,
+            });
+        }
+        if let Some(axis) = invalid_period(&self.periodic) {
+            return Err(Error::InvalidPeriod { axis });
+        }
         if part_ids.is_empty() {
-            return Ok(());
+            return Ok(Vec::new());
+        }
+        if let Some(index) = super::first_non_finite(points) {
+            return Err(Error::NonFiniteCoordinate { index });
         }
-        let index_fn = index_fn_2d(points, self.order as usize);
+        let order = if self.order == 0 {
+            order_for(D, points.len(), self.part_count, max_order)
+        } else {
+            self.order
+        };
+        let points = &*wrap_periodic_points(points, &self.periodic);
+        let index_fn = PointND::<D>::index_fn(points, order as usize);
         partition_indexed(
             part_ids,
             points,
-            weights.as_ref(),
+            weights,
             self.part_count,
+            self.tolerance,
             index_fn,
-        );
-        Ok(())
+        )
+        .map_err(|cell_count| Error::TooManyParts {
+            part_count: self.part_count,
+            cell_count,
+        })
     }
 }
 
-impl<W> crate::Partition<(&[Point3D], W)> for HilbertCurve
+impl crate::PartCount for HilbertCurve {
+    fn expected_part_count(&self) -> Option<usize> {
+        Some(self.part_count)
+    }
+}
+
+impl crate::Name for HilbertCurve {
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed("hilbert_curve")
+    }
+}
+
+impl<const D: usize, W> crate::Partition<(&[PointND<D>], W)> for HilbertCurve
 where
+    PointND<D>: HilbertPoint,
     W: AsRef<[f64]>,
 {
     type Metadata = ();
@@ -555,26 +761,9 @@ where
     fn partition(
         &mut self,
         part_ids: &mut [usize],
-        (points, weights): (&[Point3D], W),
+        (points, weights): (&[PointND<D>], W),
     ) -> Result<Self::Metadata, Self::Error> {
-        const MAX_ORDER: u32 = 21;
-        if self.order > MAX_ORDER {
-            return Err(Error::InvalidOrder {
-                max: MAX_ORDER,
-                actual: self.order,
-            });
-        }
-        if part_ids.is_empty() {
-            return Ok(());
-        }
-        let index_fn = index_fn_3d(points, self.order as usize);
-        partition_indexed(
-            part_ids,
-            points,
-            weights.as_ref(),
-            self.part_count,
-            index_fn,
-        );
+        self.partition_indices(part_ids, points, weights.as_ref())?;
         Ok(())
     }
 }
@@ -585,7 +774,7 @@ mod tests {
 
     #[test]
     fn test_segment_to_segment() {
-        let mapping = segment_to_segment(0.0, 8.0, 3);
+        let mapping = segment_to_segment(0.0, 8.0, 8);
 
         assert_eq!(mapping(0.0), 0);
         assert_eq!(mapping(1.0), 0);
@@ -607,58 +796,143 @@ mod tests {
     }
 
     #[test]
-    fn test_encode_2d() {
-        let points = vec![(0, 0), (1, 1), (1, 0), (0, 1)];
+    fn test_partition_streaming() {
+        // Pseudo-random points, regenerated on each pass.
+        let stream = || {
+            (0..5000_u64).map(|i| {
+                let x = (i * 7919 % 1000) as f64 / 10.0;
+                let y = (i * 104_729 % 997) as f64 / 20.0;
+                (Point2D::new(x, y), (i % 3 + 1) as f64)
+            })
+        };
+        let (points, weights): (Vec<Point2D>, Vec<f64>) = stream().unzip();
+        let bounds = BoundingBox::from_points(points.iter().cloned()).unwrap();
+        let algo = crate::HilbertCurve {
+            part_count: 7,
+            order: 10,
+            ..Default::default()
+        };
 
-        let indices: Vec<_> = points
-            .into_iter()
-            .map(|(x, y)| encode_2d(x, y, 1))
-            .collect();
-        assert_eq!(indices, vec![0, 2, 3, 1]);
-
-        let points = vec![
-            (0, 0),
-            (1, 0),
-            (1, 1),
-            (0, 1),
-            (0, 2),
-            (0, 3),
-            (1, 3),
-            (1, 2),
-            (2, 2),
-            (2, 3),
-            (3, 3),
-            (3, 2),
-            (3, 1),
-            (2, 1),
-            (2, 0),
-            (3, 0),
-        ];
+        let streamed: Vec<(usize, usize)> =
+            algo.partition_streaming(stream, &bounds).unwrap().collect();
 
-        let expected: Vec<_> = (0..16).collect();
-        let indices: Vec<_> = points
-            .into_iter()
-            .map(|(x, y)| encode_2d(x, y, 2))
+        let mut expected = vec![0; points.len()];
+        partition_indexed(
+            &mut expected,
+            &points,
+            &weights,
+            algo.part_count,
+            algo.tolerance,
+            aabb_index_fn_2d(&bounds, algo.order as usize),
+        )
+        .unwrap();
+
+        assert_eq!(streamed.len(), points.len());
+        for (i, (index, part)) in streamed.into_iter().enumerate() {
+            assert_eq!(index, i);
+            assert_eq!(part, expected[i], "point {i}");
+        }
+    }
+
+    #[test]
+    fn test_order_for() {
+        assert_eq!(order_for(2, 1_000_000, 16, 32), 12);
+        assert_eq!(order_for(3, 1_000_000, 16, 21), 8);
+        assert_eq!(order_for(2, 3, 100, 32), 6);
+        assert_eq!(order_for(2, 0, 0, 32), 2);
+        assert_eq!(order_for(3, usize::MAX / 32, 2, 8), 8);
+    }
+
+    #[test]
+    fn test_auto_order() {
+        use crate::Partition as _;
+
+        let points = crate::mesh::grid_3d(10, 10, 10, 0.1);
+        let weights = vec![1.0; points.len()];
+        let mut partition = vec![usize::MAX; points.len()];
+
+        let mut algo = crate::HilbertCurve {
+            tolerance: 1e-6,
+            ..crate::HilbertCurve::auto_order(8)
+        };
+        algo.partition(&mut partition, (&*points, &weights))
+            .unwrap();
+        assert_eq!(algo.order, 0);
+
+        let mut part_weights = [0; 8];
+        for part in &partition {
+            part_weights[*part] += 1;
+        }
+        assert_eq!(part_weights, [125; 8]);
+    }
+
+    #[test]
+    fn test_too_many_parts() {
+        use crate::Partition as _;
+
+        // 16 points on a 4x4 grid, but an order of 1 only gives 4 cells.
+        let points: Vec<Point2D> = (0..16)
+            .map(|i| Point2D::new((i % 4) as f64, (i / 4) as f64))
             .collect();
-        assert_eq!(indices, expected);
+        let weights = vec![1.0; 16];
+        let mut partition = vec![0; 16];
+
+        let mut algo = crate::HilbertCurve {
+            part_count: 8,
+            order: 1,
+            ..Default::default()
+        };
+        let err = algo
+            .partition(&mut partition, (&*points, &weights))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::TooManyParts {
+                part_count: 8,
+                cell_count: 4,
+            },
+        ));
+
+        algo.order = 2;
+        algo.partition(&mut partition, (&*points, &weights))
+            .unwrap();
     }
 
     #[test]
-    fn test_encode_2d_slow() {
-        const ORDER: usize = 6; // encode_2d_slow requires ORDER==6.
-        for x in 0..(1 << 6) {
-            for y in 0..(1 << 6) {
-                let fast_encode = encode_2d(x, y, ORDER);
-                let slow_encode = {
-                    let zorder = unsafe {
-                        std::arch::x86_64::_pdep_u64(x, 0x5555_5555_5555_5555 << 1)
-                            | std::arch::x86_64::_pdep_u64(y, 0x5555_5555_5555_5555)
-                    };
-                    let config = 0;
-                    encode_2d_slow(zorder, ORDER, config).0
-                };
-                assert_eq!(fast_encode, slow_encode);
+    fn test_hilbert_degenerate() {
+        use crate::Partition;
+
+        // All points lie on a line, in shuffled order. For the slanted line,
+        // rounding errors give the bounding box a tiny, non-zero width.
+        let ys: Vec<f64> = (0..100).map(|i| ((i * 37) % 100) as f64 * 0.1).collect();
+        for slope in [0.0, 0.3] {
+            let points: Vec<Point2D> = ys
+                .iter()
+                .map(|y| Point2D::new(1.0 + slope * y, *y))
+                .collect();
+            let weights = vec![1.0; points.len()];
+            let mut partition = vec![0; points.len()];
+
+            crate::HilbertCurve {
+                part_count: 5,
+                order: 12,
+                ..Default::default()
+            }
+            .partition(&mut partition, (&*points, &weights))
+            .unwrap();
+
+            let mut part_weights = [0; 5];
+            for part in &partition {
+                part_weights[*part] += 1;
             }
+            assert_eq!(part_weights, [20; 5]);
+
+            // Parts are made of contiguous segments of the line.
+            let mut by_y: Vec<(f64, usize)> = ys.iter().cloned().zip(partition).collect();
+            by_y.sort_by(|(y1, _), (y2, _)| f64::total_cmp(y1, y2));
+            let mut parts: Vec<usize> = by_y.into_iter().map(|(_, part)| part).collect();
+            parts.dedup();
+            assert_eq!(parts.len(), 5);
         }
     }
 
@@ -691,6 +965,7 @@ mod tests {
         crate::HilbertCurve {
             part_count: 8,
             order: 21,
+            ..Default::default()
         }
         .partition(&mut partition, (points, weights))
         .unwrap();
@@ -708,4 +983,82 @@ mod tests {
         partition.dedup();
         assert_eq!(partition.len(), 8);
     }
+
+    #[test]
+    fn test_partition_with_permutation() {
+        let points: Vec<Point3D> = (0..512)
+            .map(|i| Point3D::new((i * 3 % 8) as f64, (i / 8 % 8) as f64, (i / 64) as f64))
+            .collect();
+        let weights = vec![1.0; points.len()];
+        let algo = HilbertCurve {
+            part_count: 6,
+            order: 4,
+            ..Default::default()
+        };
+
+        let (partition, permutation) = algo.partition_with_permutation(&points, &weights).unwrap();
+
+        let mut expected = vec![0; points.len()];
+        crate::Partition::partition(&mut algo.clone(), &mut expected, (&points[..], &weights))
+            .unwrap();
+        assert_eq!(partition, expected);
+
+        let mut sorted = permutation.clone();
+        sorted.sort();
+        assert_eq!(sorted, (0..points.len()).collect::<Vec<_>>());
+
+        // applying the permutation keeps parts contiguous
+        let reordered: Vec<usize> = permutation.iter().map(|&i| partition[i]).collect();
+        assert!(reordered.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(reordered[points.len() - 1], 5);
+    }
+
+    #[test]
+    fn test_count_distinct() {
+        assert_eq!(count_distinct(&[], 3), 0);
+        assert_eq!(count_distinct(&[4, 4, 1, 4, 1], 3), 2);
+        assert_eq!(count_distinct(&[4, 4, 1, 4, 1], 2), 2);
+        assert_eq!(count_distinct(&[1, 2, 3, 4, 5, 6], 3), 3);
+    }
+
+    #[test]
+    fn test_hilbert_deterministic() {
+        use crate::Partition;
+
+        // 1000 points cycling over the 91 nodes of a 7x13 grid, so that each
+        // Hilbert index is shared by about 11 points.  Ties must be broken
+        // the same way on every run, whatever the thread scheduling.
+        let points: Vec<Point2D> = (0..1000)
+            .map(|i| Point2D::new((i % 7) as f64, (i % 13) as f64))
+            .collect();
+        let weights = vec![1.0; points.len()];
+
+        let mut algo = crate::HilbertCurve {
+            part_count: 9,
+            order: 2,
+            ..Default::default()
+        };
+        let mut first = vec![0; points.len()];
+        algo.partition(&mut first, (&*points, &weights)).unwrap();
+        for _ in 0..8 {
+            let mut other = vec![0; points.len()];
+            algo.partition(&mut other, (&*points, &weights)).unwrap();
+            assert_eq!(first, other);
+        }
+    }
+
+    #[test]
+    fn test_periodic_seam() {
+        use crate::Partition as _;
+
+        let err = crate::geometry::check_periodic_seam(|partition, points, weights, periodic| {
+            crate::HilbertCurve {
+                part_count: 2,
+                periodic,
+                ..Default::default()
+            }
+            .partition(partition, (points, weights))
+        });
+        assert!(matches!(err, Error::InvalidPeriod { axis: 0 }));
+    }
 }
@@ -3,7 +3,10 @@
 //! Charilaos Tzovas and Henning Meyerhenke (2018, University of Cologne)
 
 use crate::geometry;
-use crate::geometry::OrientedBoundingBox;
+use crate::geometry::par_bounding_box;
+use crate::geometry::spatial::KdTree;
+use crate::geometry::BoundingSphere;
+use crate::topology::Topology;
 use crate::PointND;
 use nalgebra::allocator::Allocator;
 use nalgebra::ArrayStorage;
@@ -13,7 +16,11 @@ use nalgebra::DimDiff;
 use nalgebra::DimSub;
 use rayon::prelude::*;
 
+use std::borrow::Cow;
 use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+use std::iter;
 use std::sync::atomic::{self, AtomicPtr};
 
 use itertools::iproduct;
@@ -24,7 +31,44 @@ use itertools::Itertools;
 /// for the k-means algorithm and not a partition id
 type ClusterId = usize;
 
+/// The distance used by [KMeans] to assign points to clusters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Metric {
+    /// The L2 distance: `sqrt(sum((a_i - b_i)^2))`.
+    #[default]
+    Euclidean,
+
+    /// The L1 distance: `sum(|a_i - b_i|)`.
+    ///
+    /// Cluster centers are then computed as the coordinate-wise median of their
+    /// points, which minimizes the sum of L1 distances.  This takes precedence
+    /// over [KMeans::geometric_median].
+    Manhattan,
+
+    /// The L∞ distance: `max(|a_i - b_i|)`.
+    Chebyshev,
+}
+
+impl Metric {
+    /// The distance between `a` and `b` according to this metric.
+    pub fn distance<const D: usize>(self, a: &PointND<D>, b: &PointND<D>) -> f64 {
+        match self {
+            Metric::Euclidean => (a - b).norm(),
+            Metric::Manhattan => (a - b).lp_norm(1),
+            Metric::Chebyshev => (a - b).amax(),
+        }
+    }
+}
+
+/// The weight difference between the heaviest and the lightest clusters, in
+/// `%` of the mean cluster weight.
+///
+/// Being relative, it does not depend on the scale of the weights.
 fn imbalance(weights: &[f64]) -> f64 {
+    let mean = weights.par_iter().sum::<f64>() / weights.len() as f64;
+    if mean <= 0.0 || mean.is_nan() {
+        return 0.;
+    }
     match (
         weights
             .par_iter()
@@ -33,16 +77,128 @@ fn imbalance(weights: &[f64]) -> f64 {
             .par_iter()
             .max_by(|x, y| x.partial_cmp(y).unwrap_or(Ordering::Equal)),
     ) {
-        (Some(min), Some(max)) => max - min,
+        (Some(min), Some(max)) => (max - min) / mean * 100.,
         _ => 0.,
     }
 }
 
+/// The weight of each cluster, for each criterion, as a criterion-major array.
+fn cluster_weights(
+    assignments: &[usize],
+    criteria: &[&[f64]],
+    center_ids: &[ClusterId],
+) -> Vec<Vec<f64>> {
+    criteria
+        .iter()
+        .map(|weights| {
+            center_ids
+                .par_iter()
+                .map(|center_id| {
+                    assignments
+                        .par_iter()
+                        .zip(*weights)
+                        .filter(|(assignment, _)| *assignment == center_id)
+                        .map(|(_, weight)| *weight)
+                        .sum::<f64>()
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// The share of each criterion in the weight of each point, relative to the
+/// total weight of the criterion, as a point-major array.
+///
+/// Shares of a point sum to one.  Points that weigh nothing get the same share
+/// of every criterion.
+fn criterion_shares(criteria: &[&[f64]], point_count: usize) -> Vec<f64> {
+    let criterion_count = criteria.len();
+    let totals: Vec<f64> = criteria
+        .iter()
+        .map(|weights| weights.par_iter().sum())
+        .collect();
+    let mut shares = vec![0.0; point_count * criterion_count];
+    shares
+        .par_chunks_mut(criterion_count)
+        .enumerate()
+        .for_each(|(point, shares)| {
+            for ((share, weights), total) in shares.iter_mut().zip(criteria).zip(&totals) {
+                *share = weights[point] / total;
+            }
+            let sum: f64 = shares.iter().sum();
+            if sum > 0.0 {
+                shares.iter_mut().for_each(|share| *share /= sum);
+            } else {
+                shares.fill(1.0 / criterion_count as f64);
+            }
+        });
+    shares
+}
+
+/// The influence of a cluster on a point, that is the influences of the
+/// cluster for each criterion, weighted by the shares of the point.
+fn effective_influence(influence: &[f64], shares: &[f64]) -> f64 {
+    influence
+        .iter()
+        .zip(shares)
+        .map(|(influence, share)| influence * share)
+        .sum()
+}
+
+/// The largest influence of each cluster, over all criteria.
+fn max_influences(influences: &[f64], criterion_count: usize) -> Vec<f64> {
+    influences
+        .chunks(criterion_count)
+        .map(|influence| influence.iter().cloned().fold(f64::NEG_INFINITY, f64::max))
+        .collect()
+}
+
+/// The state of [KMeans] at the end of one of its iterations.
+///
+/// See [KMeans::on_iteration].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IterationReport {
+    /// The number of the iteration, starting at zero.
+    pub iteration: usize,
+
+    /// The largest distance a cluster center moved during this iteration.
+    pub delta_max: f64,
+
+    /// The weight difference between the heaviest and the lightest clusters,
+    /// in `%` of the mean cluster weight, as compared against `imbalance_tol`.
+    /// With several criteria, this is the largest difference over all
+    /// criteria.
+    pub imbalance: f64,
+}
+
+/// The state of [KMeans] once it has stopped.
+///
+/// See [KMeans::partition_with_report].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KMeansReport {
+    /// The number of iterations that have been run.
+    pub iterations: usize,
+
+    /// Whether the algorithm stopped because clusters moved less than
+    /// `delta_threshold`, rather than because of `max_iter`.
+    pub converged: bool,
+
+    /// The weight difference between the heaviest and the lightest clusters,
+    /// in `%` of the mean cluster weight, as compared against `imbalance_tol`.
+    /// With several criteria, this is the largest difference over all
+    /// criteria.
+    pub final_imbalance: f64,
+
+    /// The largest distance a cluster center moved during the last iteration.
+    pub final_delta: f64,
+}
+
 /// Settings to tune the balanced k-means algorithm
 ///
 /// ## Attributes
 ///   - `num_partitions`: the exact number of partitions the algorithm is guarenteed to yield.
-///   - `imbalance_tol`: the relative imbalance tolerance of the generated partitions, in `%` of the target weight of each partition.
+///   - `imbalance_tol`: the relative imbalance tolerance of the generated partitions, that is the maximum weight difference
+///      between two partitions, in `%` of their mean weight.
 ///   - `delta_threshold`: the distance threshold for the cluster movements under which the algorithm stops.
 ///   - `max_iter`: the maximum number of times each cluster will move before stopping the algorithm
 ///   - `max_balance_iter`: the maximum number of iterations of the load balancing loop. It will limit how much each cluster
@@ -50,6 +206,9 @@ fn imbalance(weights: &[f64]) -> f64 {
 ///   - `erode`: sets whether or not cluster influence is modified according to errosion's rules between each cluster movement
 ///   - `hilbert`: sets wheter or not an Hilbert curve is used to create the initial partition. If false, a Z curve is used instead.
 ///   - `mbr_early_break`: sets whether or not bounding box optimization is enabled.
+///   - `metric`: the distance used to assign points to clusters.
+///   - `geometric_median`: sets whether or not clusters are represented by their geometric median instead of their centroid.
+///   - `kd_tree`: sets whether or not the closest clusters of each point are looked up with a k-d tree.
 #[derive(Debug, Clone, Copy)]
 pub struct BalancedKmeansSettings {
     pub num_partitions: usize,
@@ -60,6 +219,24 @@ pub struct BalancedKmeansSettings {
     pub erode: bool,
     pub hilbert: bool,
     pub mbr_early_break: bool,
+    pub metric: Metric,
+    pub geometric_median: bool,
+    pub kd_tree: bool,
+}
+
+impl BalancedKmeansSettings {
+    /// The center of the given cluster of points.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `points` is empty.
+    fn center<const D: usize>(&self, points: &[PointND<D>]) -> PointND<D> {
+        match self.metric {
+            Metric::Manhattan => geometry::median(points),
+            _ if self.geometric_median => geometry::geometric_median(points),
+            Metric::Euclidean | Metric::Chebyshev => geometry::center(points),
+        }
+    }
 }
 
 impl Default for BalancedKmeansSettings {
@@ -73,58 +250,92 @@ impl Default for BalancedKmeansSettings {
             erode: false,        // for now, `erode` yields` enabled yields wrong results
             hilbert: true,
             mbr_early_break: false, // for now, `mbr_early_break` enabled yields wrong results
+            metric: Metric::Euclidean,
+            geometric_median: false,
+            kd_tree: false,
         }
     }
 }
 
 fn balanced_k_means_with_initial_partition<const D: usize>(
     points: &[PointND<D>],
-    weights: &[f64],
+    weights: &[&[f64]],
     settings: impl Into<Option<BalancedKmeansSettings>>,
     initial_partition: &mut [usize],
-) where
+    initial_centers: Option<&[PointND<D>]>,
+    fixed: Option<&[bool]>,
+    hooks: Hooks<'_>,
+) -> KMeansReport
+where
     Const<D>: DimSub<Const<1>>,
     DefaultAllocator: Allocator<f64, Const<D>, Const<D>, Buffer = ArrayStorage<f64, D, D>>
         + Allocator<f64, DimDiff<Const<D>, Const<1>>>,
 {
     let settings = settings.into().unwrap_or_default();
 
-    // validate partition soundness
-    // TODO: put this in a separate function
-    let expected_num_parts = settings.num_partitions;
-    // TODO: make this parallel
-    let center_ids = initial_partition
-        .iter()
-        .cloned()
-        .unique()
-        .collect::<Vec<_>>();
-    let current_num_parts = center_ids.len();
-    if current_num_parts != expected_num_parts {
-        panic!(
-            "Input partition is unsound, found {} initial parts",
-            center_ids.len()
-        );
-    }
+    let (center_ids, centers) = match initial_centers {
+        Some(centers) => {
+            // Cluster `i` starts at `centers[i]` and gets part ID `i`.  Points
+            // start in their closest cluster.
+            initial_partition
+                .par_iter_mut()
+                .zip(points)
+                .for_each(|(part, point)| {
+                    *part = centers
+                        .iter()
+                        .map(|center| settings.metric.distance(center, point))
+                        .enumerate()
+                        .min_by(|(_, a), (_, b)| f64::total_cmp(a, b))
+                        .map(|(i, _)| i)
+                        .unwrap();
+                });
+            ((0..centers.len()).collect(), centers.to_vec())
+        }
+        None => {
+            // validate partition soundness
+            // TODO: put this in a separate function
+            let expected_num_parts = settings.num_partitions;
+            // TODO: make this parallel
+            let center_ids = initial_partition
+                .iter()
+                .cloned()
+                .unique()
+                .collect::<Vec<_>>();
+            let current_num_parts = center_ids.len();
+            if current_num_parts != expected_num_parts {
+                panic!(
+                    "Input partition is unsound, found {} initial parts",
+                    center_ids.len()
+                );
+            }
 
-    // construct centers
-    let centers = center_ids
-        .par_iter()
-        .map(|center_id| {
-            let owned_points = points
+            // construct centers
+            let centers = center_ids
                 .par_iter()
-                .zip(initial_partition.par_iter())
-                .filter(|(_, id)| *id == center_id)
-                .map(|(p, _)| *p)
+                .map(|center_id| {
+                    let owned_points = points
+                        .par_iter()
+                        .zip(initial_partition.par_iter())
+                        .filter(|(_, id)| *id == center_id)
+                        .map(|(p, _)| *p)
+                        .collect::<Vec<_>>();
+                    settings.center(&owned_points)
+                })
                 .collect::<Vec<_>>();
-            crate::geometry::center(&owned_points)
-        })
-        .collect::<Vec<_>>();
+            (center_ids, centers)
+        }
+    };
 
-    // construct permutation
-    let mut permu = (0..points.len()).collect::<Vec<_>>();
+    // construct permutation, fixed points are left out so that they are never
+    // reassigned
+    let mut permu = match fixed {
+        Some(fixed) => (0..points.len()).filter(|i| !fixed[*i]).collect(),
+        None => (0..points.len()).collect::<Vec<_>>(),
+    };
 
-    // Generate initial influences (to 1)
-    let mut influences: Vec<_> = centers.par_iter().map(|_| 1.).collect();
+    // Generate initial influences (to 1), one per cluster and per criterion
+    let mut influences = vec![1.; centers.len() * weights.len()];
+    let shares = criterion_shares(weights, points.len());
 
     // Generate initial lower and upper bounds. These two variables represent bounds on
     // the effective distance between an point and the cluster it is assigned to.
@@ -132,7 +343,11 @@ fn balanced_k_means_with_initial_partition<const D: usize>(
     let mut ubs: Vec<_> = points.par_iter().map(|_| std::f64::MAX).collect(); // we use f64::MAX to represent infinity
 
     balanced_k_means_iter(
-        Inputs { points, weights },
+        Inputs {
+            points,
+            weights,
+            shares: &shares,
+        },
         Clusters {
             centers,
             center_ids: &center_ids,
@@ -146,13 +361,19 @@ fn balanced_k_means_with_initial_partition<const D: usize>(
         },
         &settings,
         settings.max_iter,
-    );
+        hooks,
+    )
 }
 
 #[derive(Clone, Copy)]
 struct Inputs<'a, const D: usize> {
     points: &'a [PointND<D>],
-    weights: &'a [f64],
+
+    /// The weights of the points, one slice per criterion.
+    weights: &'a [&'a [f64]],
+
+    /// See [criterion_shares].
+    shares: &'a [f64],
 }
 
 #[derive(Clone, Copy)]
@@ -161,8 +382,24 @@ struct Clusters<T, U> {
     center_ids: U,
 }
 
+/// Called at the end of each iteration with the current assignments, the
+/// algorithm stops if it returns `true`.
+type ShouldStop<'a> = dyn FnMut(&[usize]) -> bool + 'a;
+
+/// Callbacks given by the caller of the algorithm.
+struct Hooks<'a> {
+    /// Called at the end of each iteration, to report progress.
+    on_iteration: Option<&'a mut (dyn FnMut(IterationReport) + Send + Sync)>,
+
+    /// See [ShouldStop].
+    should_stop: Option<&'a mut ShouldStop<'a>>,
+}
+
 struct AlgorithmState<'a> {
     assignments: &'a mut [usize],
+
+    /// The influence of each cluster for each criterion, as a cluster-major
+    /// array.
     influences: &'a mut [f64],
     lbs: &'a mut [f64],
     ubs: &'a mut [f64],
@@ -180,12 +417,17 @@ fn balanced_k_means_iter<const D: usize>(
     state: AlgorithmState<'_>,
     settings: &BalancedKmeansSettings,
     current_iter: usize,
-) where
+    mut hooks: Hooks<'_>,
+) -> KMeansReport
+where
     Const<D>: DimSub<Const<1>>,
     DefaultAllocator: Allocator<f64, Const<D>, Const<D>, Buffer = ArrayStorage<f64, D, D>>
         + Allocator<f64, DimDiff<Const<D>, Const<1>>>,
 {
-    let Inputs { points, weights } = inputs;
+    let Inputs {
+        points, weights, ..
+    } = inputs;
+    let criterion_count = weights.len();
     let Clusters {
         centers,
         center_ids,
@@ -198,8 +440,7 @@ fn balanced_k_means_iter<const D: usize>(
     } = state;
 
     assign_and_balance(
-        points,
-        weights,
+        inputs,
         permutation,
         AlgorithmState {
             assignments,
@@ -220,7 +461,8 @@ fn balanced_k_means_iter<const D: usize>(
         // map each center id to the new center point
         // we cannot just compute the centers fron the assignments
         // because the new centers have to be in the same order as the old ones
-        .map(|center_id| {
+        .zip(centers.par_iter())
+        .map(|(center_id, center)| {
             let points = assignments
                 .par_iter()
                 .cloned()
@@ -228,7 +470,12 @@ fn balanced_k_means_iter<const D: usize>(
                 .filter(|(assignment, _)| *assignment == *center_id)
                 .map(|(_, point)| point)
                 .collect::<Vec<_>>();
-            geometry::center(&points)
+            // Clusters left empty, e.g. by explicit initial centers, stay put.
+            if points.is_empty() {
+                *center
+            } else {
+                settings.center(&points)
+            }
         })
         .collect::<Vec<_>>();
 
@@ -236,7 +483,7 @@ fn balanced_k_means_iter<const D: usize>(
     let distances_moved: Vec<_> = centers
         .par_iter()
         .zip(new_centers.clone())
-        .map(|(c1, c2)| (c1 - c2).norm())
+        .map(|(c1, c2)| settings.metric.distance(c1, &c2))
         .collect();
 
     if settings.erode {
@@ -245,16 +492,19 @@ fn balanced_k_means_iter<const D: usize>(
             .zip(points.iter().cloned())
             .into_group_map()
             .into_values()
-            .map(|points| max_distance(&points))
+            .map(|points| max_distance(&points, settings.metric))
             .sum::<f64>()
             / centers.len() as f64;
 
         // erode influence
         influences
-            .iter_mut()
+            .chunks_mut(criterion_count)
             .zip(distances_moved.iter())
             .for_each(|(influence, distance)| {
-                *influence = influence.log(10.) * (1. - erosion(*distance, average_diameters)).exp()
+                let erosion = (1. - erosion(*distance, average_diameters)).exp();
+                for influence in influence {
+                    *influence = influence.log(10.) * erosion;
+                }
             });
     }
 
@@ -263,12 +513,49 @@ fn balanced_k_means_iter<const D: usize>(
         .max_by(|d1, d2| d1.partial_cmp(d2).unwrap_or(Ordering::Equal))
         .unwrap();
 
+    let iteration = settings.max_iter - current_iter;
+    let cluster_imbalance = |assignments: &[usize]| {
+        cluster_weights(assignments, weights, center_ids)
+            .iter()
+            .map(|cluster_weights| imbalance(cluster_weights))
+            .fold(0., f64::max)
+    };
+
+    if let Some(on_iteration) = &mut hooks.on_iteration {
+        on_iteration(IterationReport {
+            iteration,
+            delta_max: *delta_max,
+            imbalance: cluster_imbalance(assignments),
+        });
+    }
+
     // if delta_max is below a given threshold, it means that the clusters no longer move a lot at each iteration
     // and the algorithm has become somewhat stable.
-    if !(*delta_max < settings.delta_threshold || current_iter == 0) {
-        relax_bounds(lbs, ubs, &distances_moved, influences);
+    let converged = *delta_max < settings.delta_threshold;
+    let report = |assignments: &[usize]| KMeansReport {
+        iterations: iteration + 1,
+        converged,
+        final_imbalance: cluster_imbalance(assignments),
+        final_delta: *delta_max,
+    };
+
+    // Let the caller stop the algorithm based on criteria that are not known
+    // to k-means, like the edge cut of the partition.
+    if let Some(should_stop) = &mut hooks.should_stop {
+        if should_stop(assignments) {
+            return report(assignments);
+        }
+    }
+
+    if !(converged || current_iter == 0) {
+        relax_bounds(
+            lbs,
+            ubs,
+            &distances_moved,
+            &max_influences(influences, criterion_count),
+        );
         balanced_k_means_iter(
-            Inputs { points, weights },
+            inputs,
             Clusters {
                 centers: new_centers,
                 center_ids,
@@ -282,23 +569,26 @@ fn balanced_k_means_iter<const D: usize>(
             },
             settings,
             current_iter - 1,
-        );
+            hooks,
+        )
+    } else {
+        report(assignments)
     }
 }
 
 /// This is the main load balance routine. It handles:
 ///   - reordering the clusters according to their distance to a bounding box of all the points
 ///   - assigning each point to the closest cluster according to the effective distance
-///   - checking partitions imbalance
-///   - increasing of diminishing clusters influence based on their imbalance
+///   - checking partitions imbalance, for each criterion
+///   - increasing of diminishing clusters influence based on their imbalance,
+///     for each criterion
 ///   - relaxing upper and lower bounds
 ///
 /// # Panics
 ///
 /// Panics if `points` is empty.
 fn assign_and_balance<const D: usize>(
-    points: &[PointND<D>],
-    weights: &[f64],
+    inputs: Inputs<'_, D>,
     permutation: &mut [usize],
     state: AlgorithmState<'_>,
     clusters: Clusters<&[PointND<D>], &[ClusterId]>,
@@ -308,6 +598,12 @@ fn assign_and_balance<const D: usize>(
     DefaultAllocator: Allocator<f64, Const<D>, Const<D>, Buffer = ArrayStorage<f64, D, D>>
         + Allocator<f64, DimDiff<Const<D>, Const<1>>>,
 {
+    let Inputs {
+        points,
+        weights,
+        shares,
+    } = inputs;
+    let criterion_count = weights.len();
     let AlgorithmState {
         assignments,
         influences,
@@ -319,18 +615,26 @@ fn assign_and_balance<const D: usize>(
         center_ids,
     } = clusters;
     // compute the distances from each cluster center to the minimal
-    // bounding rectangle of the set of points
-    let obb = OrientedBoundingBox::from_points(points).unwrap();
+    // bounding rectangle of the set of points, with the configured metric:
+    // the nearest point of an axis-aligned box is the same for all of them.
+    let mbr = par_bounding_box(points.par_iter().cloned()).unwrap();
     let distances_to_mbr = centers
         .par_iter()
-        .zip(influences.par_iter())
-        .map(|(center, influence)| obb.distance_to_point(center) * influence)
+        .zip(influences.par_chunks(criterion_count))
+        .map(|(center, influence)| {
+            let min_influence = influence.iter().cloned().fold(f64::INFINITY, f64::min);
+            let nearest = PointND::from_fn(|axis, _| {
+                f64::clamp(center[axis], mbr.p_min[axis], mbr.p_max[axis])
+            });
+            settings.metric.distance(center, &nearest) * min_influence
+        })
         .collect::<Vec<_>>();
 
     let mut zipped = centers
         .par_iter()
         .cloned()
         .zip(center_ids)
+        .enumerate()
         .zip(distances_to_mbr)
         .collect::<Vec<_>>();
 
@@ -340,29 +644,62 @@ fn assign_and_balance<const D: usize>(
 
     let (zipped, distances_to_mbr): (Vec<_>, Vec<_>) = zipped.into_par_iter().unzip();
 
+    let (order, zipped): (Vec<_>, Vec<_>) = zipped.into_par_iter().unzip();
+
     let (centers, center_ids): (Vec<_>, Vec<_>) = zipped.into_par_iter().unzip();
 
-    // Compute the weight that each cluster should be after the end of the algorithm
-    let target_weight = weights.par_iter().sum::<f64>() / (centers.len() as f64);
+    // Influences follow their clusters in the sorted order, and are put back
+    // in the caller's order at the end.
+    let caller_influences = influences;
+    let influences = &mut order
+        .iter()
+        .flat_map(|cluster| &caller_influences[cluster * criterion_count..][..criterion_count])
+        .cloned()
+        .collect::<Vec<_>>()[..];
+
+    // Centers do not move during the load balancing loop, so the tree is
+    // built once.
+    let tree = settings.kd_tree.then(|| KdTree::new(&centers));
+
+    // Compute the weight that each cluster should be after the end of the
+    // algorithm, for each criterion
+    let target_weights: Vec<f64> = weights
+        .iter()
+        .map(|weights| weights.par_iter().sum::<f64>() / (centers.len() as f64))
+        .collect();
 
     let atomic_handle = AtomicPtr::from(assignments.as_mut_ptr());
     for _ in 0..settings.max_balance_iter {
         // Compute new assignments point to cluster assignments
         // based on the current clusters and influences state
+        let min_influence = influences.iter().cloned().fold(f64::INFINITY, f64::min);
         permutation
             .par_iter()
             .zip(lbs.par_iter_mut())
             .zip(ubs.par_iter_mut())
             .for_each(|((idx, lb), ub)| {
                 if lb < ub {
-                    let (new_lb, new_ub, new_assignment) = best_values(
-                        &points[*idx],
-                        &centers,
-                        &center_ids,
-                        &distances_to_mbr,
-                        influences,
-                        settings,
-                    );
+                    let shares = &shares[*idx * criterion_count..(*idx + 1) * criterion_count];
+                    let (new_lb, new_ub, new_assignment) = match &tree {
+                        Some(tree) => best_values_kd_tree(
+                            &points[*idx],
+                            shares,
+                            tree,
+                            &center_ids,
+                            influences,
+                            min_influence,
+                            settings,
+                        ),
+                        None => best_values(
+                            &points[*idx],
+                            shares,
+                            &centers,
+                            &center_ids,
+                            &distances_to_mbr,
+                            influences,
+                            settings,
+                        ),
+                    };
 
                     *lb = new_lb;
                     *ub = new_ub;
@@ -375,51 +712,80 @@ fn assign_and_balance<const D: usize>(
                 }
             });
 
-        // Compute total weight for each cluster
-        let new_weights = center_ids
-            .par_iter()
-            .map(|center_id| {
-                assignments
-                    .par_iter()
-                    .cloned()
-                    .zip(weights.par_iter())
-                    .filter(|(assignment, _)| *assignment == *center_id)
-                    .map(|(_, weight)| *weight)
-                    .sum::<f64>()
-            })
-            .collect::<Vec<_>>();
+        // Compute total weight for each cluster and each criterion
+        let new_weights = cluster_weights(assignments, weights, &center_ids);
 
-        // return if maximum imbalance is small enough
-        if imbalance(&new_weights) < settings.imbalance_tol {
-            return;
+        // return if maximum imbalance is small enough for all criteria
+        if new_weights
+            .iter()
+            .all(|new_weights| imbalance(new_weights) < settings.imbalance_tol)
+        {
+            break;
         }
 
         // If this point is reached, the current assignments
         // are too imbalanced.
         // The influences are then adapted to produce better
         // assignments during next iteration.
+        let old_influences = influences.to_vec();
         influences
-            .par_iter_mut()
-            .zip(new_weights)
-            .for_each(|(influence, weight)| {
-                let ratio = target_weight / weight;
-                // We limit the influence variation to 5% each time
-                // to preven the algorithm from becoming unstable
-                let max_diff = 0.05 * *influence;
-                let new_influence = *influence / ratio.sqrt();
-                if (*influence - new_influence).abs() < max_diff {
-                    *influence = new_influence;
-                } else if new_influence > *influence {
-                    *influence += max_diff;
-                } else {
-                    *influence -= max_diff;
+            .par_chunks_mut(criterion_count)
+            .enumerate()
+            .for_each(|(cluster, influence)| {
+                for (criterion, influence) in influence.iter_mut().enumerate() {
+                    let ratio = target_weights[criterion] / new_weights[criterion][cluster];
+                    // We limit the influence variation to 5% each time
+                    // to preven the algorithm from becoming unstable
+                    let max_diff = 0.05 * *influence;
+                    let new_influence = *influence / ratio.sqrt();
+                    if (*influence - new_influence).abs() < max_diff {
+                        *influence = new_influence;
+                    } else if new_influence > *influence {
+                        *influence += max_diff;
+                    } else {
+                        *influence -= max_diff;
+                    }
                 }
             });
 
+        // Effective distances change with influences, so must their bounds,
+        // otherwise points would not move until their cluster does.
+        let growths: Vec<(f64, f64)> = influences
+            .chunks(criterion_count)
+            .zip(old_influences.chunks(criterion_count))
+            .map(|(new, old)| {
+                new.iter()
+                    .zip(old)
+                    .map(|(new, old)| new / old)
+                    .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), growth| {
+                        (f64::min(min, growth), f64::max(max, growth))
+                    })
+            })
+            .collect();
+        let min_growth = growths
+            .iter()
+            .map(|(min, _)| *min)
+            .fold(f64::INFINITY, f64::min);
+        let positions: HashMap<ClusterId, usize> = center_ids
+            .iter()
+            .enumerate()
+            .map(|(position, center_id)| (*center_id, position))
+            .collect();
+        permutation
+            .par_iter()
+            .zip(lbs.par_iter_mut())
+            .zip(ubs.par_iter_mut())
+            .for_each(|((idx, lb), ub)| {
+                let (_, max_growth) = growths[positions[&assignments[*idx]]];
+                *lb *= min_growth;
+                *ub *= max_growth;
+            });
+
         // Compute new centers from new assigments
         let new_centers = center_ids
             .par_iter()
-            .map(|center_id| {
+            .zip(centers.par_iter())
+            .map(|(center_id, center)| {
                 let points = assignments
                     .par_iter()
                     .cloned()
@@ -427,20 +793,60 @@ fn assign_and_balance<const D: usize>(
                     .filter(|(assignment, _)| *assignment == *center_id)
                     .map(|(_, point)| point)
                     .collect::<Vec<_>>();
-                geometry::center(&points)
+                // Clusters left empty, e.g. by explicit initial centers, stay put.
+                if points.is_empty() {
+                    *center
+                } else {
+                    settings.center(&points)
+                }
             })
             .collect::<Vec<_>>();
 
         let distances_to_old_centers: Vec<_> = centers
             .par_iter()
             .zip(new_centers.par_iter())
-            .map(|(center, new_center)| (center - new_center).norm())
+            .map(|(center, new_center)| settings.metric.distance(center, new_center))
             .collect();
 
-        relax_bounds(lbs, ubs, &distances_to_old_centers, influences);
+        relax_bounds(
+            lbs,
+            ubs,
+            &distances_to_old_centers,
+            &max_influences(influences, criterion_count),
+        );
+    }
+
+    for (influence, cluster) in influences.chunks(criterion_count).zip(order) {
+        caller_influences[cluster * criterion_count..][..criterion_count]
+            .copy_from_slice(influence);
     }
 }
 
+/// Same as [best_values], but the two closest clusters are looked up in a k-d
+/// tree built on the cluster centers, instead of scanning all of them.
+///
+/// `min_influence` must be the lowest of `influences`.  See [best_values] for
+/// the layout of `shares` and `influences`.
+fn best_values_kd_tree<const D: usize>(
+    point: &PointND<D>,
+    shares: &[f64],
+    tree: &KdTree<D>,
+    center_ids: &[ClusterId],
+    influences: &[f64],
+    min_influence: f64,
+    settings: &BalancedKmeansSettings,
+) -> (f64, f64, Option<ClusterId>) {
+    let criterion_count = shares.len();
+    let mut best = [(f64::MAX, usize::MAX); 2];
+    tree.nearest_by(point, &mut best, min_influence, &|center_idx, center| {
+        let influence = &influences[center_idx * criterion_count..][..criterion_count];
+        settings.metric.distance(center, point) * effective_influence(influence, shares)
+    });
+    let [(best_value, best_center), (snd_best_value, _)] = best;
+    let assignment = (best_center != usize::MAX).then(|| center_ids[best_center]);
+    (snd_best_value, best_value, assignment)
+}
+
 // relax lower and upper bounds according to influence
 // modification.
 //
@@ -468,8 +874,12 @@ fn relax_bounds(lbs: &mut [f64], ubs: &mut [f64], distances_moved: &[f64], influ
 
 /// Most inner loop of the algorithm that aims to optimize
 /// clusters assignments
+///
+/// `shares` are the criterion shares of the point, and `influences` holds
+/// the influences of each cluster for each criterion, cluster-major.
 fn best_values<const D: usize>(
     point: &PointND<D>,
+    shares: &[f64],
     centers: &[PointND<D>],
     center_ids: &[ClusterId],
     distances_to_mbr: &[f64],
@@ -488,13 +898,14 @@ fn best_values<const D: usize>(
         .iter()
         .zip(center_ids)
         .zip(distances_to_mbr)
-        .zip(influences)
+        .zip(influences.chunks(shares.len()))
     {
         if *distance_to_mbr > snd_best_value && settings.mbr_early_break {
             break;
         }
 
-        let effective_distance = (center - point).norm() * influence;
+        let effective_distance =
+            settings.metric.distance(center, point) * effective_influence(influence, shares);
         if effective_distance < best_value {
             assignment = Some(*id);
             snd_best_value = best_value;
@@ -514,9 +925,9 @@ fn erosion(distance_moved: f64, average_cluster_diameter: f64) -> f64 {
 }
 
 // computes the maximum distance between two points in the array
-fn max_distance<const D: usize>(points: &[PointND<D>]) -> f64 {
+fn max_distance<const D: usize>(points: &[PointND<D>], metric: Metric) -> f64 {
     iproduct!(points, points)
-        .map(|(p1, p2)| (p1 - p2).norm())
+        .map(|(p1, p2)| metric.distance(p1, p2))
         .max_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
         .unwrap()
 }
@@ -531,10 +942,13 @@ fn max_distance<const D: usize>(points: &[PointND<D>]) -> f64 {
 /// at each iteration, exchage points with other clusters that are "closer", and move by recomputing the clusters position (defined as
 /// the centroid of the points assigned to the cluster). Eventually the clusters will stop moving, yielding a new partition.
 ///
+/// Part IDs of the input partition need not be contiguous, so that a partition
+/// read from a file can be given as-is. The output partition uses the same IDs.
+///
 /// # Example
 ///
 /// ```rust
-/// # fn main() -> Result<(), std::convert::Infallible> {
+/// # fn main() -> Result<(), coupe::Error> {
 /// use coupe::Partition as _;
 /// use coupe::Point2D;
 ///
@@ -571,7 +985,16 @@ fn max_distance<const D: usize>(points: &[PointND<D>]) -> f64 {
 /// # Ok(())
 /// # }
 /// ```
-#[derive(Debug, Clone, Copy)]
+///
+/// Settings can also be set one by one through [`KMeans::builder`]:
+///
+/// ```rust
+/// let k_means = coupe::KMeans::builder()
+///     .delta_threshold(0.0)
+///     .metric(coupe::Metric::Manhattan)
+///     .build();
+/// # assert_eq!(k_means.delta_threshold, 0.0);
+/// ```
 pub struct KMeans {
     pub imbalance_tol: f64,
     pub delta_threshold: f64,
@@ -580,6 +1003,54 @@ pub struct KMeans {
     pub erode: bool,
     pub hilbert: bool,
     pub mbr_early_break: bool,
+    pub metric: Metric,
+
+    /// Whether clusters are represented by the geometric median of their
+    /// points instead of their centroid.
+    ///
+    /// The geometric median is less sensitive to outliers than the centroid,
+    /// which tends to yield more compact clusters on inputs with skewed
+    /// densities, at the cost of a few more computations per iteration.
+    pub geometric_median: bool,
+
+    /// Whether the closest clusters of each point are looked up in a k-d tree,
+    /// instead of by computing the distances to all clusters.
+    ///
+    /// This makes assignments run in `O(n log k)` instead of `O(n k)`, and is
+    /// worth enabling when there are many parts.  `mbr_early_break` has no
+    /// effect when this is enabled.
+    pub kd_tree: bool,
+
+    /// An upper bound on the diameter of the bounding sphere of each part.
+    ///
+    /// Once clusters have stopped moving, a part that is wider than this is
+    /// split in two along the longest axis of its bounding box, and clusters
+    /// are moved again with one more part, until all parts fit.  The
+    /// partition may thus end up with more parts than it started with.
+    /// Fixed points are never moved by splits.
+    pub max_diameter: Option<f64>,
+
+    /// Called at the end of each iteration, to report progress.
+    pub on_iteration: Option<Box<dyn FnMut(IterationReport) + Send + Sync>>,
+}
+
+impl fmt::Debug for KMeans {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KMeans")
+            .field("imbalance_tol", &self.imbalance_tol)
+            .field("delta_threshold", &self.delta_threshold)
+            .field("max_iter", &self.max_iter)
+            .field("max_balance_iter", &self.max_balance_iter)
+            .field("erode", &self.erode)
+            .field("hilbert", &self.hilbert)
+            .field("mbr_early_break", &self.mbr_early_break)
+            .field("metric", &self.metric)
+            .field("geometric_median", &self.geometric_median)
+            .field("kd_tree", &self.kd_tree)
+            .field("max_diameter", &self.max_diameter)
+            .field("on_iteration", &self.on_iteration.as_ref().map(|_| ".."))
+            .finish()
+    }
 }
 
 impl Default for KMeans {
@@ -592,27 +1063,433 @@ impl Default for KMeans {
             erode: false,         // for now, `erode` yields` enabled yields wrong results
             hilbert: true,
             mbr_early_break: false, // for now, `mbr_early_break` enabled yields wrong results
+            metric: Metric::Euclidean,
+            geometric_median: false,
+            kd_tree: false,
+            max_diameter: None,
+            on_iteration: None,
         }
     }
 }
 
-impl<'a, const D: usize> crate::Partition<(&'a [PointND<D>], &'a [f64])> for KMeans
-where
-    Const<D>: DimSub<Const<1>>,
-    DefaultAllocator: Allocator<f64, Const<D>, Const<D>, Buffer = ArrayStorage<f64, D, D>>
-        + Allocator<f64, DimDiff<Const<D>, Const<1>>>,
-{
-    type Metadata = ();
-    type Error = std::convert::Infallible;
+impl KMeans {
+    /// A builder for [`KMeans`], initialized with the default settings.
+    pub fn builder() -> KMeansBuilder {
+        KMeansBuilder::default()
+    }
+}
 
-    fn partition(
+/// Chainable constructor for [`KMeans`].
+///
+/// This type is returned by [`KMeans::builder`]. Settings that are not set
+/// keep their value from [`KMeans::default`].
+#[derive(Debug, Default)]
+pub struct KMeansBuilder {
+    k_means: KMeans,
+}
+
+impl KMeansBuilder {
+    /// The relative imbalance tolerance, that is the maximum weight difference
+    /// between two parts, in `%` of the mean part weight.
+    pub fn imbalance_tol(mut self, imbalance_tol: f64) -> Self {
+        self.k_means.imbalance_tol = imbalance_tol;
+        self
+    }
+
+    /// The distance threshold for cluster movements under which the algorithm stops.
+    pub fn delta_threshold(mut self, delta_threshold: f64) -> Self {
+        self.k_means.delta_threshold = delta_threshold;
+        self
+    }
+
+    /// The maximum number of times each cluster moves.
+    pub fn max_iter(mut self, max_iter: usize) -> Self {
+        self.k_means.max_iter = max_iter;
+        self
+    }
+
+    /// The maximum number of iterations of the load balancing loop.
+    pub fn max_balance_iter(mut self, max_balance_iter: usize) -> Self {
+        self.k_means.max_balance_iter = max_balance_iter;
+        self
+    }
+
+    /// Whether cluster influence is eroded between cluster movements.
+    pub fn erode(mut self, erode: bool) -> Self {
+        self.k_means.erode = erode;
+        self
+    }
+
+    /// Whether the initial partition comes from a Hilbert curve instead of a Z curve.
+    pub fn hilbert(mut self, hilbert: bool) -> Self {
+        self.k_means.hilbert = hilbert;
+        self
+    }
+
+    /// Whether the bounding box optimization is enabled.
+    pub fn mbr_early_break(mut self, mbr_early_break: bool) -> Self {
+        self.k_means.mbr_early_break = mbr_early_break;
+        self
+    }
+
+    /// The distance used to assign points to clusters.
+    pub fn metric(mut self, metric: Metric) -> Self {
+        self.k_means.metric = metric;
+        self
+    }
+
+    /// Whether clusters are represented by their geometric median instead of their centroid.
+    pub fn geometric_median(mut self, geometric_median: bool) -> Self {
+        self.k_means.geometric_median = geometric_median;
+        self
+    }
+
+    /// Whether the closest clusters of each point are looked up in a k-d tree.
+    pub fn kd_tree(mut self, kd_tree: bool) -> Self {
+        self.k_means.kd_tree = kd_tree;
+        self
+    }
+
+    /// An upper bound on the diameter of each part, at the cost of the part count.
+    pub fn max_diameter(mut self, max_diameter: f64) -> Self {
+        self.k_means.max_diameter = Some(max_diameter);
+        self
+    }
+
+    /// A function called at the end of each iteration, to report progress.
+    pub fn on_iteration(
+        mut self,
+        on_iteration: impl FnMut(IterationReport) + Send + Sync + 'static,
+    ) -> Self {
+        self.k_means.on_iteration = Some(Box::new(on_iteration));
+        self
+    }
+
+    /// The resulting [`KMeans`] settings.
+    pub fn build(self) -> KMeans {
+        self.k_means
+    }
+}
+
+/// Checks that there is one point and one weight per element, and that all
+/// coordinates are finite.
+fn check_input<const D: usize>(
+    part_ids: &[usize],
+    points: &[PointND<D>],
+    criteria: &[&[f64]],
+) -> Result<(), crate::Error> {
+    let lens = iter::once(points.len()).chain(criteria.iter().map(|weights| weights.len()));
+    for len in lens {
+        if len != part_ids.len() {
+            return Err(crate::Error::InputLenMismatch {
+                expected: part_ids.len(),
+                actual: len,
+            });
+        }
+    }
+    super::validate_points(points)
+}
+
+impl KMeans {
+    /// Same as [`Partition::partition`][crate::Partition::partition], except
+    /// that points for which `fixed` is `true` keep the part they have in
+    /// `part_ids`.
+    ///
+    /// Fixed points still count toward the weight and the center of their
+    /// part, but are never moved to another one.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InputLenMismatch`][crate::Error::InputLenMismatch] if
+    /// `points`, `weights` or `fixed` do not have the same length as
+    /// `part_ids`, and
+    /// [`Error::NonFiniteCoordinate`][crate::Error::NonFiniteCoordinate] if a
+    /// point has a NaN or infinite coordinate.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), coupe::Error> {
+    /// use coupe::Point2D;
+    ///
+    /// let points: Vec<Point2D> = (0..8).map(|i| Point2D::new(i as f64, 0.0)).collect();
+    /// let weights = [1.0; 8];
+    /// let mut partition = [0, 0, 0, 0, 0, 0, 1, 1];
+    /// let fixed = [false, false, false, false, false, false, false, true];
+    ///
+    /// coupe::KMeans::default()
+    ///     .partition_with_fixed(&mut partition, (&points, &weights), &fixed)?;
+    ///
+    /// assert_eq!(partition[7], 1);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn partition_with_fixed<const D: usize>(
         &mut self,
         part_ids: &mut [usize],
-        (points, weights): (&'a [PointND<D>], &'a [f64]),
-    ) -> Result<Self::Metadata, Self::Error> {
-        let num_partitions = 1 + *part_ids.par_iter().max().unwrap_or(&0);
+        (points, weights): (&[PointND<D>], &[f64]),
+        fixed: &[bool],
+    ) -> Result<KMeansReport, crate::Error>
+    where
+        Const<D>: DimSub<Const<1>>,
+        DefaultAllocator: Allocator<f64, Const<D>, Const<D>, Buffer = ArrayStorage<f64, D, D>>
+            + Allocator<f64, DimDiff<Const<D>, Const<1>>>,
+    {
+        if fixed.len() != part_ids.len() {
+            return Err(crate::Error::InputLenMismatch {
+                expected: part_ids.len(),
+                actual: fixed.len(),
+            });
+        }
+        check_input(part_ids, points, &[weights])?;
+        Ok(self.run(part_ids, points, &[weights], None, Some(fixed), None))
+    }
+
+    /// Same as [`Partition::partition`][crate::Partition::partition], but also
+    /// reports whether the algorithm converged, which can be used to tune
+    /// `max_iter` and `delta_threshold`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), coupe::Error> {
+    /// use coupe::Point2D;
+    ///
+    /// let points: Vec<Point2D> = (0..8).map(|i| Point2D::new(i as f64, 0.0)).collect();
+    /// let weights = [1.0; 8];
+    /// let mut partition = [0, 0, 0, 0, 0, 0, 0, 1];
+    ///
+    /// let report = coupe::KMeans::default()
+    ///     .partition_with_report(&mut partition, (&points, &weights))?;
+    ///
+    /// assert!(report.converged);
+    /// assert!(report.final_delta < 0.01);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn partition_with_report<const D: usize>(
+        &mut self,
+        part_ids: &mut [usize],
+        (points, weights): (&[PointND<D>], &[f64]),
+    ) -> Result<KMeansReport, crate::Error>
+    where
+        Const<D>: DimSub<Const<1>>,
+        DefaultAllocator: Allocator<f64, Const<D>, Const<D>, Buffer = ArrayStorage<f64, D, D>>
+            + Allocator<f64, DimDiff<Const<D>, Const<1>>>,
+    {
+        check_input(part_ids, points, &[weights])?;
+        Ok(self.run(part_ids, points, &[weights], None, None, None))
+    }
+
+    /// Same as [`KMeans::partition_with_report`], except that clusters start
+    /// from the given centers, instead of from the parts of `part_ids`.
+    ///
+    /// The cluster that starts at `centers[i]` gets part ID `i`, and the
+    /// contents of `part_ids` are ignored.  This is useful to warm-start
+    /// k-means from the centers of a previous time step, or to anchor parts
+    /// to known locations, like the ones of the processors they are sent to.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `centers` is empty.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), coupe::Error> {
+    /// use coupe::Point2D;
+    ///
+    /// let points: Vec<Point2D> = (0..8).map(|i| Point2D::new(i as f64, 0.0)).collect();
+    /// let weights = [1.0; 8];
+    /// let centers = [Point2D::new(8.0, 0.0), Point2D::new(-1.0, 0.0)];
+    /// let mut partition = [0; 8];
+    ///
+    /// coupe::KMeans::default()
+    ///     .partition_with_centers(&mut partition, (&points, &weights), &centers)?;
+    ///
+    /// assert_eq!(partition, [1, 1, 1, 1, 0, 0, 0, 0]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn partition_with_centers<const D: usize>(
+        &mut self,
+        part_ids: &mut [usize],
+        (points, weights): (&[PointND<D>], &[f64]),
+        centers: &[PointND<D>],
+    ) -> Result<KMeansReport, crate::Error>
+    where
+        Const<D>: DimSub<Const<1>>,
+        DefaultAllocator: Allocator<f64, Const<D>, Const<D>, Buffer = ArrayStorage<f64, D, D>>
+            + Allocator<f64, DimDiff<Const<D>, Const<1>>>,
+    {
+        assert!(!centers.is_empty(), "no initial center was given");
+        check_input(part_ids, points, &[weights])?;
+        Ok(self.run(part_ids, points, &[weights], Some(centers), None, None))
+    }
+
+    /// Same as [`KMeans::partition_with_report`], with several weights per
+    /// point.
+    ///
+    /// Clusters have one influence per criterion, and the load balancing loop
+    /// stops once clusters are balanced within `imbalance_tol` for all
+    /// criteria.  This can balance, for example, both the number of points and
+    /// their computational cost.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::NoCriterion`][crate::Error::NoCriterion] if `C` is
+    /// zero, and the same errors as [`KMeans::partition_with_report`]
+    /// otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), coupe::Error> {
+    /// use coupe::Point2D;
+    ///
+    /// let points: Vec<Point2D> = (0..8).map(|i| Point2D::new(i as f64, 0.0)).collect();
+    /// // One unit of the first criterion per point, and one unit of the
+    /// // second one on every other point.
+    /// let weights: Vec<[f64; 2]> = (0..8).map(|i| [1.0, (i % 2) as f64]).collect();
+    /// let mut partition = [0, 0, 0, 0, 0, 0, 1, 1];
+    ///
+    /// let report = coupe::KMeans { imbalance_tol: 5.0, ..Default::default() }
+    ///     .partition_multi_criteria(&mut partition, (&points, &weights))?;
+    ///
+    /// assert_eq!(partition, [0, 0, 0, 0, 1, 1, 1, 1]);
+    /// assert!(report.final_imbalance < 5.0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn partition_multi_criteria<const D: usize, const C: usize>(
+        &mut self,
+        part_ids: &mut [usize],
+        (points, weights): (&[PointND<D>], &[[f64; C]]),
+    ) -> Result<KMeansReport, crate::Error>
+    where
+        Const<D>: DimSub<Const<1>>,
+        DefaultAllocator: Allocator<f64, Const<D>, Const<D>, Buffer = ArrayStorage<f64, D, D>>
+            + Allocator<f64, DimDiff<Const<D>, Const<1>>>,
+    {
+        if C == 0 {
+            return Err(crate::Error::NoCriterion);
+        }
+        let criteria: Vec<Vec<f64>> = (0..C)
+            .map(|criterion| weights.par_iter().map(|weight| weight[criterion]).collect())
+            .collect();
+        let criteria: Vec<&[f64]> = criteria.iter().map(Vec::as_slice).collect();
+        check_input(part_ids, points, &criteria)?;
+        Ok(self.run(part_ids, points, &criteria, None, None, None))
+    }
+
+    /// Makes the algorithm also stop once the edge cut of the partition, as
+    /// measured on `adjacency`, has not improved for `patience` iterations.
+    ///
+    /// This is useful when k-means is used to improve the partition of a
+    /// mesh, where the clusters may keep moving slightly long after the
+    /// quality of the partition has settled.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), coupe::Error> {
+    /// use coupe::Partition as _;
+    /// use coupe::Point2D;
+    /// use coupe::sprs::CsMat;
+    ///
+    /// //    0   1   2   3
+    /// //    +---+---+---+
+    /// let points: Vec<Point2D> = (0..4).map(|i| Point2D::new(i as f64, 0.0)).collect();
+    /// let weights = [1.0; 4];
+    /// let adjacency = CsMat::new((4, 4), vec![0, 1, 3, 5, 6], vec![1, 0, 2, 1, 3, 2], vec![1; 6]);
+    /// let mut partition = [0, 1, 1, 1];
+    ///
+    /// coupe::KMeans::default()
+    ///     .with_edge_cut_patience(adjacency.view(), 3)
+    ///     .partition(&mut partition, (&points, &weights))?;
+    ///
+    /// assert_eq!(partition, [0, 0, 1, 1]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_edge_cut_patience<T>(self, adjacency: T, patience: usize) -> CutAwareKMeans<T> {
+        CutAwareKMeans {
+            k_means: self,
+            adjacency,
+            patience,
+        }
+    }
+
+    fn run<const D: usize>(
+        &mut self,
+        part_ids: &mut [usize],
+        points: &[PointND<D>],
+        weights: &[&[f64]],
+        initial_centers: Option<&[PointND<D>]>,
+        fixed: Option<&[bool]>,
+        mut should_stop: Option<&mut ShouldStop<'_>>,
+    ) -> KMeansReport
+    where
+        Const<D>: DimSub<Const<1>>,
+        DefaultAllocator: Allocator<f64, Const<D>, Const<D>, Buffer = ArrayStorage<f64, D, D>>
+            + Allocator<f64, DimDiff<Const<D>, Const<1>>>,
+    {
+        let mut report = self.run_once(
+            part_ids,
+            points,
+            weights,
+            initial_centers,
+            fixed,
+            should_stop.as_deref_mut(),
+        );
+        if let Some(max_diameter) = self.max_diameter {
+            // Each split adds a part, so there cannot be more splits than
+            // points, unless k-means keeps emptying clusters.
+            for _ in 0..points.len() {
+                if !split_widest_cluster(part_ids, points, fixed, max_diameter) {
+                    break;
+                }
+                report = self.run_once(
+                    part_ids,
+                    points,
+                    weights,
+                    None,
+                    fixed,
+                    should_stop.as_deref_mut(),
+                );
+            }
+        }
+        report
+    }
+
+    fn run_once<const D: usize>(
+        &mut self,
+        part_ids: &mut [usize],
+        points: &[PointND<D>],
+        weights: &[&[f64]],
+        initial_centers: Option<&[PointND<D>]>,
+        fixed: Option<&[bool]>,
+        should_stop: Option<&mut ShouldStop<'_>>,
+    ) -> KMeansReport
+    where
+        Const<D>: DimSub<Const<1>>,
+        DefaultAllocator: Allocator<f64, Const<D>, Const<D>, Buffer = ArrayStorage<f64, D, D>>
+            + Allocator<f64, DimDiff<Const<D>, Const<1>>>,
+    {
+        let num_partitions = match initial_centers {
+            Some(centers) => centers.len(),
+            None => part_ids.iter().unique().count(),
+        };
         if num_partitions < 2 {
-            return Ok(());
+            if initial_centers.is_some() {
+                part_ids.par_iter_mut().for_each(|part| *part = 0);
+            }
+            return KMeansReport {
+                iterations: 0,
+                converged: true,
+                final_imbalance: 0.0,
+                final_delta: 0.0,
+            };
         }
         let settings = BalancedKmeansSettings {
             num_partitions,
@@ -623,8 +1500,827 @@ where
             erode: self.erode,
             hilbert: self.hilbert,
             mbr_early_break: self.mbr_early_break,
+            metric: self.metric,
+            geometric_median: self.geometric_median,
+            kd_tree: self.kd_tree,
+        };
+        balanced_k_means_with_initial_partition(
+            points,
+            weights,
+            settings,
+            part_ids,
+            initial_centers,
+            fixed,
+            Hooks {
+                on_iteration: self
+                    .on_iteration
+                    .as_deref_mut()
+                    .map(|on_iteration| on_iteration as _),
+                should_stop: should_stop.map(|should_stop| should_stop as _),
+            },
+        )
+    }
+}
+
+/// Splits in two the widest part among those whose bounding sphere is wider
+/// than `max_diameter`, along the longest axis of its bounding box.  Points
+/// past the middle of the box are moved to a new part.
+///
+/// Parts that only fixed points could leave are skipped.  Returns whether a
+/// part has been split.
+fn split_widest_cluster<const D: usize>(
+    part_ids: &mut [usize],
+    points: &[PointND<D>],
+    fixed: Option<&[bool]>,
+    max_diameter: f64,
+) -> bool {
+    let new_part = match part_ids.iter().max() {
+        Some(max) => max + 1,
+        None => return false,
+    };
+
+    let mut parts: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (index, part) in part_ids.iter().enumerate() {
+        parts.entry(*part).or_default().push(index);
+    }
+    let mut too_wide: Vec<(f64, usize, Vec<PointND<D>>, Vec<usize>)> = parts
+        .into_iter()
+        .filter_map(|(part, indices)| {
+            let part_points: Vec<PointND<D>> = indices.iter().map(|i| points[*i]).collect();
+            let diameter = 2.0 * BoundingSphere::from_points(&part_points)?.radius();
+            if diameter <= max_diameter {
+                return None;
+            }
+            Some((diameter, part, part_points, indices))
+        })
+        .collect();
+    too_wide.sort_by(|(d1, p1, _, _), (d2, p2, _, _)| f64::total_cmp(d2, d1).then(p1.cmp(p2)));
+
+    for (_, _, part_points, indices) in too_wide {
+        let bb = par_bounding_box(part_points.par_iter().cloned()).unwrap();
+        let (axis, _) = bb.extents().argmax();
+        let middle = bb.center()[axis];
+        let mut split = false;
+        for (point, index) in part_points.iter().zip(indices) {
+            let is_fixed = fixed.is_some_and(|fixed| fixed[index]);
+            if middle < point[axis] && !is_fixed {
+                part_ids[index] = new_part;
+                split = true;
+            }
+        }
+        if split {
+            return true;
+        }
+    }
+    false
+}
+
+impl crate::Name for KMeans {
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed("k_means")
+    }
+}
+
+impl<'a, const D: usize> crate::Partition<(&'a [PointND<D>], &'a [f64])> for KMeans
+where
+    Const<D>: DimSub<Const<1>>,
+    DefaultAllocator: Allocator<f64, Const<D>, Const<D>, Buffer = ArrayStorage<f64, D, D>>
+        + Allocator<f64, DimDiff<Const<D>, Const<1>>>,
+{
+    type Metadata = ();
+    type Error = crate::Error;
+
+    fn partition(
+        &mut self,
+        part_ids: &mut [usize],
+        (points, weights): (&'a [PointND<D>], &'a [f64]),
+    ) -> Result<Self::Metadata, Self::Error> {
+        check_input(part_ids, points, &[weights])?;
+        self.run(part_ids, points, &[weights], None, None, None);
+        Ok(())
+    }
+}
+
+/// [KMeans] that also stops when the edge cut of the partition plateaus.
+///
+/// This type is returned by [`KMeans::with_edge_cut_patience`].
+#[derive(Debug)]
+pub struct CutAwareKMeans<T> {
+    pub k_means: KMeans,
+
+    /// The topology on which the edge cut is measured.
+    pub adjacency: T,
+
+    /// The number of consecutive iterations without any improvement of the
+    /// edge cut after which the algorithm stops.
+    pub patience: usize,
+}
+
+impl<T> crate::Name for CutAwareKMeans<T> {
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed("cut_aware_k_means")
+    }
+}
+
+impl<'a, const D: usize, T> crate::Partition<(&'a [PointND<D>], &'a [f64])> for CutAwareKMeans<T>
+where
+    Const<D>: DimSub<Const<1>>,
+    DefaultAllocator: Allocator<f64, Const<D>, Const<D>, Buffer = ArrayStorage<f64, D, D>>
+        + Allocator<f64, DimDiff<Const<D>, Const<1>>>,
+    T: Topology<i64> + Sync,
+{
+    type Metadata = ();
+    type Error = crate::Error;
+
+    fn partition(
+        &mut self,
+        part_ids: &mut [usize],
+        (points, weights): (&'a [PointND<D>], &'a [f64]),
+    ) -> Result<Self::Metadata, Self::Error> {
+        check_input(part_ids, points, &[weights])?;
+        let adjacency = &self.adjacency;
+        let patience = self.patience;
+        let mut best_cut = adjacency.edge_cut(part_ids);
+        let mut stale_iter = 0;
+        let mut should_stop = |assignments: &[usize]| {
+            let cut = adjacency.edge_cut(assignments);
+            if cut < best_cut {
+                best_cut = cut;
+                stale_iter = 0;
+            } else {
+                stale_iter += 1;
+            }
+            patience <= stale_iter
         };
-        balanced_k_means_with_initial_partition(points, weights, settings, part_ids);
+        self.k_means.run(
+            part_ids,
+            points,
+            &[weights],
+            None,
+            None,
+            Some(&mut should_stop),
+        );
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Partition as _;
+    use crate::Point2D;
+
+    #[test]
+    fn test_influences_follow_clusters() {
+        // A 20x5 grid, and two clusters outside of it: a far one on the
+        // right with a low influence, and a close one on the left.
+        let points: Vec<Point2D> = (0..100)
+            .map(|i| Point2D::new((i % 20) as f64, (i / 20) as f64))
+            .collect();
+        let weights = [1.0; 100];
+        let centers = [Point2D::new(40.0, 2.0), Point2D::new(-3.0, 2.0)];
+        let center_ids = [0, 1];
+        let mut assignments = [0; 100];
+        let mut influences = [0.5, 1.0];
+        let mut lbs = [0.0; 100];
+        let mut ubs = [f64::MAX; 100];
+
+        // Clusters are sorted by their effective distance to the points,
+        // which swaps them.
+        assign_and_balance(
+            Inputs {
+                points: &points,
+                weights: &[&weights],
+                shares: &criterion_shares(&[&weights], points.len()),
+            },
+            &mut (0..points.len()).collect::<Vec<_>>(),
+            AlgorithmState {
+                assignments: &mut assignments,
+                influences: &mut influences,
+                lbs: &mut lbs,
+                ubs: &mut ubs,
+            },
+            Clusters {
+                centers: &centers,
+                center_ids: &center_ids,
+            },
+            &BalancedKmeansSettings {
+                imbalance_tol: f64::INFINITY,
+                ..Default::default()
+            },
+        );
+
+        // In the middle row, the right end is effectively closer to the far
+        // cluster, and the left end to the close one.
+        assert_eq!(assignments[40], 1);
+        assert_eq!(assignments[59], 0);
+        assert_eq!(influences, [0.5, 1.0]);
+    }
+
+    #[test]
+    fn test_bounds_follow_influences() {
+        // A 10x10 grid, whose left half weighs three times more than its
+        // right half.  Clusters start at the center of each half, so they do
+        // not move when points are first assigned to them.
+        let points: Vec<Point2D> = (0..100)
+            .map(|i| Point2D::new((i % 10) as f64, (i / 10) as f64))
+            .collect();
+        let weights: Vec<f64> = points
+            .iter()
+            .map(|p| if p.x < 5.0 { 3.0 } else { 1.0 })
+            .collect();
+        let centers = [Point2D::new(2.0, 4.5), Point2D::new(7.0, 4.5)];
+        let mut assignments = [0; 100];
+        let mut influences = [1.0, 1.0];
+        let mut lbs = [0.0; 100];
+        let mut ubs = [f64::MAX; 100];
+        let imbalance_tol = 50.0;
+
+        assign_and_balance(
+            Inputs {
+                points: &points,
+                weights: &[&weights],
+                shares: &criterion_shares(&[&weights], points.len()),
+            },
+            &mut (0..points.len()).collect::<Vec<_>>(),
+            AlgorithmState {
+                assignments: &mut assignments,
+                influences: &mut influences,
+                lbs: &mut lbs,
+                ubs: &mut ubs,
+            },
+            Clusters {
+                centers: &centers,
+                center_ids: &[0, 1],
+            },
+            &BalancedKmeansSettings {
+                imbalance_tol,
+                max_balance_iter: 50,
+                ..Default::default()
+            },
+        );
+
+        // Points must move as influences change, even though clusters do not.
+        let cluster_weights = cluster_weights(&assignments, &[&weights], &[0, 1]);
+        assert!(
+            imbalance(&cluster_weights[0]) < imbalance_tol,
+            "{cluster_weights:?}"
+        );
+        assert!(influences[0] > influences[1], "{influences:?}");
+    }
+
+    #[test]
+    fn test_builder() {
+        let default = KMeans::default();
+        let built = KMeans::builder().build();
+        assert_eq!(built.imbalance_tol, default.imbalance_tol);
+        assert_eq!(built.delta_threshold, default.delta_threshold);
+        assert_eq!(built.max_iter, default.max_iter);
+        assert_eq!(built.max_balance_iter, default.max_balance_iter);
+        assert_eq!(built.erode, default.erode);
+        assert_eq!(built.hilbert, default.hilbert);
+        assert_eq!(built.mbr_early_break, default.mbr_early_break);
+        assert_eq!(built.metric, default.metric);
+        assert_eq!(built.geometric_median, default.geometric_median);
+        assert_eq!(built.kd_tree, default.kd_tree);
+
+        let built = KMeans::builder()
+            .imbalance_tol(1.0)
+            .delta_threshold(0.5)
+            .max_iter(3)
+            .max_balance_iter(4)
+            .erode(true)
+            .hilbert(false)
+            .mbr_early_break(true)
+            .metric(Metric::Chebyshev)
+            .geometric_median(true)
+            .kd_tree(true)
+            .build();
+        assert_eq!(built.imbalance_tol, 1.0);
+        assert_eq!(built.delta_threshold, 0.5);
+        assert_eq!(built.max_iter, 3);
+        assert_eq!(built.max_balance_iter, 4);
+        assert!(built.erode);
+        assert!(!built.hilbert);
+        assert!(built.mbr_early_break);
+        assert_eq!(built.metric, Metric::Chebyshev);
+        assert!(built.geometric_median);
+        assert!(built.kd_tree);
+    }
+
+    #[test]
+    fn test_fixed_points() {
+        // Two rows of 10 points, split unevenly: the first 15 points in part
+        // 0, the last 5 in part 1.  Two points are pinned in the part that is
+        // on the other side of the domain.
+        let points: Vec<Point2D> = (0..20)
+            .map(|i| Point2D::new((i % 10) as f64, (i / 10) as f64))
+            .collect();
+        let weights = [1.0; 20];
+        let mut partition: Vec<usize> = (0..20).map(|i| usize::from(i >= 15)).collect();
+        partition[1] = 1;
+        partition[18] = 0;
+        let mut fixed = [false; 20];
+        fixed[1] = true;
+        fixed[18] = true;
+
+        KMeans {
+            delta_threshold: 0.0,
+            ..Default::default()
+        }
+        .partition_with_fixed(&mut partition, (&points, &weights), &fixed)
+        .unwrap();
+
+        assert_eq!(partition[1], 1);
+        assert_eq!(partition[18], 0);
+        let part0_count = partition.iter().filter(|p| **p == 0).count();
+        assert!((9..=11).contains(&part0_count), "{partition:?}");
+    }
+
+    #[test]
+    fn test_fixed_len_mismatch() {
+        let points: Vec<Point2D> = (0..4).map(|i| Point2D::new(i as f64, 0.0)).collect();
+        let weights = [1.0; 4];
+        let mut partition = [0, 0, 1, 1];
+
+        let err = KMeans::default()
+            .partition_with_fixed(&mut partition, (&points, &weights), &[false; 3])
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            crate::Error::InputLenMismatch {
+                expected: 4,
+                actual: 3,
+            }
+        ));
+
+        let err = KMeans::default()
+            .partition_with_fixed(&mut partition, (&points, &weights[..2]), &[false; 4])
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            crate::Error::InputLenMismatch {
+                expected: 4,
+                actual: 2,
+            }
+        ));
+    }
+
+    #[test]
+    fn test_sparse_part_ids() {
+        let points = [
+            Point2D::new(0., 0.),
+            Point2D::new(1., 0.),
+            Point2D::new(2., 0.),
+            Point2D::new(0., 5.),
+            Point2D::new(1., 5.),
+            Point2D::new(2., 5.),
+            Point2D::new(0., 10.),
+            Point2D::new(1., 10.),
+            Point2D::new(2., 10.),
+        ];
+        let weights = [1.0; 9];
+        let mut partition = [3, 42, 42, 42, 42, 42, 42, 42, 7];
+
+        KMeans {
+            delta_threshold: 0.0,
+            ..Default::default()
+        }
+        .partition(&mut partition, (&points[..], &weights[..]))
+        .unwrap();
+
+        let mut part_ids = partition.to_vec();
+        part_ids.sort();
+        part_ids.dedup();
+        assert_eq!(part_ids, [3, 7, 42]);
+        for row in partition.chunks(3) {
+            assert!(row.iter().all(|part| *part == row[0]));
+        }
+    }
+
+    #[test]
+    fn test_on_iteration() {
+        use std::sync::Arc;
+        use std::sync::Mutex;
+
+        let points = [
+            Point2D::new(0., 0.),
+            Point2D::new(1., 0.),
+            Point2D::new(2., 0.),
+            Point2D::new(0., 5.),
+            Point2D::new(1., 5.),
+            Point2D::new(2., 5.),
+            Point2D::new(0., 10.),
+            Point2D::new(1., 10.),
+            Point2D::new(2., 10.),
+        ];
+        let weights = [1.0; 9];
+        let mut partition = [0, 2, 2, 2, 2, 2, 2, 2, 1];
+
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let reports_ref = Arc::clone(&reports);
+        KMeans::builder()
+            .delta_threshold(0.0)
+            .max_iter(10)
+            .on_iteration(move |report| reports_ref.lock().unwrap().push(report))
+            .build()
+            .partition(&mut partition, (&points[..], &weights[..]))
+            .unwrap();
+
+        let reports = reports.lock().unwrap();
+        assert_eq!(reports.len(), 11);
+        for (i, report) in reports.iter().enumerate() {
+            assert_eq!(report.iteration, i);
+        }
+        for window in reports.windows(2) {
+            assert!(window[1].delta_max <= window[0].delta_max);
+        }
+        assert_eq!(reports.last().unwrap().imbalance, 0.0);
+    }
+
+    #[test]
+    fn test_imbalance_is_relative() {
+        let points: Vec<Point2D> = (0..100)
+            .map(|i| Point2D::new((i % 10) as f64, (i / 10) as f64))
+            .collect();
+        let weights: Vec<f64> = (0..100).map(|i| (1 + i % 3) as f64).collect();
+        // Scaling by a power of two is exact, so both runs make the same
+        // decisions if the tolerance is relative.
+        let scaled_weights: Vec<f64> = weights.iter().map(|w| w * 1024.0).collect();
+        let initial: Vec<usize> = points.iter().map(|p| usize::from(7.0 <= p.x)).collect();
+        let k_means = || KMeans {
+            imbalance_tol: 5.0,
+            ..Default::default()
+        };
+
+        let mut partition = initial.clone();
+        let report = k_means()
+            .partition_with_report(&mut partition, (&points, &weights))
+            .unwrap();
+        let mut scaled_partition = initial;
+        let scaled_report = k_means()
+            .partition_with_report(&mut scaled_partition, (&points, &scaled_weights))
+            .unwrap();
+
+        assert_eq!(partition, scaled_partition);
+        assert_eq!(report, scaled_report);
+        assert!(report.final_imbalance < 5.0, "{report:?}");
+    }
+
+    #[test]
+    fn test_report() {
+        let points: Vec<Point2D> = (0..40)
+            .map(|i| Point2D::new((i % 10) as f64, (i / 10) as f64))
+            .collect();
+        let weights = vec![1.0; 40];
+        let initial: Vec<usize> = (0..40).map(|i| usize::from(i == 39)).collect();
+
+        let mut partition = initial.clone();
+        let report = KMeans::default()
+            .partition_with_report(&mut partition, (&points, &weights))
+            .unwrap();
+        assert!(report.converged, "{report:?}");
+        assert!(report.final_delta < KMeans::default().delta_threshold);
+        assert_eq!(report.final_imbalance, 0.0);
+
+        let mut partition = initial;
+        let report = KMeans {
+            max_iter: 1,
+            ..Default::default()
+        }
+        .partition_with_report(&mut partition, (&points, &weights))
+        .unwrap();
+        assert!(!report.converged, "{report:?}");
+        assert_eq!(report.iterations, 2);
+    }
+
+    #[test]
+    fn test_multi_criteria() {
+        // A 20x10 grid of points that weigh on the first criterion, and 40
+        // points that only weigh on the second one, packed on the left side.
+        let mut points: Vec<Point2D> = (0..200)
+            .map(|i| Point2D::new((i % 20) as f64, (i / 20) as f64))
+            .collect();
+        let mut weights = vec![[1.0, 0.0]; 200];
+        for i in 0..40 {
+            points.push(Point2D::new((i % 4) as f64 + 0.5, (i / 4) as f64 + 0.5));
+            weights.push([0.0, 1.0]);
+        }
+        let initial_partition: Vec<usize> =
+            points.iter().map(|p| usize::from(10.0 <= p.x)).collect();
+        let criterion_imbalances = |partition: &[usize]| {
+            let mut part_weights = [[0.0; 2]; 2];
+            for (part, weight) in partition.iter().zip(&weights) {
+                part_weights[*part][0] += weight[0];
+                part_weights[*part][1] += weight[1];
+            }
+            [0, 1].map(|criterion| {
+                let [left, right] = [0, 1].map(|part| part_weights[part][criterion]);
+                f64::abs(left - right) / (left + right) * 200.0
+            })
+        };
+        let imbalance_tol = 30.0;
+
+        // A single influence per cluster can only balance the sum of the
+        // criteria, which leaves the second one in the left part.
+        let summed_weights: Vec<f64> = weights.iter().map(|w| w[0] + w[1]).collect();
+        let mut partition = initial_partition.clone();
+        KMeans {
+            imbalance_tol,
+            ..Default::default()
+        }
+        .partition(&mut partition, (&points, &summed_weights))
+        .unwrap();
+        let [_, second] = criterion_imbalances(&partition);
+        assert!(imbalance_tol < second, "{second}");
+
+        let mut partition = initial_partition;
+        let report = KMeans {
+            imbalance_tol,
+            ..Default::default()
+        }
+        .partition_multi_criteria(&mut partition, (&points, &weights))
+        .unwrap();
+        let [first, second] = criterion_imbalances(&partition);
+        assert!(
+            first < imbalance_tol && second < imbalance_tol,
+            "{first} {second}"
+        );
+        assert!(report.final_imbalance < imbalance_tol);
+    }
+
+    #[test]
+    fn test_multi_criteria_bad_input() {
+        let points: Vec<Point2D> = (0..4).map(|i| Point2D::new(i as f64, 0.0)).collect();
+        let mut partition = [0, 0, 1, 1];
+
+        let err = KMeans::default()
+            .partition_multi_criteria(&mut partition, (&points, &[[1.0, 1.0]; 3]))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            crate::Error::InputLenMismatch {
+                expected: 4,
+                actual: 3,
+            }
+        ));
+
+        let err = KMeans::default()
+            .partition_multi_criteria(&mut partition, (&points[..2], &[[1.0]; 4]))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            crate::Error::InputLenMismatch {
+                expected: 4,
+                actual: 2,
+            }
+        ));
+
+        let err = KMeans::default()
+            .partition_multi_criteria(&mut partition, (&points, &[[]; 4]))
+            .unwrap_err();
+        assert!(matches!(err, crate::Error::NoCriterion));
+    }
+
+    #[test]
+    fn test_edge_cut_patience() {
+        use crate::Grid;
+        use std::num::NonZeroUsize;
+        use std::sync::atomic::AtomicUsize;
+        use std::sync::Arc;
+
+        let side = NonZeroUsize::new(20).unwrap();
+        let grid = Grid::new_2d(side, side);
+        let points: Vec<Point2D> = (0..400)
+            .map(|i| Point2D::new((i % 20) as f64, (i / 20) as f64))
+            .collect();
+        let weights = [1.0; 400];
+        // Four parts, scattered all over the grid.
+        let initial: Vec<usize> = (0..400).map(|i| i * 7 % 4).collect();
+
+        let k_means = |iter_count: &Arc<AtomicUsize>| {
+            let iter_count = Arc::clone(iter_count);
+            KMeans::builder()
+                .delta_threshold(0.0)
+                .max_iter(100)
+                .on_iteration(move |_| {
+                    iter_count.fetch_add(1, atomic::Ordering::Relaxed);
+                })
+                .build()
+        };
+
+        let plain_iter_count = Arc::new(AtomicUsize::new(0));
+        let mut plain = initial.clone();
+        k_means(&plain_iter_count)
+            .partition(&mut plain, (&points[..], &weights[..]))
+            .unwrap();
+
+        let cut_aware_iter_count = Arc::new(AtomicUsize::new(0));
+        let mut cut_aware = initial.clone();
+        k_means(&cut_aware_iter_count)
+            .with_edge_cut_patience(grid, 3)
+            .partition(&mut cut_aware, (&points[..], &weights[..]))
+            .unwrap();
+
+        let plain_iter_count = plain_iter_count.load(atomic::Ordering::Relaxed);
+        let cut_aware_iter_count = cut_aware_iter_count.load(atomic::Ordering::Relaxed);
+        let plain_cut: i64 = grid.edge_cut(&plain);
+        let cut_aware_cut: i64 = grid.edge_cut(&cut_aware);
+        assert!(cut_aware_iter_count < plain_iter_count);
+        assert!(cut_aware_cut as f64 <= 1.1 * plain_cut as f64);
+    }
+
+    #[test]
+    fn test_kd_tree_assignment() {
+        use rand::Rng as _;
+        use rand::SeedableRng as _;
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+        let points: Vec<Point2D> = (0..2000)
+            .map(|_| Point2D::new(rng.gen(), rng.gen()))
+            .collect();
+        let weights = vec![1.0; points.len()];
+        // A 4x4 grid of square parts.
+        let initial: Vec<usize> = points
+            .iter()
+            .map(|p| (4.0 * p.x) as usize + 4 * (4.0 * p.y) as usize)
+            .collect();
+
+        let run = |kd_tree| {
+            let mut partition = initial.clone();
+            KMeans {
+                max_iter: 10,
+                kd_tree,
+                ..Default::default()
+            }
+            .partition(&mut partition, (&points[..], &weights[..]))
+            .unwrap();
+            partition
+        };
+
+        assert_eq!(run(true), run(false));
+    }
+
+    #[test]
+    fn test_metric_distance() {
+        let a = Point2D::new(1.0, -2.0);
+        let b = Point2D::new(4.0, 2.0);
+        assert_eq!(Metric::Euclidean.distance(&a, &b), 5.0);
+        assert_eq!(Metric::Manhattan.distance(&a, &b), 7.0);
+        assert_eq!(Metric::Chebyshev.distance(&a, &b), 4.0);
+    }
+
+    #[test]
+    fn test_metric_assignment() {
+        // Cluster 0 is spread along the x axis and centered on (0, 0), cluster
+        // 1 is spread along the y axis and centered on (1, -2).  The point
+        // (3, 0) is 3 away from (0, 0) with all metrics, but is (2, 2) away
+        // from (1, -2), which is closer in L2 and L∞, but farther in L1.
+        let points = [
+            Point2D::new(-3.0, 0.0),
+            Point2D::new(-1.0, 0.0),
+            Point2D::new(1.0, 0.0),
+            Point2D::new(3.0, 0.0),
+            Point2D::new(1.0, -1.5),
+            Point2D::new(1.0, -2.5),
+        ];
+        let weights = [1.0; 6];
+        let initial_partition = [0, 0, 0, 0, 1, 1];
+
+        let run = |metric| {
+            let mut partition = initial_partition;
+            KMeans {
+                // Only one assignment step, without influence balancing.
+                max_iter: 0,
+                imbalance_tol: f64::INFINITY,
+                metric,
+                ..Default::default()
+            }
+            .partition(&mut partition, (&points, &weights))
+            .unwrap();
+            partition
+        };
+
+        assert_eq!(run(Metric::Euclidean), [0, 0, 0, 1, 1, 1]);
+        assert_eq!(run(Metric::Chebyshev), [0, 0, 0, 1, 1, 1]);
+        assert_eq!(run(Metric::Manhattan), [0, 0, 0, 0, 1, 1]);
+    }
+
+    #[test]
+    fn test_geometric_median_assignment() {
+        // Cluster 0 is a small cross around (0, 0), plus a point at (6, 0) and
+        // a far outlier at (30, 0) that pulls its centroid to about (5, 0).
+        // Cluster 1 is centered on (10, 0).
+        let points = [
+            Point2D::new(-1.0, 0.0),
+            Point2D::new(0.0, 0.0),
+            Point2D::new(1.0, 0.0),
+            Point2D::new(0.0, 1.0),
+            Point2D::new(0.0, -1.0),
+            Point2D::new(6.0, 0.0),
+            Point2D::new(30.0, 0.0),
+            Point2D::new(10.0, -1.0),
+            Point2D::new(10.0, 0.0),
+            Point2D::new(10.0, 1.0),
+        ];
+        let weights = [1.0; 10];
+        let initial_partition = [0, 0, 0, 0, 0, 0, 0, 1, 1, 1];
+
+        let run = |geometric_median| {
+            let mut partition = initial_partition;
+            KMeans {
+                // Only one assignment step, without influence balancing.
+                max_iter: 0,
+                imbalance_tol: f64::INFINITY,
+                geometric_median,
+                ..Default::default()
+            }
+            .partition(&mut partition, (&points, &weights))
+            .unwrap();
+            partition
+        };
+
+        assert_eq!(run(false), [0, 0, 0, 0, 0, 0, 1, 1, 1, 1]);
+        assert_eq!(run(true), [0, 0, 0, 0, 0, 1, 1, 1, 1, 1]);
+    }
+
+    #[test]
+    fn test_initial_centers() {
+        // Two groups along the x axis, with centers given in reverse order and
+        // a third center far away from both.
+        let points: Vec<Point2D> = (0..10)
+            .map(|i| Point2D::new(if i < 5 { i as f64 } else { 20.0 + i as f64 }, 0.0))
+            .collect();
+        let weights = [1.0; 10];
+        let centers = [
+            Point2D::new(25.0, 0.0),
+            Point2D::new(2.0, 0.0),
+            Point2D::new(0.0, 100.0),
+        ];
+        let mut partition = [usize::MAX; 10];
+
+        KMeans {
+            imbalance_tol: f64::INFINITY,
+            ..Default::default()
+        }
+        .partition_with_centers(&mut partition, (&points, &weights), &centers)
+        .unwrap();
+
+        assert_eq!(partition, [1, 1, 1, 1, 1, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_max_diameter() {
+        // Two parts over a line of 40 points cannot both be 12 wide.
+        let points: Vec<Point2D> = (0..40).map(|i| Point2D::new(i as f64, 0.0)).collect();
+        let weights = [1.0; 40];
+        let mut partition: Vec<usize> = (0..40).map(|i| i / 20).collect();
+
+        KMeans {
+            max_diameter: Some(12.0),
+            ..Default::default()
+        }
+        .partition(&mut partition, (&points, &weights))
+        .unwrap();
+
+        let part_count = partition.iter().unique().count();
+        assert!(part_count > 2, "expected extra parts, got {part_count}");
+        for part in partition.iter().unique() {
+            let xs = points
+                .iter()
+                .zip(&partition)
+                .filter(|(_, p)| *p == part)
+                .map(|(point, _)| point.x);
+            let (min, max) = xs.minmax().into_option().unwrap();
+            assert!(max - min <= 12.0, "part {part} spans [{min}, {max}]");
+        }
+    }
+
+    #[test]
+    fn test_mbr_early_break_metric() {
+        // The third center is 0.9 away from (1, 1) in L∞, but 0.9√2 away from
+        // the bounding box of the points in L2.  The first two centers are 1
+        // away from (1, 1), so a Euclidean bound would prune the third one.
+        let points = [Point2D::new(0.0, 0.0), Point2D::new(1.0, 1.0)];
+        let weights = [1.0; 2];
+        let centers = [
+            Point2D::new(0.0, 0.0),
+            Point2D::new(0.0, 1.0),
+            Point2D::new(1.9, 1.9),
+        ];
+        let mut partition = [usize::MAX; 2];
+
+        KMeans {
+            // Only one assignment step, without influence balancing.
+            max_iter: 0,
+            imbalance_tol: f64::INFINITY,
+            mbr_early_break: true,
+            metric: Metric::Chebyshev,
+            ..Default::default()
+        }
+        .partition_with_centers(&mut partition, (&points, &weights), &centers)
+        .unwrap();
+
+        assert_eq!(partition, [0, 2]);
+    }
+}
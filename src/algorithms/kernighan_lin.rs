@@ -6,6 +6,9 @@
 use crate::topology::Topology;
 use itertools::Itertools;
 
+use std::borrow::Cow;
+
+#[allow(clippy::too_many_arguments)]
 fn kernighan_lin<T>(
     part_ids: &mut [usize],
     weights: &[f64],
@@ -14,6 +17,7 @@ fn kernighan_lin<T>(
     max_flips_per_pass: Option<usize>,
     max_imbalance_per_flip: Option<f64>,
     max_bad_move_in_a_row: usize,
+    fixed: Option<&[bool]>,
 ) where
     T: Topology<f64> + Sync,
 {
@@ -30,9 +34,11 @@ fn kernighan_lin<T>(
         max_flips_per_pass,
         max_imbalance_per_flip,
         max_bad_move_in_a_row,
+        fixed,
     );
 }
 
+#[allow(clippy::too_many_arguments)]
 fn kernighan_lin_2_impl<T>(
     initial_partition: &mut [usize],
     weights: &[f64],
@@ -41,6 +47,7 @@ fn kernighan_lin_2_impl<T>(
     max_flips_per_pass: Option<usize>,
     _max_imbalance_per_flip: Option<f64>,
     max_bad_move_in_a_row: usize,
+    fixed: Option<&[bool]>,
 ) where
     T: Topology<f64> + Sync,
 {
@@ -75,7 +82,11 @@ fn kernighan_lin_2_impl<T>(
                                         // let mut ids_before_flip = Vec::new(); // the target id for reverting a flip
 
         let mut gains: Vec<f64> = vec![0.; initial_partition.len()];
-        let mut locks = vec![false; initial_partition.len()];
+        // Fixed vertices are locked from the start, so they are never swapped.
+        let mut locks = match fixed {
+            Some(fixed) => fixed.to_vec(),
+            None => vec![false; initial_partition.len()],
+        };
 
         // pass loop
         for _ in 0..(initial_partition.len() / 2).min(max_flips_per_pass.unwrap_or(std::usize::MAX))
@@ -92,7 +103,7 @@ fn kernighan_lin_2_impl<T>(
             }
 
             // find max gain for first part
-            let (max_pos_1, max_gain_1) = gains
+            let (max_pos_1, max_gain_1) = match gains
                 .iter()
                 .zip(locks.iter())
                 .zip(weights.iter())
@@ -102,7 +113,10 @@ fn kernighan_lin_2_impl<T>(
                 })
                 .map(|(idx, ((gain, _), _))| (idx, *gain))
                 .max_by(|(_, g1), (_, g2)| g1.partial_cmp(g2).unwrap())
-                .unwrap();
+            {
+                Some(v) => v,
+                None => break,
+            };
 
             // update gain of neighbors
             for (j, w) in adjacency.neighbors(max_pos_1) {
@@ -114,7 +128,7 @@ fn kernighan_lin_2_impl<T>(
             }
 
             // find max gain for second part
-            let (max_pos_2, max_gain_2) = gains
+            let (max_pos_2, max_gain_2) = match gains
                 .iter()
                 .zip(locks.iter())
                 .zip(weights.iter())
@@ -124,7 +138,10 @@ fn kernighan_lin_2_impl<T>(
                 })
                 .map(|(idx, ((gain, _), _))| (idx, *gain))
                 .max_by(|(_, g1), (_, g2)| g1.partial_cmp(g2).unwrap())
-                .unwrap();
+            {
+                Some(v) => v,
+                None => break,
+            };
 
             let total_gain = max_gain_1 + max_gain_2;
 
@@ -146,13 +163,17 @@ fn kernighan_lin_2_impl<T>(
             cut_saves.push(adjacency.edge_cut(initial_partition));
         }
 
-        // lookup for best cutsize
-        let (best_pos, best_cut) = cut_saves
+        // lookup for best cutsize, no swap could be made if all vertices of a
+        // part are locked
+        let (best_pos, best_cut) = match cut_saves
             .iter()
             .cloned()
             .enumerate()
             .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
-            .unwrap();
+        {
+            Some(v) => v,
+            None => break,
+        };
 
         // rewind swaps
         tracing::info!(
@@ -261,18 +282,62 @@ pub struct KernighanLin {
     pub max_bad_move_in_a_row: usize,
 }
 
-impl<'a, T> crate::Partition<(T, &'a [f64])> for KernighanLin
-where
-    T: Topology<f64> + Sync,
-{
-    type Metadata = ();
-    type Error = std::convert::Infallible;
+impl crate::Name for KernighanLin {
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed("kernighan_lin")
+    }
+}
 
-    fn partition(
+impl KernighanLin {
+    /// Same as [`Partition::partition`][crate::Partition::partition], except
+    /// that vertices for which `fixed` is `true` keep the part they have in
+    /// `part_ids`.
+    ///
+    /// Fixed vertices still count toward the edge cut, but are never swapped.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), coupe::Error> {
+    /// use coupe::Grid;
+    /// use std::num::NonZeroUsize;
+    ///
+    /// let grid = Grid::new_2d(NonZeroUsize::new(4).unwrap(), NonZeroUsize::new(2).unwrap());
+    /// let weights = [1.0; 8];
+    /// let mut partition = [0, 0, 1, 1, 0, 1, 0, 1];
+    /// let mut fixed = [false; 8];
+    /// fixed[5] = true;
+    ///
+    /// coupe::KernighanLin { max_passes: Some(1), ..Default::default() }
+    ///     .partition_with_fixed(&mut partition, (grid, &weights), &fixed)?;
+    ///
+    /// assert_eq!(partition[5], 1);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn partition_with_fixed<T>(
         &mut self,
         part_ids: &mut [usize],
-        (adjacency, weights): (T, &'a [f64]),
-    ) -> Result<Self::Metadata, Self::Error> {
+        (adjacency, weights): (T, &[f64]),
+        fixed: &[bool],
+    ) -> Result<(), crate::Error>
+    where
+        T: Topology<f64> + Sync,
+    {
+        if part_ids.len() != fixed.len() {
+            return Err(crate::Error::InputLenMismatch {
+                expected: part_ids.len(),
+                actual: fixed.len(),
+            });
+        }
+        self.run(part_ids, adjacency, weights, Some(fixed));
+        Ok(())
+    }
+
+    fn run<T>(&self, part_ids: &mut [usize], adjacency: T, weights: &[f64], fixed: Option<&[bool]>)
+    where
+        T: Topology<f64> + Sync,
+    {
         kernighan_lin(
             part_ids,
             weights,
@@ -281,7 +346,81 @@ where
             self.max_flips_per_pass,
             self.max_imbalance_per_flip,
             self.max_bad_move_in_a_row,
+            fixed,
         );
+    }
+}
+
+impl<'a, T> crate::Partition<(T, &'a [f64])> for KernighanLin
+where
+    T: Topology<f64> + Sync,
+{
+    type Metadata = ();
+    type Error = std::convert::Infallible;
+
+    fn partition(
+        &mut self,
+        part_ids: &mut [usize],
+        (adjacency, weights): (T, &'a [f64]),
+    ) -> Result<Self::Metadata, Self::Error> {
+        self.run(part_ids, adjacency, weights, None);
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Grid;
+    use crate::Partition as _;
+    use std::num::NonZeroUsize;
+
+    #[test]
+    fn test_fixed() {
+        // The 4x2 grid of the example of KernighanLin, where the best swap is
+        // between vertices 5 and 6.
+        let grid = Grid::new_2d(NonZeroUsize::new(4).unwrap(), NonZeroUsize::new(2).unwrap());
+        let weights = [1.0; 8];
+        let initial = [0, 0, 1, 1, 0, 1, 0, 1];
+        let mut algorithm = KernighanLin {
+            max_passes: Some(1),
+            max_flips_per_pass: Some(1),
+            max_imbalance_per_flip: None,
+            max_bad_move_in_a_row: 1,
+        };
+
+        let mut partition = initial;
+        algorithm
+            .partition(&mut partition, (grid, &weights[..]))
+            .unwrap();
+        assert_eq!(partition[5], 0);
+        assert_eq!(partition[6], 1);
+
+        let mut fixed = [false; 8];
+        fixed[5] = true;
+        let mut partition = initial;
+        algorithm
+            .partition_with_fixed(&mut partition, (grid, &weights), &fixed)
+            .unwrap();
+        assert_eq!(partition[5], 1);
+
+        // No swap can be made when a whole part is fixed.
+        let fixed = initial.map(|part| part == 0);
+        let mut partition = initial;
+        algorithm
+            .partition_with_fixed(&mut partition, (grid, &weights), &fixed)
+            .unwrap();
+        assert_eq!(partition, initial);
+
+        let err = algorithm
+            .partition_with_fixed(&mut partition, (grid, &weights), &[false; 9])
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            crate::Error::InputLenMismatch {
+                expected: 8,
+                actual: 9,
+            },
+        ));
+    }
+}
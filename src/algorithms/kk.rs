@@ -1,5 +1,6 @@
 use super::Error;
 use num_traits::Zero;
+use std::borrow::Cow;
 use std::collections::BinaryHeap;
 use std::ops::Sub;
 use std::ops::SubAssign;
@@ -157,6 +158,18 @@ pub struct KarmarkarKarp {
     pub part_count: usize,
 }
 
+impl crate::PartCount for KarmarkarKarp {
+    fn expected_part_count(&self) -> Option<usize> {
+        Some(self.part_count)
+    }
+}
+
+impl crate::Name for KarmarkarKarp {
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed("karmarkar_karp")
+    }
+}
+
 impl<W> crate::Partition<W> for KarmarkarKarp
 where
     W: IntoIterator,
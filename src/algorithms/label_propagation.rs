@@ -0,0 +1,513 @@
+use crate::topology::Topology;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::mem;
+
+/// Runs label propagation until convergence or `max_iter` sweeps.
+///
+/// Returns the community of each vertex, numbered from 0, and the number of
+/// communities.
+fn propagate_labels<T>(adjacency: &T, max_iter: usize) -> (Vec<usize>, usize)
+where
+    T: Topology<f64>,
+{
+    let mut labels: Vec<usize> = (0..adjacency.len()).collect();
+    let mut label_weights = HashMap::new();
+
+    for _ in 0..max_iter {
+        let mut changed = false;
+        for vertex in 0..adjacency.len() {
+            label_weights.clear();
+            for (neighbor, edge_weight) in adjacency.neighbors(vertex) {
+                if neighbor != vertex {
+                    *label_weights.entry(labels[neighbor]).or_insert(0.0) += edge_weight;
+                }
+            }
+            let best_weight = match label_weights.values().cloned().reduce(f64::max) {
+                Some(w) => w,
+                None => continue, // isolated vertex
+            };
+            // Keep the current label on ties, so that the algorithm converges.
+            if label_weights.get(&labels[vertex]) == Some(&best_weight) {
+                continue;
+            }
+            let best_label = label_weights
+                .iter()
+                .filter(|(_, weight)| **weight == best_weight)
+                .map(|(label, _)| *label)
+                .min()
+                .unwrap();
+            labels[vertex] = best_label;
+            changed = true;
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    // Renumber communities from 0.
+    let mut ids = HashMap::new();
+    for label in &mut labels {
+        let next_id = ids.len();
+        *label = *ids.entry(*label).or_insert(next_id);
+    }
+    let community_count = ids.len();
+    (labels, community_count)
+}
+
+/// A community load, ordered with [`f64::total_cmp`].
+#[derive(Clone, Copy)]
+struct Load(f64);
+
+impl PartialEq for Load {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Load {}
+
+impl PartialOrd for Load {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Load {
+    fn cmp(&self, other: &Self) -> Ordering {
+        f64::total_cmp(&self.0, &other.0)
+    }
+}
+
+/// The lightest community of `queue`, after out-of-date entries have been
+/// dropped.
+fn lightest(
+    queue: &mut BinaryHeap<Reverse<(Load, usize)>>,
+    loads: &[f64],
+    members: &[Vec<usize>],
+) -> Option<usize> {
+    while let Some(Reverse((load, c))) = queue.peek() {
+        if !members[*c].is_empty() && *load == Load(loads[*c]) {
+            return Some(*c);
+        }
+        queue.pop();
+    }
+    None
+}
+
+/// Merges the lightest community into its most connected neighbor, until
+/// only `part_count` communities are left.
+///
+/// Merged communities are left empty, so that the IDs of the others do not
+/// change during the merges; [`remove_empty`] then renumbers them.
+fn merge_lightest<T>(
+    adjacency: &T,
+    part_count: usize,
+    communities: &mut [usize],
+    loads: &mut [f64],
+    members: &mut [Vec<usize>],
+) where
+    T: Topology<f64>,
+{
+    // Entries are not updated when a community grows or is merged, a new one
+    // is pushed instead, and the old one is skipped by `lightest`.
+    let mut queue: BinaryHeap<Reverse<(Load, usize)>> = loads
+        .iter()
+        .enumerate()
+        .map(|(c, load)| Reverse((Load(*load), c)))
+        .collect();
+    let mut community_count = loads.len();
+    let mut connections: HashMap<usize, f64> = HashMap::new();
+
+    while part_count < community_count {
+        let lightest_community = lightest(&mut queue, loads, members).unwrap();
+        let merged = mem::take(&mut members[lightest_community]);
+
+        connections.clear();
+        for vertex in &merged {
+            for (neighbor, edge_weight) in adjacency.neighbors(*vertex) {
+                let c = communities[neighbor];
+                if c != lightest_community {
+                    *connections.entry(c).or_insert(0.0) += edge_weight;
+                }
+            }
+        }
+        let target = connections
+            .iter()
+            .max_by(|(c1, w1), (c2, w2)| f64::total_cmp(w1, w2).then(c2.cmp(c1)))
+            .map(|(c, _)| *c)
+            .unwrap_or_else(|| {
+                // Disconnected community: merge it with the lightest other one.
+                lightest(&mut queue, loads, members).unwrap()
+            });
+
+        for vertex in &merged {
+            communities[*vertex] = target;
+        }
+        members[target].extend(merged);
+        loads[target] += loads[lightest_community];
+        queue.push(Reverse((Load(loads[target]), target)));
+        community_count -= 1;
+    }
+}
+
+/// Removes the communities left empty by [`merge_lightest`], and renumbers
+/// the others from 0.
+fn remove_empty(communities: &mut [usize], loads: &mut Vec<f64>, members: &mut Vec<Vec<usize>>) {
+    let mut next_id = 0;
+    for c in 0..members.len() {
+        if members[c].is_empty() {
+            continue;
+        }
+        for vertex in &members[c] {
+            communities[*vertex] = next_id;
+        }
+        loads[next_id] = loads[c];
+        members.swap(next_id, c);
+        next_id += 1;
+    }
+    loads.truncate(next_id);
+    members.truncate(next_id);
+}
+
+/// Splits the heaviest community that has more than one vertex in two, by
+/// growing a region from one of its vertices until it holds half of its
+/// weight.
+///
+/// Returns `false` if no community can be split.
+fn split_heaviest<T>(
+    adjacency: &T,
+    weights: &[f64],
+    communities: &mut [usize],
+    loads: &mut Vec<f64>,
+    members: &mut Vec<Vec<usize>>,
+) -> bool
+where
+    T: Topology<f64>,
+{
+    let heaviest = match (0..loads.len())
+        .filter(|c| members[*c].len() > 1)
+        .max_by(|c1, c2| f64::total_cmp(&loads[*c1], &loads[*c2]))
+    {
+        Some(c) => c,
+        None => return false,
+    };
+    let new = loads.len();
+    let half = loads[heaviest] / 2.0;
+    let mut grown_load = 0.0;
+    let mut grown_size = 0;
+
+    let mut queue = VecDeque::new();
+    let mut next_seed = 0;
+    while grown_load < half && grown_size + 1 < members[heaviest].len() {
+        let vertex = match queue.pop_front() {
+            Some(v) => v,
+            // The community might not be connected, hence the restart.
+            None => {
+                while communities[members[heaviest][next_seed]] != heaviest {
+                    next_seed += 1;
+                }
+                members[heaviest][next_seed]
+            }
+        };
+        if communities[vertex] != heaviest {
+            continue;
+        }
+        communities[vertex] = new;
+        grown_load += weights[vertex];
+        grown_size += 1;
+        queue.extend(
+            adjacency
+                .neighbors(vertex)
+                .map(|(neighbor, _)| neighbor)
+                .filter(|neighbor| communities[*neighbor] == heaviest),
+        );
+    }
+
+    let (grown, kept) = members[heaviest]
+        .iter()
+        .copied()
+        .partition(|vertex| communities[*vertex] == new);
+    loads[heaviest] -= grown_load;
+    members[heaviest] = kept;
+    loads.push(grown_load);
+    members.push(grown);
+    true
+}
+
+fn label_propagation<T>(
+    part_ids: &mut [usize],
+    weights: &[f64],
+    adjacency: T,
+    part_count: usize,
+    max_iter: usize,
+) where
+    T: Topology<f64>,
+{
+    let (mut communities, community_count) = propagate_labels(&adjacency, max_iter);
+
+    let mut loads = vec![0.0; community_count];
+    let mut members = vec![Vec::new(); community_count];
+    for (vertex, (community, weight)) in communities.iter().zip(weights).enumerate() {
+        loads[*community] += weight;
+        members[*community].push(vertex);
+    }
+
+    if part_count < loads.len() {
+        merge_lightest(
+            &adjacency,
+            part_count,
+            &mut communities,
+            &mut loads,
+            &mut members,
+        );
+        remove_empty(&mut communities, &mut loads, &mut members);
+    }
+    while loads.len() < part_count {
+        if !split_heaviest(
+            &adjacency,
+            weights,
+            &mut communities,
+            &mut loads,
+            &mut members,
+        ) {
+            break;
+        }
+    }
+
+    part_ids.copy_from_slice(&communities);
+}
+
+/// # Label propagation
+///
+/// A topology-only partitioning algorithm, which does not need vertex
+/// coordinates.
+///
+/// Each vertex starts with its own label, then repeatedly takes the label
+/// that is the most common among its neighbors, weighted by edge weights,
+/// until labels stop changing or `max_iter` sweeps have been done.  Vertices
+/// that end up with the same label form a community.
+///
+/// Communities are then merged (the lightest one into its most connected
+/// neighbor) or split (the heaviest one in two halves, by region growing)
+/// until there are exactly `part_count` of them.  If there are fewer vertices
+/// than `part_count`, some parts will be empty.
+///
+/// The resulting parts follow the natural clusters of the graph, but are not
+/// balanced.  Combine this algorithm with a partition improving algorithm to
+/// balance them.
+///
+/// # Example
+///
+/// ```rust
+/// # fn main() -> Result<(), coupe::Error> {
+/// use coupe::Partition as _;
+/// use coupe::sprs::CsMat;
+///
+/// // Two triangles linked by one edge:
+/// //
+/// //  0        3
+/// //  | \    / |
+/// //  |  2--4  |
+/// //  | /    \ |
+/// //  1        5
+/// let mut adjacency = CsMat::empty(coupe::sprs::CSR, 6);
+/// for (a, b) in [(0, 1), (0, 2), (1, 2), (2, 4), (3, 4), (3, 5), (4, 5)] {
+///     adjacency.insert(a, b, 1.0);
+///     adjacency.insert(b, a, 1.0);
+/// }
+/// let weights = [1.0; 6];
+/// let mut partition = [0; 6];
+///
+/// coupe::LabelPropagation { part_count: 2, ..Default::default() }
+///     .partition(&mut partition, (adjacency.view(), &weights))?;
+///
+/// assert_eq!(partition[0], partition[1]);
+/// assert_eq!(partition[0], partition[2]);
+/// assert_eq!(partition[3], partition[4]);
+/// assert_eq!(partition[3], partition[5]);
+/// assert_ne!(partition[0], partition[3]);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Reference
+///
+/// Raghavan, U. N., Albert, R., Kumara, S., 2007. Near linear time algorithm
+/// to detect community structures in large-scale networks. *Physical Review
+/// E*, 76(3). <https://doi.org/10.1103/PhysRevE.76.036106>
+#[derive(Debug, Clone, Copy)]
+pub struct LabelPropagation {
+    pub part_count: usize,
+
+    /// Maximum number of label propagation sweeps over the vertices.
+    pub max_iter: usize,
+}
+
+impl Default for LabelPropagation {
+    fn default() -> Self {
+        Self {
+            part_count: 2,
+            max_iter: 100,
+        }
+    }
+}
+
+impl crate::PartCount for LabelPropagation {
+    fn expected_part_count(&self) -> Option<usize> {
+        Some(self.part_count)
+    }
+}
+
+impl crate::Name for LabelPropagation {
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed("label_propagation")
+    }
+}
+
+impl<'a, T> crate::Partition<(T, &'a [f64])> for LabelPropagation
+where
+    T: Topology<f64>,
+{
+    type Metadata = ();
+    type Error = crate::Error;
+
+    fn partition(
+        &mut self,
+        part_ids: &mut [usize],
+        (adjacency, weights): (T, &'a [f64]),
+    ) -> Result<Self::Metadata, Self::Error> {
+        if part_ids.len() != weights.len() {
+            return Err(crate::Error::InputLenMismatch {
+                expected: part_ids.len(),
+                actual: weights.len(),
+            });
+        }
+        if part_ids.len() != adjacency.len() {
+            return Err(crate::Error::InputLenMismatch {
+                expected: part_ids.len(),
+                actual: adjacency.len(),
+            });
+        }
+        if part_ids.is_empty() || self.part_count == 0 {
+            return Ok(());
+        }
+        label_propagation(part_ids, weights, adjacency, self.part_count, self.max_iter);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Grid;
+    use crate::Partition as _;
+    use sprs::CsMat;
+    use std::num::NonZeroUsize;
+
+    /// Two cliques of `size` vertices, linked by a single edge.
+    fn two_cliques(size: usize) -> CsMat<f64> {
+        let mut adjacency = CsMat::empty(sprs::CSR, 2 * size);
+        for offset in [0, size] {
+            for a in offset..offset + size {
+                for b in offset..offset + size {
+                    if a != b {
+                        adjacency.insert(a, b, 1.0);
+                    }
+                }
+            }
+        }
+        adjacency.insert(size - 1, size, 1.0);
+        adjacency.insert(size, size - 1, 1.0);
+        adjacency
+    }
+
+    #[test]
+    fn test_two_cliques() {
+        let adjacency = two_cliques(5);
+        let weights = [1.0; 10];
+        let mut partition = [0; 10];
+
+        LabelPropagation {
+            part_count: 2,
+            ..Default::default()
+        }
+        .partition(&mut partition, (adjacency.view(), &weights))
+        .unwrap();
+
+        assert!(partition[..5].iter().all(|p| *p == partition[0]));
+        assert!(partition[5..].iter().all(|p| *p == partition[5]));
+        assert_ne!(partition[0], partition[5]);
+    }
+
+    #[test]
+    fn test_merge_and_split() {
+        let adjacency = two_cliques(5);
+        let weights = [1.0; 10];
+
+        let mut partition = [0; 10];
+        LabelPropagation {
+            part_count: 1,
+            ..Default::default()
+        }
+        .partition(&mut partition, (adjacency.view(), &weights))
+        .unwrap();
+        assert_eq!(partition, [0; 10]);
+
+        let mut partition = [0; 10];
+        LabelPropagation {
+            part_count: 4,
+            ..Default::default()
+        }
+        .partition(&mut partition, (adjacency.view(), &weights))
+        .unwrap();
+        let mut parts = partition.to_vec();
+        parts.sort();
+        parts.dedup();
+        assert_eq!(parts, [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_grid_parts_are_connected() {
+        let grid = Grid::new_2d(NonZeroUsize::new(8).unwrap(), NonZeroUsize::new(8).unwrap());
+        let weights = [1.0; 64];
+        let mut partition = [0; 64];
+
+        LabelPropagation {
+            part_count: 3,
+            ..Default::default()
+        }
+        .partition(&mut partition, (grid, &weights[..]))
+        .unwrap();
+
+        let components = Topology::<f64>::connected_components_per_part(&grid, &partition);
+        assert_eq!(components, [1, 1, 1]);
+    }
+
+    #[test]
+    fn test_isolated_vertices() {
+        // Every vertex is its own community, so merges fall back to the
+        // lightest other community.
+        let adjacency: CsMat<f64> = CsMat::zero((1000, 1000));
+        let weights = [1.0; 1000];
+        let mut partition = [0; 1000];
+
+        LabelPropagation {
+            part_count: 3,
+            ..Default::default()
+        }
+        .partition(&mut partition, (adjacency.view(), &weights))
+        .unwrap();
+
+        let mut sizes = [0; 3];
+        for part in partition {
+            sizes[part] += 1;
+        }
+        let (min, max) = (sizes.iter().min().unwrap(), sizes.iter().max().unwrap());
+        assert!(max - min <= max / 2, "{sizes:?}");
+    }
+}
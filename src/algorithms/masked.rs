@@ -0,0 +1,216 @@
+use crate::geometry::PointND;
+use std::borrow::Cow;
+
+/// # Masked
+///
+/// Turns a partitioner algorithm into a partition improving algorithm, by
+/// re-partitioning only the elements selected by a mask.
+///
+/// The inner algorithm is run on the points and weights of the masked
+/// elements only, and the part IDs it returns are written back as-is.  The
+/// other elements keep their part ID, so a masked element and an unmasked one
+/// end up in the same part when they share an ID.  This lets the inner
+/// algorithm re-balance the masked region into parts that already exist.
+///
+/// This is useful to re-decompose a region of the mesh, for example after it
+/// has been refined, without moving the rest of it.
+///
+/// Partitioning returns [`Error::InputLenMismatch`][crate::Error::InputLenMismatch]
+/// if `mask`, the points or the weights do not have one value per element.
+/// Errors of the inner algorithm are converted into [`Error`][crate::Error].
+///
+/// # Example
+///
+/// ```rust
+/// # fn main() -> Result<(), coupe::Error> {
+/// use coupe::Partition as _;
+/// use coupe::Point2D;
+///
+/// let points: Vec<Point2D> = (0..6).map(|i| Point2D::new(i as f64, 0.0)).collect();
+/// let weights = [1.0; 6];
+/// let mut partition = [0, 0, 0, 0, 0, 0];
+///
+/// // Split the last four points in two, and leave the first two in part 0.
+/// coupe::Masked {
+///     algorithm: coupe::MultiJagged { part_count: 2, max_iter: 4, target_weights: None },
+///     mask: vec![false, false, true, true, true, true],
+/// }
+/// .partition(&mut partition, (&points, &weights))?;
+///
+/// assert_eq!(partition, [0, 0, 0, 0, 1, 1]);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Masked<A> {
+    pub algorithm: A,
+
+    /// Whether each element is re-partitioned, indexed by element ID.
+    pub mask: Vec<bool>,
+}
+
+impl<'a, const D: usize, A, M, E> crate::Partition<(&'a [PointND<D>], &'a [f64])> for Masked<A>
+where
+    A: for<'b> crate::Partition<(&'b [PointND<D>], &'b [f64]), Metadata = M, Error = E>,
+    E: Into<crate::Error>,
+{
+    /// The metadata of the inner algorithm.
+    type Metadata = M;
+    type Error = crate::Error;
+
+    fn partition(
+        &mut self,
+        part_ids: &mut [usize],
+        (points, weights): (&'a [PointND<D>], &'a [f64]),
+    ) -> Result<Self::Metadata, Self::Error> {
+        for len in [self.mask.len(), points.len(), weights.len()] {
+            if len != part_ids.len() {
+                return Err(crate::Error::InputLenMismatch {
+                    expected: part_ids.len(),
+                    actual: len,
+                });
+            }
+        }
+
+        let members: Vec<usize> = (0..part_ids.len()).filter(|e| self.mask[*e]).collect();
+        let sub_points: Vec<PointND<D>> = members.iter().map(|e| points[*e]).collect();
+        let sub_weights: Vec<f64> = members.iter().map(|e| weights[*e]).collect();
+        let mut sub_ids = vec![0; members.len()];
+        let metadata = self
+            .algorithm
+            .partition(&mut sub_ids, (&sub_points, &sub_weights))
+            .map_err(Into::into)?;
+
+        for (element, sub_id) in members.into_iter().zip(sub_ids) {
+            part_ids[element] = sub_id;
+        }
+
+        Ok(metadata)
+    }
+}
+
+impl<A> crate::Name for Masked<A>
+where
+    A: crate::Name,
+{
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Owned(format!("masked({})", self.algorithm.name()))
+    }
+}
+
+impl<A> crate::PartCount for Masked<A> {
+    /// Unmasked elements may be in any part, so this is always `None`.
+    fn expected_part_count(&self) -> Option<usize> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MultiJagged;
+    use crate::Partition as _;
+    use crate::Point2D;
+
+    #[test]
+    fn test_half_grid() {
+        let points: Vec<Point2D> = (0..64)
+            .map(|i| Point2D::new((i % 8) as f64, (i / 8) as f64))
+            .collect();
+        let weights = [1.0; 64];
+        let initial: Vec<usize> = (0..64).map(|i| i % 3).collect();
+        let mut partition = initial.clone();
+
+        // Re-partition the right half of the grid.
+        let mask: Vec<bool> = points.iter().map(|p| 4.0 <= p.x).collect();
+        Masked {
+            algorithm: MultiJagged {
+                part_count: 4,
+                max_iter: 2,
+                target_weights: None,
+            },
+            mask: mask.clone(),
+        }
+        .partition(&mut partition, (&points, &weights))
+        .unwrap();
+
+        let mut sizes = [0; 4];
+        for ((part, initial), masked) in partition.iter().zip(&initial).zip(&mask) {
+            if *masked {
+                sizes[*part] += 1;
+            } else {
+                assert_eq!(part, initial);
+            }
+        }
+        assert_eq!(sizes, [8; 4]);
+    }
+
+    #[test]
+    fn test_shared_part_ids() {
+        let points: Vec<Point2D> = (0..4).map(|i| Point2D::new(i as f64, 0.0)).collect();
+        let weights = [1.0; 4];
+        let mut partition = [0, 1, 1, 1];
+
+        // The inner algorithm numbers its parts from 0, like the unmasked
+        // element, which thus shares its part with masked ones.
+        Masked {
+            algorithm: MultiJagged {
+                part_count: 2,
+                max_iter: 4,
+                target_weights: None,
+            },
+            mask: vec![false, true, true, true],
+        }
+        .partition(&mut partition, (&points, &weights))
+        .unwrap();
+
+        assert_eq!(partition[0], 0);
+        assert!(partition[1..].contains(&0));
+    }
+
+    #[test]
+    fn test_input_len_mismatch() {
+        let points: Vec<Point2D> = (0..4).map(|i| Point2D::new(i as f64, 0.0)).collect();
+        let mut partition = [0; 4];
+        let mut algorithm = Masked {
+            algorithm: MultiJagged {
+                part_count: 2,
+                max_iter: 4,
+                target_weights: None,
+            },
+            mask: vec![true; 3],
+        };
+
+        let err = algorithm
+            .partition(&mut partition, (&points, &[1.0; 4]))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            crate::Error::InputLenMismatch {
+                expected: 4,
+                actual: 3
+            }
+        ));
+        algorithm.mask.push(true);
+        let err = algorithm
+            .partition(&mut partition, (&points[..2], &[1.0; 4]))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            crate::Error::InputLenMismatch {
+                expected: 4,
+                actual: 2
+            }
+        ));
+        let err = algorithm
+            .partition(&mut partition, (&points, &[1.0; 5]))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            crate::Error::InputLenMismatch {
+                expected: 4,
+                actual: 5
+            }
+        ));
+    }
+}
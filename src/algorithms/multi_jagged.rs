@@ -7,11 +7,13 @@
 
 use approx::Ulps;
 
+use super::utils::parallel_prefix_sum;
+use super::PartitionTree;
 use crate::geometry::*;
 use rayon::prelude::*;
 
+use std::borrow::Cow;
 use std::sync::atomic::AtomicPtr;
-use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
 
 // prime functions are currently unused but may be useful to compute a
@@ -60,6 +62,33 @@ struct PartitionScheme {
     pub next: Option<Vec<PartitionScheme>>,
 }
 
+impl PartitionScheme {
+    /// The number of final parts this scheme generates.
+    fn part_count(&self) -> usize {
+        match &self.next {
+            Some(next) if self.num_splits != 0 => next.iter().map(Self::part_count).sum(),
+            _ => 1,
+        }
+    }
+
+    /// The hierarchy of parts this scheme generates, numbered depth first
+    /// from `*first_id`.
+    fn partition_tree(&self, first_id: &mut usize) -> PartitionTree {
+        match &self.next {
+            Some(next) if self.num_splits != 0 => PartitionTree::Node(
+                next.iter()
+                    .map(|scheme| scheme.partition_tree(first_id))
+                    .collect(),
+            ),
+            _ => {
+                let part = *first_id;
+                *first_id += 1;
+                PartitionTree::Leaf { part }
+            }
+        }
+    }
+}
+
 // Computes a partitioning scheme i.e. how to split points at each iteration given
 // a number of partitions and a number of max iterations.
 //
@@ -153,9 +182,10 @@ fn multi_jagged<const D: usize>(
     weights: &[f64],
     num_parts: usize,
     max_iter: usize,
+    target_weights: &[f64],
 ) {
     let partition_scheme = partition_scheme(num_parts, max_iter);
-    multi_jagged_with_scheme(partition, points, weights, partition_scheme);
+    multi_jagged_with_scheme(partition, points, weights, partition_scheme, target_weights);
 }
 
 fn multi_jagged_with_scheme<const D: usize>(
@@ -163,10 +193,10 @@ fn multi_jagged_with_scheme<const D: usize>(
     points: &[PointND<D>],
     weights: &[f64],
     partition_scheme: PartitionScheme,
+    target_weights: &[f64],
 ) {
     let len = points.len();
     let mut permutation = (0..len).into_par_iter().collect::<Vec<_>>();
-    let part_id = AtomicUsize::new(0);
 
     multi_jagged_recurse(
         points,
@@ -175,10 +205,25 @@ fn multi_jagged_with_scheme<const D: usize>(
         &AtomicPtr::new(partition.as_mut_ptr()),
         0,
         partition_scheme,
-        &part_id,
+        Parts {
+            first_id: 0,
+            target_weights,
+        },
     );
 }
 
+/// The parts generated by a sub-scheme.
+#[derive(Clone, Copy)]
+struct Parts<'a> {
+    /// The ID of the first part of the sub-scheme.  Parts are numbered in
+    /// depth-first order.
+    first_id: usize,
+
+    /// The target weights of the parts of the sub-scheme, or an empty slice if
+    /// all parts have the same target weight.
+    target_weights: &'a [f64],
+}
+
 fn multi_jagged_recurse<const D: usize>(
     points: &[PointND<D>],
     weights: &[f64],
@@ -186,19 +231,50 @@ fn multi_jagged_recurse<const D: usize>(
     partition: &AtomicPtr<usize>,
     current_coord: usize,
     partition_scheme: PartitionScheme,
-    part_id: &AtomicUsize,
+    parts: Parts<'_>,
 ) {
     if partition_scheme.num_splits != 0 {
         super::recursive_bisection::axis_sort(points, permutation, current_coord);
 
-        let split_positions =
-            compute_split_positions(weights, permutation, &partition_scheme.modifiers);
+        let next = partition_scheme.next.unwrap();
+        let mut sub_parts = Vec::with_capacity(next.len());
+        let mut first_id = parts.first_id;
+        let mut first_target = 0;
+        for scheme in &next {
+            let part_count = scheme.part_count();
+            let target_weights = if parts.target_weights.is_empty() {
+                parts.target_weights
+            } else {
+                &parts.target_weights[first_target..first_target + part_count]
+            };
+            sub_parts.push(Parts {
+                first_id,
+                target_weights,
+            });
+            first_id += part_count;
+            first_target += part_count;
+        }
+
+        // Split the weight in proportion to the target weights of the
+        // sub-schemes, if any.
+        let total_target: f64 = parts.target_weights.iter().sum();
+        let modifiers = if total_target > 0.0 {
+            sub_parts
+                .iter()
+                .map(|sub_parts| sub_parts.target_weights.iter().sum::<f64>() / total_target)
+                .collect()
+        } else {
+            partition_scheme.modifiers
+        };
+
+        let split_positions = compute_split_positions(weights, permutation, &modifiers);
         let mut sub_permutations = split_at_mut_many(permutation, &split_positions);
 
         sub_permutations
             .par_iter_mut()
-            .zip(partition_scheme.next.unwrap())
-            .for_each(|(permu, scheme)| {
+            .zip(next)
+            .zip(sub_parts)
+            .for_each(|((permu, scheme), sub_parts)| {
                 multi_jagged_recurse(
                     points,
                     weights,
@@ -206,11 +282,11 @@ fn multi_jagged_recurse<const D: usize>(
                     partition,
                     (current_coord + 1) % D,
                     scheme,
-                    part_id,
+                    sub_parts,
                 )
             });
     } else {
-        let part_id = part_id.fetch_add(1, Ordering::Relaxed);
+        let part_id = parts.first_id;
         permutation.par_iter().for_each(|idx| {
             let ptr = partition.load(Ordering::Relaxed);
             unsafe { std::ptr::write(ptr.add(*idx), part_id) }
@@ -219,73 +295,38 @@ fn multi_jagged_recurse<const D: usize>(
 }
 
 // This is pub(crate) because it's also used in the hilbert_curve module
+//
+// Returns, for each modifier but the last, the index in `permutation` at which
+// to split the points. Zero-weight points that follow a split go to the left
+// side. If all weights are zero, points are split by count instead.
 pub(crate) fn compute_split_positions(
     weights: &[f64],
     permutation: &[usize],
     modifiers: &[f64],
 ) -> Vec<usize> {
     let (_last_modifier, modifiers) = modifiers.split_last().unwrap();
-    let num_splits = modifiers.len();
 
-    let total_weight = permutation.par_iter().map(|idx| weights[*idx]).sum::<f64>();
-    let weight_thresholds: Vec<_> = modifiers
+    let mut prefix_weights = parallel_prefix_sum(weights, permutation);
+    let mut total_weight = prefix_weights.last().copied().unwrap_or(0.0);
+    if total_weight == 0.0 {
+        prefix_weights = (1..=permutation.len()).map(|count| count as f64).collect();
+        total_weight = permutation.len() as f64;
+    }
+
+    modifiers
         .iter()
         .scan(0.0, |consumed_weight, modifier| {
             *consumed_weight += total_weight * modifier;
             Some(*consumed_weight)
         })
-        .collect();
-
-    let mut scan = permutation
-        .par_iter()
-        .enumerate()
-        .fold_with((std::usize::MAX, 0.), |(low, acc), (idx, val)| {
-            (usize::min(idx, low), acc + weights[*val])
-        })
-        .collect::<Vec<_>>()
-        .into_iter();
-
-    let mut ret = Vec::with_capacity(num_splits);
-    let mut current_weights_sum = 0.;
-    let mut current_weights_sums_cache = Vec::with_capacity(num_splits);
-
-    for threshold in &weight_thresholds {
-        // if this condition is verified, it means that a block of the scan contained more than one threshold
-        // and the current threshold was skipped during previous iteration. We just
-        // push the last element again and skip the rest of the iteration
-        if current_weights_sum > *threshold {
-            let last = ret[ret.len() - 1];
-            ret.push(last);
-            let last = current_weights_sums_cache[current_weights_sums_cache.len() - 1];
-            current_weights_sums_cache.push(last);
-            continue;
-        }
-
-        'inner: loop {
-            let current = scan.next().unwrap();
-            if current_weights_sum + current.1 > *threshold {
-                ret.push(current.0);
-                current_weights_sums_cache.push(current_weights_sum);
-                current_weights_sum += current.1;
-                break 'inner;
-            }
-            current_weights_sum += current.1;
-        }
-    }
-
-    ret.into_par_iter()
-        .zip(current_weights_sums_cache)
-        .zip(weight_thresholds)
-        .map(|((mut idx, mut sum), threshold)| {
-            while sum + weights[permutation[idx]] < threshold
-                // multiplication between modifiers and weights can cause nasty
-                // rounding precision loss which would put an element in a wrong part
-                || Ulps::default().eq(&threshold, &(sum + weights[permutation[idx]]))
-            {
-                sum += weights[permutation[idx]];
-                idx += 1;
-            }
-            idx
+        .map(|threshold| {
+            // Prefix weights are sorted since weights are non-negative.
+            prefix_weights.partition_point(|prefix_weight| {
+                *prefix_weight < threshold
+                    // multiplication between modifiers and weights can cause nasty
+                    // rounding precision loss which would put an element in a wrong part
+                    || Ulps::default().eq(&threshold, prefix_weight)
+            })
         })
         .collect()
 }
@@ -328,10 +369,15 @@ pub(crate) fn split_at_mut_many<'a, T>(
 ///
 /// More iteration does not necessarily result in a better partition.
 ///
+/// Parts are numbered in the order in which the partition scheme lists them,
+/// that is, depth first.  By default, all parts are given the same target
+/// weight, but unequal ones can be given with `target_weights`, for instance
+/// to give more work to faster processors.
+///
 /// # Example
 ///
 /// ```rust
-/// # fn main() -> Result<(), std::convert::Infallible> {
+/// # fn main() -> Result<(), coupe::Error> {
 /// use coupe::Partition as _;
 /// use coupe::Point2D;
 ///
@@ -350,7 +396,7 @@ pub(crate) fn split_at_mut_many<'a, T>(
 /// let mut partition = [0; 9];
 ///
 /// // generate a partition of 9 parts
-/// coupe::MultiJagged { part_count: 9, max_iter: 4 }
+/// coupe::MultiJagged { part_count: 9, max_iter: 4, target_weights: None }
 ///     .partition(&mut partition, (&points, &weights))?;
 ///
 /// for i in 0..9 {
@@ -364,22 +410,99 @@ pub(crate) fn split_at_mut_many<'a, T>(
 /// # Ok(())
 /// # }
 /// ```
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub struct MultiJagged {
     pub part_count: usize,
     pub max_iter: usize,
+
+    /// The target weight of each part, relative to the others, indexed by
+    /// part ID.  For example, `[2.0, 1.0]` asks for a first part twice as
+    /// heavy as the second one.
+    ///
+    /// Must have `part_count` non-negative values.  `None` means all parts
+    /// have the same target weight.
+    pub target_weights: Option<Vec<f64>>,
+}
+
+impl MultiJagged {
+    /// Same as [`partition`][crate::Partition::partition], but also returns
+    /// the hierarchy of the parts.
+    ///
+    /// The children of a node are the parts made by one multi-section of its
+    /// points, so the tree has at most `max_iter` levels.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), coupe::Error> {
+    /// use coupe::Point2D;
+    ///
+    /// let points: Vec<Point2D> = (0..16)
+    ///     .map(|i| Point2D::new((i % 4) as f64, (i / 4) as f64))
+    ///     .collect();
+    /// let weights = [1.0; 16];
+    /// let mut partition = [0; 16];
+    ///
+    /// // Two groups of two parts.
+    /// let tree = coupe::MultiJagged { part_count: 4, max_iter: 2, target_weights: None }
+    ///     .partition_with_tree(&mut partition, (&points, &weights))?;
+    ///
+    /// assert_eq!(tree.groups(1), [0, 0, 1, 1]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn partition_with_tree<const D: usize>(
+        &mut self,
+        part_ids: &mut [usize],
+        (points, weights): (&[PointND<D>], &[f64]),
+    ) -> Result<PartitionTree, crate::Error> {
+        crate::Partition::partition(self, part_ids, (points, weights))?;
+        Ok(partition_scheme(self.part_count, self.max_iter).partition_tree(&mut 0))
+    }
+}
+
+impl crate::PartCount for MultiJagged {
+    fn expected_part_count(&self) -> Option<usize> {
+        Some(self.part_count)
+    }
+}
+
+impl crate::Name for MultiJagged {
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed("multi_jagged")
+    }
 }
 
 impl<'a, const D: usize> crate::Partition<(&'a [PointND<D>], &'a [f64])> for MultiJagged {
     type Metadata = ();
-    type Error = std::convert::Infallible;
+    type Error = crate::Error;
 
     fn partition(
         &mut self,
         part_ids: &mut [usize],
         (points, weights): (&'a [PointND<D>], &'a [f64]),
     ) -> Result<Self::Metadata, Self::Error> {
-        multi_jagged(part_ids, points, weights, self.part_count, self.max_iter);
+        let target_weights = self.target_weights.as_deref().unwrap_or(&[]);
+        if let Some(target_weights) = &self.target_weights {
+            if target_weights.len() != self.part_count {
+                return Err(crate::Error::InputLenMismatch {
+                    expected: self.part_count,
+                    actual: target_weights.len(),
+                });
+            }
+            if target_weights.iter().any(|target| *target < 0.0) {
+                return Err(crate::Error::NegativeValues);
+            }
+        }
+        super::validate_points(points)?;
+        multi_jagged(
+            part_ids,
+            points,
+            weights,
+            self.part_count,
+            self.max_iter,
+            target_weights,
+        );
         Ok(())
     }
 }
@@ -413,6 +536,137 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_compute_split_positions_zero_weights() {
+        let weights = [0., 1., 0., 0., 1., 0., 1., 0., 0., 1., 0., 0.];
+        let permutation: Vec<usize> = (0..weights.len()).collect();
+
+        // Zero-weight points that follow a split go to the left side.
+        let split_positions = compute_split_positions(&weights, &permutation, &[0.5, 0.5]);
+        assert_eq!(split_positions, [6]);
+
+        let split_positions =
+            compute_split_positions(&weights, &permutation, &[0.25, 0.25, 0.25, 0.25]);
+        assert_eq!(split_positions, [4, 6, 9]);
+
+        // Trailing zero-weight points.
+        let weights = [1., 1., 0., 0.];
+        let split_positions = compute_split_positions(&weights, &permutation[..4], &[0.5, 0.5]);
+        assert_eq!(split_positions, [1]);
+        let split_positions = compute_split_positions(&weights, &permutation[..4], &[1.0, 0.0]);
+        assert_eq!(split_positions, [4]);
+
+        // Without any weight, points are split by count.
+        let weights = [0.; 6];
+        let split_positions =
+            compute_split_positions(&weights, &permutation[..6], &[1. / 3., 1. / 3., 1. / 3.]);
+        assert_eq!(split_positions, [2, 4]);
+    }
+
+    #[test]
+    fn test_multi_jagged_zero_weights() {
+        use crate::Partition as _;
+
+        let points: Vec<Point2D> = (0..64)
+            .map(|i| Point2D::new((i % 8) as f64, (i / 8) as f64))
+            .collect();
+        let weights: Vec<f64> = (0..64)
+            .map(|i| if i % 3 == 0 { 1.0 } else { 0.0 })
+            .collect();
+        let mut partition = vec![0; 64];
+
+        MultiJagged {
+            part_count: 4,
+            max_iter: 2,
+            target_weights: None,
+        }
+        .partition(&mut partition, (&points, &weights))
+        .unwrap();
+
+        let mut part_weights = [0.0; 4];
+        for (part, weight) in partition.iter().zip(&weights) {
+            part_weights[*part] += weight;
+        }
+        let total_weight: f64 = weights.iter().sum();
+        for part_weight in part_weights {
+            assert!(
+                (part_weight - total_weight / 4.0).abs() <= 1.0,
+                "{part_weights:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_multi_jagged_target_weights() {
+        use crate::Partition as _;
+
+        let points: Vec<Point2D> = (0..300)
+            .map(|i| Point2D::new((i % 30) as f64, (i / 30) as f64))
+            .collect();
+        let weights = vec![1.0; points.len()];
+        let mut partition = vec![0; points.len()];
+
+        MultiJagged {
+            part_count: 2,
+            max_iter: 2,
+            target_weights: Some(vec![2.0, 1.0]),
+        }
+        .partition(&mut partition, (&points, &weights))
+        .unwrap();
+
+        let imbalance = crate::analysis::imbalance_vs_targets(&partition, &weights, &[2.0, 1.0]);
+        assert!(imbalance < 0.05, "{imbalance}");
+
+        // Targets follow the depth-first part numbering.
+        let targets = [1.0, 1.0, 1.0, 3.0, 1.0, 1.0];
+        MultiJagged {
+            part_count: 6,
+            max_iter: 2,
+            target_weights: Some(targets.to_vec()),
+        }
+        .partition(&mut partition, (&points, &weights))
+        .unwrap();
+
+        let imbalance = crate::analysis::imbalance_vs_targets(&partition, &weights, &targets);
+        assert!(imbalance < 0.1, "{imbalance}");
+    }
+
+    #[test]
+    fn test_partition_tree() {
+        let points: Vec<Point2D> = (0..144)
+            .map(|i| Point2D::new((i % 12) as f64, (i / 12) as f64))
+            .collect();
+        let weights = vec![1.0; points.len()];
+        let mut partition = vec![0; points.len()];
+
+        let tree = MultiJagged {
+            part_count: 6,
+            max_iter: 2,
+            target_weights: None,
+        }
+        .partition_with_tree(&mut partition, (&points, &weights))
+        .unwrap();
+
+        // Leaves are the parts of the flat partition.
+        let mut parts = partition.clone();
+        parts.sort();
+        parts.dedup();
+        assert_eq!(tree.parts(), parts);
+        for part in parts {
+            assert_eq!(tree.path(part).unwrap().len(), 2);
+        }
+
+        // First-level groups are slabs along the first axis.
+        let groups = tree.groups(1);
+        let mut slabs = [(f64::INFINITY, f64::NEG_INFINITY); 3];
+        for (point, part) in points.iter().zip(&partition) {
+            let slab = &mut slabs[groups[*part]];
+            slab.0 = f64::min(slab.0, point.x);
+            slab.1 = f64::max(slab.1, point.x);
+        }
+        assert_eq!(slabs, [(0.0, 3.0), (4.0, 7.0), (8.0, 11.0)]);
+    }
+
     #[test]
     fn test_split_at_mut_many() {
         let array = &mut [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12];
@@ -0,0 +1,106 @@
+/// The hierarchy of parts of a nested decomposition.
+///
+/// Algorithms that split the input recursively, like [Rcb][crate::Rcb] and
+/// [MultiJagged][crate::MultiJagged], first make a few large parts, which are
+/// in turn split into smaller parts, and so on.  The final parts are the
+/// leaves of this tree, and the inner nodes group parts that stem from the
+/// same split.
+///
+/// This maps well onto hierarchical machines: for example, the children of the
+/// root can be given to compute nodes, and their leaves to the cores of each
+/// node, so that most communications stay within a node.
+///
+/// # Example
+///
+/// ```rust
+/// use coupe::PartitionTree;
+///
+/// // Two groups of two parts.
+/// let tree = PartitionTree::Node(vec![
+///     PartitionTree::Node(vec![PartitionTree::Leaf { part: 0 }, PartitionTree::Leaf { part: 1 }]),
+///     PartitionTree::Node(vec![PartitionTree::Leaf { part: 2 }, PartitionTree::Leaf { part: 3 }]),
+/// ]);
+///
+/// assert_eq!(tree.path(2), Some(vec![1, 0]));
+/// assert_eq!(tree.parts(), [0, 1, 2, 3]);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PartitionTree {
+    /// A group of parts, split into the given sub-trees.
+    Node(Vec<PartitionTree>),
+
+    /// A final part.
+    Leaf { part: usize },
+}
+
+impl PartitionTree {
+    /// The IDs of the final parts, in tree order.
+    pub fn parts(&self) -> Vec<usize> {
+        let mut parts = Vec::new();
+        self.visit_leaves(&mut Vec::new(), &mut |part, _| parts.push(part));
+        parts
+    }
+
+    /// The position of the given part in the tree, as the index of the child
+    /// to descend into at each level, starting from the root.
+    ///
+    /// Parts that share a prefix of their paths belong to the same group at
+    /// the corresponding level.  Returns `None` if the part is not in the tree.
+    pub fn path(&self, part: usize) -> Option<Vec<usize>> {
+        let mut found = None;
+        self.visit_leaves(&mut Vec::new(), &mut |leaf, path| {
+            if leaf == part {
+                found = Some(path.to_vec());
+            }
+        });
+        found
+    }
+
+    /// The group each part belongs to at the given depth, indexed by part ID.
+    ///
+    /// Groups are numbered in tree order.  Parts that are shallower than
+    /// `depth` are their own group.  The result can be applied to a partition
+    /// to get the coarser partition of a level of the hierarchy.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use coupe::PartitionTree;
+    ///
+    /// let tree = PartitionTree::Node(vec![
+    ///     PartitionTree::Node(vec![PartitionTree::Leaf { part: 0 }, PartitionTree::Leaf { part: 1 }]),
+    ///     PartitionTree::Leaf { part: 2 },
+    /// ]);
+    ///
+    /// assert_eq!(tree.groups(1), [0, 0, 1]);
+    /// ```
+    pub fn groups(&self, depth: usize) -> Vec<usize> {
+        let mut leaves = Vec::new();
+        self.visit_leaves(&mut Vec::new(), &mut |part, path| {
+            leaves.push((part, path[..usize::min(depth, path.len())].to_vec()));
+        });
+        let part_count = leaves.iter().map(|(part, _)| part + 1).max().unwrap_or(0);
+        let mut groups = vec![0; part_count];
+        let mut group = 0;
+        for (i, (part, prefix)) in leaves.iter().enumerate() {
+            if 0 < i && *prefix != leaves[i - 1].1 {
+                group += 1;
+            }
+            groups[*part] = group;
+        }
+        groups
+    }
+
+    fn visit_leaves(&self, path: &mut Vec<usize>, f: &mut impl FnMut(usize, &[usize])) {
+        match self {
+            PartitionTree::Node(children) => {
+                for (i, child) in children.iter().enumerate() {
+                    path.push(i);
+                    child.visit_leaves(path, f);
+                    path.pop();
+                }
+            }
+            PartitionTree::Leaf { part } => f(*part, path),
+        }
+    }
+}
@@ -0,0 +1,274 @@
+//! An implementation of the Peano space filling curve.
+//!
+//! The Peano curve is the base-3 counterpart of the Hilbert curve. The
+//! minimal bounding rectangle of the set of points is split into
+//! `3^order * 3^order` cells, which the curve visits column by column in a
+//! serpentine fashion, recursively. Consecutive cells on the curve always
+//! share a side, so there is no diagonal jump.
+//!
+//! Cell indices are computed digit by digit, following Peano's original
+//! definition: the base-3 digits of the index alternate between the digits of
+//! `x` and `y`, each of them being reflected (`d` becomes `2 - d`) when the
+//! sum of the previous digits of the other coordinate is odd.
+//!
+//! The complexity of encoding a point is `O(order)`.
+
+use super::hilbert_curve::flat_axes;
+use super::hilbert_curve::partition_indexed;
+use super::hilbert_curve::segment_to_segment;
+use crate::geometry::invalid_period;
+use crate::geometry::wrap_periodic_points;
+use crate::geometry::OrientedBoundingBox;
+use crate::Point2D;
+use coupe_core::peano::encode_2d;
+use coupe_core::peano::MAX_ORDER;
+use std::borrow::Cow;
+use std::fmt;
+
+/// Returns a function that maps 2D points to their peano curve index.
+///
+/// Panics if `points` is empty.
+fn index_fn_2d(points: &[Point2D], order: u32) -> impl Fn(&Point2D) -> u64 {
+    let mbr = OrientedBoundingBox::from_points(points).unwrap();
+    let aabb = mbr.aabb();
+    let cell_count = 3_u64.pow(order);
+    let flat = flat_axes(aabb, cell_count);
+    let x_mapping = segment_to_segment(aabb.p_min.x, aabb.p_max.x, cell_count);
+    let y_mapping = segment_to_segment(aabb.p_min.y, aabb.p_max.y, cell_count);
+    move |p| {
+        let p = mbr.obb_to_aabb(p);
+        match flat {
+            [false, false] => encode_2d(x_mapping(p.x), y_mapping(p.y), order),
+            [false, true] => x_mapping(p.x),
+            [true, false] => y_mapping(p.y),
+            [true, true] => 0,
+        }
+    }
+}
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// Invalid space filling curve order.
+    InvalidOrder { max: u32, actual: u32 },
+
+    /// Points span fewer distinct cells of the curve than there are parts.
+    TooManyParts {
+        part_count: usize,
+        cell_count: usize,
+    },
+
+    /// A point has a NaN or infinite coordinate.
+    NonFiniteCoordinate { index: usize },
+
+    /// The period of an axis is empty or not finite.
+    InvalidPeriod { axis: usize },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidOrder { max, actual } => {
+                write!(
+                    f,
+                    "given peano curve order too high. Got {}, max={}.",
+                    actual, max
+                )
+            }
+            Error::TooManyParts {
+                part_count,
+                cell_count,
+            } => write!(
+                f,
+                "cannot make {part_count} parts out of {cell_count} distinct peano curve cells, try a higher order",
+            ),
+            Error::NonFiniteCoordinate { index } => {
+                write!(f, "point {index} has a non-finite coordinate")
+            }
+            Error::InvalidPeriod { axis } => {
+                write!(f, "the period of axis {axis} is empty or not finite")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// # Peano space-filling curve algorithm
+///
+/// Projects points on the peano curve and splits this curve into a given
+/// amount of parts.
+///
+/// The peano curve depends on a grid resolution called `order`. Basically,
+/// the minimal bounding rectangle of the set of points is split into
+/// `3^order * 3^order` cells.  All the points in a given cell will have the
+/// same encoding.
+///
+/// Unlike the Z-curve, consecutive cells on the peano curve are always
+/// adjacent, which gives parts without long jumps.
+///
+/// The complexity of encoding a point is `O(order)`.
+///
+/// # Example
+///
+/// ```rust
+/// # fn main() -> Result<(), coupe::PeanoCurveError> {
+/// use coupe::Partition as _;
+/// use coupe::Point2D;
+///
+/// let points: &[Point2D] = &[
+///     Point2D::new(0., 0.),
+///     Point2D::new(1., 1.),
+///     Point2D::new(0., 10.),
+///     Point2D::new(1., 9.),
+///     Point2D::new(9., 1.),
+///     Point2D::new(10., 0.),
+///     Point2D::new(10., 10.),
+///     Point2D::new(9., 9.),
+/// ];
+/// let weights = vec![1.0; 8];
+/// let mut partition = vec![0; 8];
+///
+/// // generate a partition of 4 parts
+/// coupe::PeanoCurve { part_count: 4, ..Default::default() }
+///     .partition(&mut partition, (points, weights))?;
+///
+/// assert_eq!(partition[0], partition[1]);
+/// assert_eq!(partition[2], partition[3]);
+/// assert_eq!(partition[4], partition[5]);
+/// assert_eq!(partition[6], partition[7]);
+///
+/// # partition.sort();
+/// # partition.dedup();
+/// # assert_eq!(partition.len(), 4);
+///
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # References
+///
+/// Peano, G., 1890. Sur une courbe, qui remplit toute une aire plane.
+/// *Mathematische Annalen*, 36(1), pp. 157–160.
+/// <https://doi.org/10.1007/BF01199438>
+#[derive(Clone, Debug)]
+pub struct PeanoCurve {
+    pub part_count: usize,
+    pub order: u32,
+
+    /// Tolerance on the relative weight difference between the two sides of
+    /// each cut.
+    pub tolerance: f64,
+
+    /// Same meaning as [`HilbertCurve::periodic`][crate::HilbertCurve::periodic].
+    pub periodic: Vec<Option<(f64, f64)>>,
+}
+
+impl Default for PeanoCurve {
+    fn default() -> Self {
+        Self {
+            part_count: 2,
+            order: 8,
+            tolerance: 0.05,
+            periodic: Vec::new(),
+        }
+    }
+}
+
+impl crate::PartCount for PeanoCurve {
+    fn expected_part_count(&self) -> Option<usize> {
+        Some(self.part_count)
+    }
+}
+
+impl crate::Name for PeanoCurve {
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed("peano_curve")
+    }
+}
+
+impl<W> crate::Partition<(&[Point2D], W)> for PeanoCurve
+where
+    W: AsRef<[f64]>,
+{
+    type Metadata = ();
+    type Error = Error;
+
+    fn partition(
+        &mut self,
+        part_ids: &mut [usize],
+        (points, weights): (&[Point2D], W),
+    ) -> Result<Self::Metadata, Self::Error> {
+        if self.order > MAX_ORDER {
+            return Err(Error::InvalidOrder {
+                max: MAX_ORDER,
+                actual: self.order,
+            });
+        }
+        if let Some(axis) = invalid_period(&self.periodic) {
+            return Err(Error::InvalidPeriod { axis });
+        }
+        if part_ids.is_empty() {
+            return Ok(());
+        }
+        if let Some(index) = super::first_non_finite(points) {
+            return Err(Error::NonFiniteCoordinate { index });
+        }
+        let points = &*wrap_periodic_points(points, &self.periodic);
+        let index_fn = index_fn_2d(points, self.order);
+        partition_indexed(
+            part_ids,
+            points,
+            weights.as_ref(),
+            self.part_count,
+            self.tolerance,
+            index_fn,
+        )
+        .map_err(|cell_count| Error::TooManyParts {
+            part_count: self.part_count,
+            cell_count,
+        })
+        .map(drop)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invalid_order() {
+        use crate::Partition as _;
+
+        let points = [Point2D::new(0.0, 0.0), Point2D::new(1.0, 1.0)];
+        let mut partition = [0; 2];
+        let result = PeanoCurve {
+            part_count: 2,
+            order: MAX_ORDER + 1,
+            ..Default::default()
+        }
+        .partition(&mut partition, (&points[..], [1.0; 2]));
+        assert!(matches!(
+            result,
+            Err(Error::InvalidOrder {
+                max: MAX_ORDER,
+                actual: 21,
+            })
+        ));
+    }
+
+    #[test]
+    fn test_periodic_seam() {
+        use crate::Partition as _;
+
+        let err = crate::geometry::check_periodic_seam(|partition, points, weights, periodic| {
+            PeanoCurve {
+                part_count: 2,
+                periodic,
+                ..Default::default()
+            }
+            .partition(partition, (points, weights))
+        });
+        assert!(matches!(err, Error::InvalidPeriod { axis: 0 }));
+    }
+}
@@ -1,4 +1,12 @@
 use super::Error;
+use super::PartitionTree;
+use crate::geometry::inertia_matrix;
+use crate::geometry::invalid_period;
+use crate::geometry::par_bounding_box;
+use crate::geometry::periodic_seams;
+use crate::geometry::weighted_inertia_matrix;
+use crate::geometry::wrap_periodic;
+use crate::geometry::Matrix;
 use crate::geometry::OrientedBoundingBox;
 use crate::geometry::PointND;
 use crate::BoundingBox;
@@ -8,9 +16,11 @@ use nalgebra::Const;
 use nalgebra::DefaultAllocator;
 use nalgebra::DimDiff;
 use nalgebra::DimSub;
+use nalgebra::SMatrix;
 use nalgebra::ToTypenum;
 use num_traits::ToPrimitive;
 use rayon::prelude::*;
+use std::borrow::Cow;
 use std::cmp;
 use std::iter::Sum;
 use std::mem::MaybeUninit;
@@ -452,7 +462,8 @@ fn reorder_split<const D: usize, W>(
     reorder_split_scalar(items, pivot, coord)
 }
 
-/// Splits the given items into two sets of similar weights (parallel version).
+/// Splits the given items into two sets, the left one holding `ratio` times
+/// the total weight (parallel version).
 fn par_rcb_split<const D: usize, W>(
     items: Items<'_, D, W>,
     coord: usize,
@@ -460,6 +471,7 @@ fn par_rcb_split<const D: usize, W>(
     mut min: f32,
     mut max: f32,
     sum: W,
+    ratio: f64,
 ) -> SplitResult<'_, D, W>
 where
     W: RcbWeight,
@@ -544,8 +556,8 @@ where
             }
         };
 
+        let ideal_weight_left = sum.to_f64().unwrap() * ratio;
         let imbalance = {
-            let ideal_weight_left = sum.to_f64().unwrap() / 2.0;
             let weight_left = weight_left.to_f64().unwrap();
             f64::abs((weight_left - ideal_weight_left) / ideal_weight_left)
         };
@@ -563,8 +575,7 @@ where
         }
         prev_count_left = count_left;
 
-        let weight_right = sum - weight_left;
-        if weight_left < weight_right {
+        if weight_left.to_f64().unwrap() < ideal_weight_left {
             min = split_target;
         } else {
             max = split_target;
@@ -572,6 +583,13 @@ where
     }
 }
 
+/// Returns the tree of the cuts that have been made, or `None` if `items` is
+/// empty.
+///
+/// `targets` holds the target weights of the `2^iter_count` leaves of this
+/// subtree, in the order of [PartNumbering::Binary], or is empty for equal
+/// target weights.
+#[allow(clippy::too_many_arguments)]
 fn rcb_recurse<const D: usize, W>(
     items: Items<'_, D, W>,
     iter_count: usize,
@@ -580,11 +598,13 @@ fn rcb_recurse<const D: usize, W>(
     tolerance: f64,
     sum: W,
     bb: BoundingBox<D>,
-) where
+    targets: &[f64],
+) -> Option<BisectionTree>
+where
     W: RcbWeight,
 {
     if items.parts.is_empty() {
-        return;
+        return None;
     }
     if iter_count == 0 {
         let span = tracing::info_span!(
@@ -598,24 +618,25 @@ fn rcb_recurse<const D: usize, W>(
             .parts
             .into_par_iter()
             .for_each(|part| part.store(iter_id, Ordering::Relaxed));
-        return;
+        return Some(BisectionTree::Leaf { part: iter_id });
     }
 
     let min = bb.p_min[coord] as f32;
     let max = bb.p_max[coord] as f32;
+    let (ratio, targets_left, targets_right) = split_targets(targets);
     let SplitResult {
         left,
         right,
         weight_left,
         split_pos,
-    } = par_rcb_split(items, coord, tolerance, min, max, sum);
+    } = par_rcb_split(items, coord, tolerance, min, max, sum, ratio);
 
     let mut bb_left = bb.clone();
     bb_left.p_max[coord] = split_pos as f64;
     let mut bb_right = bb;
     bb_right.p_min[coord] = split_pos as f64;
 
-    rayon::join(
+    let (left, right) = rayon::join(
         || {
             rcb_recurse(
                 left,
@@ -625,6 +646,7 @@ fn rcb_recurse<const D: usize, W>(
                 tolerance,
                 weight_left,
                 bb_left,
+                targets_left,
             )
         },
         || {
@@ -636,18 +658,183 @@ fn rcb_recurse<const D: usize, W>(
                 tolerance,
                 sum - weight_left,
                 bb_right,
+                targets_right,
+            )
+        },
+    );
+
+    // When one side is empty, the cut is useless: every point of this region
+    // belongs to the other side.
+    match (left, right) {
+        (Some(left), Some(right)) => Some(BisectionTree::Split {
+            coord,
+            position: f64::from(split_pos),
+            left: Box::new(left),
+            right: Box::new(right),
+        }),
+        (Some(tree), None) | (None, Some(tree)) => Some(tree),
+        (None, None) => None,
+    }
+}
+
+/// The share of the weight that goes to the left side of a cut, and the target
+/// weights of both sides, given the target weights of the leaves below the
+/// cut.  See [rcb_recurse].
+fn split_targets(targets: &[f64]) -> (f64, &[f64], &[f64]) {
+    if targets.is_empty() {
+        return (0.5, targets, targets);
+    }
+    let (left, right) = targets.split_at(targets.len() / 2);
+    let left_sum: f64 = left.iter().sum();
+    let total: f64 = left_sum + right.iter().sum::<f64>();
+    let ratio = if total == 0.0 { 0.5 } else { left_sum / total };
+    (ratio, left, right)
+}
+
+/// Fast path of [rcb] for one-dimensional points.
+///
+/// Points are sorted once, and each bisection then picks the cut that best
+/// halves the weight of its segment, from the prefix sums of the sorted
+/// weights.  Cuts are only made between points of different coordinates, so
+/// that the resulting tree locates points consistently with the partition.
+fn rcb_1d<const D: usize, W>(
+    partition: &mut [usize],
+    points: &[PointND<D>],
+    weights: &[W],
+    iter_count: usize,
+    numbering: PartNumbering,
+    targets: &[f64],
+) -> BisectionTree
+where
+    W: RcbWeight,
+{
+    debug_assert_eq!(D, 1);
+
+    let mut permutation: Vec<usize> = (0..points.len()).collect();
+    axis_sort(points, &mut permutation, 0);
+    let coords: Vec<f64> = permutation.iter().map(|i| points[*i][0]).collect();
+    let mut prefix = Vec::with_capacity(points.len() + 1);
+    prefix.push(0.0);
+    let mut sum = 0.0;
+    for i in &permutation {
+        sum += weights[*i].to_f64().unwrap();
+        prefix.push(sum);
+    }
+
+    let mut sorted_parts = vec![0; points.len()];
+    let mut tree = match rcb_1d_recurse(
+        &coords,
+        &prefix,
+        0,
+        &mut sorted_parts,
+        iter_count,
+        0,
+        targets,
+    ) {
+        Some(tree) => tree,
+        None => return BisectionTree::Leaf { part: 0 }, // `points` is empty.
+    };
+
+    let part_id = |iter_id: usize| numbering.part_id(iter_id, iter_count);
+    tree.map_parts(&part_id);
+
+    // Part IDs must start from zero.
+    let part_id_offset = sorted_parts
+        .iter()
+        .map(|part| part_id(*part))
+        .min()
+        .unwrap();
+    for (i, part) in permutation.into_iter().zip(sorted_parts) {
+        partition[i] = part_id(part) - part_id_offset;
+    }
+    tree.map_parts(&|part| part - part_id_offset);
+
+    tree
+}
+
+/// Recursion of [rcb_1d] on the sorted points `lo..lo + parts.len()`.
+///
+/// Part IDs and `targets` are the same as in [rcb_recurse].
+fn rcb_1d_recurse(
+    coords: &[f64],
+    prefix: &[f64],
+    lo: usize,
+    parts: &mut [usize],
+    iter_count: usize,
+    iter_id: usize,
+    targets: &[f64],
+) -> Option<BisectionTree> {
+    if parts.is_empty() {
+        return None;
+    }
+    if iter_count == 0 {
+        parts.fill(iter_id);
+        return Some(BisectionTree::Leaf { part: iter_id });
+    }
+
+    let hi = lo + parts.len();
+    let (ratio, targets_left, targets_right) = split_targets(targets);
+    let target = prefix[lo] + (prefix[hi] - prefix[lo]) * ratio;
+    // Like in rcb_recurse, cuts are made in single precision, so that
+    // BisectionTree::locate finds the same parts.
+    let split = (lo..=hi)
+        .filter(|&k| k == lo || k == hi || (coords[k - 1] as f32) < (coords[k] as f32))
+        .min_by(|&k1, &k2| {
+            let d1 = f64::abs(prefix[k1] - target);
+            let d2 = f64::abs(prefix[k2] - target);
+            f64::total_cmp(&d1, &d2)
+        })
+        .unwrap();
+
+    let (left_parts, right_parts) = parts.split_at_mut(split - lo);
+    let (left, right) = rayon::join(
+        || {
+            rcb_1d_recurse(
+                coords,
+                prefix,
+                lo,
+                left_parts,
+                iter_count - 1,
+                2 * iter_id + 1,
+                targets_left,
+            )
+        },
+        || {
+            rcb_1d_recurse(
+                coords,
+                prefix,
+                split,
+                right_parts,
+                iter_count - 1,
+                2 * iter_id + 2,
+                targets_right,
             )
         },
     );
+
+    match (left, right) {
+        (Some(left), Some(right)) => Some(BisectionTree::Split {
+            coord: 0,
+            position: f64::from(coords[split] as f32),
+            left: Box::new(left),
+            right: Box::new(right),
+        }),
+        (Some(tree), None) | (None, Some(tree)) => Some(tree),
+        (None, None) => None,
+    }
 }
 
+/// Callers must check that coordinates are finite, with
+/// [`super::validate_points`].
 fn rcb<const D: usize, P, W>(
     partition: &mut [usize],
     points: P,
     weights: W,
     iter_count: usize,
     tolerance: f64,
-) -> Result<(), Error>
+    numbering: PartNumbering,
+    target_weights: Option<&[f64]>,
+) -> Result<BisectionTree, Error>
 where
     P: rayon::iter::IntoParallelIterator<Item = PointND<D>>,
     P::Iter: rayon::iter::IndexedParallelIterator + Clone,
@@ -671,6 +858,37 @@ where
         });
     }
 
+    // Leaf target weights, in the order of PartNumbering::Binary.
+    let targets: Vec<f64> = match target_weights {
+        Some(target_weights) => {
+            let leaf_count = u32::try_from(iter_count)
+                .ok()
+                .and_then(|iter_count| 1_usize.checked_shl(iter_count))
+                .ok_or(Error::TooManyParts)?;
+            if target_weights.len() != leaf_count {
+                return Err(Error::InputLenMismatch {
+                    expected: leaf_count,
+                    actual: target_weights.len(),
+                });
+            }
+            if target_weights.iter().any(|target| *target < 0.0) {
+                return Err(Error::NegativeValues);
+            }
+            (0..leaf_count)
+                .map(|leaf| target_weights[numbering.part_id(leaf + leaf_count - 1, iter_count)])
+                .collect()
+        }
+        None => Vec::new(),
+    };
+
+    if D == 1 {
+        let points: Vec<PointND<D>> = points.collect();
+        let weights: Vec<W::Item> = weights.collect();
+        return Ok(rcb_1d(
+            partition, &points, &weights, iter_count, numbering, &targets,
+        ));
+    }
+
     let mut coords = array_init(|coord| {
         points
             .clone()
@@ -682,9 +900,9 @@ where
     let atomic_partition = crate::as_atomic(partition);
     let mut atomic_partition: Vec<&AtomicUsize> = atomic_partition.par_iter().collect();
     let sum = weights.par_iter().cloned().sum();
-    let bb = match BoundingBox::from_points(points) {
+    let bb = match par_bounding_box(points) {
         Some(v) => v,
-        None => return Ok(()), // `items` is empty.
+        None => return Ok(BisectionTree::Leaf { part: 0 }), // `items` is empty.
     };
 
     let points = array_map_mut(&mut coords, |coord| &mut coord[..]);
@@ -693,15 +911,237 @@ where
         weights: &mut weights,
         parts: &mut atomic_partition,
     };
-    rcb_recurse(items, iter_count, 0, 0, tolerance, sum, bb);
+    let mut tree = rcb_recurse(items, iter_count, 0, 0, tolerance, sum, bb, &targets)
+        .expect("rcb_recurse returned no tree on a non-empty input");
+
+    let part_id = |iter_id: usize| numbering.part_id(iter_id, iter_count);
+    partition
+        .par_iter_mut()
+        .for_each(|part| *part = part_id(*part));
+    tree.map_parts(&part_id);
 
     // Part IDs must start from zero.
     let part_id_offset = *partition.par_iter().min().unwrap();
     partition
         .par_iter_mut()
         .for_each(|part_id| *part_id -= part_id_offset);
+    tree.map_parts(&|part| part - part_id_offset);
 
-    Ok(())
+    Ok(tree)
+}
+
+/// The cuts made by a run of [Rcb].
+///
+/// Each [`Split`][BisectionTree::Split] node is an hyperplane orthogonal to
+/// the `coord`-th axis.  Points whose `coord`-th coordinate is strictly lower
+/// than `position` are in the `left` subtree, the others are in the `right`
+/// subtree.  [`Leaf`][BisectionTree::Leaf] nodes hold the ID of a part.
+///
+/// Cuts that leave one side empty are omitted, so that all leaves correspond
+/// to non-empty parts.
+///
+/// When [Rcb] has periodic axes, the root of the tree is a
+/// [`Periodic`][BisectionTree::Periodic] node, which holds the periods in
+/// which points have been wrapped, and the cuts made in these coordinates.
+///
+/// # Serialization
+///
+/// When the `serde` feature is enabled, this type implements serde's
+/// `Serialize` and `Deserialize` traits.  In JSON, the tree of a 2-part
+/// partition looks like this:
+///
+/// ```json
+/// {
+///   "split": {
+///     "coord": 0,
+///     "position": 0.5,
+///     "left": { "leaf": { "part": 0 } },
+///     "right": { "leaf": { "part": 1 } }
+///   }
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum BisectionTree {
+    Split {
+        coord: usize,
+        position: f64,
+        left: Box<BisectionTree>,
+        right: Box<BisectionTree>,
+    },
+    Leaf {
+        part: usize,
+    },
+    Periodic {
+        periodic: Vec<Option<(f64, f64)>>,
+        tree: Box<BisectionTree>,
+    },
+}
+
+impl BisectionTree {
+    /// The ID of the part the given point falls into.
+    ///
+    /// For the points that have been partitioned, this is the part ID they
+    /// have been assigned.
+    pub fn locate<const D: usize>(&self, point: &PointND<D>) -> usize {
+        let mut point = *point;
+        let mut node = self;
+        loop {
+            match node {
+                BisectionTree::Split {
+                    coord,
+                    position,
+                    left,
+                    right,
+                } => {
+                    // RCB compares single-precision coordinates.
+                    let coordinate = f64::from(point[*coord] as f32);
+                    node = if coordinate < *position { left } else { right };
+                }
+                BisectionTree::Leaf { part } => return *part,
+                BisectionTree::Periodic { periodic, tree } => {
+                    point = wrap_periodic(point, periodic);
+                    node = tree;
+                }
+            }
+        }
+    }
+
+    fn map_parts(&mut self, f: &impl Fn(usize) -> usize) {
+        match self {
+            BisectionTree::Split { left, right, .. } => {
+                left.map_parts(f);
+                right.map_parts(f);
+            }
+            BisectionTree::Leaf { part } => *part = f(*part),
+            BisectionTree::Periodic { tree, .. } => tree.map_parts(f),
+        }
+    }
+
+    /// The share of the weight that went to the lower side of each cut.
+    ///
+    /// `partition` and `weights` are those RCB has been run on.  Cuts are
+    /// listed depth first, each one before the cuts of its lower side, then
+    /// those of its upper side, so the first value is the share of the root
+    /// cut.  With equal target weights, values far from 0.5 tell which cuts
+    /// introduced imbalance.  Cuts with no weight on either side have a share
+    /// of 0.5.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), coupe::Error> {
+    /// use coupe::Partition as _;
+    /// use coupe::Point2D;
+    ///
+    /// let points = [
+    ///     Point2D::new(0.0, 0.0),
+    ///     Point2D::new(1.0, 0.0),
+    ///     Point2D::new(2.0, 0.0),
+    ///     Point2D::new(3.0, 0.0),
+    /// ];
+    /// let weights = [1.0, 1.0, 1.0, 3.0];
+    /// let mut partition = [0; 4];
+    ///
+    /// let tree = coupe::Rcb { iter_count: 1, ..Default::default() }
+    ///     .partition(&mut partition, (points, weights))?;
+    ///
+    /// assert_eq!(tree.split_balance(&partition, &weights), [0.5]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn split_balance<W>(&self, partition: &[usize], weights: &[W]) -> Vec<f64>
+    where
+        W: ToPrimitive,
+    {
+        let part_count = partition.iter().max().map_or(0, |max_part| max_part + 1);
+        let mut part_weights = vec![0.0; part_count];
+        for (part, weight) in partition.iter().zip(weights) {
+            part_weights[*part] += weight.to_f64().unwrap();
+        }
+
+        /// Pushes the shares of the cuts of `node`, and returns its weight.
+        fn visit(node: &BisectionTree, part_weights: &[f64], shares: &mut Vec<f64>) -> f64 {
+            match node {
+                BisectionTree::Split { left, right, .. } => {
+                    let share_idx = shares.len();
+                    shares.push(0.5);
+                    let left = visit(left, part_weights, shares);
+                    let right = visit(right, part_weights, shares);
+                    if 0.0 < left + right {
+                        shares[share_idx] = left / (left + right);
+                    }
+                    left + right
+                }
+                BisectionTree::Leaf { part } => part_weights.get(*part).copied().unwrap_or(0.0),
+                BisectionTree::Periodic { tree, .. } => visit(tree, part_weights, shares),
+            }
+        }
+
+        let mut shares = Vec::new();
+        visit(self, &part_weights, &mut shares);
+        shares
+    }
+
+    /// The hierarchy of parts of this tree.
+    ///
+    /// Each cut becomes a node with two children, the lower side first.
+    pub fn partition_tree(&self) -> PartitionTree {
+        match self {
+            BisectionTree::Split { left, right, .. } => {
+                PartitionTree::Node(vec![left.partition_tree(), right.partition_tree()])
+            }
+            BisectionTree::Leaf { part } => PartitionTree::Leaf { part: *part },
+            BisectionTree::Periodic { tree, .. } => tree.partition_tree(),
+        }
+    }
+}
+
+/// How [Rcb] numbers the parts it creates.
+///
+/// Each part is a leaf of the [BisectionTree], reached by a sequence of
+/// `iter_count` split decisions.  Reading these decisions as bits, 0 for the
+/// lower side of a cut and 1 for the upper side, gives the *binary number* of
+/// the part, the first decision being its most significant bit.
+///
+/// Whatever the numbering, part IDs are then shifted so that the lowest one is
+/// zero.  Leaves are missing from the tree when a cut leaves one side empty,
+/// in which case IDs might not be contiguous.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PartNumbering {
+    /// Parts are numbered by their binary number.  Parts with consecutive IDs
+    /// are next to each other in the tree.
+    #[default]
+    Binary,
+
+    /// Parts are numbered by the reflected Gray code of their binary number.
+    /// Parts that are next to each other in the tree, which share a cut, get
+    /// IDs that differ by a single bit.  This is useful to map parts to the
+    /// nodes of a hypercube or torus network.
+    Gray,
+
+    /// Parts are numbered by their binary number with its bits reversed, that
+    /// is the first decision gives the least significant bit.  Parts whose IDs
+    /// have the same high-order bits are close together in the last levels of
+    /// the tree.
+    BitReversal,
+}
+
+impl PartNumbering {
+    /// The ID of the leaf with the given heap index (see [rcb_recurse]), at
+    /// depth `iter_count`.
+    fn part_id(self, iter_id: usize, iter_count: usize) -> usize {
+        let binary = iter_id - ((1 << iter_count) - 1);
+        match self {
+            PartNumbering::Binary => binary,
+            PartNumbering::Gray => binary ^ (binary >> 1),
+            PartNumbering::BitReversal if iter_count == 0 => binary,
+            PartNumbering::BitReversal => {
+                binary.reverse_bits() >> (usize::BITS as usize - iter_count)
+            }
+        }
+    }
 }
 
 /// Trait alias for values accepted as weights by [Rcb] and [Rib].
@@ -734,6 +1174,13 @@ where
 /// weighted. Finally, recurse by reapplying the algorithm to the two parts with
 /// an other normal vector selection.
 ///
+/// The cuts are returned as a [BisectionTree], which can be used to find in
+/// which part new points fall.
+///
+/// One-dimensional points (`PointND<1>`) are sorted once and each cut is then
+/// the one that best balances the weights on both sides, which makes the
+/// `tolerance` irrelevant.
+///
 /// # Example
 ///
 /// ```rust
@@ -776,7 +1223,7 @@ where
 /// Bramas, B., 2017. A Novel Hybrid Quicksort Algorithm Vectorized using
 /// AVX-512 on Intel Skylake. *International Journal of Advanced Computer
 /// Science and Applications*, 8(10). <doi:10.14569/IJACSA.2017.081044>.
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct Rcb {
     /// The number of iterations of the algorithm. This will yield a partition
     /// of at most `2^num_iter` parts.
@@ -789,6 +1236,46 @@ pub struct Rcb {
     ///
     /// Negative values are interpreted as zeroes.
     pub tolerance: f64,
+
+    /// How parts are numbered, see [PartNumbering].
+    pub part_numbering: PartNumbering,
+
+    /// The target weight of each part, relative to the others, indexed by
+    /// part ID.  For example, `[2.0, 1.0]` asks for a first part twice as
+    /// heavy as the second one.  Each cut then splits the weight in proportion
+    /// to the targets of the parts on both of its sides.
+    ///
+    /// Must have `2^iter_count` non-negative values.  `None` means all parts
+    /// have the same target weight.
+    pub target_weights: Option<Vec<f64>>,
+
+    /// The period of each axis along which space wraps around, indexed by
+    /// axis, for meshes with periodic boundary conditions.
+    ///
+    /// Coordinates along an axis with a `Some((min, max))` period are mapped
+    /// into one period before partitioning, so that points that are actually
+    /// neighbors end up in the same parts.  Periods are shifted to start in
+    /// the widest gap between points, so that parts may span the boundary of
+    /// the domain.  The returned tree then holds these shifted periods, see
+    /// [`BisectionTree::Periodic`].  By default, no axis is periodic.
+    ///
+    /// Periods must be non-empty and finite, otherwise partitioning fails
+    /// with [`Error::InvalidPeriod`].
+    pub periodic: Vec<Option<(f64, f64)>>,
+}
+
+impl crate::PartCount for Rcb {
+    fn expected_part_count(&self) -> Option<usize> {
+        u32::try_from(self.iter_count)
+            .ok()
+            .and_then(|iter_count| 1_usize.checked_shl(iter_count))
+    }
+}
+
+impl crate::Name for Rcb {
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed("rcb")
+    }
 }
 
 impl<const D: usize, P, W> crate::Partition<(P, W)> for Rcb
@@ -799,7 +1286,7 @@ where
     W::Item: RcbWeight,
     W::Iter: rayon::iter::IndexedParallelIterator,
 {
-    type Metadata = ();
+    type Metadata = BisectionTree;
     type Error = Error;
 
     fn partition(
@@ -807,22 +1294,56 @@ where
         part_ids: &mut [usize],
         (points, weights): (P, W),
     ) -> Result<Self::Metadata, Self::Error> {
-        rcb(part_ids, points, weights, self.iter_count, self.tolerance)
+        if let Some(axis) = invalid_period(&self.periodic) {
+            return Err(Error::InvalidPeriod { axis });
+        }
+        let points = points.into_par_iter();
+        super::validate_points(points.clone())?;
+        let periodic = if self.periodic.iter().all(Option::is_none) {
+            Vec::new()
+        } else {
+            periodic_seams(points.clone(), &self.periodic)
+        };
+        let wrapped = {
+            let periodic = periodic.clone();
+            points.map(move |point| wrap_periodic(point, &periodic))
+        };
+        let tree = rcb(
+            part_ids,
+            wrapped,
+            weights,
+            self.iter_count,
+            self.tolerance,
+            self.part_numbering,
+            self.target_weights.as_deref(),
+        )?;
+        if periodic.is_empty() {
+            return Ok(tree);
+        }
+        Ok(BisectionTree::Periodic {
+            periodic,
+            tree: Box::new(tree),
+        })
     }
 }
 
 // pub because it is also useful for multijagged and required for benchmarks
+//
+// NaN coordinates are sorted last, and points that share the same coordinate
+// are sorted by index, so that the output does not depend on the input order
+// of `permutation`.
 pub fn axis_sort<const D: usize>(
     points: &[PointND<D>],
     permutation: &mut [usize],
     current_coord: usize,
 ) {
-    permutation.par_sort_unstable_by(|i1, i2| {
-        if points[*i1][current_coord] < points[*i2][current_coord] {
-            cmp::Ordering::Less
-        } else {
-            cmp::Ordering::Greater
-        }
+    permutation.par_sort_unstable_by(|&i1, &i2| {
+        let c1 = points[i1][current_coord];
+        let c2 = points[i2][current_coord];
+        c1.is_nan()
+            .cmp(&c2.is_nan())
+            .then_with(|| c1.partial_cmp(&c2).unwrap_or(cmp::Ordering::Equal))
+            .then(i1.cmp(&i2))
     })
 }
 
@@ -832,7 +1353,9 @@ fn rib<const D: usize, W>(
     weights: W,
     n_iter: usize,
     tolerance: f64,
-) -> Result<(), Error>
+    uniform_inertia: bool,
+    inertia_levels: usize,
+) -> Result<RibMetadata<D>, Error>
 where
     Const<D>: DimSub<Const<1>>,
     DefaultAllocator: Allocator<f64, Const<D>, Const<D>, Buffer = ArrayStorage<f64, D, D>>
@@ -841,13 +1364,245 @@ where
     W::Item: RcbWeight,
     W::Iter: rayon::iter::IndexedParallelIterator,
 {
-    let obb = match OrientedBoundingBox::from_points(points) {
-        Some(v) => v,
-        None => return Ok(()),
+    super::validate_points(points)?;
+    let weights: Vec<W::Item> = weights.into_par_iter().collect();
+    if weights.len() != points.len() {
+        return Err(Error::InputLenMismatch {
+            expected: points.len(),
+            actual: weights.len(),
+        });
+    }
+    rib_recurse(
+        partition,
+        points,
+        &weights,
+        n_iter,
+        tolerance,
+        uniform_inertia,
+        inertia_levels,
+    )
+}
+
+/// Runs RIB, computing the inertia of the first `inertia_levels` levels of
+/// parts.  The levels below are cut in the basis of the last level.
+#[allow(clippy::too_many_arguments)]
+fn rib_recurse<const D: usize, W>(
+    partition: &mut [usize],
+    points: &[PointND<D>],
+    weights: &[W],
+    n_iter: usize,
+    tolerance: f64,
+    uniform_inertia: bool,
+    inertia_levels: usize,
+) -> Result<RibMetadata<D>, Error>
+where
+    Const<D>: DimSub<Const<1>>,
+    DefaultAllocator: Allocator<f64, Const<D>, Const<D>, Buffer = ArrayStorage<f64, D, D>>
+        + Allocator<f64, DimDiff<Const<D>, Const<1>>>,
+    W: RcbWeight,
+{
+    if points.is_empty() {
+        return Ok(RibMetadata {
+            axes: SMatrix::identity(),
+            tree: BisectionTree::Leaf { part: 0 },
+            halves: None,
+        });
+    }
+
+    if inertia_levels == 0 {
+        let tree = rcb(
+            partition,
+            points.par_iter().cloned(),
+            weights.par_iter().cloned(),
+            n_iter,
+            tolerance,
+            PartNumbering::Binary,
+            None,
+        )?;
+        return Ok(RibMetadata {
+            axes: SMatrix::identity(),
+            tree,
+            halves: None,
+        });
+    }
+
+    if inertia_levels == 1 || n_iter <= 1 {
+        return rib_rotated(
+            partition,
+            points,
+            weights,
+            n_iter,
+            tolerance,
+            uniform_inertia,
+        );
+    }
+
+    // Make the first cut, then compute the inertia of each side again.
+    let mut metadata = rib_rotated(partition, points, weights, 1, tolerance, uniform_inertia)?;
+    let half = |side: usize| {
+        let indices: Vec<usize> = (0..points.len())
+            .filter(|idx| partition[*idx] == side)
+            .collect();
+        let points: Vec<PointND<D>> = indices.iter().map(|idx| points[*idx]).collect();
+        let weights: Vec<W> = indices.iter().map(|idx| weights[*idx]).collect();
+        let mut half_partition = vec![0; indices.len()];
+        let metadata = rib_recurse(
+            &mut half_partition,
+            &points,
+            &weights,
+            n_iter - 1,
+            tolerance,
+            uniform_inertia,
+            inertia_levels - 1,
+        )?;
+        Ok::<_, Error>((indices, half_partition, metadata))
     };
+    let (left, right) = rayon::join(|| half(0), || half(1));
+    let (left, right) = (left?, right?);
+
+    // Same numbering as PartNumbering::Binary: the first cut gives the most
+    // significant bit of part IDs.
+    let offset = 1 << (n_iter - 1);
+    let (right_indices, right_partition, mut right_metadata) = right;
+    right_metadata.map_parts(&|part| part + offset);
+    for (idx, part) in right_indices.into_iter().zip(right_partition) {
+        partition[idx] = part + offset;
+    }
+    let (left_indices, left_partition, left_metadata) = left;
+    for (idx, part) in left_indices.into_iter().zip(left_partition) {
+        partition[idx] = part;
+    }
+    metadata.halves = Some(Box::new([left_metadata, right_metadata]));
+
+    Ok(metadata)
+}
+
+/// Runs RCB in the basis of the inertia axes of the given points.
+fn rib_rotated<const D: usize, W>(
+    partition: &mut [usize],
+    points: &[PointND<D>],
+    weights: &[W],
+    n_iter: usize,
+    tolerance: f64,
+    uniform_inertia: bool,
+) -> Result<RibMetadata<D>, Error>
+where
+    Const<D>: DimSub<Const<1>>,
+    DefaultAllocator: Allocator<f64, Const<D>, Const<D>, Buffer = ArrayStorage<f64, D, D>>
+        + Allocator<f64, DimDiff<Const<D>, Const<1>>>,
+    W: RcbWeight,
+{
+    let inertia = if uniform_inertia {
+        inertia_matrix(points)
+    } else {
+        weighted_inertia_matrix(points, weights)
+    };
+    if let Some(axes) = coordinate_axes(&inertia) {
+        // The points are already aligned with the coordinate axes, so they
+        // only need their coordinates to be reordered, which is exact.
+        let obb_to_aabb = axes.transpose();
+        let points = points.par_iter().map(|p| obb_to_aabb * p);
+        let tree = rcb(
+            partition,
+            points,
+            weights.par_iter().cloned(),
+            n_iter,
+            tolerance,
+            PartNumbering::Binary,
+            None,
+        )?;
+        return Ok(RibMetadata {
+            axes,
+            tree,
+            halves: None,
+        });
+    }
+
+    let obb = OrientedBoundingBox::from_inertia_matrix(points, inertia).unwrap();
     let points = points.par_iter().map(|p| obb.obb_to_aabb(p));
     // When the rotation is done, we just apply RCB
-    rcb(partition, points, weights, n_iter, tolerance)
+    let tree = rcb(
+        partition,
+        points,
+        weights.par_iter().cloned(),
+        n_iter,
+        tolerance,
+        PartNumbering::Binary,
+        None,
+    )?;
+    Ok(RibMetadata {
+        axes: *obb.axes(),
+        tree,
+        halves: None,
+    })
+}
+
+/// Relative size of the off-diagonal terms of an inertia matrix under which
+/// points are considered aligned with the coordinate axes.
+const AXIS_ALIGNED_THRESHOLD: f64 = 1e-9;
+
+/// The coordinate axes sorted by decreasing inertia, as the columns of a
+/// permutation matrix, if the given inertia matrix is nearly diagonal.
+fn coordinate_axes<const D: usize>(inertia: &Matrix<D>) -> Option<Matrix<D>> {
+    for i in 0..D {
+        for j in 0..i {
+            let scale = f64::sqrt(inertia[(i, i)] * inertia[(j, j)]);
+            if AXIS_ALIGNED_THRESHOLD * scale < f64::abs(inertia[(i, j)]) {
+                return None;
+            }
+        }
+    }
+
+    // Stable, so that ties keep the coordinate order, like in RCB.
+    let mut order: Vec<usize> = (0..D).collect();
+    order.sort_by(|a, b| f64::total_cmp(&inertia[(*b, *b)], &inertia[(*a, *a)]));
+
+    let mut axes = Matrix::zeros();
+    for (column, coord) in order.into_iter().enumerate() {
+        axes[(coord, column)] = 1.0;
+    }
+    Some(axes)
+}
+
+/// The frame and the cuts of a run of [Rib].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RibMetadata<const D: usize> {
+    /// The basis in which the points were bisected, as the columns of an
+    /// orthogonal matrix.  The first column is the inertia axis of the points.
+    pub axes: SMatrix<f64, D, D>,
+
+    /// The cuts made by RCB, in the coordinates of `axes`.
+    ///
+    /// When `halves` is set, this only holds the first cut.
+    pub tree: BisectionTree,
+
+    /// The frames and cuts of both sides of the first cut, lower side first,
+    /// when [`Rib::inertia_levels`] asked for their inertia to be computed
+    /// again.
+    pub halves: Option<Box<[RibMetadata<D>; 2]>>,
+}
+
+impl<const D: usize> RibMetadata<D> {
+    /// The part a point would be given, had it been part of the input.
+    pub fn locate(&self, point: &PointND<D>) -> usize {
+        // The same computation as the one of OrientedBoundingBox::obb_to_aabb,
+        // for results to match the partition exactly.  Axes are orthonormal,
+        // so no inverse needs to be computed.
+        let obb_to_aabb = self.axes.transpose();
+        let part = self.tree.locate(&(obb_to_aabb * point));
+        match &self.halves {
+            Some(halves) => halves[part].locate(point),
+            None => part,
+        }
+    }
+
+    fn map_parts(&mut self, f: &impl Fn(usize) -> usize) {
+        match &mut self.halves {
+            Some(halves) => halves.iter_mut().for_each(|half| half.map_parts(f)),
+            None => self.tree.map_parts(f),
+        }
+    }
 }
 
 /// # Recursive Inertial Bisection algorithm
@@ -859,6 +1614,35 @@ where
 /// the new basis is colinear to the inertia axis of the set of points. This has
 /// the goal of producing better shaped partition than [RCB][crate::Rcb].
 ///
+/// When the points are already aligned with the coordinate axes, no rotation
+/// is made: coordinates are only reordered so that the first one has the
+/// largest spread.  In particular, RIB and RCB give the same partition when the
+/// first coordinate has the largest spread.
+///
+/// By default, the basis change is computed once, from the inertia of the
+/// whole point set, and all cuts are then made along the axes of the new
+/// basis, at every level of the recursion.  Hence, RIB only costs one inertia
+/// computation and one rotation more than RCB, whatever `iter_count`: it
+/// corrects the global orientation of the mesh, while sub-parts keep
+/// axis-aligned cuts.  To apply the same rotation to other point sets
+/// beforehand, and then run RCB on them, see
+/// [`affine::rotate`][crate::affine::rotate].
+///
+/// Set `inertia_levels` to compute the inertia of the sub-parts of the first
+/// levels again, so that their cuts follow their own orientation.  This gives
+/// better shaped parts on curved or irregular meshes, at the cost of one
+/// inertia computation and one rotation per part of these levels.  With
+/// `inertia_levels` equal to `iter_count`, every cut is orthogonal to the
+/// inertia axis of the part it splits, which is the classic RIB.  With zero,
+/// no rotation is made and RIB is RCB.
+///
+/// The inertia of the points is weighted, so that heavy points pull the
+/// inertia axis towards them, and the cuts follow the distribution of the
+/// weight rather than the one of the points.  Set `uniform_inertia` to count
+/// all points the same instead.
+///
+/// The new basis and the cuts made in it are returned as a [RibMetadata].
+///
 /// # Example
 ///
 /// ```rust
@@ -898,7 +1682,7 @@ where
 /// Williams, Roy D., 1991. Performance of dynamic load balancing algorithms for
 /// unstructured mesh calculations. *Concurrency: Practice and Experience*,
 /// 3(5):457–481. <doi:10.1002/cpe.4330030502>.
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug)]
 pub struct Rib {
     /// The number of iterations of the algorithm. This will yield a partition
     /// of at most `2^num_iter` parts.
@@ -906,6 +1690,42 @@ pub struct Rib {
 
     /// Same meaning as [`Rcb::tolerance`].
     pub tolerance: f64,
+
+    /// Whether to ignore weights when computing the inertia axis of the
+    /// points.  Weights are still used to balance parts.
+    pub uniform_inertia: bool,
+
+    /// The number of levels of the recursion for which the inertia axis of
+    /// each part is computed.  Levels below are cut along the axes of the
+    /// last computed basis.
+    ///
+    /// Defaults to one: only the inertia of the whole point set is used.
+    pub inertia_levels: usize,
+}
+
+impl Default for Rib {
+    fn default() -> Self {
+        Self {
+            iter_count: 0,
+            tolerance: 0.0,
+            uniform_inertia: false,
+            inertia_levels: 1,
+        }
+    }
+}
+
+impl crate::PartCount for Rib {
+    fn expected_part_count(&self) -> Option<usize> {
+        u32::try_from(self.iter_count)
+            .ok()
+            .and_then(|iter_count| 1_usize.checked_shl(iter_count))
+    }
+}
+
+impl crate::Name for Rib {
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed("rib")
+    }
 }
 
 impl<'a, const D: usize, W> crate::Partition<(&'a [PointND<D>], W)> for Rib
@@ -917,7 +1737,7 @@ where
     W::Item: RcbWeight,
     W::Iter: rayon::iter::IndexedParallelIterator,
 {
-    type Metadata = ();
+    type Metadata = RibMetadata<D>;
     type Error = Error;
 
     fn partition(
@@ -925,7 +1745,15 @@ where
         part_ids: &mut [usize],
         (points, weights): (&'a [PointND<D>], W),
     ) -> Result<Self::Metadata, Self::Error> {
-        rib(part_ids, points, weights, self.iter_count, self.tolerance)
+        rib(
+            part_ids,
+            points,
+            weights,
+            self.iter_count,
+            self.tolerance,
+            self.uniform_inertia,
+            self.inertia_levels,
+        )
     }
 }
 
@@ -935,6 +1763,7 @@ mod tests {
     use proptest::prelude::*;
 
     use super::*;
+    use crate::geometry::Matrix;
     use crate::geometry::Point2D;
 
     fn gen_point_sample() -> Vec<Point2D> {
@@ -1038,6 +1867,28 @@ mod tests {
         assert_eq!(permutation, vec![3, 6, 5, 1, 0, 2, 4]);
     }
 
+    #[test]
+    fn test_axis_sort_ties_and_nan() {
+        let points = [
+            Point2D::new(1.0, 0.0),
+            Point2D::new(f64::NAN, 0.0),
+            Point2D::new(0.0, 0.0),
+            Point2D::new(1.0, 0.0),
+            Point2D::new(-0.0, 0.0),
+            Point2D::new(-f64::NAN, 0.0),
+            Point2D::new(1.0, 0.0),
+        ];
+        let expected = vec![2, 4, 0, 3, 6, 1, 5];
+
+        let mut permutation = (0..points.len()).collect::<Vec<usize>>();
+        axis_sort(&points, &mut permutation, 0);
+        assert_eq!(permutation, expected);
+
+        let mut permutation = (0..points.len()).rev().collect::<Vec<usize>>();
+        axis_sort(&points, &mut permutation, 0);
+        assert_eq!(permutation, expected);
+    }
+
     proptest!(
         #[test]
         fn test_par_rcb_split(
@@ -1065,12 +1916,12 @@ mod tests {
                 right,
                 weight_left,
                 split_pos,
-            } = par_rcb_split(items, 0, 0.05, min, max, sum);
+            } = par_rcb_split(items, 0, 0.05, min, max, sum, 0.5);
 
             prop_assert!(left.points[0].iter().all(|l| *l < split_pos));
             prop_assert!(right.points[0].iter().all(|r| split_pos <= *r));
-            prop_assert_eq!(weight_left, left.weights.iter().sum());
-            prop_assert_eq!(sum - weight_left, right.weights.iter().sum());
+            prop_assert_eq!(weight_left, left.weights.iter().sum::<u32>());
+            prop_assert_eq!(sum - weight_left, right.weights.iter().sum::<u32>());
         }
     );
 
@@ -1093,7 +1944,17 @@ mod tests {
             .num_threads(1) // make the test deterministic
             .build()
             .unwrap()
-            .install(|| rcb(&mut partition, points, weights, 2, 0.05))
+            .install(|| {
+                rcb(
+                    &mut partition,
+                    points,
+                    weights,
+                    2,
+                    0.05,
+                    PartNumbering::Binary,
+                    None,
+                )
+            })
             .unwrap();
 
         assert_eq!(partition[0], partition[6]);
@@ -1113,4 +1974,657 @@ mod tests {
         assert_eq!(p3.count(), 2);
         assert_eq!(p4.count(), 2);
     }
+
+    #[test]
+    fn test_rcb_tree_locate() {
+        let points = gen_point_sample();
+        let weights = [1.0; 7];
+        let mut partition = [0; 7];
+
+        let tree = rcb(
+            &mut partition,
+            points.clone(),
+            weights,
+            2,
+            0.05,
+            PartNumbering::Binary,
+            None,
+        )
+        .unwrap();
+
+        for (point, part) in points.iter().zip(partition) {
+            assert_eq!(tree.locate(point), part);
+        }
+
+        // Leaves of the hierarchy are the parts of the flat partition.
+        let mut parts = partition.to_vec();
+        parts.sort();
+        parts.dedup();
+        let partition_tree = tree.partition_tree();
+        assert_eq!(partition_tree.parts(), parts);
+        for part in parts {
+            assert_eq!(partition_tree.path(part).unwrap().len(), 2);
+        }
+    }
+
+    #[test]
+    fn test_rcb_split_balance() {
+        // Three points of equal weight cannot be halved.
+        let points = [
+            Point2D::new(0.0, 0.0),
+            Point2D::new(1.0, 0.0),
+            Point2D::new(2.0, 0.0),
+        ];
+        let weights = [1.0; 3];
+        let mut partition = [0; 3];
+
+        let tree = rcb(
+            &mut partition,
+            points,
+            weights,
+            1,
+            0.05,
+            PartNumbering::Binary,
+            None,
+        )
+        .unwrap();
+        let shares = tree.split_balance(&partition, &weights);
+        assert_eq!(shares.len(), 1);
+        assert!(
+            shares[0] == 1.0 / 3.0 || shares[0] == 2.0 / 3.0,
+            "{shares:?}"
+        );
+
+        // Two levels of cuts on a grid split evenly.
+        let points: Vec<Point2D> = (0..16)
+            .map(|i| Point2D::new((i % 4) as f64, (i / 4) as f64))
+            .collect();
+        let weights = vec![1; 16];
+        let mut partition = vec![0; 16];
+        let tree = rcb(
+            &mut partition,
+            points.par_iter().cloned(),
+            weights.par_iter().cloned(),
+            2,
+            0.05,
+            PartNumbering::Binary,
+            None,
+        )
+        .unwrap();
+        assert_eq!(tree.split_balance(&partition, &weights), [0.5; 3]);
+    }
+
+    #[cfg(feature = "sprs")]
+    #[test]
+    fn test_rcb_periodic() {
+        use crate::Partition as _;
+        use crate::Topology as _;
+
+        // An 8x8 grid that wraps around along x, where half of the points of
+        // the first column are given one period away, past the last column.
+        let side = 8;
+        let points: Vec<Point2D> = (0..side * side)
+            .map(|i| {
+                let (x, y) = (i % side, i / side);
+                if x == 0 && y % 2 == 1 {
+                    Point2D::new(side as f64, y as f64)
+                } else {
+                    Point2D::new(x as f64, y as f64)
+                }
+            })
+            .collect();
+        let mut adjacency = sprs::TriMat::new((side * side, side * side));
+        for i in 0..side * side {
+            let (x, y) = (i % side, i / side);
+            let right = (x + 1) % side + y * side;
+            adjacency.add_triplet(i, right, 1);
+            adjacency.add_triplet(right, i, 1);
+            if y + 1 < side {
+                adjacency.add_triplet(i, i + side, 1);
+                adjacency.add_triplet(i + side, i, 1);
+            }
+        }
+        let adjacency: sprs::CsMat<i64> = adjacency.to_csr();
+        let weights = vec![1.0; points.len()];
+
+        let mut naive = vec![0; points.len()];
+        Rcb {
+            iter_count: 1,
+            ..Default::default()
+        }
+        .partition(
+            &mut naive,
+            (points.par_iter().cloned(), weights.par_iter().cloned()),
+        )
+        .unwrap();
+
+        let mut periodic = vec![0; points.len()];
+        Rcb {
+            iter_count: 1,
+            periodic: vec![Some((0.0, side as f64)), None],
+            ..Default::default()
+        }
+        .partition(
+            &mut periodic,
+            (points.par_iter().cloned(), weights.par_iter().cloned()),
+        )
+        .unwrap();
+
+        // A cut of the ring needs two seams of `side` edges.
+        assert_eq!(adjacency.view().edge_cut(&periodic), 2 * side as i64);
+        assert!(adjacency.view().edge_cut(&periodic) < adjacency.view().edge_cut(&naive));
+    }
+
+    #[test]
+    fn test_rcb_periodic_seam() {
+        use crate::Partition as _;
+
+        // Two blocks of 6 columns in a domain that wraps around along x, the
+        // first one being split by the boundary of the domain.
+        let columns = [0.0, 0.5, 1.0, 8.5, 9.0, 9.5, 4.0, 4.5, 5.0, 5.5, 6.0, 6.5];
+        let points: Vec<Point2D> = columns
+            .iter()
+            .flat_map(|x| (0..4).map(move |y| Point2D::new(*x, y as f64)))
+            .collect();
+        let weights = vec![1.0; points.len()];
+        let block_len = 6 * 4;
+
+        let partition = |periodic| {
+            let mut partition = vec![0; points.len()];
+            let tree = Rcb {
+                iter_count: 1,
+                periodic,
+                ..Default::default()
+            }
+            .partition(
+                &mut partition,
+                (points.par_iter().cloned(), weights.par_iter().cloned()),
+            )
+            .unwrap();
+            (partition, tree)
+        };
+
+        let (naive, _) = partition(Vec::new());
+        assert!(naive[..block_len].iter().any(|part| *part != naive[0]));
+
+        // The first block is made a single part, which spans the boundary.
+        let (periodic, tree) = partition(vec![Some((0.0, 10.0)), None]);
+        let (wrapping, other) = periodic.split_at(block_len);
+        assert!(wrapping.iter().all(|part| *part == wrapping[0]));
+        assert!(other.iter().all(|part| *part != wrapping[0]));
+
+        // Points are located in the parts they were given, and so are their
+        // images in other periods.
+        for (point, part) in points.iter().zip(&periodic) {
+            assert_eq!(tree.locate(point), *part);
+            let image = Point2D::new(point.x - 10.0, point.y);
+            assert_eq!(tree.locate(&image), *part);
+        }
+
+        let mut partition = vec![0; points.len()];
+        let err = Rcb {
+            iter_count: 1,
+            periodic: vec![None, Some((1.0, 1.0))],
+            ..Default::default()
+        }
+        .partition(
+            &mut partition,
+            (points.par_iter().cloned(), weights.par_iter().cloned()),
+        )
+        .unwrap_err();
+        assert!(matches!(err, Error::InvalidPeriod { axis: 1 }));
+    }
+
+    #[test]
+    fn test_rcb_gray_numbering() {
+        use crate::Partition as _;
+
+        fn leaves(tree: &BisectionTree, parts: &mut Vec<usize>) {
+            match tree {
+                BisectionTree::Split { left, right, .. } => {
+                    leaves(left, parts);
+                    leaves(right, parts);
+                }
+                BisectionTree::Leaf { part } => parts.push(*part),
+                BisectionTree::Periodic { tree, .. } => leaves(tree, parts),
+            }
+        }
+
+        let points: Vec<Point2D> = (0..256)
+            .map(|i| Point2D::new((i % 16) as f64, (i / 16) as f64))
+            .collect();
+        let weights = vec![1.0; points.len()];
+        let mut partition = vec![0; points.len()];
+
+        let tree = Rcb {
+            iter_count: 4,
+            part_numbering: PartNumbering::Gray,
+            ..Default::default()
+        }
+        .partition(
+            &mut partition,
+            (points.par_iter().cloned(), weights.par_iter().cloned()),
+        )
+        .unwrap();
+
+        let mut parts = Vec::new();
+        leaves(&tree, &mut parts);
+        assert_eq!(
+            parts.iter().cloned().sorted().collect::<Vec<_>>(),
+            (0..16).collect::<Vec<_>>()
+        );
+        for pair in parts.windows(2) {
+            assert_eq!((pair[0] ^ pair[1]).count_ones(), 1, "{parts:?}");
+        }
+        for (point, part) in points.iter().zip(partition) {
+            assert_eq!(tree.locate(point), part);
+        }
+    }
+
+    #[test]
+    fn test_part_numbering() {
+        // Leaves at depth 3 have heap indices 7 to 14.
+        let ids = |numbering: PartNumbering| {
+            (7..15)
+                .map(|iter_id| numbering.part_id(iter_id, 3))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(PartNumbering::Binary), [0, 1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(ids(PartNumbering::Gray), [0, 1, 3, 2, 6, 7, 5, 4]);
+        assert_eq!(ids(PartNumbering::BitReversal), [0, 4, 2, 6, 1, 5, 3, 7]);
+        assert_eq!(PartNumbering::BitReversal.part_id(0, 0), 0);
+    }
+
+    #[test]
+    fn test_rcb_target_weights() {
+        use crate::Partition as _;
+
+        let points: Vec<Point2D> = (0..300)
+            .map(|i| Point2D::new((i % 30) as f64, (i / 30) as f64))
+            .collect();
+        let weights = vec![1.0; points.len()];
+        let mut partition = vec![0; points.len()];
+
+        Rcb {
+            iter_count: 1,
+            target_weights: Some(vec![2.0, 1.0]),
+            ..Default::default()
+        }
+        .partition(
+            &mut partition,
+            (points.par_iter().cloned(), weights.par_iter().cloned()),
+        )
+        .unwrap();
+
+        let imbalance = crate::analysis::imbalance_vs_targets(&partition, &weights, &[2.0, 1.0]);
+        assert!(imbalance < 0.05, "{imbalance}");
+
+        let err = Rcb {
+            iter_count: 2,
+            target_weights: Some(vec![2.0, 1.0]),
+            ..Default::default()
+        }
+        .partition(
+            &mut partition,
+            (points.par_iter().cloned(), weights.par_iter().cloned()),
+        )
+        .unwrap_err();
+        assert!(
+            matches!(
+                err,
+                crate::Error::InputLenMismatch {
+                    expected: 4,
+                    actual: 2
+                }
+            ),
+            "{err:?}"
+        );
+
+        let err = Rcb {
+            iter_count: usize::BITS as usize,
+            target_weights: Some(vec![2.0, 1.0]),
+            ..Default::default()
+        }
+        .partition(
+            &mut partition,
+            (points.par_iter().cloned(), weights.par_iter().cloned()),
+        )
+        .unwrap_err();
+        assert!(matches!(err, crate::Error::TooManyParts), "{err:?}");
+    }
+
+    #[test]
+    fn test_rcb_1d() {
+        use crate::Partition as _;
+
+        // A weighted line, in shuffled order, with some duplicated positions.
+        let count = 101;
+        let positions: Vec<f64> = (0..count).map(|i| ((i * 37) % count / 2) as f64).collect();
+        let points: Vec<PointND<1>> = positions.iter().map(|x| PointND::from([*x])).collect();
+        let weights: Vec<f64> = (0..count).map(|i| (i % 7 + 1) as f64).collect();
+        let mut partition = vec![0; count];
+
+        let tree = Rcb {
+            iter_count: 2,
+            ..Default::default()
+        }
+        .partition(
+            &mut partition,
+            (points.par_iter().cloned(), weights.par_iter().cloned()),
+        )
+        .unwrap();
+
+        // Parts are contiguous segments of the line, in order.
+        let mut order: Vec<usize> = (0..count).collect();
+        order.sort_by(|i, j| f64::total_cmp(&positions[*i], &positions[*j]));
+        let parts_in_order: Vec<usize> = order.iter().map(|i| partition[*i]).dedup().collect();
+        assert_eq!(parts_in_order, [0, 1, 2, 3]);
+
+        // Parts are balanced, up to twice the weight of two positions.
+        let loads =
+            crate::imbalance::compute_parts_load(&partition, 4, weights.par_iter().cloned());
+        let ideal = weights.iter().sum::<f64>() / 4.0;
+        for load in loads {
+            assert!(f64::abs(load - ideal) <= 4.0 * 7.0, "{load} vs {ideal}");
+        }
+
+        for (point, part) in points.iter().zip(&partition) {
+            assert_eq!(tree.locate(point), *part);
+        }
+    }
+
+    #[test]
+    fn test_rcb_1d_single_precision() {
+        use crate::Partition as _;
+
+        // Two pairs of points one f32 ulp apart, the points of each pair
+        // only differing in double precision.
+        let ulp = f64::from(f32::EPSILON);
+        let positions = [1.0, 1.0 + 1e-12, 1.0 + ulp, 1.0 + ulp + 1e-12];
+        let points: Vec<PointND<1>> = positions.iter().map(|x| PointND::from([*x])).collect();
+        let weights = [1.0, 1.0, 1.0, 3.0];
+        let mut partition = [0; 4];
+
+        let tree = Rcb {
+            iter_count: 1,
+            ..Default::default()
+        }
+        .partition(
+            &mut partition,
+            (points.par_iter().cloned(), weights.par_iter().cloned()),
+        )
+        .unwrap();
+
+        // The balanced cut would split the second pair, which single
+        // precision cannot tell apart.
+        assert_eq!(partition, [0, 0, 1, 1]);
+        for (point, part) in points.iter().zip(&partition) {
+            assert_eq!(tree.locate(point), *part);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_rcb_tree_serde() {
+        let points = gen_point_sample();
+        let weights = [1.0; 7];
+        let mut partition = [0; 7];
+
+        let tree = rcb(
+            &mut partition,
+            points,
+            weights,
+            3,
+            0.05,
+            PartNumbering::Binary,
+            None,
+        )
+        .unwrap();
+
+        let json = serde_json::to_string(&tree).unwrap();
+        let deserialized: BisectionTree = serde_json::from_str(&json).unwrap();
+        assert_eq!(tree, deserialized);
+
+        let json = r#"{
+            "split": {
+                "coord": 0,
+                "position": 0.5,
+                "left": { "leaf": { "part": 0 } },
+                "right": { "leaf": { "part": 1 } }
+            }
+        }"#;
+        let tree: BisectionTree = serde_json::from_str(json).unwrap();
+        assert_eq!(tree.locate(&Point2D::new(0.2, 4.0)), 0);
+        assert_eq!(tree.locate(&Point2D::new(0.5, 4.0)), 1);
+    }
+
+    #[test]
+    fn test_rib_axis_aligned() {
+        use crate::Partition as _;
+
+        // A 16x4 grid, longer along x.
+        let points: Vec<Point2D> = (0..64)
+            .map(|i| Point2D::new((i % 16) as f64 * 0.1, (i / 16) as f64 * 0.1))
+            .collect();
+        let weights = vec![1.0; points.len()];
+
+        let mut rcb_partition = vec![0; points.len()];
+        Rcb {
+            iter_count: 3,
+            ..Default::default()
+        }
+        .partition(
+            &mut rcb_partition,
+            (points.par_iter().cloned(), weights.par_iter().cloned()),
+        )
+        .unwrap();
+
+        let mut rib_partition = vec![0; points.len()];
+        let metadata = Rib {
+            iter_count: 3,
+            ..Default::default()
+        }
+        .partition(&mut rib_partition, (&points, weights.par_iter().cloned()))
+        .unwrap();
+
+        assert_eq!(metadata.axes, Matrix::<2>::identity());
+        assert_eq!(rib_partition, rcb_partition);
+
+        // The same grid, longer along y: coordinates are swapped.
+        let points: Vec<Point2D> = points.iter().map(|p| Point2D::new(p.y, p.x)).collect();
+        let mut swapped_partition = vec![0; points.len()];
+        let metadata = Rib {
+            iter_count: 3,
+            ..Default::default()
+        }
+        .partition(
+            &mut swapped_partition,
+            (&points, weights.par_iter().cloned()),
+        )
+        .unwrap();
+
+        assert_eq!(metadata.axes, Matrix::<2>::from([[0.0, 1.0], [1.0, 0.0]]));
+        assert_eq!(swapped_partition, rcb_partition);
+        for (point, part) in points.iter().zip(&swapped_partition) {
+            assert_eq!(metadata.locate(point), *part);
+        }
+    }
+
+    #[test]
+    fn test_rib_is_rcb_after_rotation() {
+        use crate::Partition as _;
+
+        // A grid tilted by 30 degrees.
+        let rotation = nalgebra::Rotation2::new(std::f64::consts::FRAC_PI_6);
+        let points: Vec<Point2D> = (0..128)
+            .map(|i| rotation * Point2D::new((i % 16) as f64, (i / 16) as f64))
+            .collect();
+        let weights = vec![1.0; points.len()];
+
+        let mut rib_partition = vec![0; points.len()];
+        let metadata = Rib {
+            iter_count: 4,
+            ..Default::default()
+        }
+        .partition(&mut rib_partition, (&points, weights.par_iter().cloned()))
+        .unwrap();
+
+        // The rotation is only computed at the top level: every level below
+        // is cut along the axes of the same basis, like RCB would do on
+        // rotated points.
+        let obb_to_aabb = metadata.axes.transpose();
+        let rotated = crate::affine::rotate(&points, &obb_to_aabb);
+        let mut rcb_partition = vec![0; points.len()];
+        Rcb {
+            iter_count: 4,
+            ..Default::default()
+        }
+        .partition(
+            &mut rcb_partition,
+            (rotated.par_iter().cloned(), weights.par_iter().cloned()),
+        )
+        .unwrap();
+
+        assert!(crate::partition_equivalent(&rib_partition, &rcb_partition));
+    }
+
+    #[test]
+    fn test_rib_axes() {
+        // An elongated cloud, along a direction 30 degrees above the x axis.
+        let angle = std::f64::consts::FRAC_PI_6;
+        let direction = Point2D::new(angle.cos(), angle.sin());
+        let normal = Point2D::new(-angle.sin(), angle.cos());
+        let points: Vec<Point2D> = (0..200)
+            .map(|i| {
+                let along = i as f64 - 100.0;
+                let across = ((i * 7) % 5) as f64 * 0.5 - 1.0;
+                Point2D::new(3.0, -2.0) + along * direction + across * normal
+            })
+            .collect();
+        let weights = vec![1.0; points.len()];
+        let mut partition = vec![0; points.len()];
+
+        let metadata = rib(&mut partition, &points, weights, 3, 0.05, false, 1).unwrap();
+
+        let inertia_axis = metadata.axes.column(0);
+        assert!(inertia_axis.dot(&direction).abs() > 0.999);
+        assert!((metadata.axes.transpose() * metadata.axes - Matrix::identity()).norm() < 1e-12);
+
+        for (point, part) in points.iter().zip(partition) {
+            assert_eq!(metadata.locate(point), part);
+        }
+    }
+
+    #[test]
+    fn test_rib_weighted_inertia() {
+        use crate::Partition as _;
+
+        // Light points along a line, tilted by 30 degrees, and two heavy
+        // points on each side of it.
+        let (sin, cos) = f64::sin_cos(std::f64::consts::FRAC_PI_6);
+        let along = Point2D::new(cos, sin);
+        let across = Point2D::new(-sin, cos);
+        let mut points: Vec<Point2D> = (-5..=5).map(|i| along * i as f64).collect();
+        let mut weights = vec![1.0; points.len()];
+        points.extend([across * 3.0, across * -3.0]);
+        weights.extend([100.0, 100.0]);
+
+        let inertia_axis = |uniform_inertia| {
+            let mut partition = vec![0; points.len()];
+            let metadata = Rib {
+                iter_count: 1,
+                uniform_inertia,
+                ..Default::default()
+            }
+            .partition(&mut partition, (&*points, weights.par_iter().cloned()))
+            .unwrap();
+            Point2D::from(metadata.axes.column(0))
+        };
+
+        assert!(0.99 < f64::abs(inertia_axis(true).dot(&along)));
+        assert!(0.99 < f64::abs(inertia_axis(false).dot(&across)));
+    }
+
+    #[test]
+    fn test_rib_inertia_levels() {
+        use crate::Partition as _;
+
+        // Two thick arms, 60 and 120 degrees above the x axis, in a V shape.
+        let points: Vec<Point2D> = [
+            std::f64::consts::FRAC_PI_3,
+            2.0 * std::f64::consts::FRAC_PI_3,
+        ]
+        .into_iter()
+        .flat_map(|angle| {
+            let (sin, cos) = f64::sin_cos(angle);
+            let along = Point2D::new(cos, sin);
+            let across = Point2D::new(-sin, cos);
+            (0..1000).map(move |i| along * (i / 10) as f64 + across * ((i % 10) as f64 - 4.5))
+        })
+        .collect();
+        let weights = vec![1.0; points.len()];
+        let iter_count = 4;
+
+        let run = |inertia_levels| {
+            let mut partition = vec![0; points.len()];
+            let metadata = Rib {
+                iter_count,
+                tolerance: 0.05,
+                inertia_levels,
+                ..Default::default()
+            }
+            .partition(&mut partition, (&*points, weights.par_iter().cloned()))
+            .unwrap();
+            for (point, part) in points.iter().zip(&partition) {
+                assert_eq!(metadata.locate(point), *part);
+            }
+            (partition, metadata)
+        };
+
+        // The number of inertia computations.
+        fn basis_count(metadata: &RibMetadata<2>) -> usize {
+            match &metadata.halves {
+                Some(halves) => 1 + halves.iter().map(basis_count).sum::<usize>(),
+                None => 1,
+            }
+        }
+
+        // The squared distance of points to the center of their part.
+        let spread = |partition: &[usize]| -> f64 {
+            let part_count = 1 << iter_count;
+            let centers = crate::algorithms::utils::part_centers(partition, &points, part_count);
+            points
+                .iter()
+                .zip(partition)
+                .map(|(point, part)| (point - centers[*part].unwrap()).norm_squared())
+                .sum()
+        };
+
+        let (rcb_like, metadata) = run(0);
+        assert_eq!(metadata.axes, Matrix::<2>::identity());
+        let mut rcb_partition = vec![0; points.len()];
+        Rcb {
+            iter_count,
+            tolerance: 0.05,
+            ..Default::default()
+        }
+        .partition(
+            &mut rcb_partition,
+            (points.par_iter().cloned(), weights.par_iter().cloned()),
+        )
+        .unwrap();
+        assert_eq!(rcb_like, rcb_partition);
+
+        let (top_level, metadata) = run(1);
+        assert_eq!(basis_count(&metadata), 1);
+
+        let (full, metadata) = run(iter_count);
+        assert_eq!(basis_count(&metadata), (1 << iter_count) - 1);
+        assert_eq!(run(usize::MAX).0, full);
+
+        // Computing the inertia of every part costs more, but the cuts follow
+        // the orientation of each arm and parts are more compact.
+        assert!(spread(&top_level) <= spread(&rcb_like));
+        assert!(spread(&full) < 0.8 * spread(&top_level));
+    }
 }
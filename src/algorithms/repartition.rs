@@ -0,0 +1,240 @@
+use super::utils::load_imbalance;
+use super::utils::part_centers;
+use super::utils::part_loads;
+use crate::geometry::PointND;
+
+use std::borrow::Cow;
+
+/// Diagnostic data for a [Repartition] run.
+#[non_exhaustive]
+#[derive(Debug, Default, Clone)]
+pub struct Metadata {
+    /// Number of rounds that have been run.
+    pub iterations: usize,
+
+    /// Number of elements that changed part.
+    pub migrated: usize,
+
+    /// Imbalance of the output partition.
+    pub imbalance: f64,
+}
+
+fn repartition<const D: usize>(
+    partition: &mut [usize],
+    points: &[PointND<D>],
+    weights: &[f64],
+    part_count: usize,
+    tolerance: f64,
+    max_iter: usize,
+) -> Metadata {
+    let mut metadata = Metadata::default();
+    let mut loads = part_loads(partition, weights, part_count);
+    let ideal = loads.iter().sum::<f64>() / part_count as f64;
+
+    for _ in 0..max_iter {
+        if load_imbalance(&loads) <= tolerance {
+            break;
+        }
+        metadata.iterations += 1;
+
+        let by_load = |p: &usize, q: &usize| f64::total_cmp(&loads[*p], &loads[*q]);
+        let heaviest = (0..part_count).max_by(by_load).unwrap();
+        let lightest = (0..part_count).min_by(by_load).unwrap();
+
+        // Elements of the heaviest part, those that are the closest to the
+        // lightest part (relative to their own part) first.
+        let centers = part_centers(partition, points, part_count);
+        let source_center = centers[heaviest].unwrap();
+        let distance_increase = |point: &PointND<D>| {
+            let to_source = (point - source_center).norm();
+            match centers[lightest] {
+                Some(target_center) => (point - target_center).norm() - to_source,
+                None => -to_source,
+            }
+        };
+        let mut candidates: Vec<(f64, usize)> = (0..partition.len())
+            .filter(|element| partition[*element] == heaviest)
+            .map(|element| (distance_increase(&points[element]), element))
+            .collect();
+        candidates.sort_by(|(d1, e1), (d2, e2)| f64::total_cmp(d1, d2).then(e1.cmp(e2)));
+
+        // Only move elements while it makes the pair of parts more balanced.
+        let mut moves = 0;
+        for (_, element) in candidates {
+            if loads[heaviest] <= ideal || ideal <= loads[lightest] {
+                break;
+            }
+            let weight = weights[element];
+            if loads[heaviest] - weight < loads[lightest] + weight {
+                continue;
+            }
+            partition[element] = lightest;
+            loads[heaviest] -= weight;
+            loads[lightest] += weight;
+            moves += 1;
+        }
+        if moves == 0 {
+            break;
+        }
+    }
+
+    metadata.imbalance = load_imbalance(&loads);
+    metadata
+}
+
+/// # Incremental repartitioning
+///
+/// An improvement algorithm that re-balances an existing partition while
+/// moving as few elements as possible.  It is meant to be used when a mesh
+/// changes slightly between two steps of a simulation, where computing a new
+/// partition from scratch would migrate most elements between processes.
+///
+/// The input partition is left untouched as long as its imbalance is within
+/// `tolerance`.  Otherwise, elements are moved one round at a time, from the
+/// heaviest part to the lightest one, until the imbalance is within
+/// `tolerance`.  Moved elements are chosen according to their position, so
+/// that parts stay compact: those that are the closest to the lightest part,
+/// relative to the center of their own part, are moved first.
+///
+/// The number of elements that changed part can be measured with
+/// [`analysis::migration_cost`][crate::analysis::migration_cost].
+///
+/// # Example
+///
+/// ```rust
+/// # fn main() -> Result<(), coupe::Error> {
+/// use coupe::Partition as _;
+/// use coupe::Point2D;
+///
+/// let points: Vec<Point2D> = (0..8).map(|i| Point2D::new(i as f64, 0.0)).collect();
+/// let weights = [1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 3.0];
+/// let previous = [0, 0, 0, 0, 1, 1, 1, 1];
+/// let mut partition = previous;
+///
+/// coupe::Repartition { tolerance: 0.0, ..Default::default() }
+///     .partition(&mut partition, (&points, &weights))?;
+///
+/// assert_eq!(partition, [0, 0, 0, 0, 0, 1, 1, 1]);
+/// assert_eq!(coupe::analysis::migration_cost(&previous, &partition), 1);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Repartition {
+    /// The algorithm stops as soon as the imbalance of the partition is below
+    /// this value.
+    pub tolerance: f64,
+
+    /// Maximum number of rounds.
+    pub max_iter: usize,
+}
+
+impl Default for Repartition {
+    fn default() -> Self {
+        Self {
+            tolerance: 0.05,
+            max_iter: 64,
+        }
+    }
+}
+
+impl crate::Name for Repartition {
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed("repartition")
+    }
+}
+
+impl<'a, const D: usize> crate::Partition<(&'a [PointND<D>], &'a [f64])> for Repartition {
+    type Metadata = Metadata;
+    type Error = crate::Error;
+
+    fn partition(
+        &mut self,
+        part_ids: &mut [usize],
+        (points, weights): (&'a [PointND<D>], &'a [f64]),
+    ) -> Result<Self::Metadata, Self::Error> {
+        if part_ids.len() != points.len() {
+            return Err(crate::Error::InputLenMismatch {
+                expected: part_ids.len(),
+                actual: points.len(),
+            });
+        }
+        if part_ids.len() != weights.len() {
+            return Err(crate::Error::InputLenMismatch {
+                expected: part_ids.len(),
+                actual: weights.len(),
+            });
+        }
+        if weights.iter().any(|weight| *weight < 0.0) {
+            return Err(crate::Error::NegativeValues);
+        }
+        super::validate_points(points)?;
+        let part_count = match part_ids.iter().max() {
+            Some(max) => max + 1,
+            None => return Ok(Metadata::default()),
+        };
+        let previous = part_ids.to_vec();
+        let mut metadata = repartition(
+            part_ids,
+            points,
+            weights,
+            part_count,
+            self.tolerance,
+            self.max_iter,
+        );
+        metadata.migrated = crate::analysis::migration_cost(&previous, part_ids);
+        Ok(metadata)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Partition as _;
+    use crate::Point2D;
+
+    #[test]
+    fn test_small_perturbation() {
+        // A 20x20 grid cut in 4 vertical strips.
+        let points: Vec<Point2D> = (0..400)
+            .map(|i| Point2D::new((i % 20) as f64, (i / 20) as f64))
+            .collect();
+        let previous: Vec<usize> = (0..400).map(|i| (i % 20) / 5).collect();
+
+        // Some elements of the first strip get heavier.
+        let mut weights = vec![1.0; 400];
+        for weight in weights.iter_mut().step_by(20).take(10) {
+            *weight = 2.0;
+        }
+
+        let mut partition = previous.clone();
+        let mut algo = Repartition {
+            tolerance: 0.01,
+            ..Default::default()
+        };
+        let metadata = algo.partition(&mut partition, (&points, &weights)).unwrap();
+
+        assert!(metadata.imbalance <= algo.tolerance, "{metadata:?}");
+        assert!(metadata.migrated <= 10, "{metadata:?}");
+        assert_eq!(
+            metadata.migrated,
+            crate::analysis::migration_cost(&previous, &partition),
+        );
+    }
+
+    #[test]
+    fn test_balanced_input_is_untouched() {
+        let points: Vec<Point2D> = (0..16).map(|i| Point2D::new(i as f64, 0.0)).collect();
+        let weights = [1.0; 16];
+        let mut partition: Vec<usize> = (0..16).map(|i| i % 4).collect();
+        let expected = partition.clone();
+
+        let metadata = Repartition::default()
+            .partition(&mut partition, (&points, &weights))
+            .unwrap();
+
+        assert_eq!(partition, expected);
+        assert_eq!(metadata.iterations, 0);
+        assert_eq!(metadata.migrated, 0);
+    }
+}
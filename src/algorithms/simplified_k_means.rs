@@ -0,0 +1,266 @@
+//! An implementation of Lloyd's k-means algorithm, without balancing.
+
+use crate::PointND;
+use rayon::prelude::*;
+use std::borrow::Cow;
+
+/// The index of the center closest to `point`.
+fn closest_center<const D: usize>(centers: &[PointND<D>], point: &PointND<D>) -> usize {
+    centers
+        .iter()
+        .map(|center| (center - point).norm_squared())
+        .enumerate()
+        .min_by(|(_, a), (_, b)| f64::total_cmp(a, b))
+        .map(|(i, _)| i)
+        .unwrap()
+}
+
+/// Picks `part_count` points as initial centers, each one being the point
+/// farthest from the centers picked before it.  The first one is the point
+/// closest to the weighted centroid of all points.
+fn farthest_first_centers<const D: usize>(
+    points: &[PointND<D>],
+    weights: &[f64],
+    part_count: usize,
+) -> Vec<PointND<D>> {
+    let total_weight: f64 = weights.par_iter().sum();
+    let centroid = if total_weight > 0.0 {
+        crate::weighted_center(points, weights)
+    } else {
+        crate::center(points)
+    };
+    let first = closest_center(points, &centroid);
+
+    let mut centers = vec![points[first]];
+    let mut distances: Vec<f64> = points
+        .par_iter()
+        .map(|point| (point - points[first]).norm_squared())
+        .collect();
+    while centers.len() < part_count {
+        let (farthest, _) = distances
+            .par_iter()
+            .enumerate()
+            .max_by(|(i, a), (j, b)| f64::total_cmp(a, b).then(j.cmp(i)))
+            .unwrap();
+        let center = points[farthest];
+        centers.push(center);
+        distances
+            .par_iter_mut()
+            .zip(points)
+            .for_each(|(distance, point)| {
+                *distance = f64::min(*distance, (point - center).norm_squared());
+            });
+    }
+    centers
+}
+
+/// # Simplified k-means algorithm
+///
+/// Lloyd's k-means algorithm: points are assigned to their closest center,
+/// and centers are moved to the weighted centroid of their points, until
+/// assignments stop changing or `max_iter` iterations have been done.
+///
+/// Unlike [KMeans][crate::KMeans], this algorithm needs no initial partition
+/// and does not try to balance part weights, so it is simpler and faster when
+/// parts need to follow the clusters of the input rather than to be of equal
+/// weights.  Initial centers are chosen deterministically, each one being the
+/// point farthest from the previous ones, which picks outliers first on noisy
+/// inputs.  Both assignment and update steps run in parallel.
+///
+/// The number of iterations done is returned as metadata.
+///
+/// # Example
+///
+/// ```rust
+/// # fn main() -> Result<(), coupe::Error> {
+/// use coupe::Partition as _;
+/// use coupe::Point2D;
+///
+/// let points = [
+///     Point2D::new(0., 0.),
+///     Point2D::new(1., 0.),
+///     Point2D::new(9., 9.),
+///     Point2D::new(10., 9.),
+/// ];
+/// let weights = [1.; 4];
+/// let mut partition = [0; 4];
+///
+/// coupe::SimplifiedKMeans { part_count: 2, ..Default::default() }
+///     .partition(&mut partition, (&points, &weights))?;
+///
+/// assert_eq!(partition[0], partition[1]);
+/// assert_eq!(partition[2], partition[3]);
+/// assert_ne!(partition[0], partition[2]);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct SimplifiedKMeans {
+    pub part_count: usize,
+
+    /// Maximum number of assignment and update steps.
+    pub max_iter: usize,
+}
+
+impl Default for SimplifiedKMeans {
+    fn default() -> Self {
+        Self {
+            part_count: 2,
+            max_iter: 100,
+        }
+    }
+}
+
+impl crate::PartCount for SimplifiedKMeans {
+    fn expected_part_count(&self) -> Option<usize> {
+        Some(self.part_count)
+    }
+}
+
+impl crate::Name for SimplifiedKMeans {
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed("simplified_k_means")
+    }
+}
+
+impl<'a, const D: usize> crate::Partition<(&'a [PointND<D>], &'a [f64])> for SimplifiedKMeans {
+    type Metadata = usize;
+    type Error = crate::Error;
+
+    fn partition(
+        &mut self,
+        part_ids: &mut [usize],
+        (points, weights): (&'a [PointND<D>], &'a [f64]),
+    ) -> Result<Self::Metadata, Self::Error> {
+        if part_ids.len() != points.len() {
+            return Err(crate::Error::InputLenMismatch {
+                expected: part_ids.len(),
+                actual: points.len(),
+            });
+        }
+        if part_ids.len() != weights.len() {
+            return Err(crate::Error::InputLenMismatch {
+                expected: part_ids.len(),
+                actual: weights.len(),
+            });
+        }
+        super::validate_points(points)?;
+        if points.is_empty() || self.part_count == 0 {
+            return Ok(0);
+        }
+
+        let part_count = usize::min(self.part_count, points.len());
+        let mut centers = farthest_first_centers(points, weights, part_count);
+        part_ids
+            .par_iter_mut()
+            .zip(points)
+            .for_each(|(part, point)| *part = closest_center(&centers, point));
+
+        for iter in 0..self.max_iter {
+            let (sums, part_weights) = part_ids
+                .par_iter()
+                .zip(points)
+                .zip(weights)
+                .fold(
+                    || {
+                        (
+                            vec![PointND::<D>::zeros(); part_count],
+                            vec![0.0; part_count],
+                        )
+                    },
+                    |(mut sums, mut part_weights), ((part, point), weight)| {
+                        sums[*part] += point * *weight;
+                        part_weights[*part] += weight;
+                        (sums, part_weights)
+                    },
+                )
+                .reduce(
+                    || {
+                        (
+                            vec![PointND::<D>::zeros(); part_count],
+                            vec![0.0; part_count],
+                        )
+                    },
+                    |(mut sums0, mut weights0), (sums1, weights1)| {
+                        for (s0, s1) in sums0.iter_mut().zip(sums1) {
+                            *s0 += s1;
+                        }
+                        for (w0, w1) in weights0.iter_mut().zip(weights1) {
+                            *w0 += w1;
+                        }
+                        (sums0, weights0)
+                    },
+                );
+            for ((center, sum), part_weight) in centers.iter_mut().zip(sums).zip(part_weights) {
+                // Parts with no weight keep their center.
+                if part_weight > 0.0 {
+                    *center = sum / part_weight;
+                }
+            }
+
+            let changed = part_ids
+                .par_iter_mut()
+                .zip(points)
+                .map(|(part, point)| {
+                    let closest = closest_center(&centers, point);
+                    let changed = closest != *part;
+                    *part = closest;
+                    changed
+                })
+                .reduce(|| false, |a, b| a || b);
+            if !changed {
+                return Ok(iter + 1);
+            }
+        }
+
+        Ok(self.max_iter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Partition as _;
+    use crate::Point2D;
+
+    #[test]
+    fn test_three_blobs() {
+        let blob_centers = [
+            Point2D::new(0.0, 0.0),
+            Point2D::new(20.0, 5.0),
+            Point2D::new(8.0, 30.0),
+        ];
+        let points: Vec<Point2D> = (0..300)
+            .map(|i| {
+                let offset = Point2D::new((i * 7 % 11) as f64, (i * 13 % 9) as f64) * 0.3;
+                blob_centers[i % 3] + offset
+            })
+            .collect();
+        let weights = vec![1.0; points.len()];
+        let mut partition = vec![usize::MAX; points.len()];
+
+        let mut algo = SimplifiedKMeans {
+            part_count: 3,
+            max_iter: 50,
+        };
+        let iter_count = algo.partition(&mut partition, (&points, &weights)).unwrap();
+        assert!(iter_count < algo.max_iter, "did not converge");
+
+        let expected: Vec<usize> = (0..points.len()).map(|i| i % 3).collect();
+        assert!(crate::partition_equivalent(&partition, &expected));
+    }
+
+    #[test]
+    fn test_fewer_points_than_parts() {
+        let points = [Point2D::new(0.0, 0.0), Point2D::new(1.0, 0.0)];
+        let mut partition = [usize::MAX; 2];
+        SimplifiedKMeans {
+            part_count: 4,
+            ..Default::default()
+        }
+        .partition(&mut partition, (&points, &[1.0; 2]))
+        .unwrap();
+        assert_ne!(partition[0], partition[1]);
+        assert!(partition.iter().all(|part| *part < 4));
+    }
+}
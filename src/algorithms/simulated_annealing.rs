@@ -0,0 +1,300 @@
+use crate::topology::Topology;
+use rand::rngs::StdRng;
+use rand::Rng as _;
+use rand::SeedableRng as _;
+
+use std::borrow::Cow;
+
+/// Diagnostic data for a [SimulatedAnnealing] run.
+#[non_exhaustive]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Metadata {
+    /// Number of proposed moves that have been accepted.
+    pub accepted_moves: usize,
+
+    /// Number of accepted moves that made the partition worse.
+    pub uphill_moves: usize,
+
+    /// Number of accepted moves that have been reverted at the end of the run,
+    /// to return the best partition found.
+    pub reverted_moves: usize,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn simulated_annealing<T>(
+    partition: &mut [usize],
+    weights: &[f64],
+    adjacency: T,
+    part_count: usize,
+    initial_temp: f64,
+    cooling_rate: f64,
+    max_iter: usize,
+    balance_penalty: f64,
+    seed: Option<u64>,
+) -> Metadata
+where
+    T: Topology<i64>,
+{
+    let mut rng = match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    let mut metadata = Metadata::default();
+
+    let mut loads = vec![0.0; part_count];
+    for (part, weight) in partition.iter().zip(weights) {
+        loads[*part] += weight;
+    }
+    let ideal_load = loads.iter().sum::<f64>() / part_count as f64;
+    if ideal_load == 0.0 {
+        return metadata;
+    }
+
+    // The energy of a partition is its edge cut, plus a penalty on the squared
+    // distance between each part's load and the ideal load.  Only energy
+    // differences are tracked.
+    let mut energy = 0.0;
+    let mut best_energy = 0.0;
+    let mut moves_since_best: Vec<(usize, usize)> = Vec::new();
+
+    let mut temperature = initial_temp;
+    let mut neighbor_parts = Vec::new();
+    for _ in 0..max_iter {
+        temperature *= cooling_rate;
+
+        // Only boundary vertices are moved, to a part one of their neighbors
+        // is in.
+        let vertex = rng.gen_range(0..partition.len());
+        let source = partition[vertex];
+        neighbor_parts.clear();
+        neighbor_parts.extend(
+            adjacency
+                .neighbors(vertex)
+                .map(|(neighbor, _)| partition[neighbor])
+                .filter(|part| *part != source),
+        );
+        if neighbor_parts.is_empty() {
+            continue;
+        }
+        let target = neighbor_parts[rng.gen_range(0..neighbor_parts.len())];
+
+        let mut cut_delta = 0;
+        for (neighbor, edge_weight) in adjacency.neighbors(vertex) {
+            if neighbor == vertex {
+                continue;
+            }
+            if partition[neighbor] == source {
+                cut_delta += edge_weight;
+            } else if partition[neighbor] == target {
+                cut_delta -= edge_weight;
+            }
+        }
+        let weight = weights[vertex];
+        let balance_delta = 2.0 * weight * (weight + loads[target] - loads[source]) / ideal_load;
+        let delta = cut_delta as f64 + balance_penalty * balance_delta;
+
+        // Metropolis criterion.
+        let accept = delta <= 0.0
+            || (0.0 < temperature && rng.gen::<f64>() < f64::exp(-delta / temperature));
+        if !accept {
+            continue;
+        }
+
+        partition[vertex] = target;
+        loads[source] -= weight;
+        loads[target] += weight;
+        energy += delta;
+        metadata.accepted_moves += 1;
+        if 0.0 < delta {
+            metadata.uphill_moves += 1;
+        }
+        moves_since_best.push((vertex, source));
+        if energy < best_energy {
+            best_energy = energy;
+            moves_since_best.clear();
+        }
+    }
+
+    // Go back to the best partition that has been seen.
+    metadata.reverted_moves = moves_since_best.len();
+    for (vertex, source) in moves_since_best.into_iter().rev() {
+        partition[vertex] = source;
+    }
+
+    metadata
+}
+
+/// # Simulated annealing
+///
+/// A partition improving algorithm that minimizes the edge cut of a partition,
+/// while keeping its parts balanced.
+///
+/// At each iteration, a random vertex that has a neighbor in another part is
+/// proposed to move to that part.  Moves that decrease the energy of the
+/// partition are always accepted, while moves that increase it by `delta` are
+/// accepted with probability `exp(-delta / temperature)` (Metropolis
+/// criterion).  The temperature starts at `initial_temp` and is multiplied by
+/// `cooling_rate` at each iteration.
+///
+/// The energy of a partition is its edge cut, plus `balance_penalty` times the
+/// sum over parts of `(load - ideal_load)² / ideal_load`.
+///
+/// Since worsening moves are accepted, the algorithm can escape the local
+/// minima where greedy algorithms like [Fiduccia-Mattheyses][crate::FiducciaMattheyses]
+/// or [Kernighan-Lin][crate::KernighanLin] get stuck.  The best partition
+/// found during the run is returned.  Unlike these algorithms, it works with
+/// any number of parts.
+///
+/// # Example
+///
+/// ```rust
+/// # fn main() -> Result<(), coupe::Error> {
+/// use coupe::Partition as _;
+/// use coupe::Topology as _;
+/// use coupe::sprs::CsMat;
+///
+/// //    0   1   2   3   4   5
+/// //    +---+---+---+---+---+
+/// let mut adjacency = CsMat::empty(coupe::sprs::CSR, 6);
+/// for i in 0..5 {
+///     adjacency.insert(i, i + 1, 1);
+///     adjacency.insert(i + 1, i, 1);
+/// }
+/// let weights = [1.0; 6];
+/// let mut partition = [0, 1, 0, 1, 0, 1];
+///
+/// coupe::SimulatedAnnealing::default()
+///     .partition(&mut partition, (adjacency.view(), &weights))?;
+///
+/// assert_eq!(adjacency.view().edge_cut(&partition), 1);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Reference
+///
+/// Kirkpatrick, S., Gelatt, C. D., Vecchi, M. P., 1983. Optimization by
+/// simulated annealing. *Science*, 220(4598):671–680.
+/// <doi:10.1126/science.220.4598.671>.
+#[derive(Debug, Clone, Copy)]
+pub struct SimulatedAnnealing {
+    /// The temperature at the start of the run.  Higher temperatures make
+    /// worsening moves more likely to be accepted.
+    pub initial_temp: f64,
+
+    /// The factor applied to the temperature after each iteration, between
+    /// zero and one.
+    pub cooling_rate: f64,
+
+    /// The number of proposed moves.
+    pub max_iter: usize,
+
+    /// The weight of the balance term in the energy, relative to the edge cut.
+    pub balance_penalty: f64,
+
+    /// Seed of the random number generator, or `None` for a random seed.
+    pub seed: Option<u64>,
+}
+
+impl Default for SimulatedAnnealing {
+    fn default() -> Self {
+        Self {
+            initial_temp: 2.0,
+            cooling_rate: 0.999,
+            max_iter: 10_000,
+            balance_penalty: 1.0,
+            seed: Some(0),
+        }
+    }
+}
+
+impl crate::Name for SimulatedAnnealing {
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed("simulated_annealing")
+    }
+}
+
+impl<'a, T> crate::Partition<(T, &'a [f64])> for SimulatedAnnealing
+where
+    T: Topology<i64>,
+{
+    type Metadata = Metadata;
+    type Error = crate::Error;
+
+    fn partition(
+        &mut self,
+        part_ids: &mut [usize],
+        (adjacency, weights): (T, &'a [f64]),
+    ) -> Result<Self::Metadata, Self::Error> {
+        if part_ids.len() != weights.len() {
+            return Err(crate::Error::InputLenMismatch {
+                expected: part_ids.len(),
+                actual: weights.len(),
+            });
+        }
+        if part_ids.len() != adjacency.len() {
+            return Err(crate::Error::InputLenMismatch {
+                expected: part_ids.len(),
+                actual: adjacency.len(),
+            });
+        }
+        if weights.iter().any(|weight| *weight < 0.0) {
+            return Err(crate::Error::NegativeValues);
+        }
+        let part_count = match part_ids.iter().max() {
+            Some(max) => max + 1,
+            None => return Ok(Metadata::default()),
+        };
+        Ok(simulated_annealing(
+            part_ids,
+            weights,
+            adjacency,
+            part_count,
+            self.initial_temp,
+            self.cooling_rate,
+            self.max_iter,
+            self.balance_penalty,
+            self.seed,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FiducciaMattheyses;
+    use crate::Grid;
+    use crate::Partition as _;
+    use std::num::NonZeroUsize;
+
+    #[test]
+    fn test_beats_fm() {
+        // An 8x8 grid, cut in vertical strips of width 2 that alternate
+        // between both parts.  The optimal bisection has a cut of 8, but a
+        // single pass of FM is stuck with the strips.
+        let side = NonZeroUsize::new(8).unwrap();
+        let grid = Grid::new_2d(side, side);
+        let weights = [1.0; 64];
+        let initial: Vec<usize> = (0..64).map(|i| (i % 8 / 2) % 2).collect();
+
+        let mut fm_partition = initial.clone();
+        FiducciaMattheyses {
+            max_passes: Some(1),
+            max_imbalance: Some(0.05),
+            ..Default::default()
+        }
+        .partition(&mut fm_partition, (grid, &weights[..]))
+        .unwrap();
+
+        let mut sa_partition = initial;
+        SimulatedAnnealing::default()
+            .partition(&mut sa_partition, (grid, &weights[..]))
+            .unwrap();
+
+        let fm_cut: i64 = grid.edge_cut(&fm_partition);
+        let sa_cut: i64 = grid.edge_cut(&sa_partition);
+        assert!(sa_cut < fm_cut, "SA: {sa_cut}, FM: {fm_cut}");
+        assert_eq!(sa_cut, 8);
+        assert_eq!(sa_partition.iter().filter(|part| **part == 0).count(), 32);
+    }
+}
@@ -0,0 +1,459 @@
+use crate::topology::Topology;
+use nalgebra::DMatrix;
+use nalgebra::SymmetricEigen;
+use rand::rngs::StdRng;
+use rand::Rng as _;
+use rand::SeedableRng as _;
+use rayon::prelude::*;
+use std::borrow::Cow;
+
+/// Marks vertices that are not in the subgraph being bisected.
+const NOT_IN_SUBGRAPH: usize = usize::MAX;
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.par_iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+/// Subtracts `factor * b` from `a`.
+fn sub_scaled(a: &mut [f64], factor: f64, b: &[f64]) {
+    a.par_iter_mut().zip(b).for_each(|(a, b)| *a -= factor * b);
+}
+
+/// Makes the coordinates of `v` sum to zero, that is, makes `v` orthogonal to
+/// the kernel of the Laplacian of a connected graph.
+fn remove_mean(v: &mut [f64]) {
+    let mean = v.par_iter().sum::<f64>() / v.len() as f64;
+    v.par_iter_mut().for_each(|c| *c -= mean);
+}
+
+/// Approximates the Fiedler vector of the subgraph induced by `vertices`,
+/// that is, the eigenvector of its Laplacian associated with the
+/// second-smallest eigenvalue.
+///
+/// `max_iter` Lanczos iterations are done at most, but at least one, in the
+/// space orthogonal to the constant vector, with full reorthogonalization.
+/// The eigenvectors of the resulting tridiagonal matrix are then computed
+/// with nalgebra.
+///
+/// `local` maps the vertices of `vertices` to their index in it, and others
+/// to [NOT_IN_SUBGRAPH].
+fn fiedler_vector<T>(
+    adjacency: &T,
+    vertices: &[usize],
+    local: &[usize],
+    max_iter: usize,
+) -> Vec<f64>
+where
+    T: Topology<f64> + Sync,
+{
+    let n = vertices.len();
+    let laplacian = |x: &[f64]| -> Vec<f64> {
+        vertices
+            .par_iter()
+            .enumerate()
+            .map(|(i, vertex)| {
+                adjacency
+                    .neighbors(*vertex)
+                    .filter(|(neighbor, _)| neighbor != vertex)
+                    .filter_map(|(neighbor, edge_weight)| {
+                        let j = local[neighbor];
+                        (j != NOT_IN_SUBGRAPH).then(|| edge_weight * (x[i] - x[j]))
+                    })
+                    .sum()
+            })
+            .collect()
+    };
+
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut q: Vec<f64> = (0..n).map(|_| rng.gen::<f64>() - 0.5).collect();
+    remove_mean(&mut q);
+    let norm = f64::sqrt(dot(&q, &q));
+    q.par_iter_mut().for_each(|c| *c /= norm);
+
+    let mut basis: Vec<Vec<f64>> = Vec::new();
+    let mut alphas = Vec::new();
+    let mut betas: Vec<f64> = Vec::new();
+    let mut scale = 0.0;
+    for _ in 0..usize::clamp(max_iter, 1, n - 1) {
+        let mut w = laplacian(&q);
+        let alpha = dot(&w, &q);
+        sub_scaled(&mut w, alpha, &q);
+        if let (Some(beta), Some(previous)) = (betas.last(), basis.last()) {
+            sub_scaled(&mut w, *beta, previous);
+        }
+        // Rounding errors make Lanczos vectors lose orthogonality, hence
+        // the spectrum of the tridiagonal matrix gets duplicated eigenvalues.
+        for v in &basis {
+            let projection = dot(&w, v);
+            sub_scaled(&mut w, projection, v);
+        }
+        remove_mean(&mut w);
+
+        basis.push(q);
+        alphas.push(alpha);
+        scale = f64::max(scale, alpha.abs());
+        let beta = f64::sqrt(dot(&w, &w));
+        if beta <= 1e-12 * scale {
+            // The Krylov space is invariant, its eigenvectors are exact.
+            break;
+        }
+        betas.push(beta);
+        w.par_iter_mut().for_each(|c| *c /= beta);
+        q = w;
+    }
+
+    let m = alphas.len();
+    let tridiagonal = DMatrix::from_fn(m, m, |i, j| {
+        if i == j {
+            alphas[i]
+        } else if i == j + 1 {
+            betas[j]
+        } else if j == i + 1 {
+            betas[i]
+        } else {
+            0.0
+        }
+    });
+    let eigen = SymmetricEigen::new(tridiagonal);
+    let smallest = eigen.eigenvalues.argmin().0;
+    let ritz = eigen.eigenvectors.column(smallest);
+
+    let mut fiedler = vec![0.0; n];
+    for (v, y) in basis.iter().zip(ritz.iter()) {
+        sub_scaled(&mut fiedler, -y, v);
+    }
+    fiedler
+}
+
+/// Splits `vertices` in two halves of similar weights, ordered along the
+/// Fiedler vector of their subgraph.
+fn bisect<T>(
+    adjacency: &T,
+    weights: &[f64],
+    vertices: &mut [usize],
+    local: &mut [usize],
+    max_iter: usize,
+) -> usize
+where
+    T: Topology<f64> + Sync,
+{
+    for (i, vertex) in vertices.iter().enumerate() {
+        local[*vertex] = i;
+    }
+    let fiedler = fiedler_vector(adjacency, vertices, local, max_iter);
+    for vertex in vertices.iter() {
+        local[*vertex] = NOT_IN_SUBGRAPH;
+    }
+
+    let mut order: Vec<(f64, usize)> = fiedler.into_iter().zip(vertices.iter().cloned()).collect();
+    order.par_sort_unstable_by(|(a_value, a), (b_value, b)| {
+        f64::total_cmp(a_value, b_value).then(a.cmp(b))
+    });
+    for (vertex, (_, sorted)) in vertices.iter_mut().zip(order) {
+        *vertex = sorted;
+    }
+
+    // Cut where the weights on both sides are the closest, keeping at least
+    // one vertex on each side.
+    let total_weight: f64 = vertices.iter().map(|vertex| weights[*vertex]).sum();
+    let mut left_weight = 0.0;
+    let mut best = (f64::INFINITY, 1);
+    for (i, vertex) in vertices[..vertices.len() - 1].iter().enumerate() {
+        left_weight += weights[*vertex];
+        let difference = f64::abs(2.0 * left_weight - total_weight);
+        if difference < best.0 {
+            best = (difference, i + 1);
+        }
+    }
+    best.1
+}
+
+#[allow(clippy::too_many_arguments)]
+fn spectral_bisection_recurse<T>(
+    adjacency: &T,
+    weights: &[f64],
+    partition: &mut [usize],
+    vertices: &mut [usize],
+    local: &mut [usize],
+    iter_count: usize,
+    part: usize,
+    max_iter: usize,
+) where
+    T: Topology<f64> + Sync,
+{
+    if iter_count == 0 || vertices.len() <= 1 {
+        // Parts that cannot be split further take the lowest ID of their
+        // sub-tree.
+        for vertex in vertices.iter() {
+            partition[*vertex] = part << iter_count;
+        }
+        return;
+    }
+
+    let split = bisect(adjacency, weights, vertices, local, max_iter);
+    let (left, right) = vertices.split_at_mut(split);
+    spectral_bisection_recurse(
+        adjacency,
+        weights,
+        partition,
+        left,
+        local,
+        iter_count - 1,
+        2 * part,
+        max_iter,
+    );
+    spectral_bisection_recurse(
+        adjacency,
+        weights,
+        partition,
+        right,
+        local,
+        iter_count - 1,
+        2 * part + 1,
+        max_iter,
+    );
+}
+
+/// # Spectral bisection
+///
+/// A topologic algorithm that recursively splits the graph along its Fiedler
+/// vector, that is, the eigenvector of its Laplacian matrix associated with
+/// the second-smallest eigenvalue.
+///
+/// Sorting vertices by their value in the Fiedler vector puts strongly
+/// connected vertices next to each other, so splitting this order in two
+/// halves of similar weights gives a small edge cut on many graphs.  Each
+/// half is then bisected the same way, on its own subgraph, for a total of
+/// `2^iter_count` parts, numbered like those of [Rcb][crate::Rcb] with
+/// [binary numbering][crate::PartNumbering::Binary].
+///
+/// The Fiedler vector is approximated with the Lanczos algorithm, which is
+/// run for at most `max_iter` iterations.  Each iteration costs one product
+/// of the Laplacian matrix with a vector, and memory grows linearly with the
+/// number of iterations.
+///
+/// # Example
+///
+/// ```rust
+/// # fn main() -> Result<(), coupe::Error> {
+/// use coupe::Partition as _;
+/// use coupe::sprs::CsMat;
+///
+/// // Two triangles linked by one edge:
+/// //
+/// //  0        3
+/// //  | \    / |
+/// //  |  2--4  |
+/// //  | /    \ |
+/// //  1        5
+/// let mut adjacency = CsMat::empty(coupe::sprs::CSR, 6);
+/// for (a, b) in [(0, 1), (0, 2), (1, 2), (2, 4), (3, 4), (3, 5), (4, 5)] {
+///     adjacency.insert(a, b, 1.0);
+///     adjacency.insert(b, a, 1.0);
+/// }
+/// let weights = [1.0; 6];
+/// let mut partition = [0; 6];
+///
+/// coupe::SpectralBisection { iter_count: 1, ..Default::default() }
+///     .partition(&mut partition, (adjacency.view(), &weights))?;
+///
+/// assert_eq!(partition[0], partition[1]);
+/// assert_eq!(partition[0], partition[2]);
+/// assert_eq!(partition[3], partition[4]);
+/// assert_eq!(partition[3], partition[5]);
+/// assert_ne!(partition[0], partition[3]);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Reference
+///
+/// Pothen, A., Simon, H. D., Liou, K.-P., 1990. Partitioning sparse matrices
+/// with eigenvectors of graphs. *SIAM Journal on Matrix Analysis and
+/// Applications*, 11(3), pp. 430–452. <https://doi.org/10.1137/0611030>
+#[derive(Debug, Clone, Copy)]
+pub struct SpectralBisection {
+    /// The number of recursive bisections. This will yield a partition of at
+    /// most `2^iter_count` parts.
+    ///
+    /// Partitioning fails with [`Error::TooManyParts`][crate::Error::TooManyParts]
+    /// if `2^iter_count` does not fit in a `usize`.
+    pub iter_count: usize,
+
+    /// Maximum number of Lanczos iterations done to approximate each Fiedler
+    /// vector.  At least one iteration is done, even if this is zero.
+    pub max_iter: usize,
+}
+
+impl Default for SpectralBisection {
+    fn default() -> Self {
+        Self {
+            iter_count: 1,
+            max_iter: 100,
+        }
+    }
+}
+
+impl crate::PartCount for SpectralBisection {
+    fn expected_part_count(&self) -> Option<usize> {
+        u32::try_from(self.iter_count)
+            .ok()
+            .and_then(|iter_count| 1_usize.checked_shl(iter_count))
+    }
+}
+
+impl crate::Name for SpectralBisection {
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed("spectral_bisection")
+    }
+}
+
+impl<'a, T> crate::Partition<(T, &'a [f64])> for SpectralBisection
+where
+    T: Topology<f64> + Sync,
+{
+    type Metadata = ();
+    type Error = crate::Error;
+
+    fn partition(
+        &mut self,
+        part_ids: &mut [usize],
+        (adjacency, weights): (T, &'a [f64]),
+    ) -> Result<Self::Metadata, Self::Error> {
+        if part_ids.len() != weights.len() {
+            return Err(crate::Error::InputLenMismatch {
+                expected: part_ids.len(),
+                actual: weights.len(),
+            });
+        }
+        if part_ids.len() != adjacency.len() {
+            return Err(crate::Error::InputLenMismatch {
+                expected: part_ids.len(),
+                actual: adjacency.len(),
+            });
+        }
+        if crate::PartCount::expected_part_count(self).is_none() {
+            return Err(crate::Error::TooManyParts);
+        }
+        let mut vertices: Vec<usize> = (0..part_ids.len()).collect();
+        let mut local = vec![NOT_IN_SUBGRAPH; part_ids.len()];
+        spectral_bisection_recurse(
+            &adjacency,
+            weights,
+            part_ids,
+            &mut vertices,
+            &mut local,
+            self.iter_count,
+            0,
+            self.max_iter,
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Partition as _;
+    use sprs::CsMat;
+
+    /// `count` cliques of `size` vertices, each linked to the next one by a
+    /// single edge.  Vertex `v` is in clique `v % count`, so that cliques are
+    /// not contiguous in vertex order.
+    fn clique_chain(count: usize, size: usize) -> CsMat<f64> {
+        let vertex = |clique: usize, i: usize| i * count + clique;
+        let mut adjacency = CsMat::empty(sprs::CSR, count * size);
+        for clique in 0..count {
+            for a in 0..size {
+                for b in 0..size {
+                    if a != b {
+                        adjacency.insert(vertex(clique, a), vertex(clique, b), 1.0);
+                    }
+                }
+            }
+            if clique + 1 < count {
+                adjacency.insert(vertex(clique, 0), vertex(clique + 1, 1), 1.0);
+                adjacency.insert(vertex(clique + 1, 1), vertex(clique, 0), 1.0);
+            }
+        }
+        adjacency
+    }
+
+    #[test]
+    fn test_two_clusters() {
+        let adjacency = clique_chain(2, 8);
+        let weights = [1.0; 16];
+        let mut partition = [usize::MAX; 16];
+
+        SpectralBisection::default()
+            .partition(&mut partition, (adjacency.view(), &weights[..]))
+            .unwrap();
+
+        let expected: Vec<usize> = (0..16).map(|v| v % 2).collect();
+        assert!(crate::partition_equivalent(&partition, &expected));
+        assert_eq!(adjacency.view().edge_cut(&partition), 1.0);
+    }
+
+    #[test]
+    fn test_four_clusters() {
+        let adjacency = clique_chain(4, 6);
+        let weights = [1.0; 24];
+        let mut partition = [usize::MAX; 24];
+
+        SpectralBisection {
+            iter_count: 2,
+            ..Default::default()
+        }
+        .partition(&mut partition, (adjacency.view(), &weights[..]))
+        .unwrap();
+
+        let expected: Vec<usize> = (0..24).map(|v| v % 4).collect();
+        assert!(crate::partition_equivalent(&partition, &expected));
+        assert_eq!(adjacency.view().edge_cut(&partition), 3.0);
+    }
+
+    #[test]
+    fn test_small_inputs() {
+        let adjacency = clique_chain(1, 3);
+        let mut partition = [usize::MAX; 3];
+        SpectralBisection {
+            iter_count: 3,
+            ..Default::default()
+        }
+        .partition(&mut partition, (adjacency.view(), &[1.0; 3][..]))
+        .unwrap();
+        let mut parts = partition.to_vec();
+        parts.sort();
+        parts.dedup();
+        assert_eq!(parts.len(), 3);
+        assert!(parts.iter().all(|part| *part < 8));
+
+        let adjacency: CsMat<f64> = CsMat::empty(sprs::CSR, 0);
+        SpectralBisection::default()
+            .partition(&mut [], (adjacency.view(), &[][..]))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_degenerate_settings() {
+        let adjacency = clique_chain(2, 4);
+        let weights = [1.0; 8];
+        let mut partition = [usize::MAX; 8];
+
+        SpectralBisection {
+            max_iter: 0,
+            ..Default::default()
+        }
+        .partition(&mut partition, (adjacency.view(), &weights[..]))
+        .unwrap();
+        assert!(partition.iter().all(|part| *part < 2));
+
+        let err = SpectralBisection {
+            iter_count: usize::BITS as usize,
+            ..Default::default()
+        }
+        .partition(&mut partition, (adjacency.view(), &weights[..]))
+        .unwrap_err();
+        assert!(matches!(err, crate::Error::TooManyParts));
+    }
+}
@@ -0,0 +1,218 @@
+use crate::geometry::PointND;
+use std::borrow::Cow;
+
+/// # Subdivide
+///
+/// Turns a partitioner algorithm into a partition improving algorithm, by
+/// re-partitioning each part of the input partition independently.
+///
+/// The inner algorithm is run once per non-empty part, on the points and
+/// weights of that part only.  Its output is then renumbered so that part
+/// IDs stay contiguous: parts are processed in increasing ID order, and the
+/// sub-parts of a part are numbered after those of the previous parts.  When
+/// the inner algorithm splits each of the `n` input parts into `k` non-empty
+/// sub-parts, sub-part `s` of part `p` thus gets the ID `p * k + s`.
+///
+/// This allows chaining two partitioners, for example to re-balance the
+/// clusters found by a first, coarse partitioner.
+///
+/// Errors of the inner algorithm are converted into [`Error`][crate::Error].
+///
+/// # Example
+///
+/// ```rust
+/// # fn main() -> Result<(), coupe::Error> {
+/// use coupe::Partition as _;
+/// use coupe::Point2D;
+///
+/// let points: Vec<Point2D> = (0..8).map(|i| Point2D::new(i as f64, 0.0)).collect();
+/// let weights = [1.0; 8];
+/// let mut partition = [0; 8];
+///
+/// let mj = coupe::MultiJagged { part_count: 2, max_iter: 4, target_weights: None };
+/// mj.clone().partition(&mut partition, (&points, &weights))?;
+/// coupe::Subdivide(mj).partition(&mut partition, (&points, &weights))?;
+///
+/// assert_eq!(partition, [0, 0, 1, 1, 2, 2, 3, 3]);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Subdivide<A>(pub A);
+
+impl<'a, const D: usize, A, M, E> crate::Partition<(&'a [PointND<D>], &'a [f64])> for Subdivide<A>
+where
+    A: for<'b> crate::Partition<(&'b [PointND<D>], &'b [f64]), Metadata = M, Error = E>,
+    E: Into<crate::Error>,
+{
+    /// The metadata of each run of the inner algorithm, in part ID order.
+    type Metadata = Vec<M>;
+    type Error = crate::Error;
+
+    fn partition(
+        &mut self,
+        part_ids: &mut [usize],
+        (points, weights): (&'a [PointND<D>], &'a [f64]),
+    ) -> Result<Self::Metadata, Self::Error> {
+        if part_ids.len() != points.len() {
+            return Err(crate::Error::InputLenMismatch {
+                expected: part_ids.len(),
+                actual: points.len(),
+            });
+        }
+        if part_ids.len() != weights.len() {
+            return Err(crate::Error::InputLenMismatch {
+                expected: part_ids.len(),
+                actual: weights.len(),
+            });
+        }
+
+        let part_count = match part_ids.iter().max() {
+            Some(max) => max + 1,
+            None => return Ok(Vec::new()),
+        };
+
+        let mut members = vec![Vec::new(); part_count];
+        for (element, part) in part_ids.iter().enumerate() {
+            members[*part].push(element);
+        }
+
+        let mut metadata = Vec::with_capacity(part_count);
+        let mut next_part = 0;
+        for members in members {
+            if members.is_empty() {
+                continue;
+            }
+            let sub_points: Vec<PointND<D>> = members.iter().map(|e| points[*e]).collect();
+            let sub_weights: Vec<f64> = members.iter().map(|e| weights[*e]).collect();
+            let mut sub_ids = vec![0; members.len()];
+            metadata.push(
+                self.0
+                    .partition(&mut sub_ids, (&sub_points, &sub_weights))
+                    .map_err(Into::into)?,
+            );
+
+            let sub_part_count = 1 + sub_ids.iter().max().unwrap();
+            for (element, sub_id) in members.into_iter().zip(sub_ids) {
+                part_ids[element] = next_part + sub_id;
+            }
+            next_part += sub_part_count;
+        }
+
+        Ok(metadata)
+    }
+}
+
+impl<A> crate::Name for Subdivide<A>
+where
+    A: crate::Name,
+{
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Owned(format!("subdivide({})", self.0.name()))
+    }
+}
+
+impl<A> crate::PartCount for Subdivide<A> {
+    /// The number of sub-parts depends on how many parts the input partition
+    /// has, so this is always `None`.
+    fn expected_part_count(&self) -> Option<usize> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MultiJagged;
+    use crate::Partition as _;
+    use crate::Point2D;
+
+    #[test]
+    fn test_two_then_two() {
+        // A 4x4 grid of points, first cut in two vertical halves.
+        let points: Vec<Point2D> = (0..16)
+            .map(|i| Point2D::new((i % 4) as f64, (i / 4) as f64))
+            .collect();
+        let weights = [1.0; 16];
+        let mut partition: Vec<usize> = (0..16).map(|i| (i % 4) / 2).collect();
+
+        let metadata = Subdivide(MultiJagged {
+            part_count: 2,
+            max_iter: 4,
+            target_weights: None,
+        })
+        .partition(&mut partition, (&points, &weights))
+        .unwrap();
+        assert_eq!(metadata.len(), 2);
+
+        let mut sizes = [0; 4];
+        for (i, part) in partition.iter().enumerate() {
+            // Sub-parts stay within their original part.
+            assert_eq!(part / 2, (i % 4) / 2, "{partition:?}");
+            sizes[*part] += 1;
+        }
+        assert_eq!(sizes, [4; 4]);
+    }
+
+    #[test]
+    fn test_name() {
+        use crate::HilbertCurve;
+        use crate::Name as _;
+
+        let algorithm = Subdivide(HilbertCurve::default());
+        assert_eq!(algorithm.name(), "subdivide(hilbert_curve)");
+        assert_eq!(
+            Subdivide(algorithm).name(),
+            "subdivide(subdivide(hilbert_curve))"
+        );
+    }
+
+    #[test]
+    fn test_empty_parts_are_skipped() {
+        let points: Vec<Point2D> = (0..4).map(|i| Point2D::new(i as f64, 0.0)).collect();
+        let weights = [1.0; 4];
+        let mut partition = [2, 2, 2, 2];
+
+        Subdivide(MultiJagged {
+            part_count: 2,
+            max_iter: 4,
+            target_weights: None,
+        })
+        .partition(&mut partition, (&points, &weights))
+        .unwrap();
+
+        assert_eq!(partition, [0, 0, 1, 1]);
+    }
+
+    #[test]
+    fn test_input_len_mismatch() {
+        let points: Vec<Point2D> = (0..4).map(|i| Point2D::new(i as f64, 0.0)).collect();
+        let mut partition = [0; 4];
+        let mut algorithm = Subdivide(MultiJagged {
+            part_count: 2,
+            max_iter: 4,
+            target_weights: None,
+        });
+
+        let err = algorithm
+            .partition(&mut partition, (&points[..3], &[1.0; 4]))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            crate::Error::InputLenMismatch {
+                expected: 4,
+                actual: 3
+            }
+        ));
+        let err = algorithm
+            .partition(&mut partition, (&points, &[1.0; 5]))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            crate::Error::InputLenMismatch {
+                expected: 4,
+                actual: 5
+            }
+        ));
+    }
+}
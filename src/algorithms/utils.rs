@@ -0,0 +1,125 @@
+//! Helpers shared by several algorithms.
+
+use crate::geometry::PointND;
+
+use rayon::prelude::*;
+
+/// Inclusive prefix sums of `weights`, in the order given by `permutation`.
+///
+/// `prefix[i]` is the sum of `weights[permutation[j]]` for all `j <= i`.
+///
+/// Sums are computed in parallel, chunk by chunk.  Each chunk is summed
+/// sequentially, so inputs that fit in a single chunk give the same result as
+/// a sequential scan.
+pub(crate) fn parallel_prefix_sum(weights: &[f64], permutation: &[usize]) -> Vec<f64> {
+    const CHUNK_SIZE: usize = 4096;
+
+    let mut prefix: Vec<f64> = permutation.par_iter().map(|idx| weights[*idx]).collect();
+
+    let chunk_totals: Vec<f64> = prefix
+        .par_chunks_mut(CHUNK_SIZE)
+        .map(|chunk| {
+            let mut sum = 0.0;
+            for weight in chunk {
+                sum += *weight;
+                *weight = sum;
+            }
+            sum
+        })
+        .collect();
+
+    let chunk_offsets: Vec<f64> = chunk_totals
+        .iter()
+        .scan(0.0, |sum, total| {
+            let offset = *sum;
+            *sum += total;
+            Some(offset)
+        })
+        .collect();
+
+    prefix
+        .par_chunks_mut(CHUNK_SIZE)
+        .zip(chunk_offsets)
+        .skip(1)
+        .for_each(|(chunk, offset)| {
+            for weight in chunk {
+                *weight += offset;
+            }
+        });
+
+    prefix
+}
+
+/// The load of each part.
+pub(crate) fn part_loads(partition: &[usize], weights: &[f64], part_count: usize) -> Vec<f64> {
+    let mut loads = vec![0.0; part_count];
+    for (part, weight) in partition.iter().zip(weights) {
+        loads[*part] += weight;
+    }
+    loads
+}
+
+/// The centroid of each part, or `None` for empty parts.
+pub(crate) fn part_centers<const D: usize>(
+    partition: &[usize],
+    points: &[PointND<D>],
+    part_count: usize,
+) -> Vec<Option<PointND<D>>> {
+    let mut sums = vec![PointND::<D>::zeros(); part_count];
+    let mut counts = vec![0; part_count];
+    for (part, point) in partition.iter().zip(points) {
+        sums[*part] += point;
+        counts[*part] += 1;
+    }
+    sums.into_iter()
+        .zip(counts)
+        .map(|(sum, count)| (count > 0).then(|| sum / count as f64))
+        .collect()
+}
+
+/// The relative difference between the heaviest load and the average one, or
+/// zero if all loads are zero.
+pub(crate) fn load_imbalance(loads: &[f64]) -> f64 {
+    let total: f64 = loads.iter().sum();
+    if total == 0.0 {
+        return 0.0;
+    }
+    let ideal = total / loads.len() as f64;
+    let max = loads.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    (max - ideal) / ideal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parallel_prefix_sum() {
+        assert!(parallel_prefix_sum(&[], &[]).is_empty());
+        assert_eq!(
+            parallel_prefix_sum(&[1.0, 2.0, 3.0, 4.0], &[3, 0, 2, 1]),
+            [4.0, 5.0, 8.0, 10.0],
+        );
+
+        // Several chunks.  Integer weights keep the sums exact.
+        let count = 10_000;
+        let weights: Vec<f64> = (0..count).map(|i| (i % 13) as f64).collect();
+        let permutation: Vec<usize> = (0..count).map(|i| (i * 7919) % count).collect();
+        let expected: Vec<f64> = permutation
+            .iter()
+            .scan(0.0, |sum, idx| {
+                *sum += weights[*idx];
+                Some(*sum)
+            })
+            .collect();
+        assert_eq!(parallel_prefix_sum(&weights, &permutation), expected);
+    }
+
+    #[test]
+    fn test_load_imbalance() {
+        let loads = part_loads(&[0, 1, 1, 2, 1], &[2.0, 2.0, 3.0, 4.0, 1.0], 4);
+        assert_eq!(loads, [2.0, 6.0, 4.0, 0.0]);
+        assert_eq!(load_imbalance(&loads), 1.0);
+        assert_eq!(load_imbalance(&[0.0; 3]), 0.0);
+    }
+}
@@ -5,6 +5,7 @@ use num_traits::One;
 use num_traits::Zero;
 use rayon::iter::IntoParallelRefIterator as _;
 use rayon::iter::ParallelIterator as _;
+use std::borrow::Cow;
 use std::ops::AddAssign;
 use std::ops::Div;
 use std::ops::Mul;
@@ -182,6 +183,12 @@ where
 #[derive(Clone, Copy, Debug)]
 pub struct VnBest;
 
+impl crate::Name for VnBest {
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed("vn_best")
+    }
+}
+
 impl<W> crate::Partition<W> for VnBest
 where
     W: IntoIterator,
@@ -2,6 +2,7 @@ use crate::Error;
 use itertools::Itertools as _;
 use rayon::iter::IntoParallelRefIterator as _;
 use rayon::iter::ParallelIterator as _;
+use std::borrow::Cow;
 use std::iter::Sum;
 use std::ops::AddAssign;
 use std::ops::Sub;
@@ -125,6 +126,12 @@ where
 #[derive(Clone, Copy, Debug)]
 pub struct VnFirst;
 
+impl crate::Name for VnFirst {
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed("vn_first")
+    }
+}
+
 impl<'a, W> crate::Partition<&'a [W]> for VnFirst
 where
     W: VnFirstWeight,
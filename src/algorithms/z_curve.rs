@@ -14,9 +14,16 @@
 //!   - `TopLeft => 2`
 //!   - `TopRight => 3`
 //!
+//! In 2D, this mapping can be changed through [ZCurve::quadrant_order].
+//!
 //! Finally, the points are reordered according to the order of their hash.
+//! Points that share the same hash are ordered by their index in the input
+//! slice, so that the resulting partition is reproducible.
 
 use super::multi_jagged::split_at_mut_many;
+use super::utils::parallel_prefix_sum;
+use crate::geometry::invalid_period;
+use crate::geometry::wrap_periodic_points;
 use crate::geometry::OrientedBoundingBox;
 use crate::PointND;
 
@@ -29,7 +36,8 @@ use nalgebra::DimSub;
 use nalgebra::ToTypenum;
 use rayon::prelude::*;
 
-use std::cmp::Ordering;
+use std::borrow::Cow;
+use std::fmt;
 use std::sync::atomic::{self, AtomicPtr};
 
 // Z-curve hash can get quite large. For instance,
@@ -38,115 +46,275 @@ use std::sync::atomic::{self, AtomicPtr};
 type HashType = u128;
 const HASH_TYPE_MAX: HashType = std::u128::MAX;
 
+/// A quadrant of a 2D cell of the region tree.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Quadrant {
+    BottomLeft,
+    BottomRight,
+    TopLeft,
+    TopRight,
+}
+
+impl Quadrant {
+    /// The traversal order of the Z-curve, which is the default of
+    /// [ZCurve::quadrant_order].
+    pub const Z_ORDER: [Quadrant; 4] = [
+        Quadrant::BottomLeft,
+        Quadrant::BottomRight,
+        Quadrant::TopLeft,
+        Quadrant::TopRight,
+    ];
+
+    /// The region of a cell, as numbered by
+    /// [OrientedBoundingBox::region].
+    fn region(self) -> u32 {
+        match self {
+            Quadrant::BottomLeft => 0b00,
+            Quadrant::BottomRight => 0b01,
+            Quadrant::TopLeft => 0b10,
+            Quadrant::TopRight => 0b11,
+        }
+    }
+}
+
+/// The regions of a cell, in the order in which they are traversed.
+fn traversal<const D: usize>(quadrant_order: &[Quadrant; 4]) -> Result<Vec<u32>, Error> {
+    if D != 2 {
+        return Ok((0..1 << D).collect());
+    }
+    let traversal: Vec<u32> = quadrant_order.iter().map(|q| q.region()).collect();
+    if !(0..4).all(|region| traversal.contains(&region)) {
+        return Err(Error::InvalidQuadrantOrder);
+    }
+    Ok(traversal)
+}
+
+// fills `partition` and returns the indices of the points sorted along the
+// curve
 fn z_curve_partition<const D: usize>(
     partition: &mut [usize],
     points: &[PointND<D>],
+    weights: &[f64],
     part_count: usize,
     order: u32,
-) where
+    quadrant_order: &[Quadrant; 4],
+    periodic: &[Option<(f64, f64)>],
+) -> Result<Vec<usize>, Error>
+where
     Const<D>: DimSub<Const<1>> + ToTypenum,
     DefaultAllocator: Allocator<f64, Const<D>, Const<D>, Buffer = ArrayStorage<f64, D, D>>
         + Allocator<f64, DimDiff<Const<D>, Const<1>>>,
 {
-    debug_assert_eq!(partition.len(), points.len());
+    if partition.len() != points.len() {
+        return Err(Error::InputLenMismatch {
+            expected: partition.len(),
+            actual: points.len(),
+        });
+    }
+    if partition.len() != weights.len() {
+        return Err(Error::InputLenMismatch {
+            expected: partition.len(),
+            actual: weights.len(),
+        });
+    }
 
     let max_order = (HASH_TYPE_MAX as f64).log(f64::from(1 << D)) as u32;
-    assert!(
-        order <= max_order,
-        "Cannot use the z-curve partition algorithm with an order > {} because it would currently overflow hashes capacity",
-        max_order,
-    );
+    if order > max_order {
+        return Err(Error::InvalidOrder {
+            max: max_order,
+            actual: order,
+        });
+    }
+
+    if let Some(axis) = invalid_period(periodic) {
+        return Err(Error::InvalidPeriod { axis });
+    }
+    let points = &*wrap_periodic_points(points, periodic);
+
+    // `traversal` maps the 2-bit codes of hashes to regions, and `codes` maps
+    // regions to codes.
+    let traversal = traversal::<D>(quadrant_order)?;
+    let mut codes = vec![0; traversal.len()];
+    for (code, region) in traversal.iter().enumerate() {
+        codes[*region as usize] = code as u8;
+    }
 
     // Bounding box used to construct Point hashes
     let obb = match OrientedBoundingBox::from_points(points) {
         Some(v) => v,
-        None => return,
+        None => return Ok((0..points.len()).collect()),
     };
 
     let mut permutation: Vec<_> = (0..points.len()).into_par_iter().collect();
 
     // reorder points
-    z_curve_partition_recurse(points, order, &obb, &mut permutation);
+    let cell_count =
+        z_curve_partition_recurse(points, order, &obb, &traversal, &codes, &mut permutation);
+    if cell_count < part_count {
+        return Err(Error::TooManyParts {
+            part_count,
+            cell_count,
+        });
+    }
 
-    let points_per_partition = points.len() / part_count;
-    let remainder = points.len() % part_count;
+    // cut the curve at equal-weight thresholds: each point goes to the part
+    // in which the middle of its weight span falls
+    let prefix = parallel_prefix_sum(weights, &permutation);
+    let total_weight = prefix.last().cloned().unwrap_or(0.0);
+    if total_weight <= 0.0 {
+        partition.par_iter_mut().for_each(|part| *part = 0);
+        return Ok(permutation);
+    }
 
     let atomic_handle = AtomicPtr::from(partition.as_mut_ptr());
-
-    // give an id to each partition
-    //
-    // instead of calling par_chunks with points_per_partition, which could yield an extra
-    // undesired partition, or a quite high partition imbalance, we compute an index threshold
-    // that splits chunks of len points_per_partition and point_per_partition + 1.
-    // Doing so makes sure there is always the correct amount of chunks, and that they are not too imbalanced.
-    let threshold_idx = (points_per_partition + 1) * remainder;
-    permutation[..threshold_idx]
-        .par_chunks(points_per_partition + 1)
-        .chain(permutation[threshold_idx..].par_chunks(points_per_partition))
-        .enumerate()
-        .for_each(|(id, chunk)| {
+    permutation
+        .par_iter()
+        .zip(&prefix)
+        .for_each(|(idx, prefix_weight)| {
+            let middle = prefix_weight - weights[*idx] / 2.0;
+            let id = usize::min(
+                (middle / total_weight * part_count as f64) as usize,
+                part_count - 1,
+            );
             let ptr = atomic_handle.load(atomic::Ordering::Relaxed);
-            for idx in chunk {
-                unsafe { std::ptr::write(ptr.add(*idx), id) }
-            }
+            unsafe { std::ptr::write(ptr.add(*idx), id) }
         });
+
+    Ok(permutation)
 }
 
-// reorders `permu` to sort points by increasing z-curve hash
+// reorders `permu` to sort points by increasing z-curve hash, and returns the
+// number of distinct hashes
+//
+// Only the points of `permu` are looked at, so that each level of the region
+// tree costs O(n) in total, and the temporary buffer of a node is freed before
+// its children are processed.
 fn z_curve_partition_recurse<const D: usize>(
     points: &[PointND<D>],
     order: u32,
     mbr: &OrientedBoundingBox<D>,
+    traversal: &[u32],
+    codes: &[u8],
     permu: &mut [usize],
-) {
+) -> usize {
     // we stop recursion if there is only 1 point left to avoid useless calls
     if order == 0 || permu.len() <= 1 {
-        return;
+        return usize::from(!permu.is_empty());
     }
 
-    // compute the quadrant in which each point is.
+    // compute the code of the quadrant in which each point is.
     // default to dummy value for points outside of the current mbr
-    let regions = points
+    let mut regions = permu
         .par_iter()
-        .map(|p| mbr.region(p).unwrap_or(0))
+        .map(|idx| {
+            let region = mbr.region(&points[*idx]).unwrap_or(0);
+            (codes[region as usize], *idx)
+        })
         .collect::<Vec<_>>();
 
-    // use pdqsort to break equal elements pattern
-    permu.par_sort_unstable_by_key(|idx| regions[*idx] as u8);
+    // use pdqsort to break equal elements pattern, and break ties with the
+    // original index so that the output does not depend on thread scheduling
+    regions.par_sort_unstable();
 
     // Now we need to split the permutation array in 2^dim
     // such that each subslice contains only points from the same quadrant
     // instead of traversing the whole array, we can just perform a few binary searches
     // to find the split positions since the array is already sorted
+    let split_positions = (1..2usize.pow(D as u32))
+        .map(|n| regions.partition_point(|(region, _)| (*region as usize) < n))
+        .collect::<Vec<_>>();
 
-    let mut split_positions = (1..2usize.pow(D as u32)).collect::<Vec<_>>();
-    for n in split_positions.iter_mut() {
-        *n = permu
-            .binary_search_by(|idx| {
-                if (regions[*idx] as u8) < *n as u8 {
-                    Ordering::Less
-                } else {
-                    Ordering::Greater
-                }
-            })
-            .unwrap_err();
-    }
+    permu
+        .par_iter_mut()
+        .zip(&regions)
+        .for_each(|(idx, (_, sorted_idx))| *idx = *sorted_idx);
+    drop(regions);
 
     let slices = split_at_mut_many(permu, &split_positions);
-    slices.into_par_iter().enumerate().for_each(|(i, slice)| {
-        z_curve_partition_recurse(points, order - 1, &mbr.sub_mbr(i as u32), slice);
-    })
+    slices
+        .into_par_iter()
+        .enumerate()
+        .map(|(i, slice)| {
+            let sub_region = mbr.sub_region(traversal[i]);
+            z_curve_partition_recurse(points, order - 1, &sub_region, traversal, codes, slice)
+        })
+        .sum()
+}
+
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// Points span fewer distinct cells of the curve than there are parts.
+    TooManyParts {
+        part_count: usize,
+        cell_count: usize,
+    },
+
+    /// A point has a NaN or infinite coordinate.
+    NonFiniteCoordinate { index: usize },
+
+    /// Input sets don't have matching lengths.
+    InputLenMismatch { expected: usize, actual: usize },
+
+    /// The order is so high that hashes would overflow.
+    InvalidOrder { max: u32, actual: u32 },
+
+    /// [ZCurve::quadrant_order] does not hold each quadrant exactly once.
+    InvalidQuadrantOrder,
+
+    /// The period of an axis is empty or not finite.
+    InvalidPeriod { axis: usize },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::TooManyParts {
+                part_count,
+                cell_count,
+            } => write!(
+                f,
+                "cannot make {part_count} parts out of {cell_count} distinct z-curve cells, try a higher order",
+            ),
+            Error::NonFiniteCoordinate { index } => {
+                write!(f, "point {index} has a non-finite coordinate")
+            }
+            Error::InputLenMismatch { expected, actual } => write!(
+                f,
+                "input sets don't have the same length (expected {expected} items, got {actual})",
+            ),
+            Error::InvalidOrder { max, actual } => {
+                write!(f, "given z-curve order too high. Got {actual}, max={max}.")
+            }
+            Error::InvalidQuadrantOrder => {
+                write!(f, "the quadrant order must hold each quadrant once")
+            }
+            Error::InvalidPeriod { axis } => {
+                write!(f, "the period of axis {axis} is empty or not finite")
+            }
+        }
+    }
 }
 
+impl std::error::Error for Error {}
+
 /// # Z space-filling curve algorithm
 ///
 /// The Z-curve uses space hashing to partition points. The points in the same part of a partition
 /// have the same Z-hash. This hash is computed by recursively constructing a N-dimensional region tree.
 ///
+/// Points are then sorted along the curve, which is cut into parts of similar
+/// weights.  Each point goes to the part in which the middle of its weight
+/// span falls, so that the weight of each part is within the largest point
+/// weight of the average part weight.
+///
+/// Partitioning fails with [`Error::TooManyParts`] when points span fewer
+/// cells than `part_count`, in which case a higher `order` is needed.
+///
 /// # Example
 ///
 /// ```rust
-/// # fn main() -> Result<(), std::convert::Infallible> {
+/// # fn main() -> Result<(), coupe::ZCurveError> {
 /// use coupe::Partition as _;
 /// use coupe::Point2D;
 ///
@@ -160,11 +328,12 @@ fn z_curve_partition_recurse<const D: usize>(
 ///     Point2D::new(10., 10.),
 ///     Point2D::new(9., 9.),
 /// ];
+/// let weights = [1.0; 8];
 /// let mut partition = [0; 8];
 ///
 /// // generate a partition of 4 parts
-/// coupe::ZCurve { part_count: 4, order: 5 }
-///     .partition(&mut partition, &points)?;
+/// coupe::ZCurve { part_count: 4, order: 5, ..Default::default() }
+///     .partition(&mut partition, (&points, &weights))?;
 ///
 /// assert_eq!(partition[0], partition[1]);
 /// assert_eq!(partition[2], partition[3]);
@@ -173,27 +342,116 @@ fn z_curve_partition_recurse<const D: usize>(
 /// # Ok(())
 /// # }
 /// ```  
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub struct ZCurve {
     pub part_count: usize,
+
+    /// The maximum depth of the region tree.
+    ///
+    /// Cells are refined until they hold at most one point or this depth is
+    /// reached, after which all points of a cell share the same hash.  Hence,
+    /// clusters of coincident points do not make the recursion unbounded.
+    /// Orders that would overflow hashes make partitioning fail with
+    /// [`Error::InvalidOrder`].
     pub order: u32,
+
+    /// The order in which the quadrants of each cell are traversed, in 2D.
+    ///
+    /// The `i`-th quadrant of this array gets the 2-bit code `i` in point
+    /// hashes, so for example `[BottomLeft, TopLeft, BottomRight, TopRight]`
+    /// gives an N-order curve.  Each quadrant must appear once.  This is
+    /// ignored in other dimensions.  Defaults to [Quadrant::Z_ORDER].
+    pub quadrant_order: [Quadrant; 4],
+
+    /// Same meaning as [`HilbertCurve::periodic`][crate::HilbertCurve::periodic].
+    pub periodic: Vec<Option<(f64, f64)>>,
+}
+
+impl Default for ZCurve {
+    fn default() -> Self {
+        Self {
+            part_count: 2,
+            order: 8,
+            quadrant_order: Quadrant::Z_ORDER,
+            periodic: Vec::new(),
+        }
+    }
+}
+
+impl ZCurve {
+    /// Partitions points like [`partition`][crate::Partition::partition],
+    /// and also returns the order of the points along the curve.
+    ///
+    /// The first vector holds the part ID of each point.  The second one
+    /// maps positions along the curve to indices in `points`, such that the
+    /// points of each part are contiguous in it.
+    pub fn partition_with_permutation<const D: usize, W>(
+        &self,
+        points: &[PointND<D>],
+        weights: W,
+    ) -> Result<(Vec<usize>, Vec<usize>), Error>
+    where
+        W: AsRef<[f64]>,
+        Const<D>: DimSub<Const<1>> + ToTypenum,
+        DefaultAllocator: Allocator<f64, Const<D>, Const<D>, Buffer = ArrayStorage<f64, D, D>>
+            + Allocator<f64, DimDiff<Const<D>, Const<1>>>,
+    {
+        if let Some(index) = super::first_non_finite(points) {
+            return Err(Error::NonFiniteCoordinate { index });
+        }
+        let mut partition = vec![0; points.len()];
+        let permutation = z_curve_partition(
+            &mut partition,
+            points,
+            weights.as_ref(),
+            self.part_count,
+            self.order,
+            &self.quadrant_order,
+            &self.periodic,
+        )?;
+        Ok((partition, permutation))
+    }
+}
+
+impl crate::PartCount for ZCurve {
+    fn expected_part_count(&self) -> Option<usize> {
+        Some(self.part_count)
+    }
+}
+
+impl crate::Name for ZCurve {
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed("z_curve")
+    }
 }
 
-impl<'a, const D: usize> crate::Partition<&'a [PointND<D>]> for ZCurve
+impl<'a, const D: usize, W> crate::Partition<(&'a [PointND<D>], W)> for ZCurve
 where
+    W: AsRef<[f64]>,
     Const<D>: DimSub<Const<1>> + ToTypenum,
     DefaultAllocator: Allocator<f64, Const<D>, Const<D>, Buffer = ArrayStorage<f64, D, D>>
         + Allocator<f64, DimDiff<Const<D>, Const<1>>>,
 {
     type Metadata = ();
-    type Error = std::convert::Infallible;
+    type Error = Error;
 
     fn partition(
         &mut self,
         part_ids: &mut [usize],
-        points: &'a [PointND<D>],
+        (points, weights): (&'a [PointND<D>], W),
     ) -> Result<Self::Metadata, Self::Error> {
-        z_curve_partition(part_ids, points, self.part_count, self.order);
+        if let Some(index) = super::first_non_finite(points) {
+            return Err(Error::NonFiniteCoordinate { index });
+        }
+        z_curve_partition(
+            part_ids,
+            points,
+            weights.as_ref(),
+            self.part_count,
+            self.order,
+            &self.quadrant_order,
+            &self.periodic,
+        )?;
         Ok(())
     }
 }
@@ -217,7 +475,7 @@ mod tests {
         ];
 
         let mut ids = [0; 8];
-        z_curve_partition(&mut ids, &points, 4, 1);
+        z_curve_partition(&mut ids, &points, &[1.0; 8], 4, 1, &Quadrant::Z_ORDER, &[]).unwrap();
         for id in ids {
             println!("{}", id);
         }
@@ -226,4 +484,279 @@ mod tests {
         assert_eq!(ids[2], ids[5]);
         assert_eq!(ids[3], ids[6]);
     }
+
+    #[test]
+    fn test_partition_with_permutation() {
+        let points: Vec<Point2D> = (0..64)
+            .map(|i| Point2D::new((i * 5 % 8) as f64, (i / 8) as f64))
+            .collect();
+        let weights = vec![1.0; points.len()];
+
+        let (partition, permutation) = ZCurve {
+            part_count: 4,
+            order: 4,
+            ..Default::default()
+        }
+        .partition_with_permutation(&points, &weights)
+        .unwrap();
+
+        let mut sorted = permutation.clone();
+        sorted.sort();
+        assert_eq!(sorted, (0..points.len()).collect::<Vec<_>>());
+
+        // applying the permutation keeps parts contiguous
+        let reordered: Vec<usize> = permutation.iter().map(|&i| partition[i]).collect();
+        assert!(reordered.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(reordered[0], 0);
+        assert_eq!(reordered[points.len() - 1], 3);
+    }
+
+    #[test]
+    fn test_partition_deterministic() {
+        // Many points share the same cell, so the order in which they are
+        // sorted decides which part the boundary points end up in.
+        let points: Vec<Point2D> = (0..1000)
+            .map(|i| Point2D::from([(i % 7) as f64, (i % 13) as f64]))
+            .collect();
+        let weights = vec![1.0; points.len()];
+
+        let mut first = vec![0; points.len()];
+        z_curve_partition(&mut first, &points, &weights, 9, 2, &Quadrant::Z_ORDER, &[]).unwrap();
+        for _ in 0..8 {
+            let mut other = vec![0; points.len()];
+            z_curve_partition(&mut other, &points, &weights, 9, 2, &Quadrant::Z_ORDER, &[])
+                .unwrap();
+            assert_eq!(first, other);
+        }
+    }
+
+    #[test]
+    fn test_weighted_partition() {
+        use crate::Partition as _;
+
+        // A line of points, the left half being three times heavier.
+        let points: Vec<Point2D> = (0..32).map(|i| Point2D::from([i as f64, 0.])).collect();
+        let weights: Vec<f64> = (0..32).map(|i| if i < 16 { 3.0 } else { 1.0 }).collect();
+        let mut partition = vec![0; 32];
+
+        ZCurve {
+            part_count: 4,
+            order: 6,
+            ..Default::default()
+        }
+        .partition(&mut partition, (&points, &weights))
+        .unwrap();
+
+        let mut part_weights = [0.0; 4];
+        for (part, weight) in partition.iter().zip(&weights) {
+            part_weights[*part] += weight;
+        }
+        // Splitting by point count would give [24, 24, 8, 8].
+        for part_weight in part_weights {
+            assert!(f64::abs(part_weight - 16.0) <= 3.0, "{part_weights:?}");
+        }
+    }
+
+    #[test]
+    fn test_too_many_parts() {
+        // 16 points on a 4x4 grid, but an order of 1 only gives 4 cells.
+        let points: Vec<Point2D> = (0..16)
+            .map(|i| Point2D::from([(i % 4) as f64, (i / 4) as f64]))
+            .collect();
+        let weights = [1.0; 16];
+
+        let mut partition = [0; 16];
+        let err = z_curve_partition(
+            &mut partition,
+            &points,
+            &weights,
+            8,
+            1,
+            &Quadrant::Z_ORDER,
+            &[],
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::TooManyParts {
+                part_count: 8,
+                cell_count: 4,
+            },
+        ));
+
+        z_curve_partition(
+            &mut partition,
+            &points,
+            &weights,
+            8,
+            2,
+            &Quadrant::Z_ORDER,
+            &[],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_invalid_order() {
+        let points: Vec<Point2D> = (0..4).map(|i| Point2D::new(i as f64, 0.0)).collect();
+        let mut partition = [0; 4];
+
+        let err = z_curve_partition(
+            &mut partition,
+            &points,
+            &[1.0; 4],
+            4,
+            65,
+            &Quadrant::Z_ORDER,
+            &[],
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::InvalidOrder {
+                max: 64,
+                actual: 65,
+            },
+        ));
+    }
+
+    #[test]
+    fn test_input_len_mismatch() {
+        use crate::Partition as _;
+
+        let points: Vec<Point2D> = (0..4).map(|i| Point2D::new(i as f64, 0.0)).collect();
+        let mut partition = [0; 4];
+        let mut algo = ZCurve::default();
+
+        let err = algo
+            .partition(&mut partition, (&points[..3], [1.0; 4]))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::InputLenMismatch {
+                expected: 4,
+                actual: 3,
+            },
+        ));
+        let err = algo
+            .partition(&mut partition, (&points[..], [1.0; 3]))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::InputLenMismatch {
+                expected: 4,
+                actual: 3,
+            },
+        ));
+        let err = algo
+            .partition_with_permutation(&points, [1.0; 5])
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::InputLenMismatch {
+                expected: 4,
+                actual: 5,
+            },
+        ));
+    }
+
+    #[test]
+    fn test_quadrant_order() {
+        // An 8x4 grid, so that the axes of the region tree are the x and y
+        // axes, cut in one part per quadrant.
+        let points: Vec<Point2D> = (0..32)
+            .map(|i| Point2D::new((i % 8) as f64, (i / 8) as f64))
+            .collect();
+        let weights = vec![1.0; points.len()];
+        let quadrants: Vec<usize> = points
+            .iter()
+            .map(|p| usize::from(p.x > 3.5) + 2 * usize::from(p.y > 1.5))
+            .collect();
+
+        let partition = |quadrant_order| {
+            ZCurve {
+                part_count: 4,
+                order: 1,
+                quadrant_order,
+                ..Default::default()
+            }
+            .partition_with_permutation(&points, &weights)
+            .unwrap()
+        };
+        let (z_order, z_permutation) = partition(Quadrant::Z_ORDER);
+        let (n_order, n_permutation) = partition([
+            Quadrant::BottomLeft,
+            Quadrant::TopLeft,
+            Quadrant::BottomRight,
+            Quadrant::TopRight,
+        ]);
+
+        // Both orders make the same parts, but traverse them differently.
+        assert!(crate::partition_equivalent(&z_order, &quadrants));
+        assert!(crate::partition_equivalent(&n_order, &quadrants));
+        assert_ne!(z_order, n_order);
+        for (partition, permutation) in [(&z_order, &z_permutation), (&n_order, &n_permutation)] {
+            let reordered: Vec<usize> = permutation.iter().map(|i| partition[*i]).collect();
+            assert!(reordered.windows(2).all(|w| w[0] <= w[1]));
+        }
+
+        let invalid = ZCurve {
+            quadrant_order: [Quadrant::BottomLeft; 4],
+            ..Default::default()
+        }
+        .partition_with_permutation(&points, &weights);
+        assert!(matches!(invalid, Err(Error::InvalidQuadrantOrder)));
+    }
+
+    #[test]
+    fn test_coincident_points() {
+        // Two clusters of 50 copies of the same point, and one point apart.
+        let points: Vec<Point2D> = (0..101)
+            .map(|i| match i {
+                0..=49 => Point2D::new(1.0, 1.0),
+                50..=99 => Point2D::new(-3.0, 2.0),
+                _ => Point2D::new(0.0, 10.0),
+            })
+            .collect();
+        let weights = vec![1.0; points.len()];
+
+        // Coincident points cannot be told apart, whatever the depth of the
+        // region tree, which stops refining them at `order`.
+        let (partition, permutation) = ZCurve {
+            part_count: 3,
+            order: 30,
+            ..Default::default()
+        }
+        .partition_with_permutation(&points, &weights)
+        .unwrap();
+
+        // Points that share a hash are next to each other on the curve...
+        let first = permutation.iter().position(|i| *i < 50).unwrap();
+        assert!(permutation[first..first + 50].iter().all(|i| *i < 50));
+
+        // ...and the curve is still cut in balanced parts.
+        let mut sizes = [0; 3];
+        for part in partition {
+            sizes[part] += 1;
+        }
+        assert!(
+            sizes.iter().all(|size| 33 <= *size && *size <= 34),
+            "{sizes:?}"
+        );
+    }
+
+    #[test]
+    fn test_periodic_seam() {
+        use crate::Partition as _;
+
+        let err = crate::geometry::check_periodic_seam(|partition, points, weights, periodic| {
+            ZCurve {
+                part_count: 4,
+                periodic,
+                ..Default::default()
+            }
+            .partition(partition, (points, weights))
+        });
+        assert!(matches!(err, Error::InvalidPeriod { axis: 0 }));
+    }
 }
@@ -0,0 +1,1430 @@
+//! Quality metrics for partitions.
+
+use crate::geometry::par_bounding_box;
+use crate::geometry::Matrix;
+use crate::geometry::OrientedBoundingBox;
+use crate::topology::Topology;
+use crate::KdTree;
+use crate::PointND;
+use nalgebra::allocator::Allocator;
+use nalgebra::ArrayStorage;
+use nalgebra::Const;
+use nalgebra::DefaultAllocator;
+use nalgebra::DimDiff;
+use nalgebra::DimSub;
+use rayon::iter::IndexedParallelIterator as _;
+use rayon::iter::IntoParallelRefIterator as _;
+use rayon::iter::ParallelIterator as _;
+#[cfg(feature = "sprs")]
+use sprs::CsMat;
+#[cfg(feature = "sprs")]
+use sprs::CsMatView;
+#[cfg(feature = "sprs")]
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fmt;
+#[cfg(feature = "sprs")]
+use std::io;
+
+/// The compactness of each part, a score between 0 and 1.
+///
+/// The score of a part is computed from the sides of the bounding box of its
+/// points, aligned with their inertia axis.  It is the geometric mean of the
+/// side lengths divided by the longest one, that is the side of the
+/// hypercube that has the same volume as the box, relative to the longest
+/// side.  A square or a cube scores 1, while a sliver scores close to 0,
+/// regardless of the dimension.
+///
+/// The returned vector is indexed by part ID.  Parts that have no points, or
+/// whose points are all at the same location, score 0.
+///
+/// # Example
+///
+/// ```rust
+/// use coupe::Point2D;
+///
+/// let points = [
+///     // part 0: a unit square
+///     Point2D::new(0.0, 0.0),
+///     Point2D::new(1.0, 0.0),
+///     Point2D::new(1.0, 1.0),
+///     Point2D::new(0.0, 1.0),
+///     // part 1: a thin rectangle
+///     Point2D::new(2.0, 0.0),
+///     Point2D::new(12.0, 0.0),
+///     Point2D::new(12.0, 0.1),
+///     Point2D::new(2.0, 0.1),
+/// ];
+/// let partition = [0, 0, 0, 0, 1, 1, 1, 1];
+///
+/// let compactness = coupe::analysis::compactness(&partition, &points);
+/// assert!(0.99 < compactness[0]);
+/// assert!(compactness[1] < 0.15);
+/// ```
+pub fn compactness<const D: usize>(partition: &[usize], points: &[PointND<D>]) -> Vec<f64>
+where
+    Const<D>: DimSub<Const<1>>,
+    DefaultAllocator: Allocator<f64, Const<D>, Const<D>, Buffer = ArrayStorage<f64, D, D>>
+        + Allocator<f64, DimDiff<Const<D>, Const<1>>>,
+{
+    debug_assert_eq!(partition.len(), points.len());
+
+    let part_count = partition.iter().max().map_or(0, |max_part| max_part + 1);
+    let mut part_points = vec![Vec::new(); part_count];
+    for (part, point) in partition.iter().zip(points) {
+        part_points[*part].push(*point);
+    }
+
+    part_points
+        .into_iter()
+        .map(|points| {
+            let obb = match OrientedBoundingBox::from_points(&points) {
+                Some(obb) => obb,
+                None => return 0.0,
+            };
+            let aabb = obb.aabb();
+            let sides = aabb.p_max - aabb.p_min;
+            let longest = sides.max();
+            if longest <= 0.0 {
+                return 0.0;
+            }
+            let mean_side = sides.iter().product::<f64>().powf(1.0 / D as f64);
+            f64::min(1.0, mean_side / longest)
+        })
+        .collect()
+}
+
+/// The aspect ratio of each part, computed from the weighted inertia of its
+/// points.
+///
+/// The inertia matrix of a part is the weighted covariance of its points, the
+/// same matrix from which [Rib][crate::Rib] derives its cut directions, with
+/// each point counted according to its weight.  The aspect ratio is the
+/// square root of the ratio between its largest and smallest eigenvalues,
+/// that is the ratio between the spreads of the part along its longest and
+/// shortest principal axes.
+///
+/// Unlike [compactness], which looks at the bounding box of the points, light
+/// outliers barely change the result, while heavy ones stretch the part
+/// accordingly.
+///
+/// The returned vector is indexed by part ID.  A ratio of 1 means the mass of
+/// the part is evenly spread in all directions.  Parts that have no weight get
+/// a ratio of 1, and flat parts a ratio of infinity.
+///
+/// # Panics
+///
+/// Panics if `partition`, `points` and `weights` do not have the same length.
+///
+/// # Example
+///
+/// ```rust
+/// use coupe::Point2D;
+///
+/// let points = [
+///     // part 0: a unit square
+///     Point2D::new(0.0, 0.0),
+///     Point2D::new(1.0, 0.0),
+///     Point2D::new(1.0, 1.0),
+///     Point2D::new(0.0, 1.0),
+///     // part 1: a rectangle, four times longer than it is wide
+///     Point2D::new(2.0, 0.0),
+///     Point2D::new(6.0, 0.0),
+///     Point2D::new(6.0, 1.0),
+///     Point2D::new(2.0, 1.0),
+/// ];
+/// let weights = [1.0; 8];
+/// let partition = [0, 0, 0, 0, 1, 1, 1, 1];
+///
+/// let ratios = coupe::analysis::weighted_aspect_ratios(&partition, &points, &weights);
+/// assert!(f64::abs(ratios[0] - 1.0) < 1e-9);
+/// assert!(f64::abs(ratios[1] - 4.0) < 1e-9);
+/// ```
+pub fn weighted_aspect_ratios<const D: usize>(
+    partition: &[usize],
+    points: &[PointND<D>],
+    weights: &[f64],
+) -> Vec<f64>
+where
+    Const<D>: DimSub<Const<1>>,
+    DefaultAllocator: Allocator<f64, Const<D>, Const<D>, Buffer = ArrayStorage<f64, D, D>>
+        + Allocator<f64, DimDiff<Const<D>, Const<1>>>,
+{
+    assert_eq!(partition.len(), points.len());
+    assert_eq!(partition.len(), weights.len());
+
+    let part_count = partition.iter().max().map_or(0, |max_part| max_part + 1);
+    let mut part_weights = vec![0.0; part_count];
+    let mut centers = vec![PointND::<D>::zeros(); part_count];
+    for ((part, point), weight) in partition.iter().zip(points).zip(weights) {
+        part_weights[*part] += weight;
+        centers[*part] += point * *weight;
+    }
+    for (center, part_weight) in centers.iter_mut().zip(&part_weights) {
+        if 0.0 < *part_weight {
+            *center /= *part_weight;
+        }
+    }
+
+    let mut inertias = vec![Matrix::<D>::zeros(); part_count];
+    for ((part, point), weight) in partition.iter().zip(points).zip(weights) {
+        let offset = point - centers[*part];
+        inertias[*part] += offset * offset.transpose() * *weight;
+    }
+
+    inertias
+        .into_iter()
+        .zip(part_weights)
+        .map(|(inertia, part_weight)| {
+            if part_weight <= 0.0 {
+                return 1.0;
+            }
+            let eigenvalues = inertia.symmetric_eigenvalues();
+            let max = eigenvalues.max();
+            let min = f64::max(0.0, eigenvalues.min());
+            if max <= 0.0 {
+                return 1.0;
+            }
+            f64::sqrt(max / min)
+        })
+        .collect()
+}
+
+/// Geometric and topologic quality of a partition, as returned by
+/// [evaluate].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PartitionEvaluation {
+    /// One more than the largest part ID.
+    pub part_count: usize,
+
+    /// Relative weight difference between the heaviest part and the ideal
+    /// part weight, as computed by [imbalance][crate::imbalance::imbalance].
+    pub imbalance: f64,
+
+    /// The largest aspect ratio among parts, as computed by
+    /// [weighted_aspect_ratios].
+    pub max_aspect_ratio: f64,
+
+    /// Total weight of the edges that link different parts, as computed by
+    /// [Topology::edge_cut].
+    pub edge_cut: f64,
+}
+
+/// Computes both the geometric and the topologic quality of a partition.
+///
+/// Geometric algorithms like [Rcb][crate::Rcb] or
+/// [HilbertCurve][crate::HilbertCurve] only look at coordinates.  When an
+/// adjacency is also available, this tells in one call how balanced and well
+/// shaped their parts are, and how many edges they cut, so that one can
+/// decide whether running a graph refiner such as
+/// [FiducciaMattheyses][crate::FiducciaMattheyses] is worth it.
+///
+/// # Panics
+///
+/// Panics if `partition`, `points` and `weights` do not have the same length.
+///
+/// # Example
+///
+/// ```rust
+/// # fn main() -> Result<(), coupe::Error> {
+/// use coupe::Partition as _;
+/// use std::num::NonZeroUsize;
+///
+/// let side = NonZeroUsize::new(8).unwrap();
+/// let grid = coupe::Grid::new_2d(side, side);
+/// let points = coupe::mesh::grid_2d(8, 8, 1.0);
+/// let weights = [1.0; 64];
+/// let mut partition = [0; 64];
+///
+/// coupe::Rcb { iter_count: 2, ..Default::default() }
+///     .partition(&mut partition, (points.clone(), weights))?;
+///
+/// let evaluation = coupe::evaluate(&partition, &points, &weights, grid);
+/// assert_eq!(evaluation.part_count, 4);
+/// assert_eq!(evaluation.imbalance, 0.0);
+/// assert_eq!(evaluation.edge_cut, 16.0);
+/// # Ok(())
+/// # }
+/// ```
+pub fn evaluate<const D: usize, T>(
+    partition: &[usize],
+    points: &[PointND<D>],
+    weights: &[f64],
+    adjacency: T,
+) -> PartitionEvaluation
+where
+    T: Topology<f64> + Sync,
+    Const<D>: DimSub<Const<1>>,
+    DefaultAllocator: Allocator<f64, Const<D>, Const<D>, Buffer = ArrayStorage<f64, D, D>>
+        + Allocator<f64, DimDiff<Const<D>, Const<1>>>,
+{
+    let part_count = partition.iter().max().map_or(0, |max_part| max_part + 1);
+    let max_aspect_ratio = weighted_aspect_ratios(partition, points, weights)
+        .into_iter()
+        .fold(1.0, f64::max);
+    PartitionEvaluation {
+        part_count,
+        imbalance: crate::imbalance::imbalance(part_count, partition, weights.par_iter().cloned()),
+        max_aspect_ratio,
+        edge_cut: adjacency.edge_cut(partition),
+    }
+}
+
+/// The number of elements that are not in the same part in `previous` and
+/// `current`.
+///
+/// When parts are mapped to processes, this is the number of elements that
+/// need to be sent to another process to go from one partition to the other.
+///
+/// # Panics
+///
+/// Panics if `previous` and `current` have different lengths.
+///
+/// # Example
+///
+/// ```rust
+/// let previous = [0, 0, 1, 1];
+/// let current = [0, 1, 1, 1];
+/// assert_eq!(coupe::analysis::migration_cost(&previous, &current), 1);
+/// ```
+pub fn migration_cost(previous: &[usize], current: &[usize]) -> usize {
+    assert_eq!(previous.len(), current.len());
+    previous
+        .iter()
+        .zip(current)
+        .filter(|(previous, current)| previous != current)
+        .count()
+}
+
+/// The signed deviation of the load of each part from the mean part load.
+///
+/// Positive values are for overloaded parts, and negative ones for
+/// underloaded parts.  Unlike [`imbalance::imbalance`][crate::imbalance::imbalance],
+/// which only gives the worst deviation, this tells which parts need to give or
+/// receive weight, for example to choose the parts to re-balance.
+///
+/// The returned vector is indexed by part ID, and sums to zero (up to rounding
+/// errors).  Empty parts get a deviation of minus the mean part load.
+///
+/// # Panics
+///
+/// Panics if `partition` and `weights` have different lengths.
+///
+/// # Example
+///
+/// ```rust
+/// let partition = [0, 0, 1, 2];
+/// let weights = [2.0, 2.0, 1.0, 1.0];
+///
+/// let deviations = coupe::analysis::imbalance_per_part(&partition, &weights);
+/// assert_eq!(deviations, [2.0, -1.0, -1.0]);
+/// ```
+pub fn imbalance_per_part(partition: &[usize], weights: &[f64]) -> Vec<f64> {
+    assert_eq!(partition.len(), weights.len());
+
+    let part_count = partition.iter().max().map_or(0, |max_part| max_part + 1);
+    let mut loads = vec![0.0; part_count];
+    for (part, weight) in partition.iter().zip(weights) {
+        loads[*part] += weight;
+    }
+    let mean_load = loads.iter().sum::<f64>() / part_count as f64;
+    for load in &mut loads {
+        *load -= mean_load;
+    }
+    loads
+}
+
+/// The imbalance of a partition with respect to per-part target weights.
+///
+/// `target_weights` is indexed by part ID and only matters relative to its
+/// sum: each part is expected to receive the same share of the total weight as
+/// its target has of the sum of targets.  The result is the largest relative
+/// excess of a part load over its expected load, as in
+/// [`imbalance::imbalance`][crate::imbalance::imbalance] with equal targets.
+///
+/// Parts with a zero target are not expected to receive any weight: the
+/// imbalance is infinite if one of them does.
+///
+/// # Panics
+///
+/// Panics if `partition` and `weights` have different lengths, or if
+/// `partition` contains a part ID out of `target_weights`' bounds.
+///
+/// # Example
+///
+/// ```rust
+/// let partition = [0, 0, 1, 1];
+/// let weights = [1.0, 1.0, 1.0, 1.0];
+///
+/// // Part 0 should receive three times as much weight as part 1.
+/// let imbalance = coupe::analysis::imbalance_vs_targets(&partition, &weights, &[3.0, 1.0]);
+/// assert_eq!(imbalance, 1.0);
+/// ```
+pub fn imbalance_vs_targets(partition: &[usize], weights: &[f64], target_weights: &[f64]) -> f64 {
+    assert_eq!(partition.len(), weights.len());
+
+    let mut loads = vec![0.0; target_weights.len()];
+    for (part, weight) in partition.iter().zip(weights) {
+        loads[*part] += weight;
+    }
+    let total_weight: f64 = loads.iter().sum();
+    let total_target: f64 = target_weights.iter().sum();
+    loads
+        .iter()
+        .zip(target_weights)
+        .map(|(load, target)| {
+            if *target == 0.0 {
+                return if *load == 0.0 { 0.0 } else { f64::INFINITY };
+            }
+            let expected = total_weight * target / total_target;
+            (load - expected) / expected
+        })
+        .fold(0.0, f64::max)
+}
+
+/// Whether two partitions group elements the same way, regardless of part IDs.
+///
+/// This is the case when there is a one-to-one mapping between the part IDs of
+/// `a` and those of `b` that turns `a` into `b`.  Partitions of different
+/// lengths are never equivalent.
+///
+/// # Example
+///
+/// ```rust
+/// assert!(coupe::partition_equivalent(&[0, 0, 1, 2], &[5, 5, 0, 1]));
+/// assert!(!coupe::partition_equivalent(&[0, 0, 1, 2], &[0, 0, 1, 1]));
+/// ```
+pub fn partition_equivalent(a: &[usize], b: &[usize]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut a_to_b = HashMap::new();
+    let mut b_to_a = HashMap::new();
+    a.iter().zip(b).all(|(part_a, part_b)| {
+        *a_to_b.entry(part_a).or_insert(part_b) == part_b
+            && *b_to_a.entry(part_b).or_insert(part_a) == part_a
+    })
+}
+
+/// The part of the nearest point of `points` to each query point.
+///
+/// This extends a partition to points that were not partitioned, for
+/// example the nodes of another mesh coupled with the partitioned one.  Unlike
+/// [`BisectionTree::locate`][crate::BisectionTree::locate], this works with
+/// partitions made by any algorithm.  Ties between points at the same
+/// distance are broken arbitrarily.
+///
+/// Nearest points are found with a [`KdTree`], built once, and queries are
+/// processed in parallel.
+///
+/// # Panics
+///
+/// Panics if `points` and `partition` have different lengths, or if `points`
+/// is empty while `queries` is not.
+///
+/// # Example
+///
+/// ```rust
+/// use coupe::Point2D;
+///
+/// let points = [
+///     Point2D::new(0.0, 0.0),
+///     Point2D::new(1.0, 0.0),
+///     Point2D::new(4.0, 0.0),
+///     Point2D::new(5.0, 0.0),
+/// ];
+/// let partition = [0, 0, 1, 1];
+/// let queries = [Point2D::new(-1.0, 1.0), Point2D::new(3.0, 0.5)];
+///
+/// let parts = coupe::classify_nearest(&points, &partition, &queries);
+/// assert_eq!(parts, [0, 1]);
+/// ```
+pub fn classify_nearest<const D: usize>(
+    points: &[PointND<D>],
+    partition: &[usize],
+    queries: &[PointND<D>],
+) -> Vec<usize> {
+    assert_eq!(points.len(), partition.len());
+
+    let tree = KdTree::new(points);
+    queries
+        .par_iter()
+        .map(|query| {
+            let (nearest, _distance) = tree
+                .nearest(query)
+                .expect("cannot classify points against an empty point set");
+            partition[nearest]
+        })
+        .collect()
+}
+
+/// An invariant of a partition that [`verify_partition`] found broken.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum VerifyError {
+    /// An element has not been assigned a part, that is, its part ID is
+    /// `usize::MAX`.
+    Unassigned { index: usize },
+
+    /// The partition does not have the expected number of distinct part IDs.
+    WrongPartCount { expected: usize, actual: usize },
+
+    /// No element has been assigned to a part, although higher part IDs are
+    /// used or expected.
+    EmptyPart { part: usize },
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyError::Unassigned { index } => write!(f, "element {index} has no part"),
+            VerifyError::WrongPartCount { expected, actual } => {
+                write!(f, "expected {expected} parts, got {actual}")
+            }
+            VerifyError::EmptyPart { part } => write!(f, "part {part} is empty"),
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+/// Checks that every element has been assigned a part, and that parts are
+/// numbered from zero without gaps.
+///
+/// `usize::MAX` is treated as the part ID of unassigned elements, so filling
+/// the partition with it beforehand catches algorithms that skip elements.
+/// When `expected_parts` is given, the partition must also have exactly that
+/// many distinct part IDs.
+///
+/// Checks are done in parallel, and the first invariant found broken is
+/// reported in this order: unassigned elements, part count, empty parts.
+///
+/// # Example
+///
+/// ```rust
+/// use coupe::VerifyError;
+///
+/// assert_eq!(coupe::verify_partition(&[0, 1, 1, 2], Some(3)), Ok(()));
+/// assert_eq!(
+///     coupe::verify_partition(&[0, 2, 2], None),
+///     Err(VerifyError::EmptyPart { part: 1 }),
+/// );
+/// ```
+pub fn verify_partition(
+    partition: &[usize],
+    expected_parts: Option<usize>,
+) -> Result<(), VerifyError> {
+    if let Some(index) = partition
+        .par_iter()
+        .position_first(|part| *part == usize::MAX)
+    {
+        return Err(VerifyError::Unassigned { index });
+    }
+
+    let parts = partition
+        .par_iter()
+        .fold(HashSet::new, |mut parts, part| {
+            parts.insert(*part);
+            parts
+        })
+        .reduce(HashSet::new, |mut parts0, parts1| {
+            parts0.extend(parts1);
+            parts0
+        });
+    if let Some(expected) = expected_parts {
+        if parts.len() != expected {
+            return Err(VerifyError::WrongPartCount {
+                expected,
+                actual: parts.len(),
+            });
+        }
+    }
+
+    // All part IDs are in 0..part_count exactly when none of them is
+    // missing, so the search stops after at most parts.len() steps.
+    let part_count = expected_parts.unwrap_or(parts.len());
+    if let Some(part) = (0..part_count).find(|part| !parts.contains(part)) {
+        return Err(VerifyError::EmptyPart { part });
+    }
+    Ok(())
+}
+
+/// Renumbers the parts of `new` so that they match the parts of `reference`
+/// they overlap the most.
+///
+/// Partitioners number parts arbitrarily, so two similar partitions can have
+/// very different IDs, and [`migration_cost`] then counts elements that do not
+/// need to move.  Aligning the new partition on the previous one first gives
+/// the actual number of elements to send.
+///
+/// Parts are matched greedily, the pairs of parts that share the most elements
+/// first.  Parts of `new` that are left without a match get the lowest IDs not
+/// taken by matched parts.
+///
+/// # Panics
+///
+/// Panics if `new` and `reference` have different lengths.
+///
+/// # Example
+///
+/// ```rust
+/// let reference = [0, 0, 1, 1, 2, 2];
+/// let mut new = [2, 2, 2, 0, 1, 1];
+///
+/// coupe::align_partition(&mut new, &reference);
+///
+/// assert_eq!(new, [0, 0, 0, 1, 2, 2]);
+/// assert_eq!(coupe::analysis::migration_cost(&reference, &new), 1);
+/// ```
+pub fn align_partition(new: &mut [usize], reference: &[usize]) {
+    assert_eq!(new.len(), reference.len());
+
+    let mut overlaps: HashMap<(usize, usize), usize> = HashMap::new();
+    for (new_part, reference_part) in new.iter().zip(reference) {
+        *overlaps.entry((*new_part, *reference_part)).or_default() += 1;
+    }
+    let mut overlaps: Vec<_> = overlaps.into_iter().collect();
+    overlaps.sort_unstable_by(|(parts_a, count_a), (parts_b, count_b)| {
+        count_b.cmp(count_a).then(parts_a.cmp(parts_b))
+    });
+
+    let mut relabeling = HashMap::new();
+    let mut taken = HashSet::new();
+    for ((new_part, reference_part), _) in overlaps {
+        if !relabeling.contains_key(&new_part) && !taken.contains(&reference_part) {
+            relabeling.insert(new_part, reference_part);
+            taken.insert(reference_part);
+        }
+    }
+
+    let mut unmatched: Vec<usize> = new
+        .iter()
+        .filter(|part| !relabeling.contains_key(part))
+        .copied()
+        .collect();
+    unmatched.sort_unstable();
+    unmatched.dedup();
+    let mut free_ids = (0..).filter(|id| !taken.contains(id));
+    for part in unmatched {
+        relabeling.insert(part, free_ids.next().unwrap());
+    }
+
+    for part in new {
+        *part = relabeling[part];
+    }
+}
+
+/// Fills the empty parts of `partition`, so that each of the `part_count`
+/// parts gets at least one point.
+///
+/// Some solvers require every process to have work, but partitioners can
+/// leave parts empty, for example when the number of points is not much
+/// larger than the number of parts.  Each empty part takes half of the points
+/// of the part that has the most points at that time: those on the far side
+/// of that part along its longest axis, so that both halves stay compact.
+///
+/// Parts are left empty when there are fewer points than parts.
+///
+/// # Panics
+///
+/// Panics if `partition` and `points` have different lengths, or if
+/// `partition` has part IDs greater than or equal to `part_count`.
+///
+/// # Example
+///
+/// ```rust
+/// use coupe::Point2D;
+///
+/// let points: Vec<Point2D> = (0..6).map(|i| Point2D::new(i as f64, 0.0)).collect();
+/// let mut partition = [0, 0, 0, 0, 1, 1];
+///
+/// coupe::rebalance_empty_parts(&mut partition, 3, &points);
+///
+/// assert_eq!(partition, [0, 0, 2, 2, 1, 1]);
+/// ```
+pub fn rebalance_empty_parts<const D: usize>(
+    partition: &mut [usize],
+    part_count: usize,
+    points: &[PointND<D>],
+) {
+    assert_eq!(partition.len(), points.len());
+
+    let mut part_sizes = vec![0; part_count];
+    for part in &*partition {
+        part_sizes[*part] += 1;
+    }
+
+    for empty_part in 0..part_count {
+        if part_sizes[empty_part] != 0 {
+            continue;
+        }
+        let (largest_part, largest_size) = part_sizes
+            .iter()
+            .cloned()
+            .enumerate()
+            .max_by_key(|(_, size)| *size)
+            .unwrap();
+        if largest_size < 2 {
+            return;
+        }
+
+        let mut members: Vec<usize> = (0..partition.len())
+            .filter(|i| partition[*i] == largest_part)
+            .collect();
+        let bb = par_bounding_box(members.par_iter().map(|i| points[*i])).unwrap();
+        let axis = (bb.p_max - bb.p_min).iamax();
+        members.sort_by(|a, b| f64::total_cmp(&points[*a][axis], &points[*b][axis]));
+
+        let moved_count = largest_size / 2;
+        for i in &members[largest_size - moved_count..] {
+            partition[*i] = empty_part;
+        }
+        part_sizes[largest_part] -= moved_count;
+        part_sizes[empty_part] = moved_count;
+    }
+}
+
+/// Merges adjacent parts until at most `target_part_count` remain, and
+/// returns the resulting number of parts.
+///
+/// This is the coarsening counterpart of re-partitioning each part, useful to
+/// run on fewer processes than planned without partitioning again.  At each
+/// step, the two adjacent parts with the lowest combined weight are merged, so
+/// that merged parts stay connected and as light as possible.  Ties are broken
+/// by part IDs.  Merging stops early when no two remaining parts are adjacent.
+///
+/// Parts are then renumbered from zero, in the order of their lowest original
+/// ID, and empty parts are dropped.
+///
+/// # Panics
+///
+/// Panics if `partition`, `adjacency` and `weights` do not have the same
+/// length.
+///
+/// # Example
+///
+/// ```rust
+/// use coupe::sprs::CsMat;
+///
+/// //    0   1   2   3
+/// //    +---+---+---+
+/// let adjacency = CsMat::new((4, 4), vec![0, 1, 3, 5, 6], vec![1, 0, 2, 1, 3, 2], vec![1.0; 6]);
+/// let weights = [1.0, 1.0, 3.0, 1.0];
+/// let mut partition = [0, 1, 2, 3];
+///
+/// let part_count = coupe::merge_parts(&mut partition, adjacency.view(), &weights, 2);
+///
+/// assert_eq!(part_count, 2);
+/// assert_eq!(partition, [0, 0, 1, 1]);
+/// ```
+pub fn merge_parts<T>(
+    partition: &mut [usize],
+    adjacency: T,
+    weights: &[f64],
+    target_part_count: usize,
+) -> usize
+where
+    T: Topology<f64>,
+{
+    assert_eq!(partition.len(), adjacency.len());
+    assert_eq!(partition.len(), weights.len());
+
+    let part_count = partition.iter().max().map_or(0, |max_part| max_part + 1);
+    let mut part_weights = vec![0.0; part_count];
+    let mut non_empty = vec![false; part_count];
+    for (part, weight) in partition.iter().zip(weights) {
+        part_weights[*part] += weight;
+        non_empty[*part] = true;
+    }
+    let mut neighbors = vec![BTreeSet::new(); part_count];
+    for (vertex, vertex_part) in partition.iter().enumerate() {
+        for (neighbor, _) in adjacency.neighbors(vertex) {
+            let neighbor_part = partition[neighbor];
+            if neighbor_part != *vertex_part {
+                neighbors[*vertex_part].insert(neighbor_part);
+                neighbors[neighbor_part].insert(*vertex_part);
+            }
+        }
+    }
+
+    // Each part is merged into the part of lowest ID among the two.
+    let mut merged_into: Vec<usize> = (0..part_count).collect();
+    let mut remaining = non_empty.iter().filter(|non_empty| **non_empty).count();
+    while target_part_count < remaining {
+        let lightest_pair = neighbors
+            .iter()
+            .enumerate()
+            .flat_map(|(a, a_neighbors)| a_neighbors.range(a + 1..).map(move |b| (a, *b)))
+            .min_by(|(a0, b0), (a1, b1)| {
+                let w0 = part_weights[*a0] + part_weights[*b0];
+                let w1 = part_weights[*a1] + part_weights[*b1];
+                f64::total_cmp(&w0, &w1).then((a0, b0).cmp(&(a1, b1)))
+            });
+        let Some((a, b)) = lightest_pair else {
+            break;
+        };
+
+        part_weights[a] += part_weights[b];
+        merged_into[b] = a;
+        for c in std::mem::take(&mut neighbors[b]) {
+            neighbors[c].remove(&b);
+            if c != a {
+                neighbors[c].insert(a);
+                neighbors[a].insert(c);
+            }
+        }
+        remaining -= 1;
+    }
+
+    let mut new_ids = vec![usize::MAX; part_count];
+    let mut next_id = 0;
+    for part in 0..part_count {
+        if !non_empty[part] {
+            continue;
+        }
+        let mut root = part;
+        while merged_into[root] != root {
+            root = merged_into[root];
+        }
+        if new_ids[root] == usize::MAX {
+            new_ids[root] = next_id;
+            next_id += 1;
+        }
+        new_ids[part] = new_ids[root];
+    }
+    for part in partition.iter_mut() {
+        *part = new_ids[*part];
+    }
+    next_id
+}
+
+/// The boundary vertices of each part, that is the vertices that have at least
+/// one neighbor in another part.
+///
+/// The returned vector is indexed by part ID, and lists vertices in increasing
+/// order.  In overlapping domain decomposition methods, these are the vertices
+/// around which halos are built.
+///
+/// # Example
+///
+/// ```rust
+/// use coupe::sprs::CsMat;
+///
+/// //    0   1   2   3
+/// //    +---+---+---+
+/// let adjacency = CsMat::new((4, 4), vec![0, 1, 3, 5, 6], vec![1, 0, 2, 1, 3, 2], vec![1.0; 6]);
+/// let partition = [0, 0, 1, 1];
+///
+/// let boundaries = coupe::analysis::part_boundaries(adjacency.view(), &partition);
+/// assert_eq!(boundaries, [[1], [2]]);
+/// ```
+pub fn part_boundaries<T, E>(adjacency: T, partition: &[usize]) -> Vec<Vec<usize>>
+where
+    T: Topology<E>,
+{
+    debug_assert_eq!(partition.len(), adjacency.len());
+
+    let part_count = partition.iter().max().map_or(0, |max_part| max_part + 1);
+    let mut boundaries = vec![Vec::new(); part_count];
+    for (vertex, vertex_part) in partition.iter().enumerate() {
+        if adjacency
+            .neighbors(vertex)
+            .any(|(neighbor, _)| partition[neighbor] != *vertex_part)
+        {
+            boundaries[*vertex_part].push(vertex);
+        }
+    }
+    boundaries
+}
+
+/// The total weight of the edges that leave each part.
+///
+/// Each cut edge counts for both of the parts it links, so the weights sum
+/// up to twice the [edge cut][Topology::edge_cut].  Parts with the highest
+/// values communicate the most, and are the first candidates for refinement.
+///
+/// The returned vector has one `(part, weight)` pair per part ID, in
+/// increasing order, so that it can be sorted by weight without losing track
+/// of parts.
+///
+/// # Example
+///
+/// ```rust
+/// use coupe::sprs::CsMat;
+///
+/// //    0   1   2   3
+/// //    +---+---+---+
+/// let adjacency = CsMat::new((4, 4), vec![0, 1, 3, 5, 6], vec![1, 0, 2, 1, 3, 2], vec![1.0; 6]);
+/// let partition = [0, 1, 1, 2];
+///
+/// let mut cuts = coupe::analysis::cut_per_part(adjacency.view(), &partition);
+/// cuts.sort_by(|(_, a), (_, b)| f64::total_cmp(b, a));
+/// assert_eq!(cuts[0], (1, 2.0));
+/// ```
+pub fn cut_per_part<T>(adjacency: T, partition: &[usize]) -> Vec<(usize, f64)>
+where
+    T: Topology<f64>,
+{
+    debug_assert_eq!(partition.len(), adjacency.len());
+
+    let part_count = partition.iter().max().map_or(0, |max_part| max_part + 1);
+    let mut cuts: Vec<(usize, f64)> = (0..part_count).map(|part| (part, 0.0)).collect();
+    for (vertex, vertex_part) in partition.iter().enumerate() {
+        for (neighbor, edge_weight) in adjacency.neighbors(vertex) {
+            if partition[neighbor] != *vertex_part {
+                cuts[*vertex_part].1 += edge_weight;
+            }
+        }
+    }
+    cuts
+}
+
+/// The quotient graph of a partition, also called part graph.
+///
+/// Parts are the nodes of the quotient graph, and two parts are linked when
+/// an edge of `adjacency` links them.  The weight of a quotient edge is the
+/// total weight of such edges, that is, the communication volume between the
+/// two parts.  Unlike the edge cut, this tells which parts communicate, and
+/// how much.
+///
+/// The returned matrix is symmetric if `adjacency` is, and has one row per
+/// part.  It has no diagonal entries.
+///
+/// # Example
+///
+/// ```rust
+/// use coupe::sprs::CsMat;
+///
+/// //    0   1   2   3
+/// //    +---+---+---+
+/// let adjacency = CsMat::new((4, 4), vec![0, 1, 3, 5, 6], vec![1, 0, 2, 1, 3, 2], vec![1.0; 6]);
+/// let partition = [0, 0, 1, 1];
+///
+/// let quotient = coupe::analysis::quotient_graph(adjacency.view(), &partition);
+/// assert_eq!(quotient.get(0, 1), Some(&1.0));
+/// assert_eq!(quotient.nnz(), 2);
+/// ```
+#[cfg(feature = "sprs")]
+pub fn quotient_graph<T>(adjacency: T, partition: &[usize]) -> CsMat<f64>
+where
+    T: Topology<f64>,
+{
+    debug_assert_eq!(partition.len(), adjacency.len());
+
+    let part_count = partition.iter().max().map_or(0, |max_part| max_part + 1);
+    let mut edges = BTreeMap::new();
+    for (vertex, vertex_part) in partition.iter().enumerate() {
+        for (neighbor, edge_weight) in adjacency.neighbors(vertex) {
+            let neighbor_part = partition[neighbor];
+            if *vertex_part != neighbor_part {
+                *edges.entry((*vertex_part, neighbor_part)).or_insert(0.0) += edge_weight;
+            }
+        }
+    }
+
+    let mut indptr = vec![0; part_count + 1];
+    for (part, _) in edges.keys() {
+        indptr[part + 1] += 1;
+    }
+    for part in 0..part_count {
+        indptr[part + 1] += indptr[part];
+    }
+    let (indices, data) = edges
+        .into_iter()
+        .map(|((_, neighbor_part), weight)| (neighbor_part, weight))
+        .unzip();
+
+    CsMat::new((part_count, part_count), indptr, indices, data)
+}
+
+/// Writes a quotient graph, as returned by [quotient_graph], in the GraphViz
+/// DOT format.
+///
+/// Each part is a node, and edges are labeled with their weight.  The graph
+/// is written as undirected, from the upper triangle of `quotient`.
+///
+/// # Example
+///
+/// ```rust
+/// use coupe::sprs::CsMat;
+///
+/// let quotient = CsMat::new((2, 2), vec![0, 1, 2], vec![1, 0], vec![3.0; 2]);
+///
+/// let mut dot = Vec::new();
+/// coupe::analysis::write_dot(&mut dot, quotient.view())?;
+/// assert_eq!(
+///     String::from_utf8(dot).unwrap(),
+///     "graph quotient {\n    0;\n    1;\n    0 -- 1 [label=\"3\"];\n}\n",
+/// );
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[cfg(feature = "sprs")]
+pub fn write_dot(mut w: impl io::Write, quotient: CsMatView<'_, f64>) -> io::Result<()> {
+    writeln!(w, "graph quotient {{")?;
+    for part in 0..quotient.rows() {
+        writeln!(w, "    {part};")?;
+    }
+    for (weight, (part, neighbor_part)) in quotient.iter() {
+        if part < neighbor_part {
+            writeln!(w, "    {part} -- {neighbor_part} [label=\"{weight}\"];")?;
+        }
+    }
+    writeln!(w, "}}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Point2D;
+    use crate::Point3D;
+
+    #[test]
+    fn test_evaluate_rcb_pipeline() {
+        use crate::Partition as _;
+        use std::num::NonZeroUsize;
+
+        let grid =
+            crate::Grid::new_2d(NonZeroUsize::new(8).unwrap(), NonZeroUsize::new(8).unwrap());
+        let points = crate::mesh::grid_2d(8, 8, 1.0);
+        let weights = [1.0; 64];
+        let mut partition = [0; 64];
+
+        crate::Rcb {
+            iter_count: 1,
+            ..Default::default()
+        }
+        .partition(
+            &mut partition,
+            (points.par_iter().cloned(), weights.par_iter().cloned()),
+        )
+        .unwrap();
+        let halves = evaluate(&partition, &points, &weights, grid);
+        assert_eq!(halves.part_count, 2);
+        assert_eq!(halves.imbalance, 0.0);
+        assert_eq!(halves.edge_cut, 8.0);
+        // Halves are 8x4 grids of points, whose variances along their axes
+        // are 21/4 and 5/4.
+        assert!(f64::abs(halves.max_aspect_ratio - f64::sqrt(4.2)) < 1e-9);
+
+        crate::Rcb {
+            iter_count: 2,
+            ..Default::default()
+        }
+        .partition(
+            &mut partition,
+            (points.par_iter().cloned(), weights.par_iter().cloned()),
+        )
+        .unwrap();
+        let quarters = evaluate(&partition, &points, &weights, grid);
+        assert_eq!(quarters.part_count, 4);
+        assert_eq!(quarters.imbalance, 0.0);
+        assert_eq!(quarters.edge_cut, 16.0);
+        assert!(f64::abs(quarters.max_aspect_ratio - 1.0) < 1e-9);
+    }
+
+    #[test]
+    fn test_compactness_square_and_sliver() {
+        let mut points = Vec::new();
+        let mut partition = Vec::new();
+        // part 0: a 10x10 grid of points.
+        for i in 0..10 {
+            for j in 0..10 {
+                points.push(Point2D::new(i as f64, j as f64));
+                partition.push(0);
+            }
+        }
+        // part 1: a 50x1 sliver, tilted by 30 degrees.
+        let (sin, cos) = f64::sin_cos(std::f64::consts::FRAC_PI_6);
+        for i in 0..50 {
+            for j in 0..2 {
+                let (x, y) = (i as f64, j as f64);
+                points.push(Point2D::new(100.0 + x * cos - y * sin, x * sin + y * cos));
+                partition.push(1);
+            }
+        }
+
+        let compactness = compactness(&partition, &points);
+        assert_eq!(compactness.len(), 2);
+        assert!(0.99 < compactness[0], "{compactness:?}");
+        assert!(compactness[1] < 0.2, "{compactness:?}");
+    }
+
+    #[test]
+    fn test_compactness_3d() {
+        let mut points = Vec::new();
+        let mut partition = Vec::new();
+        for (part, (x, y, z)) in [(4, 4, 4), (40, 2, 2)].into_iter().enumerate() {
+            for i in 0..x {
+                for j in 0..y {
+                    for k in 0..z {
+                        let offset = 100.0 * part as f64;
+                        points.push(Point3D::new(offset + i as f64, j as f64, k as f64));
+                        partition.push(part);
+                    }
+                }
+            }
+        }
+        // An empty part.
+        partition
+            .iter_mut()
+            .filter(|p| **p == 1)
+            .for_each(|p| *p = 2);
+
+        let compactness = compactness(&partition, &points);
+        assert_eq!(compactness.len(), 3);
+        assert!(0.99 < compactness[0], "{compactness:?}");
+        assert_eq!(compactness[1], 0.0);
+        assert!(compactness[2] < 0.1, "{compactness:?}");
+    }
+
+    #[test]
+    fn test_weighted_aspect_ratios_offset_point() {
+        // A 5x5 grid of points, and a point far on its right.
+        let mut points: Vec<Point2D> = (0..25)
+            .map(|i| Point2D::new((i % 5) as f64, (i / 5) as f64))
+            .collect();
+        points.push(Point2D::new(20.0, 2.0));
+        let partition = vec![0; points.len()];
+
+        let unweighted = weighted_aspect_ratios(&partition, &points, &[1.0; 26]);
+
+        let mut weights = [1.0; 26];
+        weights[25] = 10.0;
+        let heavy = weighted_aspect_ratios(&partition, &points, &weights);
+
+        weights[25] = 0.01;
+        let light = weighted_aspect_ratios(&partition, &points, &weights);
+
+        assert!(unweighted[0] < heavy[0], "{unweighted:?} {heavy:?}");
+        assert!(light[0] < unweighted[0], "{light:?} {unweighted:?}");
+        assert!(light[0] < 1.1, "{light:?}");
+    }
+
+    #[test]
+    fn test_weighted_aspect_ratios_degenerate() {
+        let points = [
+            Point2D::new(0.0, 0.0),
+            Point2D::new(1.0, 0.0),
+            Point2D::new(2.0, 0.0),
+            Point2D::new(5.0, 5.0),
+        ];
+        let partition = [0, 0, 0, 2];
+        let weights = [1.0; 4];
+
+        let ratios = weighted_aspect_ratios(&partition, &points, &weights);
+        assert_eq!(ratios, [f64::INFINITY, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_imbalance_per_part_skewed() {
+        // Part 0 weighs 9, part 1 weighs 2 and part 2 weighs 1, for a mean of
+        // 4.
+        let partition = [0, 1, 0, 2, 0, 1];
+        let weights = [3.0, 1.0, 5.0, 1.0, 1.0, 1.0];
+
+        let deviations = imbalance_per_part(&partition, &weights);
+        assert_eq!(deviations, [5.0, -2.0, -3.0]);
+        assert_eq!(deviations.iter().sum::<f64>(), 0.0);
+    }
+
+    #[test]
+    fn test_imbalance_vs_targets() {
+        // Loads of 6, 3 and 1 for targets of 6:3:1.
+        let partition = [0, 0, 1, 2];
+        let weights = [4.0, 2.0, 3.0, 1.0];
+        assert_eq!(
+            imbalance_vs_targets(&partition, &weights, &[6.0, 3.0, 1.0]),
+            0.0
+        );
+
+        // Part 2 is expected to receive 2.5.
+        let imbalance = imbalance_vs_targets(&partition, &weights, &[2.0, 1.0, 1.0]);
+        assert!((imbalance - 0.2).abs() < 1e-12, "{imbalance}");
+
+        // Empty parts with a zero target are balanced, others are not.
+        let partition = [0, 0, 1];
+        let weights = [1.0, 1.0, 1.0];
+        let imbalance = imbalance_vs_targets(&partition, &weights, &[2.0, 1.0, 0.0]);
+        assert_eq!(imbalance, 0.0);
+        let imbalance = imbalance_vs_targets(&partition, &weights, &[2.0, 0.0, 1.0]);
+        assert_eq!(imbalance, f64::INFINITY);
+    }
+
+    #[test]
+    fn test_partition_equivalent_relabeled() {
+        assert!(partition_equivalent(&[], &[]));
+        assert!(partition_equivalent(&[0, 1, 0, 2, 1], &[0, 1, 0, 2, 1]));
+        assert!(partition_equivalent(&[0, 1, 0, 2, 1], &[7, 3, 7, 0, 3]));
+        assert!(partition_equivalent(&[2, 2, 2], &[0, 0, 0]));
+    }
+
+    #[test]
+    fn test_partition_equivalent_different() {
+        // A part of `a` is split in `b`.
+        assert!(!partition_equivalent(&[0, 0, 1, 1], &[0, 2, 1, 1]));
+        // Two parts of `a` are merged in `b`.
+        assert!(!partition_equivalent(&[0, 0, 1, 1], &[0, 0, 0, 0]));
+        // Same grouping sizes, but not the same elements.
+        assert!(!partition_equivalent(&[0, 0, 1, 1], &[0, 1, 0, 1]));
+        assert!(!partition_equivalent(&[0, 0], &[0, 0, 0]));
+    }
+
+    #[test]
+    fn test_align_partition() {
+        let reference = [0, 0, 1, 1, 2, 2, 3, 3];
+
+        // The same partition with other IDs does not migrate anything.
+        let mut new = [3, 3, 0, 0, 7, 7, 1, 1];
+        assert_eq!(migration_cost(&reference, &new), 8);
+        align_partition(&mut new, &reference);
+        assert_eq!(new, reference);
+        assert_eq!(migration_cost(&reference, &new), 0);
+
+        // Parts left without a match get the lowest free IDs.
+        let mut new = [5, 5, 5, 5, 6, 6, 8, 9];
+        align_partition(&mut new, &reference);
+        assert_eq!(new, [0, 0, 0, 0, 2, 2, 3, 1]);
+    }
+
+    #[test]
+    fn test_merge_parts() {
+        use std::num::NonZeroUsize;
+
+        // A 16x2 grid cut into 8 strips of 2x2 vertices, numbered in a shuffled
+        // order, the heavier ones in the middle.
+        let grid = crate::Grid::new_2d(
+            NonZeroUsize::new(16).unwrap(),
+            NonZeroUsize::new(2).unwrap(),
+        );
+        let strip_ids = [3, 7, 0, 5, 1, 6, 2, 4];
+        let strip_weights = [1.0, 1.0, 2.0, 5.0, 5.0, 2.0, 1.0, 1.0];
+        let original: Vec<usize> = (0..32).map(|i| strip_ids[i % 16 / 2]).collect();
+        let weights: Vec<f64> = (0..32).map(|i| strip_weights[i % 16 / 2]).collect();
+
+        let mut partition = original.clone();
+        assert_eq!(merge_parts(&mut partition, grid, &weights, 4), 4);
+        verify_partition(&partition, Some(4)).unwrap();
+
+        // Each new part is made of neighboring strips.
+        for part in 0..4 {
+            let mut strips: Vec<usize> = (0..16)
+                .step_by(2)
+                .filter(|i| partition[*i] == part)
+                .map(|i| i / 2)
+                .collect();
+            strips.dedup();
+            assert!(strips.windows(2).all(|w| w[0] + 1 == w[1]), "{strips:?}");
+        }
+        // Light strips on the sides were merged, and the heavy ones in the
+        // middle left alone.  New IDs follow the lowest original ID of parts.
+        let strip_parts: Vec<usize> = (0..16).step_by(2).map(|i| partition[i]).collect();
+        assert_eq!(strip_parts, [0, 0, 0, 3, 1, 2, 2, 2]);
+    }
+
+    #[test]
+    fn test_rebalance_empty_parts() {
+        // 16 points on a 4x4 grid, chunked into two parts out of four, as a
+        // curve partitioner can do when parts are much smaller than cells.
+        let points = crate::mesh::grid_2d(4, 4, 1.0);
+        let mut partition: Vec<usize> = points
+            .iter()
+            .map(|point| usize::from(2.0 <= point.x))
+            .collect();
+        assert!(matches!(
+            verify_partition(&partition, Some(4)),
+            Err(VerifyError::WrongPartCount { .. }),
+        ));
+
+        rebalance_empty_parts(&mut partition, 4, &points);
+        verify_partition(&partition, Some(4)).unwrap();
+        for part in 0..4 {
+            assert_eq!(partition.iter().filter(|p| **p == part).count(), 4);
+        }
+        // Parts are split across their longest axis, which is the y axis.
+        for (point, part) in points.iter().zip(&partition) {
+            assert_eq!(*part >= 2, 2.0 <= point.y, "{point:?}");
+        }
+
+        // Fewer points than parts.
+        let mut partition = [0, 0, 0];
+        rebalance_empty_parts(&mut partition, 5, &points[..3]);
+        let mut parts = partition.to_vec();
+        parts.sort();
+        assert_eq!(parts, [0, 1, 2]);
+    }
+
+    #[cfg(feature = "sprs")]
+    #[test]
+    fn test_quotient_graph_chain() {
+        // A chain of 6 vertices, with edges of weight 1, 2, 3, 4 and 5, cut in
+        // 3 parts.
+        //
+        //   0 -1- 1 -2- 2 -3- 3 -4- 4 -5- 5
+        //   `-----´     `-----´     `-----´
+        let adjacency = CsMat::new(
+            (6, 6),
+            vec![0, 1, 3, 5, 7, 9, 10],
+            vec![1, 0, 2, 1, 3, 2, 4, 3, 5, 4],
+            vec![1.0, 1.0, 2.0, 2.0, 3.0, 3.0, 4.0, 4.0, 5.0, 5.0],
+        );
+        let partition = [0, 0, 1, 1, 2, 2];
+
+        let quotient = quotient_graph(adjacency.view(), &partition);
+        assert_eq!(quotient.shape(), (3, 3));
+        assert_eq!(quotient.nnz(), 4);
+        assert_eq!(quotient.get(0, 1), Some(&2.0));
+        assert_eq!(quotient.get(1, 0), Some(&2.0));
+        assert_eq!(quotient.get(1, 2), Some(&4.0));
+        assert_eq!(quotient.get(2, 1), Some(&4.0));
+        assert_eq!(quotient.get(0, 2), None);
+
+        let mut dot = Vec::new();
+        write_dot(&mut dot, quotient.view()).unwrap();
+        let dot = String::from_utf8(dot).unwrap();
+        assert_eq!(
+            dot,
+            "graph quotient {
+    0;
+    1;
+    2;
+    0 -- 1 [label=\"2\"];
+    1 -- 2 [label=\"4\"];
+}
+",
+        );
+    }
+
+    #[cfg(feature = "sprs")]
+    #[test]
+    fn test_part_boundaries_grid() {
+        // A 2x6 grid, split between its third and fourth columns.
+        //
+        //   0 - 1 - 2 | 3 - 4 - 5
+        //   |   |   | | |   |   |
+        //   6 - 7 - 8 | 9 - 10- 11
+        let mut adjacency = sprs::TriMat::new((12, 12));
+        for vertex in 0..12 {
+            if vertex % 6 != 5 {
+                adjacency.add_triplet(vertex, vertex + 1, 1.0);
+                adjacency.add_triplet(vertex + 1, vertex, 1.0);
+            }
+            if vertex < 6 {
+                adjacency.add_triplet(vertex, vertex + 6, 1.0);
+                adjacency.add_triplet(vertex + 6, vertex, 1.0);
+            }
+        }
+        let adjacency: CsMat<f64> = adjacency.to_csr();
+        let partition: Vec<usize> = (0..12).map(|vertex| usize::from(3 <= vertex % 6)).collect();
+
+        let boundaries = part_boundaries(adjacency.view(), &partition);
+        assert_eq!(boundaries, [[2, 8], [3, 9]]);
+    }
+
+    #[cfg(feature = "sprs")]
+    #[test]
+    fn test_cut_per_part_chain() {
+        // A chain of 6 vertices split in 3 parts, with heavier edges between
+        // parts.
+        //
+        //   0 - 1 = 2 - 3 = 4 - 5
+        let mut adjacency = sprs::TriMat::new((6, 6));
+        for vertex in 0..5 {
+            let weight = if vertex % 2 == 1 { 3.0 } else { 1.0 };
+            adjacency.add_triplet(vertex, vertex + 1, weight);
+            adjacency.add_triplet(vertex + 1, vertex, weight);
+        }
+        let adjacency: CsMat<f64> = adjacency.to_csr();
+        let partition = [0, 0, 1, 1, 2, 2];
+
+        let cuts = cut_per_part(adjacency.view(), &partition);
+        assert_eq!(cuts, [(0, 3.0), (1, 6.0), (2, 3.0)]);
+
+        let total: f64 = cuts.iter().map(|(_, weight)| weight).sum();
+        assert_eq!(total, 2.0 * adjacency.view().edge_cut(&partition));
+    }
+
+    #[test]
+    fn test_classify_nearest() {
+        // Two clusters, whose centroids are (0, 0) and (10, 0), but part 0
+        // spreads towards part 1.
+        let points = [
+            Point2D::new(-1.0, 0.0),
+            Point2D::new(0.0, 0.0),
+            Point2D::new(1.0, 0.0),
+            Point2D::new(6.0, 0.0),
+            Point2D::new(9.0, 0.0),
+            Point2D::new(10.0, 0.0),
+            Point2D::new(11.0, 0.0),
+        ];
+        let partition = [0, 0, 0, 0, 1, 1, 1];
+        let queries = [
+            // closer to the centroid of part 1, but to a point of part 0
+            Point2D::new(7.0, 1.0),
+            Point2D::new(8.0, -1.0),
+            Point2D::new(-5.0, 3.0),
+        ];
+
+        let parts = classify_nearest(&points, &partition, &queries);
+        assert_eq!(parts, [0, 1, 0]);
+
+        assert!(classify_nearest::<2>(&[], &[], &[]).is_empty());
+    }
+
+    #[test]
+    fn test_verify_partition() {
+        assert_eq!(verify_partition(&[], None), Ok(()));
+        assert_eq!(verify_partition(&[], Some(0)), Ok(()));
+        assert_eq!(verify_partition(&[2, 0, 1, 1], Some(3)), Ok(()));
+        assert_eq!(
+            verify_partition(&[0, 1, usize::MAX, 1, usize::MAX], Some(2)),
+            Err(VerifyError::Unassigned { index: 2 }),
+        );
+    }
+
+    #[test]
+    fn test_verify_partition_empty_part() {
+        // parts 1 and 3 have no element
+        let partition = [0, 2, 2, 4, 0];
+        assert_eq!(
+            verify_partition(&partition, None),
+            Err(VerifyError::EmptyPart { part: 1 }),
+        );
+        assert_eq!(
+            verify_partition(&partition, Some(3)),
+            Err(VerifyError::EmptyPart { part: 1 }),
+        );
+    }
+
+    #[test]
+    fn test_verify_partition_wrong_count() {
+        let partition = [0, 1, 1, 0, 2];
+        assert_eq!(
+            verify_partition(&partition, Some(4)),
+            Err(VerifyError::WrongPartCount {
+                expected: 4,
+                actual: 3,
+            }),
+        );
+        assert_eq!(
+            verify_partition(&partition, Some(2)),
+            Err(VerifyError::WrongPartCount {
+                expected: 2,
+                actual: 3,
+            }),
+        );
+        assert_eq!(
+            verify_partition(&[], Some(1)),
+            Err(VerifyError::WrongPartCount {
+                expected: 1,
+                actual: 0,
+            }),
+        );
+    }
+
+    #[test]
+    fn test_imbalance_per_part_empty() {
+        assert!(imbalance_per_part(&[], &[]).is_empty());
+    }
+}
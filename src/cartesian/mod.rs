@@ -391,6 +391,41 @@ mod tests {
         assert_eq!(Topology::<usize>::lambda_cut(&g, &partition, weights), 10);
     }
 
+    #[test]
+    fn test_grid_connected_components_per_part() {
+        let side = NonZeroUsize::new(3).unwrap();
+        let g = Grid::new_2d(side, side);
+
+        // Grid ids and partition:
+        //
+        //     0 -- 1 -- 2    A -- A    B
+        //     |    |    |    |
+        //     3 -- 4 -- 5    A    B    A
+        //     |    |    |         |    |
+        //     6 -- 7 -- 8    B -- B    A
+        //
+        let partition = [0, 0, 1, 0, 1, 0, 1, 1, 0];
+        let components = Topology::<usize>::connected_components_per_part(&g, &partition);
+        assert_eq!(components, [2, 2]);
+
+        // Grid ids and partition:
+        //
+        //     0 -- 1 -- 2    A -- A -- A
+        //     |    |    |
+        //     3 -- 4 -- 5    B    C -- C
+        //     |    |    |    |         |
+        //     6 -- 7 -- 8    B -- B    C
+        //
+        let partition = [0, 0, 0, 1, 2, 2, 1, 1, 2];
+        let components = Topology::<usize>::connected_components_per_part(&g, &partition);
+        assert_eq!(components, [1, 1, 1]);
+
+        // Part 1 is empty.
+        let partition = [0, 0, 0, 0, 2, 2, 0, 0, 2];
+        let components = Topology::<usize>::connected_components_per_part(&g, &partition);
+        assert_eq!(components, [1, 0, 1]);
+    }
+
     #[test]
     fn test_split_at() {
         let side = NonZeroUsize::new(6).unwrap();
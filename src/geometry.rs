@@ -8,175 +8,119 @@ use nalgebra::DefaultAllocator;
 use nalgebra::DimDiff;
 use nalgebra::DimSub;
 use nalgebra::SMatrix;
-use nalgebra::SVector;
+use num_traits::ToPrimitive;
 use rayon::prelude::*;
+use std::borrow::Cow;
 
-pub type Point2D = SVector<f64, 2>;
-pub type Point3D = SVector<f64, 3>;
-pub type PointND<const D: usize> = SVector<f64, D>;
+pub mod affine;
+pub mod spatial;
+
+// With the `serde` feature, points implement `Serialize` and `Deserialize` as
+// flat arrays of coordinates, through nalgebra's own implementation.
+pub use coupe_core::geometry::BoundingBox;
+pub use coupe_core::geometry::Point2D;
+pub use coupe_core::geometry::Point3D;
+pub use coupe_core::geometry::PointND;
 pub type Matrix<const D: usize> = SMatrix<f64, D, D>;
 
-/// Axis-aligned bounding box.
+/// Same as [`BoundingBox::from_points`], computed in parallel.
+pub(crate) fn par_bounding_box<const D: usize, P>(points: P) -> Option<BoundingBox<D>>
+where
+    P: IntoParallelIterator<Item = PointND<D>>,
+    P::Iter: IndexedParallelIterator,
+{
+    let points = points.into_par_iter();
+    if points.len() == 0 {
+        return None;
+    }
+    let (p_min, p_max) = points
+        .fold_with(
+            (
+                PointND::<D>::from_element(f64::MAX),
+                PointND::<D>::from_element(f64::MIN),
+            ),
+            |(mut mins, mut maxs), vals| {
+                for ((min, max), val) in mins.iter_mut().zip(maxs.iter_mut()).zip(&vals) {
+                    if *val < *min {
+                        *min = *val;
+                    }
+                    if *max < *val {
+                        *max = *val;
+                    }
+                }
+                (mins, maxs)
+            },
+        )
+        .reduce_with(|(mins_left, maxs_left), (mins_right, maxs_right)| {
+            (
+                PointND::<D>::from_iterator(
+                    mins_left
+                        .into_iter()
+                        .zip(&mins_right)
+                        .map(|(left, right)| left.min(*right)),
+                ),
+                PointND::<D>::from_iterator(
+                    maxs_left
+                        .into_iter()
+                        .zip(&maxs_right)
+                        .map(|(left, right)| left.max(*right)),
+                ),
+            )
+        })
+        .unwrap(); // fold_with yields at least one element.
+    Some(BoundingBox { p_min, p_max })
+}
+
+/// Bounding sphere (disk in 2D).
 #[derive(Debug, Clone)]
-pub struct BoundingBox<const D: usize> {
-    pub p_min: PointND<D>,
-    pub p_max: PointND<D>,
+pub struct BoundingSphere<const D: usize> {
+    center: PointND<D>,
+    radius: f64,
 }
 
-impl<const D: usize> BoundingBox<D> {
-    /// The axis-aligned *minimum* bounding box.
+impl<const D: usize> BoundingSphere<D> {
+    /// A sphere that contains all given points.
     ///
-    /// This is the smallest rectangle (rectangular cuboid in 3D) that both
-    /// contains all given points and is aligned with the axises.
+    /// This uses Ritter's approximation, which runs in linear time, and yields
+    /// a sphere that is usually 5% to 20% larger than the minimum one.
     ///
-    /// Returns `None` iff the given iterator is empty.
-    pub fn from_points<P>(points: P) -> Option<Self>
-    where
-        P: IntoParallelIterator<Item = PointND<D>>,
-        P::Iter: IndexedParallelIterator,
-    {
-        let points = points.into_par_iter();
-        if points.len() == 0 {
-            return None;
+    /// Returns `None` iff `points` is empty.
+    pub fn from_points(points: &[PointND<D>]) -> Option<Self> {
+        let first = points.first()?;
+        let farthest_from = |origin: &PointND<D>| {
+            *points
+                .par_iter()
+                .max_by(|a, b| f64::total_cmp(&(*a - origin).norm(), &(*b - origin).norm()))
+                .unwrap()
+        };
+        let a = farthest_from(first);
+        let b = farthest_from(&a);
+
+        let mut center = (a + b) / 2.0;
+        let mut radius = (b - a).norm() / 2.0;
+        for point in points {
+            let distance = (point - center).norm();
+            if radius < distance {
+                let new_radius = (radius + distance) / 2.0;
+                center += (point - center) * (distance - new_radius) / distance;
+                radius = new_radius;
+            }
         }
-        let (p_min, p_max) = points
-            .fold_with(
-                (
-                    PointND::<D>::from_element(std::f64::MAX),
-                    PointND::<D>::from_element(std::f64::MIN),
-                ),
-                |(mut mins, mut maxs), vals| {
-                    for ((min, max), val) in mins.iter_mut().zip(maxs.iter_mut()).zip(&vals) {
-                        if *val < *min {
-                            *min = *val;
-                        }
-                        if *max < *val {
-                            *max = *val;
-                        }
-                    }
-                    (mins, maxs)
-                },
-            )
-            .reduce_with(|(mins_left, maxs_left), (mins_right, maxs_right)| {
-                (
-                    PointND::<D>::from_iterator(
-                        mins_left
-                            .into_iter()
-                            .zip(&mins_right)
-                            .map(|(left, right)| left.min(*right)),
-                    ),
-                    PointND::<D>::from_iterator(
-                        maxs_left
-                            .into_iter()
-                            .zip(&maxs_right)
-                            .map(|(left, right)| left.max(*right)),
-                    ),
-                )
-            })
-            .unwrap(); // fold_with yields at least one element.
-        Some(Self { p_min, p_max })
+
+        Some(Self { center, radius })
     }
 
     pub fn center(&self) -> PointND<D> {
-        (self.p_min + self.p_max) / 2.0
+        self.center
     }
 
-    // region = bdim...b2b1b0 where bi are bits (0 or 1)
-    // if bi is set (i.e. bi == 1) then the matching region has a i-th coordinates from center[i] to p_max[i]
-    // otherwise, the matching region has a i-th coordinates from p_min[i] to center[i]
-    pub fn sub_aabb(&self, region: u32) -> Self {
-        assert!(
-            region < 2u32.pow(D as u32),
-            "Wrong region. Region should be composed of dim bits."
-        );
-
-        let center = self.center();
-
-        let p_min =
-            PointND::<D>::from_iterator(self.p_min.iter().zip(center.iter()).enumerate().map(
-                |(i, (min, center))| {
-                    if (region >> i) & 1 == 0 {
-                        *min
-                    } else {
-                        *center
-                    }
-                },
-            ));
-
-        let p_max =
-            PointND::<D>::from_iterator(center.iter().zip(self.p_max.iter()).enumerate().map(
-                |(i, (center, max))| {
-                    if (region >> i) & 1 == 0 {
-                        *center
-                    } else {
-                        *max
-                    }
-                },
-            ));
-
-        Self { p_min, p_max }
+    pub fn radius(&self) -> f64 {
+        self.radius
     }
 
     pub fn contains(&self, point: &PointND<D>) -> bool {
-        let eps = 10. * std::f64::EPSILON;
-        self.p_min
-            .iter()
-            .zip(self.p_max.iter())
-            .zip(point.iter())
-            .all(|((min, max), point)| *point < *max + eps && *point > *min - eps)
-    }
-
-    pub fn distance_to_point(&self, point: &PointND<D>) -> f64 {
-        if !self.contains(point) {
-            let clamped = PointND::<D>::from_iterator(
-                self.p_min
-                    .iter()
-                    .zip(self.p_max.iter())
-                    .zip(point.iter())
-                    .map(|((min, max), point)| {
-                        if point > max {
-                            *max
-                        } else if point < min {
-                            *min
-                        } else {
-                            *point
-                        }
-                    }),
-            );
-            clamped.norm()
-        } else {
-            let center = self.center();
-
-            self.p_min
-                .iter()
-                .zip(self.p_max.iter())
-                .zip(point.iter())
-                .zip(center.into_iter())
-                .map(|(((min, max), point), center)| {
-                    if point > center {
-                        (max - point).abs()
-                    } else {
-                        (min - point).abs()
-                    }
-                })
-                .max_by(|a, b| a.partial_cmp(b).unwrap())
-                .unwrap()
-        }
-    }
-
-    pub fn region(&self, point: &PointND<D>) -> Option<u32> {
-        if !self.contains(point) {
-            return None;
-        }
-
-        let mut ret: u32 = 0;
-        let center = self.center();
-        for (i, (point, center)) in point.iter().zip(center.into_iter()).enumerate() {
-            if point > center {
-                ret |= 1 << i;
-            }
-        }
-        Some(ret)
+        let eps = 10. * f64::EPSILON * f64::max(1.0, self.radius);
+        (point - self.center).norm() <= self.radius + eps
     }
 }
 
@@ -204,6 +148,13 @@ impl<const D: usize> OrientedBoundingBox<D> {
         self.obb_to_aabb * point
     }
 
+    /// The axes of the bounding box, as the columns of a matrix.
+    ///
+    /// The first axis is the inertia axis of the points.
+    pub fn axes(&self) -> &Matrix<D> {
+        &self.aabb_to_obb
+    }
+
     /// The arbitrarily-oriented *minimum* bounding box.
     ///
     /// The smallest box that contains all given points.
@@ -213,12 +164,23 @@ impl<const D: usize> OrientedBoundingBox<D> {
         DefaultAllocator: Allocator<f64, Const<D>, Const<D>, Buffer = ArrayStorage<f64, D, D>>
             + Allocator<f64, DimDiff<Const<D>, Const<1>>>,
     {
-        let mat = inertia_matrix(points);
+        Self::from_inertia_matrix(points, inertia_matrix(points))
+    }
+
+    /// Same as [`OrientedBoundingBox::from_points`], with the inertia matrix
+    /// of the points already computed.
+    pub(crate) fn from_inertia_matrix(points: &[PointND<D>], mat: Matrix<D>) -> Option<Self>
+    where
+        Const<D>: DimSub<Const<1>>,
+        DefaultAllocator: Allocator<f64, Const<D>, Const<D>, Buffer = ArrayStorage<f64, D, D>>
+            + Allocator<f64, DimDiff<Const<D>, Const<1>>>,
+    {
         let vec = inertia_vector(mat);
         let aabb_to_obb = householder_reflection(&vec);
-        let obb_to_aabb = aabb_to_obb.try_inverse().unwrap();
+        // Householder reflections are orthogonal.
+        let obb_to_aabb = aabb_to_obb.transpose();
         let mapped = points.par_iter().map(|p| obb_to_aabb * p);
-        let aabb = BoundingBox::from_points(mapped)?;
+        let aabb = par_bounding_box(mapped)?;
 
         Some(Self {
             aabb,
@@ -228,8 +190,9 @@ impl<const D: usize> OrientedBoundingBox<D> {
     }
 
     /// Constructs a new Mbr that is a sub-Mbr of the current one.
-    /// More precisely, it bounds exactly the specified quadrant.
-    pub fn sub_mbr(&self, region: u32) -> Self {
+    /// More precisely, it bounds exactly the specified region, as numbered by
+    /// [`OrientedBoundingBox::region`].
+    pub fn sub_region(&self, region: u32) -> Self {
         Self {
             aabb: self.aabb.sub_aabb(region),
             aabb_to_obb: self.aabb_to_obb,
@@ -238,10 +201,27 @@ impl<const D: usize> OrientedBoundingBox<D> {
     }
 
     /// Computes the distance between a point and the current bounding box.
+    #[allow(unused)]
     pub fn distance_to_point(&self, point: &PointND<D>) -> f64 {
         self.aabb.distance_to_point(&(self.obb_to_aabb * point))
     }
 
+    /// The volume of the box (its area in 2D).
+    ///
+    /// The box being only rotated, this is the volume of the underlying
+    /// axis-aligned bounding box.
+    #[allow(unused)]
+    pub fn volume(&self) -> f64 {
+        self.aabb.volume()
+    }
+
+    /// The total measure of the faces of the box (its perimeter in 2D, its
+    /// surface area in 3D).
+    #[allow(unused)]
+    pub fn surface_area(&self) -> f64 {
+        self.aabb.surface_area()
+    }
+
     /// Computes the center of the Mbr
     #[allow(unused)]
     pub fn center(&self) -> PointND<D> {
@@ -270,17 +250,49 @@ impl<const D: usize> OrientedBoundingBox<D> {
     }
 }
 
-fn inertia_matrix<const D: usize>(points: &[PointND<D>]) -> Matrix<D> {
+pub(crate) fn inertia_matrix<const D: usize>(points: &[PointND<D>]) -> Matrix<D> {
     let centroid: PointND<D> = points.par_iter().sum();
     let centroid: PointND<D> = centroid / points.len() as f64;
 
+    // Each rayon task sums the contributions of its chunk of points into its
+    // own matrix, then these partial sums are added together.
+    points
+        .par_iter()
+        .fold(Matrix::zeros, |mut inertia, point| {
+            let offset = point - centroid;
+            inertia += offset * offset.transpose();
+            inertia
+        })
+        .reduce(Matrix::zeros, |a, b| a + b)
+}
+
+/// Same as [inertia_matrix], with each point counted according to its weight.
+///
+/// Falls back to [inertia_matrix] when the total weight is not positive.
+pub(crate) fn weighted_inertia_matrix<const D: usize, W>(
+    points: &[PointND<D>],
+    weights: &[W],
+) -> Matrix<D>
+where
+    W: ToPrimitive + Sync,
+{
+    let weights = || weights.par_iter().map(|w| w.to_f64().unwrap_or(0.0));
+    let total_weight: f64 = weights().sum();
+    if total_weight <= 0.0 {
+        return inertia_matrix(points);
+    }
+    let centroid: PointND<D> = points.par_iter().zip(weights()).map(|(p, w)| p * w).sum();
+    let centroid = centroid / total_weight;
+
     points
         .par_iter()
-        .map(|point| {
+        .zip(weights())
+        .fold(Matrix::zeros, |mut inertia, (point, weight)| {
             let offset = point - centroid;
-            offset * offset.transpose()
+            inertia += offset * offset.transpose() * weight;
+            inertia
         })
-        .sum()
+        .reduce(Matrix::zeros, |a, b| a + b)
 }
 
 pub(crate) fn inertia_vector<const D: usize>(mat: Matrix<D>) -> PointND<D>
@@ -324,12 +336,371 @@ pub(crate) fn canonical_vector<const D: usize>(nth: usize) -> PointND<D> {
     ret
 }
 
-pub(crate) fn center<const D: usize>(points: &[PointND<D>]) -> PointND<D> {
+/// The centroid (arithmetic mean) of the given points.
+///
+/// # Panics
+///
+/// Panics if `points` is empty.
+///
+/// # Example
+///
+/// ```rust
+/// use coupe::Point2D;
+///
+/// let points = [
+///     Point2D::new(0.0, 0.0),
+///     Point2D::new(2.0, 0.0),
+///     Point2D::new(2.0, 4.0),
+///     Point2D::new(0.0, 4.0),
+/// ];
+/// assert_eq!(coupe::center(&points), Point2D::new(1.0, 2.0));
+/// ```
+pub fn center<const D: usize>(points: &[PointND<D>]) -> PointND<D> {
     assert!(!points.is_empty());
     let total = points.len() as f64;
     points.par_iter().sum::<PointND<D>>() / total
 }
 
+/// The centroid of the given points, where each point counts as much as its
+/// weight.
+///
+/// # Panics
+///
+/// Panics if `points` and `weights` have different lengths, or if the weights
+/// sum to zero.
+///
+/// # Example
+///
+/// ```rust
+/// use coupe::Point2D;
+///
+/// let points = [Point2D::new(0.0, 0.0), Point2D::new(4.0, 0.0)];
+/// let weights = [3.0, 1.0];
+/// assert_eq!(coupe::weighted_center(&points, &weights), Point2D::new(1.0, 0.0));
+/// ```
+pub fn weighted_center<const D: usize>(points: &[PointND<D>], weights: &[f64]) -> PointND<D> {
+    assert_eq!(points.len(), weights.len());
+    let (sum, total_weight) = points
+        .par_iter()
+        .zip(weights)
+        .map(|(point, weight)| (point * *weight, *weight))
+        .reduce(
+            || (PointND::zeros(), 0.0),
+            |(sum1, weight1), (sum2, weight2)| (sum1 + sum2, weight1 + weight2),
+        );
+    assert!(total_weight != 0.0, "weights sum to zero");
+    sum / total_weight
+}
+
+/// The geometric median of the given points, where each point counts as much
+/// as its weight.
+///
+/// The geometric median is the point that minimizes the weighted sum of the
+/// euclidean distances to the given points.  Unlike the centroid, it is robust
+/// to outliers.  It is approximated with Weiszfeld's algorithm, starting from
+/// the weighted centroid.
+///
+/// # Panics
+///
+/// Panics if `points` and `weights` have different lengths, or if the weights
+/// sum to zero.
+///
+/// # Example
+///
+/// ```rust
+/// use coupe::Point2D;
+///
+/// let points = [
+///     Point2D::new(0.0, 0.0),
+///     Point2D::new(1.0, 0.0),
+///     Point2D::new(0.0, 1.0),
+///     Point2D::new(1.0, 1.0),
+///     Point2D::new(100.0, 100.0),
+/// ];
+/// let weights = [1.0; 5];
+/// let median = coupe::weighted_geometric_median(&points, &weights);
+/// assert!(median.x < 2.0 && median.y < 2.0);
+/// ```
+pub fn weighted_geometric_median<const D: usize>(
+    points: &[PointND<D>],
+    weights: &[f64],
+) -> PointND<D> {
+    const MAX_ITER: usize = 200;
+
+    let mut median = weighted_center(points, weights);
+    let scale = points
+        .iter()
+        .map(|point| (point - median).norm())
+        .fold(0.0, f64::max);
+    for _ in 0..MAX_ITER {
+        let (sum, total_weight) = points
+            .par_iter()
+            .zip(weights)
+            .filter_map(|(point, weight)| {
+                // Points that coincide with the current estimate are skipped,
+                // as their contribution is undefined.
+                let distance = (point - median).norm();
+                if distance <= f64::EPSILON * scale {
+                    return None;
+                }
+                Some((point * (*weight / distance), *weight / distance))
+            })
+            .reduce(
+                || (PointND::zeros(), 0.0),
+                |(sum1, weight1), (sum2, weight2)| (sum1 + sum2, weight1 + weight2),
+            );
+        if total_weight == 0.0 {
+            break;
+        }
+        let next = sum / total_weight;
+        let step = (next - median).norm();
+        median = next;
+        if step <= 1e-9 * scale {
+            break;
+        }
+    }
+    median
+}
+
+/// The geometric median of the given points.
+///
+/// See [weighted_geometric_median].
+///
+/// # Panics
+///
+/// Panics if `points` is empty.
+pub fn geometric_median<const D: usize>(points: &[PointND<D>]) -> PointND<D> {
+    assert!(!points.is_empty());
+    weighted_geometric_median(points, &vec![1.0; points.len()])
+}
+
+/// Builds points from a flat array of coordinates, `D` coordinates per point,
+/// like the ones given by most mesh formats and C APIs.
+///
+/// This is a safe and dimension-generic alternative to casting the pointer of
+/// the array.  A single point can be built with
+/// [`PointND::from_column_slice`][nalgebra::SVector::from_column_slice].
+///
+/// In debug builds, panics if the length of `coordinates` is not a multiple
+/// of `D`.  In release builds, trailing coordinates are ignored.
+///
+/// # Example
+///
+/// ```rust
+/// use coupe::Point3D;
+///
+/// let coordinates = [0.0, 1.0, 2.0, 3.0, 4.0, 5.0];
+/// let points = coupe::points_from_flat::<3>(&coordinates);
+///
+/// assert_eq!(points, [Point3D::new(0.0, 1.0, 2.0), Point3D::new(3.0, 4.0, 5.0)]);
+/// ```
+pub fn points_from_flat<const D: usize>(coordinates: &[f64]) -> Vec<PointND<D>> {
+    debug_assert_eq!(
+        coordinates.len() % D,
+        0,
+        "coordinate count is not a multiple of the dimension",
+    );
+    coordinates
+        .chunks_exact(D)
+        .map(PointND::from_column_slice)
+        .collect()
+}
+
+/// Drops the trailing coordinates of `point`, keeping its first `D2` ones.
+///
+/// This is the orthogonal projection onto the space spanned by the first `D2`
+/// axes, e.g. onto the xy plane when going from 3D to 2D, so that a 2D
+/// algorithm can be run on a 3D mesh that is flat or nearly so.
+///
+/// # Panics
+///
+/// Panics if `D2` is greater than `D`.
+///
+/// # Example
+///
+/// ```rust
+/// use coupe::{Point2D, Point3D};
+///
+/// let point = Point3D::new(1.0, 2.0, 3.0);
+/// assert_eq!(coupe::project::<3, 2>(point), Point2D::new(1.0, 2.0));
+/// ```
+pub fn project<const D: usize, const D2: usize>(point: PointND<D>) -> PointND<D2> {
+    assert!(D2 <= D, "cannot project a {D}D point onto {D2} dimensions");
+    PointND::from_fn(|axis, _| point[axis])
+}
+
+/// Pads `point` with trailing coordinates equal to `fill`, up to `D2`
+/// coordinates.
+///
+/// This is the inverse of [project] for the coordinates it keeps.
+///
+/// # Panics
+///
+/// Panics if `D2` is lower than `D`.
+///
+/// # Example
+///
+/// ```rust
+/// use coupe::{Point2D, Point3D};
+///
+/// let point = Point2D::new(1.0, 2.0);
+/// assert_eq!(coupe::lift::<2, 3>(point, 0.0), Point3D::new(1.0, 2.0, 0.0));
+/// ```
+pub fn lift<const D: usize, const D2: usize>(point: PointND<D>, fill: f64) -> PointND<D2> {
+    assert!(D <= D2, "cannot lift a {D}D point to {D2} dimensions");
+    PointND::from_fn(|axis, _| if axis < D { point[axis] } else { fill })
+}
+
+/// Maps the coordinates of `point` along periodic axes into their period.
+///
+/// `periodic` is indexed by axis.  `Some((min, max))` marks an axis along which
+/// space wraps around, `min` and `max` being the same position, as in a mesh
+/// with periodic boundary conditions.  Coordinates along that axis are mapped
+/// into `[min, max)`, so that points that are one period apart end up at the
+/// same position.  Other axes, including those past the end of `periodic`, are
+/// left as-is.
+pub(crate) fn wrap_periodic<const D: usize>(
+    mut point: PointND<D>,
+    periodic: &[Option<(f64, f64)>],
+) -> PointND<D> {
+    for (coordinate, period) in point.iter_mut().zip(periodic) {
+        if let Some((min, max)) = period {
+            *coordinate = min + (*coordinate - min).rem_euclid(max - min);
+        }
+    }
+    point
+}
+
+/// Same as [wrap_periodic], for all given points, after the seam of each
+/// periodic axis has been moved by [periodic_seams].  Points are only copied
+/// if an axis is periodic.
+pub(crate) fn wrap_periodic_points<'p, const D: usize>(
+    points: &'p [PointND<D>],
+    periodic: &[Option<(f64, f64)>],
+) -> Cow<'p, [PointND<D>]> {
+    if periodic.iter().all(Option::is_none) {
+        return Cow::Borrowed(points);
+    }
+    let periodic = periodic_seams(points.par_iter().cloned(), periodic);
+    points
+        .par_iter()
+        .map(|point| wrap_periodic(*point, &periodic))
+        .collect::<Vec<_>>()
+        .into()
+}
+
+/// The index of the first period that is empty or not finite.
+pub(crate) fn invalid_period(periodic: &[Option<(f64, f64)>]) -> Option<usize> {
+    periodic.iter().position(|period| match period {
+        Some((min, max)) => !(min.is_finite() && max.is_finite() && min < max),
+        None => false,
+    })
+}
+
+/// Shifts each period so that it starts in the middle of the widest gap
+/// between the coordinates of the given points along its axis.
+///
+/// Once wrapped, points on both sides of the start of a period are as far
+/// apart as can be, so partitioners never put them in the same part: the
+/// start of the period is a cut they do not choose.  Moving it where points
+/// are the sparsest makes this cut cross as few mesh edges as possible, and
+/// lets parts span the boundary of the domain.  Periods are left as-is when
+/// their start already is in the widest gap.
+///
+/// Periods must be valid, see [invalid_period].
+pub(crate) fn periodic_seams<const D: usize, P>(
+    points: P,
+    periodic: &[Option<(f64, f64)>],
+) -> Vec<Option<(f64, f64)>>
+where
+    P: IndexedParallelIterator<Item = PointND<D>> + Clone,
+{
+    periodic
+        .iter()
+        .enumerate()
+        .map(|(axis, period)| {
+            let (min, max) = (*period)?;
+            if D <= axis {
+                return Some((min, max));
+            }
+            let length = max - min;
+            let mut coordinates: Vec<f64> = points
+                .clone()
+                .map(|point| min + (point[axis] - min).rem_euclid(length))
+                .collect();
+            coordinates.par_sort_unstable_by(f64::total_cmp);
+            let (first, last) = match (coordinates.first(), coordinates.last()) {
+                (Some(first), Some(last)) => (*first, *last),
+                _ => return Some((min, max)),
+            };
+            let mut seam = min;
+            let mut widest_gap = first + length - last;
+            for pair in coordinates.windows(2) {
+                let gap = pair[1] - pair[0];
+                if widest_gap < gap {
+                    widest_gap = gap;
+                    seam = (pair[0] + pair[1]) / 2.0;
+                }
+            }
+            Some((seam, seam + length))
+        })
+        .collect()
+}
+
+/// Runs a partitioner on two blocks of 6 columns of points, in a domain that
+/// wraps around along x, the first block being split by the boundary of the
+/// domain, and checks that this block shares no part with the other one.
+///
+/// `partition` is given the periods of the axes.  Returns the error it gives
+/// for a NaN period.
+#[cfg(test)]
+pub(crate) fn check_periodic_seam<M, E, F>(mut partition: F) -> E
+where
+    M: std::fmt::Debug,
+    E: std::fmt::Debug,
+    F: FnMut(&mut [usize], &[Point2D], &[f64], Vec<Option<(f64, f64)>>) -> Result<M, E>,
+{
+    let columns = [0.0, 0.25, 0.5, 9.0, 9.25, 9.5, 4.0, 4.2, 4.4, 4.6, 4.8, 5.0];
+    let points: Vec<Point2D> = columns
+        .iter()
+        .flat_map(|x| (0..4).map(move |y| Point2D::new(*x, y as f64)))
+        .collect();
+    let weights = vec![1.0; points.len()];
+    let mut parts = vec![0; points.len()];
+
+    partition(&mut parts, &points, &weights, vec![Some((0.0, 10.0)), None]).unwrap();
+    let (wrapping, other) = parts.split_at(6 * 4);
+    assert!(
+        wrapping.iter().all(|part| !other.contains(part)),
+        "{parts:?}"
+    );
+
+    partition(&mut parts, &points, &weights, vec![Some((0.0, f64::NAN))]).unwrap_err()
+}
+
+/// The coordinate-wise median of the given points.
+///
+/// When there is an even number of points, the mean of the two middle values
+/// is used.
+pub(crate) fn median<const D: usize>(points: &[PointND<D>]) -> PointND<D> {
+    assert!(!points.is_empty());
+    let mid = points.len() / 2;
+    let mut values = vec![0.0; points.len()];
+    PointND::from_fn(|coord, _| {
+        for (value, point) in values.iter_mut().zip(points) {
+            *value = point[coord];
+        }
+        let (lower, median, _) = values.select_nth_unstable_by(mid, f64::total_cmp);
+        let median = *median;
+        if 2 * mid == points.len() {
+            let lower_max = lower.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+            (lower_max + median) / 2.0
+        } else {
+            median
+        }
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -360,6 +731,44 @@ mod tests {
         BoundingBox::<2>::from_points([]).unwrap();
     }
 
+    #[test]
+    fn test_bounding_sphere_2d() {
+        let points = [
+            Point2D::from([1., 1.]),
+            Point2D::from([-1., 1.]),
+            Point2D::from([0., 0.]),
+            Point2D::from([1., -1.]),
+            Point2D::from([-1., -1.]),
+        ];
+
+        let sphere = BoundingSphere::from_points(&points).unwrap();
+
+        assert_ulps_eq!(sphere.center(), Point2D::from([0., 0.]));
+        assert_ulps_eq!(sphere.radius(), f64::sqrt(2.));
+        assert!(points.iter().all(|p| sphere.contains(p)));
+        assert!(!sphere.contains(&Point2D::from([1.5, 0.])));
+    }
+
+    #[test]
+    fn test_bounding_sphere_3d() {
+        let points = [
+            Point3D::from([2., 0., 0.]),
+            Point3D::from([-2., 0., 0.]),
+            Point3D::from([0., 1., 0.]),
+            Point3D::from([0., 0., -1.5]),
+            Point3D::from([1., 1., 1.]),
+        ];
+
+        let sphere = BoundingSphere::from_points(&points).unwrap();
+
+        assert_ulps_eq!(sphere.center(), Point3D::from([0., 0., 0.]));
+        assert_ulps_eq!(sphere.radius(), 2.);
+        assert!(points.iter().all(|p| sphere.contains(p)));
+        assert!(!sphere.contains(&Point3D::from([0., 0., 2.1])));
+
+        assert!(BoundingSphere::<3>::from_points(&[]).is_none());
+    }
+
     #[test]
     fn test_inertia_matrix() {
         let points = [
@@ -374,6 +783,38 @@ mod tests {
         assert_ulps_eq!(mat, expected);
     }
 
+    #[test]
+    fn test_inertia_matrix_sequential() {
+        // Enough points for rayon to split the accumulation in several chunks.
+        let points: Vec<Point3D> = (0..100_000).map(|_| Point3D::new_random()).collect();
+        let weights: Vec<f64> = (0..points.len()).map(|i| (i % 7) as f64).collect();
+
+        let centroid = points.iter().sum::<Point3D>() / points.len() as f64;
+        let expected: Matrix<3> = points
+            .iter()
+            .map(|point| (point - centroid) * (point - centroid).transpose())
+            .sum();
+        assert_relative_eq!(inertia_matrix(&points), expected, max_relative = 1e-10);
+
+        let total_weight: f64 = weights.iter().sum();
+        let centroid = points
+            .iter()
+            .zip(&weights)
+            .map(|(point, weight)| point * *weight)
+            .sum::<Point3D>()
+            / total_weight;
+        let expected: Matrix<3> = points
+            .iter()
+            .zip(&weights)
+            .map(|(point, weight)| (point - centroid) * (point - centroid).transpose() * *weight)
+            .sum();
+        assert_relative_eq!(
+            weighted_inertia_matrix(&points, &weights),
+            expected,
+            max_relative = 1e-10,
+        );
+    }
+
     #[test]
     fn test_inertia_vector_2d() {
         let points = [
@@ -457,6 +898,197 @@ mod tests {
         assert!(q4.is_some());
     }
 
+    fn check_sub_regions<const D: usize>(points: &[PointND<D>])
+    where
+        Const<D>: DimSub<Const<1>>,
+        DefaultAllocator: Allocator<f64, Const<D>, Const<D>, Buffer = ArrayStorage<f64, D, D>>
+            + Allocator<f64, DimDiff<Const<D>, Const<1>>>,
+    {
+        let obb = OrientedBoundingBox::from_points(points).unwrap();
+        let sub_regions: Vec<_> = (0..1 << D).map(|r| obb.sub_region(r)).collect();
+
+        let sub_volume: f64 = sub_regions.iter().map(|sub| sub.volume()).sum();
+        assert_relative_eq!(sub_volume, obb.volume(), epsilon = 1e-9);
+        for point in points {
+            let region = obb.region(point).unwrap();
+            assert!(sub_regions[region as usize].contains(point));
+            assert_eq!(sub_regions[region as usize].axes(), obb.axes());
+        }
+    }
+
+    #[test]
+    fn test_obb_sub_region() {
+        check_sub_regions(&[
+            Point2D::from([0., 1.]),
+            Point2D::from([1., 0.]),
+            Point2D::from([5., 6.]),
+            Point2D::from([6., 5.]),
+            Point2D::from([2., 3.]),
+        ]);
+        check_sub_regions(&[
+            Point3D::from([0., 1., 0.]),
+            Point3D::from([1., 0., 1.]),
+            Point3D::from([5., 6., 0.]),
+            Point3D::from([6., 5., 1.]),
+            Point3D::from([2., 3., 0.5]),
+        ]);
+    }
+
+    #[test]
+    fn test_center() {
+        let points = [
+            Point2D::new(-1.0, 0.0),
+            Point2D::new(3.0, 0.0),
+            Point2D::new(1.0, 6.0),
+        ];
+        assert_ulps_eq!(center(&points), Point2D::new(1.0, 2.0));
+
+        let points = [
+            Point3D::new(0.0, 0.0, 0.0),
+            Point3D::new(2.0, 0.0, 0.0),
+            Point3D::new(0.0, 2.0, 0.0),
+            Point3D::new(0.0, 0.0, 2.0),
+        ];
+        assert_ulps_eq!(center(&points), Point3D::new(0.5, 0.5, 0.5));
+    }
+
+    #[test]
+    fn test_weighted_center() {
+        let points = [
+            Point2D::new(-1.0, 0.0),
+            Point2D::new(3.0, 0.0),
+            Point2D::new(1.0, 6.0),
+        ];
+        assert_ulps_eq!(weighted_center(&points, &[1.0, 1.0, 1.0]), center(&points));
+        assert_ulps_eq!(
+            weighted_center(&points, &[0.0, 1.0, 3.0]),
+            Point2D::new(1.5, 4.5),
+        );
+
+        let points = [
+            Point3D::new(0.0, 0.0, 0.0),
+            Point3D::new(2.0, 0.0, 0.0),
+            Point3D::new(0.0, 2.0, 0.0),
+            Point3D::new(0.0, 0.0, 2.0),
+        ];
+        assert_ulps_eq!(
+            weighted_center(&points, &[5.0, 1.0, 2.0, 0.0]),
+            Point3D::new(0.25, 0.5, 0.0),
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_weighted_center_zero_weights() {
+        weighted_center(&[Point2D::new(1.0, 1.0)], &[0.0]);
+    }
+
+    #[test]
+    fn test_volume_and_surface_area_2d() {
+        let points = [
+            Point2D::new(1.0, -1.0),
+            Point2D::new(4.0, -1.0),
+            Point2D::new(4.0, 1.0),
+            Point2D::new(1.0, 1.0),
+        ];
+        let aabb = BoundingBox::from_points(points).unwrap();
+        assert_ulps_eq!(aabb.volume(), 6.0);
+        assert_ulps_eq!(aabb.surface_area(), 10.0);
+
+        // The same rectangle, rotated by 30 degrees.
+        let rotation = nalgebra::Rotation2::new(std::f64::consts::FRAC_PI_6);
+        let rotated: Vec<Point2D> = points.iter().map(|p| rotation * p).collect();
+        let obb = OrientedBoundingBox::from_points(&rotated).unwrap();
+        assert_relative_eq!(obb.volume(), 6.0, epsilon = 1e-9);
+        assert_relative_eq!(obb.surface_area(), 10.0, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn test_volume_and_surface_area_3d() {
+        let aabb = BoundingBox {
+            p_min: Point3D::new(0.0, 0.0, 0.0),
+            p_max: Point3D::new(1.0, 2.0, 3.0),
+        };
+        assert_ulps_eq!(aabb.volume(), 6.0);
+        assert_ulps_eq!(aabb.surface_area(), 22.0);
+
+        let points: Vec<Point3D> = (0..8)
+            .map(|corner| {
+                Point3D::new(
+                    (corner & 1) as f64,
+                    2.0 * (corner >> 1 & 1) as f64,
+                    3.0 * (corner >> 2 & 1) as f64,
+                )
+            })
+            .collect();
+        // Only the first axis of an oriented box follows the points, so keep
+        // the box axis-aligned here.
+        let obb = OrientedBoundingBox::from_points(&points).unwrap();
+        assert_relative_eq!(obb.volume(), 6.0, epsilon = 1e-9);
+        assert_relative_eq!(obb.surface_area(), 22.0, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn test_median() {
+        let points = [
+            Point2D::from([3., 10.]),
+            Point2D::from([-1., 0.]),
+            Point2D::from([100., 2.]),
+        ];
+        assert_ulps_eq!(median(&points), Point2D::from([3., 2.]));
+
+        let points = [
+            Point2D::from([3., 10.]),
+            Point2D::from([-1., 0.]),
+            Point2D::from([100., 2.]),
+            Point2D::from([0., 4.]),
+        ];
+        assert_ulps_eq!(median(&points), Point2D::from([1.5, 3.]));
+    }
+
+    #[test]
+    fn test_geometric_median() {
+        // The geometric median of the vertices of a square is its center.
+        let points = [
+            Point2D::new(0.0, 0.0),
+            Point2D::new(2.0, 0.0),
+            Point2D::new(2.0, 2.0),
+            Point2D::new(0.0, 2.0),
+        ];
+        assert_relative_eq!(geometric_median(&points), Point2D::new(1.0, 1.0));
+
+        // The weighted geometric median of two points is the heaviest one.
+        let points = [Point2D::new(0.0, 0.0), Point2D::new(4.0, 0.0)];
+        let weights = [3.0, 1.0];
+        assert_relative_eq!(
+            weighted_geometric_median(&points, &weights),
+            Point2D::new(0.0, 0.0),
+            epsilon = 1e-6,
+        );
+    }
+
+    #[test]
+    fn test_geometric_median_outliers() {
+        // A 10x10 cluster centered on (4.5, 4.5), and a few far outliers.
+        let mut points: Vec<Point2D> = (0..100)
+            .map(|i| Point2D::new((i % 10) as f64, (i / 10) as f64))
+            .collect();
+        points.extend([
+            Point2D::new(1000.0, 1000.0),
+            Point2D::new(1000.0, -1000.0),
+            Point2D::new(2000.0, 0.0),
+        ]);
+        let cluster_center = Point2D::new(4.5, 4.5);
+
+        let mean_drift = (center(&points) - cluster_center).norm();
+        let median_drift = (geometric_median(&points) - cluster_center).norm();
+        assert!(median_drift < 1.0, "{median_drift}");
+        assert!(
+            100.0 * median_drift < mean_drift,
+            "{median_drift} {mean_drift}"
+        );
+    }
+
     #[test]
     fn test_householder_reflexion() {
         let el = PointND::<6>::new_random();
@@ -549,4 +1181,107 @@ mod tests {
         // change is unspecified
         assert_eq!(8, octants.iter().unique().count());
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let points = vec![
+            Point2D::from([0.0, 1.5]),
+            Point2D::from([-2.25, 1e-300]),
+            Point2D::from([f64::MAX, f64::MIN_POSITIVE]),
+        ];
+        let partition = vec![0_usize, 2, 1];
+
+        let json = serde_json::to_string(&(&points, &partition)).unwrap();
+        let (loaded_points, loaded_partition): (Vec<Point2D>, Vec<usize>) =
+            serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded_points, points);
+        assert_eq!(loaded_partition, partition);
+
+        let point = Point3D::from([1.0, 2.0, 3.0]);
+        let json = serde_json::to_string(&point).unwrap();
+        assert_eq!(json, "[1.0,2.0,3.0]");
+        assert_eq!(serde_json::from_str::<Point3D>(&json).unwrap(), point);
+
+        let point = PointND::<5>::from([1.0, -1.0, 0.5, 0.0, 4.0]);
+        let json = serde_json::to_string(&point).unwrap();
+        assert_eq!(serde_json::from_str::<PointND<5>>(&json).unwrap(), point);
+        assert!(serde_json::from_str::<PointND<4>>(&json).is_err());
+    }
+
+    #[test]
+    fn test_points_from_flat() {
+        let coordinates: Vec<f64> = (0..12).map(f64::from).collect();
+
+        let points = points_from_flat::<2>(&coordinates);
+        assert_eq!(points.len(), 6);
+        assert_eq!(points[0], Point2D::new(0.0, 1.0));
+        assert_eq!(points[5], Point2D::new(10.0, 11.0));
+
+        let points = points_from_flat::<3>(&coordinates);
+        assert_eq!(points.len(), 4);
+        assert_eq!(points[0], Point3D::new(0.0, 1.0, 2.0));
+        assert_eq!(points[3], Point3D::new(9.0, 10.0, 11.0));
+
+        assert!(points_from_flat::<3>(&[]).is_empty());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic]
+    fn test_points_from_flat_len_mismatch() {
+        points_from_flat::<3>(&[0.0; 7]);
+    }
+
+    #[test]
+    fn test_project_lift() {
+        let point = Point3D::new(1.0, -2.0, 3.0);
+        let projected: Point2D = project(point);
+        assert_eq!(projected, Point2D::new(1.0, -2.0));
+
+        let lifted: Point3D = lift(projected, 7.0);
+        assert_eq!(lifted, Point3D::new(1.0, -2.0, 7.0));
+        assert_eq!(project::<3, 2>(lifted), projected);
+
+        assert_eq!(project::<3, 3>(point), point);
+        assert_eq!(lift::<3, 3>(point, 7.0), point);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_project_to_higher_dimension() {
+        project::<2, 3>(Point2D::new(0.0, 0.0));
+    }
+
+    #[test]
+    fn test_periodic_seams() {
+        let periodic = [Some((0.0, 10.0)), None];
+        assert_eq!(invalid_period(&periodic), None);
+        assert_eq!(invalid_period(&[None, Some((1.0, 1.0))]), Some(1));
+        assert_eq!(invalid_period(&[Some((0.0, f64::INFINITY))]), Some(0));
+
+        // Points are the sparsest between 2 and 5, where the seam goes.
+        let points = [
+            Point2D::new(0.0, 0.0),
+            Point2D::new(2.0, 0.0),
+            Point2D::new(5.0, 0.0),
+            Point2D::new(7.0, 0.0),
+            Point2D::new(19.0, 0.0),
+        ];
+        assert_eq!(
+            periodic_seams(points.par_iter().cloned(), &periodic),
+            [Some((3.5, 13.5)), None],
+        );
+        let wrapped = wrap_periodic_points(&points, &periodic);
+        assert_eq!(wrapped[0], Point2D::new(10.0, 0.0));
+        assert_eq!(wrapped[1], Point2D::new(12.0, 0.0));
+        assert_eq!(wrapped[4], Point2D::new(9.0, 0.0));
+
+        // The seam already is in the widest gap.
+        let points = [Point2D::new(3.0, 0.0), Point2D::new(6.0, 0.0)];
+        assert_eq!(
+            periodic_seams(points.par_iter().cloned(), &periodic),
+            [Some((0.0, 10.0)), None],
+        );
+    }
 }
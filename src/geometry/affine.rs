@@ -0,0 +1,126 @@
+//! Affine transformations of point clouds.
+//!
+//! Aligning a mesh with the coordinate axes beforehand lets axis-aligned
+//! algorithms like [`Rcb`][crate::Rcb] find cuts as good as those of
+//! [`Rib`][crate::Rib], without the cost of computing inertia axes at each
+//! partitioning.
+
+use super::Matrix;
+use super::PointND;
+use rayon::prelude::*;
+
+/// Applies `matrix` to each point, around the origin.
+///
+/// `matrix` is expected to be a rotation matrix, although any linear map is
+/// applied as-is.
+///
+/// # Example
+///
+/// ```rust
+/// use coupe::nalgebra::Rotation2;
+/// use coupe::Point2D;
+///
+/// let points = [Point2D::new(1.0, 0.0), Point2D::new(0.0, 2.0)];
+/// let rotation = Rotation2::new(std::f64::consts::FRAC_PI_2);
+///
+/// let rotated = coupe::affine::rotate(&points, rotation.matrix());
+///
+/// assert!((rotated[0] - Point2D::new(0.0, 1.0)).norm() < 1e-12);
+/// assert!((rotated[1] - Point2D::new(-2.0, 0.0)).norm() < 1e-12);
+/// ```
+pub fn rotate<const D: usize>(points: &[PointND<D>], matrix: &Matrix<D>) -> Vec<PointND<D>> {
+    points.par_iter().map(|point| matrix * point).collect()
+}
+
+/// Adds `vector` to each point.
+///
+/// # Example
+///
+/// ```rust
+/// use coupe::Point2D;
+///
+/// let points = [Point2D::new(1.0, 0.0), Point2D::new(0.0, 2.0)];
+///
+/// let translated = coupe::affine::translate(&points, &Point2D::new(-1.0, 1.0));
+///
+/// assert_eq!(translated, [Point2D::new(0.0, 1.0), Point2D::new(-1.0, 3.0)]);
+/// ```
+pub fn translate<const D: usize>(points: &[PointND<D>], vector: &PointND<D>) -> Vec<PointND<D>> {
+    points.par_iter().map(|point| point + vector).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Point2D;
+    use crate::Point3D;
+
+    fn assert_close<const D: usize>(actual: &[PointND<D>], expected: &[PointND<D>]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).norm() < 1e-12, "{a:?} != {e:?}");
+        }
+    }
+
+    #[test]
+    fn test_rotate_2d() {
+        let points = [
+            Point2D::new(1.0, 0.0),
+            Point2D::new(1.0, 1.0),
+            Point2D::new(-2.0, 3.0),
+        ];
+        // a quarter turn counter-clockwise maps (x, y) to (-y, x)
+        let matrix = Matrix::<2>::new(0.0, -1.0, 1.0, 0.0);
+
+        let rotated = rotate(&points, &matrix);
+
+        assert_close(
+            &rotated,
+            &[
+                Point2D::new(0.0, 1.0),
+                Point2D::new(-1.0, 1.0),
+                Point2D::new(-3.0, -2.0),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_rotate_3d() {
+        let points = [Point3D::new(1.0, 2.0, 3.0), Point3D::new(0.0, -1.0, 5.0)];
+        // a quarter turn around z maps (x, y, z) to (-y, x, z)
+        let matrix = Matrix::<3>::new(0.0, -1.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0);
+
+        let rotated = rotate(&points, &matrix);
+
+        assert_close(
+            &rotated,
+            &[Point3D::new(-2.0, 1.0, 3.0), Point3D::new(1.0, 0.0, 5.0)],
+        );
+    }
+
+    #[test]
+    fn test_translate() {
+        let points = [Point2D::new(1.0, 0.0), Point2D::new(-1.0, 4.0)];
+        let translated = translate(&points, &Point2D::new(0.5, -1.0));
+        assert_eq!(
+            translated,
+            [Point2D::new(1.5, -1.0), Point2D::new(-0.5, 3.0)]
+        );
+
+        let points = [Point3D::new(1.0, 2.0, 3.0)];
+        let translated = translate(&points, &Point3D::new(-1.0, -2.0, -3.0));
+        assert_eq!(translated, [Point3D::zeros()]);
+    }
+
+    #[test]
+    fn test_rotate_then_translate_is_inverted() {
+        let points = [Point3D::new(1.0, 2.0, 3.0), Point3D::new(-4.0, 0.5, 2.0)];
+        let rotation = nalgebra::Rotation3::from_euler_angles(0.3, -1.2, 2.0);
+        let offset = Point3D::new(3.0, -1.0, 7.0);
+
+        let moved = translate(&rotate(&points, rotation.matrix()), &offset);
+        let back = rotate(&translate(&moved, &-offset), rotation.inverse().matrix());
+
+        assert_close(&back, &points);
+    }
+}
@@ -0,0 +1,270 @@
+//! Spatial indexes, to speed up proximity queries on sets of points.
+
+use super::PointND;
+
+/// Below this number of points, sub-trees are built sequentially.
+const SEQUENTIAL_BUILD_THRESHOLD: usize = 4096;
+
+/// A static k-d tree, for nearest neighbor and range queries.
+///
+/// The tree is stored implicitly: the points of a sub-tree are stored
+/// contiguously, and its root is in the middle, the points on the left and on
+/// the right of the root being those of the left and right sub-trees.  Each
+/// level splits points along the axis on which they are the most spread out.
+///
+/// Queries return the index of the points in the slice given to
+/// [`KdTree::new`].
+///
+/// # Example
+///
+/// ```rust
+/// use coupe::KdTree;
+/// use coupe::Point2D;
+///
+/// let points = [
+///     Point2D::new(0.0, 0.0),
+///     Point2D::new(4.0, 0.0),
+///     Point2D::new(0.0, 4.0),
+///     Point2D::new(4.0, 4.0),
+/// ];
+/// let tree = KdTree::new(&points);
+///
+/// let (nearest, distance) = tree.nearest(&Point2D::new(3.0, 1.0)).unwrap();
+/// assert_eq!(nearest, 1);
+/// assert_eq!(distance, f64::sqrt(2.0));
+///
+/// let mut neighbors = tree.within_radius(&Point2D::new(2.0, 0.0), 2.0);
+/// neighbors.sort();
+/// assert_eq!(neighbors, [0, 1]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct KdTree<const D: usize> {
+    points: Vec<PointND<D>>,
+    indices: Vec<usize>,
+    axes: Vec<usize>,
+}
+
+fn build<const D: usize>(points: &mut [(PointND<D>, usize)], axes: &mut [usize]) {
+    if points.len() <= 1 {
+        return;
+    }
+
+    let (mut min, mut max) = (points[0].0, points[0].0);
+    for (point, _) in &points[1..] {
+        min = min.inf(point);
+        max = max.sup(point);
+    }
+    let axis = (max - min).imax();
+
+    let mid = points.len() / 2;
+    points.select_nth_unstable_by(mid, |(p, _), (q, _)| f64::total_cmp(&p[axis], &q[axis]));
+    axes[mid] = axis;
+
+    let (left, right) = points.split_at_mut(mid);
+    let (left_axes, right_axes) = axes.split_at_mut(mid);
+    let right = &mut right[1..];
+    let right_axes = &mut right_axes[1..];
+    if mid < SEQUENTIAL_BUILD_THRESHOLD / 2 {
+        build(left, left_axes);
+        build(right, right_axes);
+    } else {
+        rayon::join(|| build(left, left_axes), || build(right, right_axes));
+    }
+}
+
+impl<const D: usize> KdTree<D> {
+    /// Build a k-d tree over the given points, in parallel.
+    pub fn new(points: &[PointND<D>]) -> Self {
+        let mut items: Vec<(PointND<D>, usize)> = points.iter().cloned().zip(0..).collect();
+        let mut axes = vec![0; points.len()];
+        build(&mut items, &mut axes);
+        let (points, indices) = items.into_iter().unzip();
+        Self {
+            points,
+            indices,
+            axes,
+        }
+    }
+
+    /// The number of points in the tree.
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    /// Whether the tree has no points.
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// The index of the point nearest to `point`, along with its euclidean
+    /// distance to `point`, or `None` if the tree is empty.
+    pub fn nearest(&self, point: &PointND<D>) -> Option<(usize, f64)> {
+        let mut best = [(f64::INFINITY, usize::MAX)];
+        self.nearest_by(point, &mut best, 1.0, &|_, p| (p - point).norm());
+        let [(distance, index)] = best;
+        (index != usize::MAX).then_some((index, distance))
+    }
+
+    /// The indices of the points whose euclidean distance to `point` is at most
+    /// `radius`, in no particular order.
+    pub fn within_radius(&self, point: &PointND<D>, radius: f64) -> Vec<usize> {
+        let mut found = Vec::new();
+        self.within_radius_rec(0, self.len(), point, radius, &mut found);
+        found
+    }
+
+    fn within_radius_rec(
+        &self,
+        lo: usize,
+        hi: usize,
+        point: &PointND<D>,
+        radius: f64,
+        found: &mut Vec<usize>,
+    ) {
+        if hi <= lo {
+            return;
+        }
+        let mid = lo + (hi - lo) / 2;
+        let root = &self.points[mid];
+        if (root - point).norm() <= radius {
+            found.push(self.indices[mid]);
+        }
+        let axis = self.axes[mid];
+        let diff = point[axis] - root[axis];
+        if diff <= radius {
+            self.within_radius_rec(lo, mid, point, radius, found);
+        }
+        if -radius <= diff {
+            self.within_radius_rec(mid + 1, hi, point, radius, found);
+        }
+    }
+
+    /// Finds the `best.len()` points that minimize `distance`, sorted by
+    /// increasing distance, as `(distance, index)` pairs.
+    ///
+    /// `best` is initialized by the caller, usually with
+    /// `(f64::INFINITY, usize::MAX)` pairs: only points closer than those
+    /// already in `best` are kept.
+    ///
+    /// `distance(index, p)` may be any function such that
+    /// `lower_bound_scale * |point[axis] - p[axis]|` is lower than
+    /// `distance(index, p)` for all axes, like euclidean, manhattan or
+    /// Chebyshev distances to `point`, multiplied by factors no lower than
+    /// `lower_bound_scale`.
+    pub(crate) fn nearest_by<F>(
+        &self,
+        point: &PointND<D>,
+        best: &mut [(f64, usize)],
+        lower_bound_scale: f64,
+        distance: &F,
+    ) where
+        F: Fn(usize, &PointND<D>) -> f64,
+    {
+        self.nearest_by_rec(0, self.len(), point, best, lower_bound_scale, distance);
+    }
+
+    fn nearest_by_rec<F>(
+        &self,
+        lo: usize,
+        hi: usize,
+        point: &PointND<D>,
+        best: &mut [(f64, usize)],
+        lower_bound_scale: f64,
+        distance: &F,
+    ) where
+        F: Fn(usize, &PointND<D>) -> f64,
+    {
+        if hi <= lo {
+            return;
+        }
+        let mid = lo + (hi - lo) / 2;
+        let root = &self.points[mid];
+        let index = self.indices[mid];
+
+        let root_distance = distance(index, root);
+        if let Some(rank) = best.iter().position(|(d, _)| root_distance < *d) {
+            best[rank..].rotate_right(1);
+            best[rank] = (root_distance, index);
+        }
+
+        let axis = self.axes[mid];
+        let diff = point[axis] - root[axis];
+        let (near, far) = if diff < 0.0 {
+            ((lo, mid), (mid + 1, hi))
+        } else {
+            ((mid + 1, hi), (lo, mid))
+        };
+        self.nearest_by_rec(near.0, near.1, point, best, lower_bound_scale, distance);
+        if f64::abs(diff) * lower_bound_scale < best[best.len() - 1].0 {
+            self.nearest_by_rec(far.0, far.1, point, best, lower_bound_scale, distance);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Point3D;
+    use rand::Rng as _;
+    use rand::SeedableRng as _;
+
+    fn random_points(rng: &mut impl rand::Rng, count: usize) -> Vec<Point3D> {
+        (0..count)
+            .map(|_| Point3D::new(rng.gen(), rng.gen(), rng.gen()))
+            .collect()
+    }
+
+    #[test]
+    fn test_nearest() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+        let points = random_points(&mut rng, 10_000);
+        let tree = KdTree::new(&points);
+        assert_eq!(tree.len(), points.len());
+
+        for query in random_points(&mut rng, 200) {
+            let (expected, expected_distance) = points
+                .iter()
+                .map(|p| (p - query).norm())
+                .enumerate()
+                .min_by(|(_, d1), (_, d2)| f64::total_cmp(d1, d2))
+                .unwrap();
+            let (nearest, distance) = tree.nearest(&query).unwrap();
+            assert_eq!(distance, expected_distance);
+            assert_eq!(nearest, expected);
+        }
+    }
+
+    #[test]
+    fn test_within_radius() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+        let points = random_points(&mut rng, 1_000);
+        let tree = KdTree::new(&points);
+
+        for query in random_points(&mut rng, 50) {
+            let radius = rng.gen_range(0.0..0.5);
+            let expected: Vec<usize> = (0..points.len())
+                .filter(|i| (points[*i] - query).norm() <= radius)
+                .collect();
+            let mut found = tree.within_radius(&query, radius);
+            found.sort();
+            assert_eq!(found, expected);
+        }
+    }
+
+    #[test]
+    fn test_empty() {
+        let tree = KdTree::<2>::new(&[]);
+        assert!(tree.is_empty());
+        assert_eq!(tree.nearest(&PointND::zeros()), None);
+        assert!(tree.within_radius(&PointND::zeros(), 1.0).is_empty());
+    }
+
+    #[test]
+    fn test_duplicate_points() {
+        let points = [Point3D::new(1.0, 1.0, 1.0); 17];
+        let tree = KdTree::new(&points);
+        let (_, distance) = tree.nearest(&Point3D::new(1.0, 1.0, 2.0)).unwrap();
+        assert_eq!(distance, 1.0);
+        assert_eq!(tree.within_radius(&Point3D::zeros(), 2.0).len(), 17);
+    }
+}
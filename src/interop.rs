@@ -0,0 +1,9 @@
+//! Adapters that expose third-party partitioners through coupe's [`Partition`]
+//! trait.
+//!
+//! Each adapter lives behind a cargo feature of the same name.
+//!
+//! [`Partition`]: crate::Partition
+
+#[cfg(feature = "scotch")]
+pub mod scotch;
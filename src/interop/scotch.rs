@@ -0,0 +1,211 @@
+//! Bindings to [SCOTCH](https://gitlab.inria.fr/scotch/scotch)'s graph
+//! partitioning routines.
+
+use scotch::graph::Data;
+use sprs::CsMatView;
+use std::borrow::Cow;
+use std::fmt;
+
+/// SCOTCH's integer type, used for vertex weights.
+pub use scotch::Num;
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// Input sets don't have matching lengths.
+    InputLenMismatch { expected: usize, actual: usize },
+
+    /// SCOTCH failed to build the graph or to partition it.
+    ///
+    /// SCOTCH usually prints the reason on standard error.
+    Scotch(scotch::Error),
+}
+
+impl From<scotch::Error> for Error {
+    fn from(err: scotch::Error) -> Self {
+        Self::Scotch(err)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InputLenMismatch { expected, actual } => write!(
+                f,
+                "input sets don't have the same length (expected {expected} items, got {actual})",
+            ),
+            Error::Scotch(_) => write!(f, "SCOTCH returned an error"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Scotch(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// # SCOTCH's default graph partitioning strategy
+///
+/// Partitions a graph into `part_count` parts with SCOTCH's default strategy,
+/// mapping it onto a complete graph architecture.
+///
+/// SCOTCH only supports integer weights and a single criterion. Edge weights
+/// are rounded to the nearest integer, and raised to 1 if lower.
+///
+/// Like other partitioners, this does nothing when `part_count` is zero.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # fn main() -> Result<(), coupe::interop::scotch::Error> {
+/// use coupe::interop::scotch::ScotchStandard;
+/// use coupe::Partition as _;
+/// use coupe::sprs::CsMat;
+///
+/// //    0  1  2  3
+/// //    4  5  6  7
+/// let mut adjacency = CsMat::empty(coupe::sprs::CSR, 8);
+/// for (a, b) in [(0, 1), (1, 2), (2, 3), (4, 5), (5, 6), (6, 7), (0, 4), (1, 5), (2, 6), (3, 7)] {
+///     adjacency.insert(a, b, 1.0);
+///     adjacency.insert(b, a, 1.0);
+/// }
+/// let weights = [1; 8];
+/// let mut partition = [0; 8];
+///
+/// ScotchStandard { part_count: 2 }
+///     .partition(&mut partition, (adjacency.view(), &weights))?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct ScotchStandard {
+    pub part_count: usize,
+}
+
+impl crate::Name for ScotchStandard {
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed("scotch_standard")
+    }
+}
+
+impl<'a, W> crate::Partition<(CsMatView<'a, f64>, W)> for ScotchStandard
+where
+    W: AsRef<[Num]>,
+{
+    type Metadata = ();
+    type Error = Error;
+
+    fn partition(
+        &mut self,
+        part_ids: &mut [usize],
+        (adjacency, weights): (CsMatView<'_, f64>, W),
+    ) -> Result<Self::Metadata, Self::Error> {
+        let weights = weights.as_ref();
+        if part_ids.len() != weights.len() {
+            return Err(Error::InputLenMismatch {
+                expected: part_ids.len(),
+                actual: weights.len(),
+            });
+        }
+        if part_ids.len() != adjacency.rows() {
+            return Err(Error::InputLenMismatch {
+                expected: part_ids.len(),
+                actual: adjacency.rows(),
+            });
+        }
+        if part_ids.is_empty() || self.part_count == 0 {
+            return Ok(());
+        }
+
+        let (xadj, adjncy, adjwgt) = adjacency.to_csr().into_raw_storage();
+        let xadj: Vec<Num> = xadj.iter().map(|i| *i as Num).collect();
+        let adjncy: Vec<Num> = adjncy.iter().map(|i| *i as Num).collect();
+        let adjwgt: Vec<Num> = adjwgt
+            .iter()
+            .map(|w| f64::max(1.0, w.round()) as Num)
+            .collect();
+
+        let graph_data = Data::new(0, &xadj, &[], weights, &[], &adjncy, &adjwgt);
+        let mut graph = scotch::Graph::build(&graph_data)?;
+        graph.check()?;
+
+        let mut strategy = scotch::Strategy::new();
+        let architecture = scotch::Architecture::complete(self.part_count as Num);
+        let mut scotch_partition = vec![0; part_ids.len()];
+        graph
+            .mapping(&architecture, &mut scotch_partition)
+            .compute(&mut strategy)?;
+
+        for (dst, src) in part_ids.iter_mut().zip(&scotch_partition) {
+            *dst = *src as usize;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Partition as _;
+    use crate::Topology as _;
+    use sprs::CsMat;
+
+    #[test]
+    fn test_partition_len() {
+        let side = 8;
+        let vertex_count = side * side;
+        let mut adjacency = CsMat::empty(sprs::CSR, vertex_count);
+        for x in 0..side {
+            for y in 0..side {
+                let v = x * side + y;
+                if x + 1 < side {
+                    adjacency.insert(v, v + side, 1.0);
+                    adjacency.insert(v + side, v, 1.0);
+                }
+                if y + 1 < side {
+                    adjacency.insert(v, v + 1, 1.0);
+                    adjacency.insert(v + 1, v, 1.0);
+                }
+            }
+        }
+        let weights = vec![1; vertex_count];
+        let mut partition = vec![0; vertex_count];
+
+        ScotchStandard { part_count: 4 }
+            .partition(&mut partition, (adjacency.view(), &weights))
+            .unwrap();
+
+        assert_eq!(partition.len(), vertex_count);
+        assert!(partition.iter().all(|part| *part < 4));
+        assert!(adjacency.view().edge_cut(&partition) < vertex_count as f64);
+    }
+
+    #[test]
+    fn test_len_mismatch() {
+        let adjacency = CsMat::<f64>::empty(sprs::CSR, 3);
+        let mut partition = [0; 3];
+        let result =
+            ScotchStandard { part_count: 2 }.partition(&mut partition, (adjacency.view(), [1; 2]));
+        assert!(matches!(
+            result,
+            Err(Error::InputLenMismatch {
+                expected: 3,
+                actual: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn test_no_parts() {
+        let adjacency = CsMat::<f64>::zero((3, 3));
+        let mut partition = [7; 3];
+        ScotchStandard { part_count: 0 }
+            .partition(&mut partition, (adjacency.view(), [1; 3]))
+            .unwrap();
+        assert_eq!(partition, [7; 3]);
+    }
+}
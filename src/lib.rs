@@ -6,6 +6,11 @@
 //! algorithms.  See its documentation for more details.  The trait is generic around its input, which means algorithms
 //! can partition different type of collections (e.g. 2D and 3D meshes).
 //!
+//! Topologic algorithms, such as [graph growth][GraphGrowth] and [label
+//! propagation][LabelPropagation], take an adjacency matrix and weights as
+//! input, without coordinates, so they can partition graphs that do not come
+//! from a mesh, like the ones read from Matrix Market files.
+//!
 //! # Available algorithms
 //!
 //! ## Partitioner algorithms
@@ -15,7 +20,12 @@
 //!   + [Hilbert curve][HilbertCurve]
 //! - [Recursive Coordinate Bisection][Rcb]
 //! - [Recursive Inertial Bisection][Rib]
+//! - [Directional Bisection][DirectionalBisection]
 //! - [Multi jagged][MultiJagged]
+//! - [Graph growth][GraphGrowth]
+//! - [Label propagation][LabelPropagation]
+//! - [Spectral bisection][SpectralBisection]
+//! - [Simplified k-means][SimplifiedKMeans]
 //! - Number partitioning:
 //!   + [Greedy][Greedy]
 //!   + [Karmarkar-Karp][KarmarkarKarp] and its [complete][CompleteKarmarkarKarp] version
@@ -28,6 +38,14 @@
 //!   + [VN-First][VnFirst]
 //! - [Fiduccia-Mattheyses][FiducciaMattheyses]
 //! - [Kernighan-Lin][KernighanLin]
+//! - [Simulated annealing][SimulatedAnnealing]
+//! - [Diffusion][Diffusion]
+//! - [Incremental repartitioning][Repartition]
+//! - [Subdivide][Subdivide], which re-partitions each part with a partitioner
+//!
+//! ## Third-party partitioners
+//!
+//! - SCOTCH, in `interop::scotch` (requires the `scotch` feature)
 
 #![cfg_attr(feature = "avx512", feature(stdsimd))]
 #![warn(
@@ -37,23 +55,47 @@
 )]
 
 mod algorithms;
+pub mod analysis;
 mod average;
 mod cartesian;
 mod defer;
 mod geometry;
 pub mod imbalance;
+pub mod interop;
+pub mod mesh;
 mod nextafter;
 mod real;
+mod registry;
 mod topology;
+pub mod weights;
 mod work_share;
 
 pub use crate::algorithms::*;
+pub use crate::analysis::align_partition;
+pub use crate::analysis::classify_nearest;
+pub use crate::analysis::evaluate;
+pub use crate::analysis::merge_parts;
+pub use crate::analysis::partition_equivalent;
+pub use crate::analysis::rebalance_empty_parts;
+pub use crate::analysis::verify_partition;
+pub use crate::analysis::PartitionEvaluation;
+pub use crate::analysis::VerifyError;
 pub use crate::average::Average;
 pub use crate::cartesian::*;
+pub use crate::geometry::affine;
+pub use crate::geometry::points_from_flat;
+pub use crate::geometry::spatial::KdTree;
 pub use crate::geometry::BoundingBox;
+pub use crate::geometry::BoundingSphere;
+pub use crate::geometry::{center, weighted_center};
+pub use crate::geometry::{geometric_median, weighted_geometric_median};
+pub use crate::geometry::{lift, project};
 pub use crate::geometry::{Point2D, Point3D, PointND};
 pub use crate::nextafter::nextafter;
 pub use crate::real::Real;
+pub use crate::registry::{
+    parse_algorithm, BoxedError, Deduplicate, InitialPartition, ParseAlgorithmError,
+};
 pub use crate::topology::Topology;
 
 pub use nalgebra;
@@ -61,6 +103,7 @@ pub use num_traits;
 pub use rayon;
 pub use sprs;
 
+use std::borrow::Cow;
 use std::cmp::Ordering;
 use std::mem;
 use std::sync::atomic::AtomicUsize;
@@ -91,6 +134,45 @@ pub trait Partition<M> {
         -> Result<Self::Metadata, Self::Error>;
 }
 
+/// Partitioning algorithms that know, before running, how many parts they
+/// will output.
+///
+/// This allows callers to allocate per-part data, like part weights, ahead of
+/// time.
+///
+/// # Example
+///
+/// ```rust
+/// use coupe::PartCount as _;
+///
+/// assert_eq!(coupe::Rcb { iter_count: 3, ..Default::default() }.expected_part_count(), Some(8));
+/// assert_eq!(coupe::Subdivide(coupe::Rcb::default()).expected_part_count(), None);
+/// ```
+pub trait PartCount {
+    /// The number of parts of the output partition, that is one plus its
+    /// maximum part ID, or `None` if it depends on the input.
+    fn expected_part_count(&self) -> Option<usize>;
+}
+
+/// Partitioning algorithms that have a human-readable name, for logs and
+/// benchmark reports.
+///
+/// Names are in snake case.  Algorithms that wrap other algorithms include the
+/// name of the latter.
+///
+/// # Example
+///
+/// ```rust
+/// use coupe::Name as _;
+///
+/// assert_eq!(coupe::Rcb::default().name(), "rcb");
+/// assert_eq!(coupe::Subdivide(coupe::Rcb::default()).name(), "subdivide(rcb)");
+/// ```
+pub trait Name {
+    /// The name of the algorithm.
+    fn name(&self) -> Cow<'static, str>;
+}
+
 fn partial_cmp<W>(a: &W, b: &W) -> Ordering
 where
     W: PartialOrd,
@@ -0,0 +1,356 @@
+//! Helpers to feed meshes to partitioning algorithms.
+
+use crate::Point2D;
+use crate::Point3D;
+use crate::PointND;
+#[cfg(feature = "sprs")]
+use rayon::iter::IndexedParallelIterator as _;
+#[cfg(feature = "sprs")]
+use rayon::iter::IntoParallelRefIterator as _;
+#[cfg(feature = "sprs")]
+use rayon::iter::ParallelIterator as _;
+#[cfg(feature = "sprs")]
+use sprs::CsMat;
+use std::io;
+
+/// The barycentre of each element of a mesh, that is the mean of the
+/// coordinates of its nodes.
+///
+/// `elements` yields, for each element, the indices of its nodes in `nodes`.
+/// Only the elements that are to be partitioned must be given, usually those
+/// of the highest dimension (e.g. triangles and quadrangles of a 2D mesh, but
+/// not its edges).
+///
+/// The result is meant to be given to geometric algorithms, like
+/// [Rcb][crate::Rcb] or [HilbertCurve][crate::HilbertCurve].
+///
+/// # Panics
+///
+/// Panics if an element has no nodes, or if a node index is out of bounds.
+///
+/// # Example
+///
+/// ```rust
+/// use coupe::Point2D;
+///
+/// // A unit square split along its diagonal:
+/// //
+/// //  3---2
+/// //  | / |
+/// //  0---1
+/// let nodes = [
+///     Point2D::new(0.0, 0.0),
+///     Point2D::new(1.0, 0.0),
+///     Point2D::new(1.0, 1.0),
+///     Point2D::new(0.0, 1.0),
+/// ];
+/// let triangles: [&[usize]; 2] = [&[0, 1, 2], &[0, 2, 3]];
+///
+/// let barycentres = coupe::mesh::element_barycentres(&nodes, triangles);
+/// assert_eq!(barycentres.len(), 2);
+/// ```
+pub fn element_barycentres<'a, const D: usize, E>(
+    nodes: &[PointND<D>],
+    elements: E,
+) -> Vec<PointND<D>>
+where
+    E: IntoIterator<Item = &'a [usize]>,
+{
+    elements
+        .into_iter()
+        .map(|element| {
+            assert!(!element.is_empty(), "element has no nodes");
+            let sum: PointND<D> = element.iter().map(|node| nodes[*node]).sum();
+            sum / element.len() as f64
+        })
+        .collect()
+}
+
+/// The adjacency matrix of the dual graph of a mesh, where elements are
+/// connected when they share a facet.
+///
+/// `elements` gives, for each element, the indices of its nodes, which must be
+/// lower than `node_count`.  As with [element_barycentres], only the elements
+/// that are to be partitioned must be given.
+///
+/// Two elements share a facet when they have at least `dimension` nodes in
+/// common, `dimension` being the dimension of the elements (e.g. 2 for
+/// triangles and quadrangles, which share edges, and 3 for tetrahedra and
+/// hexahedra, which share faces).
+///
+/// All edges have a weight of one.
+///
+/// # Example
+///
+/// ```rust
+/// // Three triangles in a row:
+/// //
+/// //  3---4
+/// //  | \ | \
+/// //  0---1---2
+/// let triangles: [&[usize]; 3] = [&[0, 1, 3], &[1, 4, 3], &[1, 2, 4]];
+///
+/// let dual = coupe::mesh::dual(5, &triangles, 2);
+/// assert_eq!(dual.outer_view(0).unwrap().indices(), [1]);
+/// assert_eq!(dual.outer_view(1).unwrap().indices(), [0, 2]);
+/// ```
+#[cfg(feature = "sprs")]
+pub fn dual(node_count: usize, elements: &[&[usize]], dimension: usize) -> CsMat<f64> {
+    let mut node_to_elements = vec![Vec::new(); node_count];
+    for (e, nodes) in elements.iter().enumerate() {
+        for node in *nodes {
+            let node_elements: &mut Vec<usize> = &mut node_to_elements[*node];
+            if node_elements.last() != Some(&e) {
+                node_elements.push(e);
+            }
+        }
+    }
+
+    let neighbors: Vec<Vec<usize>> = elements
+        .par_iter()
+        .enumerate()
+        .map(|(e1, e1_nodes)| {
+            let mut neighbors: Vec<usize> = e1_nodes
+                .iter()
+                .flat_map(|node| &node_to_elements[*node])
+                .cloned()
+                .filter(|e2| e1 != *e2)
+                .collect();
+            neighbors.sort_unstable();
+            neighbors.dedup();
+            neighbors.retain(|e2| {
+                let nodes_in_common = e1_nodes
+                    .iter()
+                    .filter(|node| elements[*e2].contains(node))
+                    .count();
+                dimension <= nodes_in_common
+            });
+            neighbors
+        })
+        .collect();
+
+    let mut indptr = Vec::with_capacity(elements.len() + 1);
+    indptr.push(0);
+    for element_neighbors in &neighbors {
+        indptr.push(indptr[indptr.len() - 1] + element_neighbors.len());
+    }
+    let indices: Vec<usize> = neighbors.into_iter().flatten().collect();
+    let data = vec![1.0; indices.len()];
+
+    CsMat::new((elements.len(), elements.len()), indptr, indices, data)
+}
+
+/// The nodes of a regular 2D lattice of `nx` by `ny` points, `spacing` apart,
+/// with the first one at the origin.
+///
+/// Points are ordered by row: the x coordinate varies first.
+///
+/// # Example
+///
+/// ```rust
+/// use coupe::Point2D;
+///
+/// let points = coupe::mesh::grid_2d(3, 2, 0.5);
+/// assert_eq!(points.len(), 6);
+/// assert_eq!(points[1], Point2D::new(0.5, 0.0));
+/// assert_eq!(points[5], Point2D::new(1.0, 0.5));
+/// ```
+pub fn grid_2d(nx: usize, ny: usize, spacing: f64) -> Vec<Point2D> {
+    (0..ny)
+        .flat_map(|y| (0..nx).map(move |x| Point2D::new(x as f64, y as f64) * spacing))
+        .collect()
+}
+
+/// The nodes of a regular 3D lattice of `nx` by `ny` by `nz` points,
+/// `spacing` apart, with the first one at the origin.
+///
+/// Points are ordered by layer, then by row: the x coordinate varies first,
+/// then the y one.
+///
+/// # Example
+///
+/// ```rust
+/// use coupe::Point3D;
+///
+/// let points = coupe::mesh::grid_3d(2, 2, 2, 1.0);
+/// assert_eq!(points.len(), 8);
+/// assert_eq!(points[6], Point3D::new(0.0, 1.0, 1.0));
+/// ```
+pub fn grid_3d(nx: usize, ny: usize, nz: usize, spacing: f64) -> Vec<Point3D> {
+    (0..nz)
+        .flat_map(|z| {
+            (0..ny).flat_map(move |y| {
+                (0..nx).map(move |x| Point3D::new(x as f64, y as f64, z as f64) * spacing)
+            })
+        })
+        .collect()
+}
+
+/// Reads points from a file in the XYZ format, that is one point per line,
+/// made of its `D` whitespace-separated coordinates.
+///
+/// Empty lines and lines starting with `#` are skipped.  Lines must have
+/// exactly `D` coordinates, so that, for example, the third coordinate of 3D
+/// points is not silently dropped when reading them as 2D points.
+///
+/// # Errors
+///
+/// Fails with [`io::ErrorKind::InvalidData`] if a line does not have `D`
+/// coordinates or if one of them is not a number, and with the errors of `r`.
+///
+/// # Example
+///
+/// ```rust
+/// use coupe::Point3D;
+///
+/// let xyz = "# x y z\n0.0 1.0 2.0\n3.0 4.0 5.0\n";
+///
+/// let points = coupe::mesh::read_xyz::<3>(xyz.as_bytes())?;
+/// assert_eq!(points, [Point3D::new(0.0, 1.0, 2.0), Point3D::new(3.0, 4.0, 5.0)]);
+///
+/// assert!(coupe::mesh::read_xyz::<2>(xyz.as_bytes()).is_err());
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn read_xyz<const D: usize>(r: impl io::BufRead) -> io::Result<Vec<PointND<D>>> {
+    let mut points = Vec::new();
+    for (line_number, line) in r.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let invalid_data = |message: String| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("line {}: {message}", line_number + 1),
+            )
+        };
+        let mut point = PointND::<D>::zeros();
+        let mut coordinate_count = 0;
+        for word in line.split_whitespace() {
+            let coordinate = word
+                .parse()
+                .map_err(|err| invalid_data(format!("{word:?}: {err}")))?;
+            if coordinate_count < D {
+                point[coordinate_count] = coordinate;
+            }
+            coordinate_count += 1;
+        }
+        if coordinate_count != D {
+            return Err(invalid_data(format!(
+                "expected {D} coordinates, found {coordinate_count}"
+            )));
+        }
+        points.push(point);
+    }
+    Ok(points)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Point2D;
+    use approx::assert_ulps_eq;
+
+    #[test]
+    fn test_two_triangles() {
+        //  2---3
+        //  | \ |
+        //  0---1
+        let nodes = [
+            Point2D::new(0.0, 0.0),
+            Point2D::new(3.0, 0.0),
+            Point2D::new(0.0, 3.0),
+            Point2D::new(3.0, 3.0),
+        ];
+        let triangles = [vec![0, 1, 2], vec![1, 3, 2]];
+
+        let barycentres = element_barycentres(&nodes, triangles.iter().map(Vec::as_slice));
+
+        assert_eq!(barycentres.len(), 2);
+        assert_ulps_eq!(barycentres[0], Point2D::new(1.0, 1.0));
+        assert_ulps_eq!(barycentres[1], Point2D::new(2.0, 2.0));
+    }
+
+    #[test]
+    fn test_grids() {
+        let points = grid_2d(4, 3, 0.25);
+        assert_eq!(points.len(), 12);
+        for (i, point) in points.iter().enumerate() {
+            let (x, y) = (i % 4, i / 4);
+            assert_eq!(*point, Point2D::new(x as f64 * 0.25, y as f64 * 0.25));
+        }
+        assert_eq!(points[11] - points[10], Point2D::new(0.25, 0.0));
+        assert_eq!(points[11] - points[7], Point2D::new(0.0, 0.25));
+
+        let points = grid_3d(2, 3, 4, 2.0);
+        assert_eq!(points.len(), 24);
+        assert_eq!(points[1] - points[0], Point3D::new(2.0, 0.0, 0.0));
+        assert_eq!(points[2] - points[0], Point3D::new(0.0, 2.0, 0.0));
+        assert_eq!(points[6] - points[0], Point3D::new(0.0, 0.0, 2.0));
+        assert_eq!(points[23], Point3D::new(2.0, 4.0, 6.0));
+
+        assert!(grid_2d(0, 5, 1.0).is_empty());
+    }
+
+    #[test]
+    fn test_read_xyz_3d_split() {
+        use crate::Partition as _;
+
+        // A column of points along z, with a small spread on x and y.
+        let xyz: String = (0..20)
+            .map(|i| format!("{} {} {}\n", (i % 3) as f64 * 0.1, (i % 2) as f64 * 0.1, i))
+            .collect();
+
+        let points = read_xyz::<3>(xyz.as_bytes()).unwrap();
+        assert_eq!(points.len(), 20);
+        assert_eq!(points[19], Point3D::new(0.1, 0.1, 19.0));
+
+        let weights = [1.0; 20];
+        let mut partition = [0; 20];
+        crate::Rib {
+            iter_count: 1,
+            ..Default::default()
+        }
+        .partition(&mut partition, (&points, weights))
+        .unwrap();
+
+        // The cut is along z: the bottom and top halves are separate parts.
+        assert!(partition[..10].iter().all(|part| *part == partition[0]));
+        assert!(partition[10..].iter().all(|part| *part == partition[19]));
+        assert_ne!(partition[0], partition[19]);
+
+        assert!(read_xyz::<2>(xyz.as_bytes()).is_err());
+    }
+
+    #[cfg(feature = "sprs")]
+    #[test]
+    fn test_dual_quad_mesh() {
+        // A structured mesh of 4x3 quadrangles, over a grid of 5x4 nodes.
+        let (width, height) = (4, 3);
+        let quads: Vec<[usize; 4]> = (0..width * height)
+            .map(|q| {
+                let (x, y) = (q % width, q / width);
+                let node = y * (width + 1) + x;
+                [node, node + 1, node + width + 2, node + width + 1]
+            })
+            .collect();
+        let elements: Vec<&[usize]> = quads.iter().map(|quad| &quad[..]).collect();
+
+        let dual = dual((width + 1) * (height + 1), &elements, 2);
+
+        assert_eq!(dual.rows(), width * height);
+        for q in 0..width * height {
+            let (x, y) = (q % width, q / width);
+            let on_x_border = x == 0 || x == width - 1;
+            let on_y_border = y == 0 || y == height - 1;
+            let expected_degree = 4 - on_x_border as usize - on_y_border as usize;
+            assert_eq!(
+                dual.outer_view(q).unwrap().nnz(),
+                expected_degree,
+                "quad {q}"
+            );
+        }
+        assert_eq!(dual.to_csc(), dual.transpose_view().to_owned());
+    }
+}
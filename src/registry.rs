@@ -0,0 +1,565 @@
+//! Geometric partitioners as trait objects, and a parser to build them from
+//! strings.
+
+use crate::HilbertPoint;
+use crate::Partition as _;
+use crate::Point2D;
+use crate::PointND;
+use nalgebra::allocator::Allocator;
+use nalgebra::ArrayStorage;
+use nalgebra::Const;
+use nalgebra::DefaultAllocator;
+use nalgebra::DimDiff;
+use nalgebra::DimSub;
+use nalgebra::ToTypenum;
+use rayon::iter::IntoParallelRefIterator as _;
+use rayon::iter::ParallelIterator as _;
+use std::any;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::error::Error as StdError;
+use std::fmt;
+use std::str::FromStr;
+
+/// Error returned by [`InitialPartition::initial_partition`].
+pub type BoxedError = Box<dyn StdError + Send + Sync>;
+
+/// Partitioners that only need the coordinates and the weights of
+/// `D`-dimensional points, and no initial partition.
+///
+/// Unlike [`Partition`][crate::Partition], this trait is object safe, so that
+/// algorithms of different types can be stored in a same collection as
+/// `Box<dyn InitialPartition<D>>`, for example one built by
+/// [parse_algorithm].  Metadata is dropped, and errors are boxed.
+///
+/// # Example
+///
+/// ```rust
+/// # fn main() -> Result<(), coupe::BoxedError> {
+/// use coupe::InitialPartition;
+///
+/// let points = coupe::mesh::grid_2d(4, 4, 1.0);
+/// let weights = [1.0; 16];
+/// let mut partition = [0; 16];
+///
+/// let mut algorithms: Vec<Box<dyn InitialPartition<2>>> = vec![
+///     Box::new(coupe::Rcb { iter_count: 2, ..Default::default() }),
+///     Box::new(coupe::HilbertCurve { part_count: 4, ..Default::default() }),
+/// ];
+/// for algorithm in &mut algorithms {
+///     algorithm.initial_partition(&mut partition, &points, &weights)?;
+///     assert_eq!(partition.iter().filter(|part| **part == 3).count(), 4);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub trait InitialPartition<const D: usize>: crate::Name + Send {
+    /// Partitions the given points and outputs the part ID of each one in
+    /// `part_ids`, like [`Partition::partition`][crate::Partition::partition].
+    fn initial_partition(
+        &mut self,
+        part_ids: &mut [usize],
+        points: &[PointND<D>],
+        weights: &[f64],
+    ) -> Result<(), BoxedError>;
+}
+
+impl<const D: usize> InitialPartition<D> for crate::Rcb {
+    fn initial_partition(
+        &mut self,
+        part_ids: &mut [usize],
+        points: &[PointND<D>],
+        weights: &[f64],
+    ) -> Result<(), BoxedError> {
+        let points = points.par_iter().cloned();
+        let weights = weights.par_iter().cloned();
+        self.partition(part_ids, (points, weights))?;
+        Ok(())
+    }
+}
+
+impl<const D: usize> InitialPartition<D> for crate::Rib
+where
+    Const<D>: DimSub<Const<1>> + ToTypenum,
+    DefaultAllocator: Allocator<f64, Const<D>, Const<D>, Buffer = ArrayStorage<f64, D, D>>
+        + Allocator<f64, DimDiff<Const<D>, Const<1>>>,
+{
+    fn initial_partition(
+        &mut self,
+        part_ids: &mut [usize],
+        points: &[PointND<D>],
+        weights: &[f64],
+    ) -> Result<(), BoxedError> {
+        self.partition(part_ids, (points, weights.par_iter().cloned()))?;
+        Ok(())
+    }
+}
+
+impl<const D: usize> InitialPartition<D> for crate::HilbertCurve
+where
+    PointND<D>: HilbertPoint,
+{
+    fn initial_partition(
+        &mut self,
+        part_ids: &mut [usize],
+        points: &[PointND<D>],
+        weights: &[f64],
+    ) -> Result<(), BoxedError> {
+        self.partition(part_ids, (points, weights))?;
+        Ok(())
+    }
+}
+
+impl InitialPartition<2> for crate::PeanoCurve {
+    fn initial_partition(
+        &mut self,
+        part_ids: &mut [usize],
+        points: &[Point2D],
+        weights: &[f64],
+    ) -> Result<(), BoxedError> {
+        self.partition(part_ids, (points, weights))?;
+        Ok(())
+    }
+}
+
+impl<const D: usize> InitialPartition<D> for crate::ZCurve
+where
+    Const<D>: DimSub<Const<1>> + ToTypenum,
+    DefaultAllocator: Allocator<f64, Const<D>, Const<D>, Buffer = ArrayStorage<f64, D, D>>
+        + Allocator<f64, DimDiff<Const<D>, Const<1>>>,
+{
+    fn initial_partition(
+        &mut self,
+        part_ids: &mut [usize],
+        points: &[PointND<D>],
+        weights: &[f64],
+    ) -> Result<(), BoxedError> {
+        self.partition(part_ids, (points, weights))?;
+        Ok(())
+    }
+}
+
+impl<const D: usize> InitialPartition<D> for crate::MultiJagged {
+    fn initial_partition(
+        &mut self,
+        part_ids: &mut [usize],
+        points: &[PointND<D>],
+        weights: &[f64],
+    ) -> Result<(), BoxedError> {
+        self.partition(part_ids, (points, weights))?;
+        Ok(())
+    }
+}
+
+impl<const D: usize> InitialPartition<D> for crate::SimplifiedKMeans {
+    fn initial_partition(
+        &mut self,
+        part_ids: &mut [usize],
+        points: &[PointND<D>],
+        weights: &[f64],
+    ) -> Result<(), BoxedError> {
+        self.partition(part_ids, (points, weights))?;
+        Ok(())
+    }
+}
+
+impl<const D: usize> crate::Name for Box<dyn InitialPartition<D>> {
+    fn name(&self) -> Cow<'static, str> {
+        (**self).name()
+    }
+}
+
+impl<const D: usize> InitialPartition<D> for Box<dyn InitialPartition<D>> {
+    fn initial_partition(
+        &mut self,
+        part_ids: &mut [usize],
+        points: &[PointND<D>],
+        weights: &[f64],
+    ) -> Result<(), BoxedError> {
+        (**self).initial_partition(part_ids, points, weights)
+    }
+}
+
+/// The index of the first point that has the same coordinates as each point.
+fn representatives<const D: usize>(points: &[PointND<D>]) -> Vec<usize> {
+    let mut first = HashMap::with_capacity(points.len());
+    points
+        .iter()
+        .enumerate()
+        .map(|(index, point)| {
+            // Adding zero turns -0.0 into 0.0, so that both have the same bits.
+            let key: [u64; D] = std::array::from_fn(|axis| (point[axis] + 0.0).to_bits());
+            *first.entry(key).or_insert(index)
+        })
+        .collect()
+}
+
+/// Wraps an [InitialPartition] so that coincident points are only given to it
+/// once.
+///
+/// When `deduplicate` is set, points that have exactly the same coordinates
+/// are merged into one point, whose weight is the sum of theirs, before
+/// `algorithm` is run.  Each point then gets the part of the point it has been
+/// merged into.  Coincident points thus always end up in the same part, and
+/// algorithms that cannot tell them apart, like space-filling curves or
+/// k-means, do not have to deal with them.
+///
+/// # Example
+///
+/// ```rust
+/// # fn main() -> Result<(), coupe::BoxedError> {
+/// use coupe::InitialPartition as _;
+/// use coupe::Point2D;
+///
+/// // Three copies of the same point, which RCB would split in two.
+/// let points = [
+///     Point2D::new(0.0, 0.0),
+///     Point2D::new(1.0, 0.0),
+///     Point2D::new(1.0, 0.0),
+///     Point2D::new(1.0, 0.0),
+/// ];
+/// let weights = [1.0; 4];
+/// let mut partition = [0; 4];
+///
+/// coupe::Deduplicate {
+///     algorithm: coupe::Rcb { iter_count: 1, ..Default::default() },
+///     deduplicate: true,
+/// }
+/// .initial_partition(&mut partition, &points, &weights)?;
+///
+/// assert_ne!(partition[0], partition[1]);
+/// assert_eq!(partition[1], partition[2]);
+/// assert_eq!(partition[1], partition[3]);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Deduplicate<A> {
+    pub algorithm: A,
+
+    /// Whether coincident points are merged.  Otherwise, `algorithm` is given
+    /// the points as-is.
+    pub deduplicate: bool,
+}
+
+impl<A> crate::Name for Deduplicate<A>
+where
+    A: crate::Name,
+{
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Owned(format!("deduplicate({})", self.algorithm.name()))
+    }
+}
+
+impl<const D: usize, A> InitialPartition<D> for Deduplicate<A>
+where
+    A: InitialPartition<D>,
+{
+    fn initial_partition(
+        &mut self,
+        part_ids: &mut [usize],
+        points: &[PointND<D>],
+        weights: &[f64],
+    ) -> Result<(), BoxedError> {
+        if !self.deduplicate {
+            return self.algorithm.initial_partition(part_ids, points, weights);
+        }
+        for len in [points.len(), weights.len()] {
+            if len != part_ids.len() {
+                return Err(Box::new(crate::Error::InputLenMismatch {
+                    expected: part_ids.len(),
+                    actual: len,
+                }));
+            }
+        }
+
+        let representatives = representatives(points);
+        let mut unique_indices = vec![0; points.len()];
+        let mut unique_points = Vec::new();
+        let mut unique_weights = Vec::new();
+        for (index, representative) in representatives.iter().enumerate() {
+            if index == *representative {
+                unique_indices[index] = unique_points.len();
+                unique_points.push(points[index]);
+                unique_weights.push(weights[index]);
+            } else {
+                unique_weights[unique_indices[*representative]] += weights[index];
+            }
+        }
+        if unique_points.len() == points.len() {
+            return self.algorithm.initial_partition(part_ids, points, weights);
+        }
+
+        let mut unique_ids = vec![0; unique_points.len()];
+        self.algorithm
+            .initial_partition(&mut unique_ids, &unique_points, &unique_weights)?;
+        for (part, representative) in part_ids.iter_mut().zip(&representatives) {
+            *part = unique_ids[unique_indices[*representative]];
+        }
+        Ok(())
+    }
+}
+
+/// Error returned by [parse_algorithm].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseAlgorithmError {
+    /// The spec does not start with the name of a supported algorithm.
+    UnknownAlgorithm(String),
+
+    /// A required argument is missing.
+    MissingArgument { name: &'static str },
+
+    /// An argument could not be parsed.
+    InvalidArgument {
+        value: String,
+        expected: &'static str,
+    },
+
+    /// The spec has more arguments than the algorithm takes.
+    TooManyArguments,
+}
+
+impl fmt::Display for ParseAlgorithmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseAlgorithmError::UnknownAlgorithm(name) => write!(f, "unknown algorithm {name:?}"),
+            ParseAlgorithmError::MissingArgument { name } => {
+                write!(f, "missing required argument {name:?}")
+            }
+            ParseAlgorithmError::InvalidArgument { value, expected } => {
+                write!(f, "argument {value:?} is not a valid {expected}")
+            }
+            ParseAlgorithmError::TooManyArguments => write!(f, "too many arguments"),
+        }
+    }
+}
+
+impl StdError for ParseAlgorithmError {}
+
+/// Builds a geometric partitioner from a spec string.
+///
+/// A spec is the name of the algorithm followed by its arguments, separated by
+/// commas like in the `--algorithm` option of `part-bench`, or by colons.
+/// Arguments in brackets are optional:
+///
+/// - `rcb,ITER_COUNT[,TOLERANCE]`: [Rcb][crate::Rcb],
+/// - `rib,ITER_COUNT[,TOLERANCE[,INERTIA_LEVELS]]`: [Rib][crate::Rib],
+/// - `hilbert,PART_COUNT[,ORDER[,TOLERANCE]]`:
+///   [HilbertCurve][crate::HilbertCurve],
+/// - `zcurve,PART_COUNT[,ORDER]`: [ZCurve][crate::ZCurve],
+/// - `multijagged,PART_COUNT[,MAX_ITER]`: [MultiJagged][crate::MultiJagged],
+/// - `simplified-kmeans,PART_COUNT[,MAX_ITER]`:
+///   [SimplifiedKMeans][crate::SimplifiedKMeans].
+///
+/// Optional arguments have the same defaults as in `part-bench`, or those of
+/// the algorithms for the ones it does not support.  Since the Hilbert curve
+/// is only implemented in 2D and 3D, so is this function.
+///
+/// # Example
+///
+/// ```rust
+/// # fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+/// use coupe::Name as _;
+///
+/// let algorithms = ["rcb:4", "hilbert:16:5"]
+///     .into_iter()
+///     .map(coupe::parse_algorithm::<3>)
+///     .collect::<Result<Vec<_>, _>>()?;
+/// assert_eq!(algorithms[0].name(), "rcb");
+/// assert_eq!(algorithms[1].name(), "hilbert_curve");
+///
+/// assert!(coupe::parse_algorithm::<3>("rcb").is_err());
+/// # Ok(())
+/// # }
+/// ```
+pub fn parse_algorithm<const D: usize>(
+    spec: &str,
+) -> Result<Box<dyn InitialPartition<D>>, ParseAlgorithmError>
+where
+    PointND<D>: HilbertPoint,
+    Const<D>: DimSub<Const<1>> + ToTypenum,
+    DefaultAllocator: Allocator<f64, Const<D>, Const<D>, Buffer = ArrayStorage<f64, D, D>>
+        + Allocator<f64, DimDiff<Const<D>, Const<1>>>,
+{
+    let mut args = spec.split([',', ':']);
+    let name = args.next().unwrap_or_default();
+
+    fn parse<T>(arg: Option<&str>) -> Result<Option<T>, ParseAlgorithmError>
+    where
+        T: FromStr,
+    {
+        arg.map(|arg| {
+            arg.parse()
+                .map_err(|_| ParseAlgorithmError::InvalidArgument {
+                    value: arg.to_string(),
+                    expected: any::type_name::<T>(),
+                })
+        })
+        .transpose()
+    }
+
+    fn require<T>(arg: Option<&str>, name: &'static str) -> Result<T, ParseAlgorithmError>
+    where
+        T: FromStr,
+    {
+        parse(arg)?.ok_or(ParseAlgorithmError::MissingArgument { name })
+    }
+
+    let algorithm: Box<dyn InitialPartition<D>> = match name {
+        "rcb" => Box::new(crate::Rcb {
+            iter_count: require(args.next(), "iter_count")?,
+            tolerance: parse(args.next())?.unwrap_or(0.05),
+            ..Default::default()
+        }),
+        "rib" => Box::new(crate::Rib {
+            iter_count: require(args.next(), "iter_count")?,
+            tolerance: parse(args.next())?.unwrap_or(0.05),
+            inertia_levels: parse(args.next())?.unwrap_or(1),
+            ..Default::default()
+        }),
+        "hilbert" => {
+            let default = crate::HilbertCurve::default();
+            Box::new(crate::HilbertCurve {
+                part_count: require(args.next(), "part_count")?,
+                order: parse(args.next())?.unwrap_or(default.order),
+                tolerance: parse(args.next())?.unwrap_or(default.tolerance),
+                ..default
+            })
+        }
+        "zcurve" => Box::new(crate::ZCurve {
+            part_count: require(args.next(), "part_count")?,
+            order: parse(args.next())?.unwrap_or(8),
+            ..Default::default()
+        }),
+        "multijagged" => {
+            let part_count = require(args.next(), "part_count")?;
+            Box::new(crate::MultiJagged {
+                part_count,
+                max_iter: parse(args.next())?.unwrap_or(part_count),
+                target_weights: None,
+            })
+        }
+        "simplified-kmeans" => {
+            let default = crate::SimplifiedKMeans::default();
+            Box::new(crate::SimplifiedKMeans {
+                part_count: require(args.next(), "part_count")?,
+                max_iter: parse(args.next())?.unwrap_or(default.max_iter),
+            })
+        }
+        _ => return Err(ParseAlgorithmError::UnknownAlgorithm(name.to_string())),
+    };
+
+    if args.next().is_some() {
+        return Err(ParseAlgorithmError::TooManyArguments);
+    }
+    Ok(algorithm)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_each_algorithm() {
+        let points = crate::mesh::grid_2d(8, 8, 1.0);
+        let weights = [1.0; 64];
+
+        for (spec, name, part_count) in [
+            ("rcb,2", "rcb", 4),
+            ("rcb:3:0.01", "rcb", 8),
+            ("rib,2", "rib", 4),
+            ("rib,3,0.05,3", "rib", 8),
+            ("hilbert:16:5", "hilbert_curve", 16),
+            ("hilbert,4", "hilbert_curve", 4),
+            ("zcurve,4,4", "z_curve", 4),
+            ("multijagged,4", "multi_jagged", 4),
+            ("multijagged,4,1", "multi_jagged", 4),
+            ("simplified-kmeans,4,10", "simplified_k_means", 4),
+        ] {
+            let mut algorithm = parse_algorithm::<2>(spec).unwrap();
+            assert_eq!(algorithm.name(), name, "{spec}");
+
+            let mut partition = [usize::MAX; 64];
+            algorithm
+                .initial_partition(&mut partition, &points, &weights)
+                .unwrap();
+            let mut parts = partition.to_vec();
+            parts.sort();
+            parts.dedup();
+            assert_eq!(parts, (0..part_count).collect::<Vec<_>>(), "{spec}");
+        }
+    }
+
+    #[test]
+    fn test_deduplicate() {
+        use crate::Name as _;
+
+        // A 4x4 grid, with a cluster of 5 coincident points in its middle.
+        let mut points = crate::mesh::grid_2d(4, 4, 1.0);
+        points.extend([Point2D::new(1.5, 1.5); 5]);
+        let weights = [1.0; 21];
+
+        for spec in [
+            "rcb,2",
+            "rib,2",
+            "hilbert,4",
+            "zcurve,4,30",
+            "multijagged,4",
+            "simplified-kmeans,4",
+        ] {
+            let mut algorithm = Deduplicate {
+                algorithm: parse_algorithm::<2>(spec).unwrap(),
+                deduplicate: true,
+            };
+            assert_eq!(
+                algorithm.name(),
+                format!("deduplicate({})", algorithm.algorithm.name())
+            );
+
+            let mut partition = [usize::MAX; 21];
+            algorithm
+                .initial_partition(&mut partition, &points, &weights)
+                .unwrap();
+            assert!(
+                partition[16..].iter().all(|part| *part == partition[16]),
+                "{spec}"
+            );
+            assert!(partition.iter().all(|part| *part < 4), "{spec}");
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |spec| parse_algorithm::<3>(spec).err().unwrap();
+        assert_eq!(
+            error("metis:kway,4"),
+            ParseAlgorithmError::UnknownAlgorithm("metis".to_string()),
+        );
+        assert_eq!(
+            error(""),
+            ParseAlgorithmError::UnknownAlgorithm(String::new())
+        );
+        assert_eq!(
+            error("hilbert"),
+            ParseAlgorithmError::MissingArgument { name: "part_count" },
+        );
+        assert!(matches!(
+            error("rcb,two"),
+            ParseAlgorithmError::InvalidArgument { .. },
+        ));
+        assert_eq!(error("zcurve,4,4,4"), ParseAlgorithmError::TooManyArguments);
+    }
+
+    #[test]
+    fn test_partition_errors() {
+        // Orders are only checked against the dimension at partition time,
+        // which must fail instead of panicking.
+        let points = crate::mesh::grid_2d(4, 4, 1.0);
+        let mut partition = [0; 16];
+        let mut algorithm = parse_algorithm::<2>("zcurve,4,100").unwrap();
+        assert!(algorithm
+            .initial_partition(&mut partition, &points, &[1.0; 16])
+            .is_err());
+    }
+}
@@ -92,6 +92,42 @@ pub trait Topology<E> {
             })
             .sum()
     }
+
+    /// The number of connected components of each part.
+    ///
+    /// The returned vector is indexed by part ID.  A part is contiguous iff it
+    /// is made of exactly one connected component.  Parts that have no
+    /// elements have zero connected components.
+    fn connected_components_per_part(&self, partition: &[usize]) -> Vec<usize> {
+        debug_assert_eq!(partition.len(), self.len());
+
+        let part_count = partition.iter().max().map_or(0, |max_part| max_part + 1);
+        let mut components = vec![0; part_count];
+        let mut visited = vec![false; self.len()];
+        let mut stack = Vec::new();
+
+        for vertex in 0..self.len() {
+            if visited[vertex] {
+                continue;
+            }
+            let part = partition[vertex];
+            components[part] += 1;
+
+            // Visit the whole component that `vertex` belongs to.
+            visited[vertex] = true;
+            stack.push(vertex);
+            while let Some(v) = stack.pop() {
+                for (neighbor, _edge_weight) in self.neighbors(v) {
+                    if !visited[neighbor] && partition[neighbor] == part {
+                        visited[neighbor] = true;
+                        stack.push(neighbor);
+                    }
+                }
+            }
+        }
+
+        components
+    }
 }
 
 impl<'a, T, E> Topology<E> for &'a T
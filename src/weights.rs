@@ -0,0 +1,112 @@
+//! Utilities to prepare weights for partitioning algorithms.
+
+use rayon::iter::IntoParallelIterator as _;
+use rayon::iter::ParallelIterator as _;
+
+/// How to combine the criteria of a weight into a single value, see
+/// [`reduce`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum ReducePolicy {
+    /// The sum of all criteria.
+    Sum,
+
+    /// The largest criterion.
+    Max,
+
+    /// The sum of all criteria, each one multiplied by the coefficient of the
+    /// same index.
+    WeightedSum(Vec<f64>),
+}
+
+/// Combines several weight arrays into one, so that algorithms that only
+/// support a single criterion can take all of them into account.
+///
+/// `criteria` holds one weight array per criterion, which must all have the
+/// same length.  An empty list of criteria gives an empty array.
+///
+/// # Panics
+///
+/// Panics if arrays have different lengths, or if the coefficients of a
+/// [`ReducePolicy::WeightedSum`] are not as many as the criteria.
+///
+/// # Example
+///
+/// ```rust
+/// use coupe::weights::ReducePolicy;
+///
+/// let cpu = [1.0, 2.0, 3.0];
+/// let memory = [4.0, 0.0, 1.0];
+///
+/// let weights = coupe::weights::reduce(&[&cpu, &memory], ReducePolicy::Max);
+/// assert_eq!(weights, [4.0, 2.0, 3.0]);
+/// ```
+pub fn reduce(criteria: &[&[f64]], policy: ReducePolicy) -> Vec<f64> {
+    let len = match criteria.first() {
+        Some(criterion) => criterion.len(),
+        None => return Vec::new(),
+    };
+    assert!(
+        criteria.iter().all(|criterion| criterion.len() == len),
+        "criteria have different lengths",
+    );
+    if let ReducePolicy::WeightedSum(coefficients) = &policy {
+        assert_eq!(
+            coefficients.len(),
+            criteria.len(),
+            "expected one coefficient per criterion",
+        );
+    }
+
+    (0..len)
+        .into_par_iter()
+        .map(|i| {
+            let values = criteria.iter().map(|criterion| criterion[i]);
+            match &policy {
+                ReducePolicy::Sum => values.sum(),
+                ReducePolicy::Max => values.fold(f64::NEG_INFINITY, f64::max),
+                ReducePolicy::WeightedSum(coefficients) => values
+                    .zip(coefficients)
+                    .map(|(value, coefficient)| value * coefficient)
+                    .sum(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CPU: [f64; 4] = [1.0, 2.0, 3.0, 0.5];
+    const MEMORY: [f64; 4] = [4.0, 0.0, 3.0, 1.5];
+
+    #[test]
+    fn test_reduce_sum() {
+        let weights = reduce(&[&CPU, &MEMORY], ReducePolicy::Sum);
+        assert_eq!(weights, [5.0, 2.0, 6.0, 2.0]);
+    }
+
+    #[test]
+    fn test_reduce_max() {
+        let weights = reduce(&[&CPU, &MEMORY], ReducePolicy::Max);
+        assert_eq!(weights, [4.0, 2.0, 3.0, 1.5]);
+    }
+
+    #[test]
+    fn test_reduce_weighted_sum() {
+        let weights = reduce(&[&CPU, &MEMORY], ReducePolicy::WeightedSum(vec![2.0, 0.5]));
+        assert_eq!(weights, [4.0, 4.0, 7.5, 1.75]);
+    }
+
+    #[test]
+    fn test_reduce_empty() {
+        assert!(reduce(&[], ReducePolicy::Sum).is_empty());
+        assert!(reduce(&[&[], &[]], ReducePolicy::Max).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "one coefficient per criterion")]
+    fn test_reduce_coefficient_count() {
+        reduce(&[&CPU, &MEMORY], ReducePolicy::WeightedSum(vec![1.0]));
+    }
+}
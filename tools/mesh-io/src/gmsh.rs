@@ -0,0 +1,443 @@
+//! Gmsh mesh file format decoder.
+//!
+//! The format is described in the [gmsh manual]. Only ASCII files are
+//! supported, in the legacy version 2 and in the current version 4.1 of the
+//! format. Sections other than `$MeshFormat`, `$Nodes` and `$Elements` are
+//! ignored.
+//!
+//! Supported elements are points, lines, triangles, quadrangles, tetrahedra
+//! and hexahedra. Element references are the first tag of each element in
+//! version 2 (usually its physical group), and the tag of its entity in
+//! version 4.
+//!
+//! Gmsh always stores three coordinates per node. The resulting mesh is 2D if
+//! all nodes lie in the `z = 0` plane, and 3D otherwise.
+//!
+//! [gmsh manual]: https://gmsh.info/doc/texinfo/gmsh.html#MSH-file-format
+
+use crate::ElementType;
+use crate::Mesh;
+use crate::Ref;
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::str::FromStr;
+
+#[derive(Debug)]
+pub enum Error {
+    /// The file does not start with a `$MeshFormat` section.
+    BadHeader,
+    /// The file is binary, or its version is not supported.
+    UnsupportedFormat,
+    /// The given element type, as numbered by gmsh, is not supported.
+    UnsupportedElement(usize),
+    /// The given line (1-based) is malformed.
+    BadLine(usize),
+    /// The element at the given line (1-based) refers to an undefined node.
+    UnknownNode(usize),
+    /// The file ends in the middle of a section.
+    UnexpectedEof,
+    Io(io::Error),
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Error {
+        Error::Io(err)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::BadHeader => write!(f, "bad file header"),
+            Error::UnsupportedFormat => write!(f, "unsupported file version or binary file"),
+            Error::UnsupportedElement(el_type) => write!(f, "unsupported element type {el_type}"),
+            Error::BadLine(line) => write!(f, "malformed line {line}"),
+            Error::UnknownNode(line) => write!(f, "line {line}: element has an undefined node"),
+            Error::UnexpectedEof => write!(f, "unexpected end of file"),
+            Error::Io(_) => write!(f, "read error"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl ElementType {
+    fn from_gmsh(el_type: usize) -> Result<Self> {
+        Ok(match el_type {
+            1 => Self::Edge,
+            2 => Self::Triangle,
+            3 => Self::Quadrilateral,
+            4 => Self::Tetrahedron,
+            5 => Self::Hexahedron,
+            15 => Self::Vertex,
+            _ => return Err(Error::UnsupportedElement(el_type)),
+        })
+    }
+}
+
+/// Whether the given buffer looks like the start of a gmsh file.
+pub fn test_format(header: &[u8]) -> bool {
+    const HEADER: &[u8] = b"$MeshFormat";
+    let start = header
+        .iter()
+        .position(|b| !b.is_ascii_whitespace())
+        .unwrap_or(header.len());
+    header[start..].starts_with(HEADER)
+}
+
+/// The non-empty lines of a file, along with their 1-based number.
+struct Lines<R> {
+    lines: io::Lines<R>,
+    line_no: usize,
+}
+
+impl<R> Lines<R>
+where
+    R: io::BufRead,
+{
+    fn next(&mut self) -> Result<Option<String>> {
+        for line in self.lines.by_ref() {
+            self.line_no += 1;
+            let line = line?;
+            if !line.trim().is_empty() {
+                return Ok(Some(line));
+            }
+        }
+        Ok(None)
+    }
+
+    fn expect(&mut self) -> Result<String> {
+        self.next()?.ok_or(Error::UnexpectedEof)
+    }
+
+    /// Parses all whitespace-separated fields of the next line.
+    fn numbers<T: FromStr>(&mut self) -> Result<Vec<T>> {
+        let line = self.expect()?;
+        line.split_whitespace()
+            .map(|field| field.parse().map_err(|_| Error::BadLine(self.line_no)))
+            .collect()
+    }
+
+    /// Skips lines until the end of the given section.
+    fn skip_section(&mut self, name: &str) -> Result<()> {
+        let end = format!("$End{name}");
+        while self.expect()?.trim() != end {}
+        Ok(())
+    }
+}
+
+fn parse<T: FromStr>(field: Option<&str>, line_no: usize) -> Result<T> {
+    field
+        .and_then(|field| field.parse().ok())
+        .ok_or(Error::BadLine(line_no))
+}
+
+#[derive(Default)]
+struct Builder {
+    coordinates: Vec<f64>,
+    /// Maps gmsh node tags to node indices.
+    node_indices: HashMap<usize, usize>,
+    topology: Vec<(ElementType, Vec<usize>, Vec<Ref>)>,
+}
+
+impl Builder {
+    fn add_node(&mut self, tag: usize, coordinates: [f64; 3]) {
+        self.node_indices.insert(tag, self.coordinates.len() / 3);
+        self.coordinates.extend(coordinates);
+    }
+
+    /// Adds an element from the remaining fields of its line.
+    fn add_element<'a>(
+        &mut self,
+        el_type: ElementType,
+        nodes: impl Iterator<Item = &'a str>,
+        el_ref: Ref,
+        line_no: usize,
+    ) -> Result<()> {
+        let group = match self.topology.iter().position(|(t, _, _)| *t == el_type) {
+            Some(group) => group,
+            None => {
+                self.topology.push((el_type, Vec::new(), Vec::new()));
+                self.topology.len() - 1
+            }
+        };
+        let (_, el_nodes, el_refs) = &mut self.topology[group];
+        let mut node_count = 0;
+        for node in nodes {
+            let node: usize = parse(Some(node), line_no)?;
+            let node = *self
+                .node_indices
+                .get(&node)
+                .ok_or(Error::UnknownNode(line_no))?;
+            el_nodes.push(node);
+            node_count += 1;
+        }
+        if node_count != el_type.node_count() {
+            return Err(Error::BadLine(line_no));
+        }
+        el_refs.push(el_ref);
+        Ok(())
+    }
+
+    fn build(self) -> Mesh {
+        let node_count = self.coordinates.len() / 3;
+        let is_flat = self.coordinates.chunks_exact(3).all(|node| node[2] == 0.0);
+        let (dimension, coordinates) = if is_flat {
+            let coordinates = self
+                .coordinates
+                .chunks_exact(3)
+                .flat_map(|node| [node[0], node[1]])
+                .collect();
+            (2, coordinates)
+        } else {
+            (3, self.coordinates)
+        };
+        Mesh::from_raw_parts(dimension, coordinates, vec![0; node_count], self.topology)
+    }
+}
+
+fn read_nodes_v2<R: io::BufRead>(lines: &mut Lines<R>, mesh: &mut Builder) -> Result<()> {
+    let node_count: usize = parse(lines.expect()?.split_whitespace().next(), lines.line_no)?;
+    for _ in 0..node_count {
+        let line = lines.expect()?;
+        let mut fields = line.split_whitespace();
+        let tag = parse(fields.next(), lines.line_no)?;
+        let x = parse(fields.next(), lines.line_no)?;
+        let y = parse(fields.next(), lines.line_no)?;
+        let z = parse(fields.next(), lines.line_no)?;
+        mesh.add_node(tag, [x, y, z]);
+    }
+    Ok(())
+}
+
+fn read_elements_v2<R: io::BufRead>(lines: &mut Lines<R>, mesh: &mut Builder) -> Result<()> {
+    let element_count: usize = parse(lines.expect()?.split_whitespace().next(), lines.line_no)?;
+    for _ in 0..element_count {
+        let line = lines.expect()?;
+        let mut fields = line.split_whitespace();
+        let _tag: usize = parse(fields.next(), lines.line_no)?;
+        let el_type = ElementType::from_gmsh(parse(fields.next(), lines.line_no)?)?;
+        let tag_count: usize = parse(fields.next(), lines.line_no)?;
+        let mut el_ref = 0;
+        for i in 0..tag_count {
+            let tag = parse(fields.next(), lines.line_no)?;
+            if i == 0 {
+                el_ref = tag;
+            }
+        }
+        mesh.add_element(el_type, fields, el_ref, lines.line_no)?;
+    }
+    Ok(())
+}
+
+fn read_nodes_v4<R: io::BufRead>(lines: &mut Lines<R>, mesh: &mut Builder) -> Result<()> {
+    let header: Vec<usize> = lines.numbers()?;
+    let block_count = *header.first().ok_or(Error::BadLine(lines.line_no))?;
+    for _ in 0..block_count {
+        let block: Vec<usize> = lines.numbers()?;
+        let node_count = match block[..] {
+            [_entity_dim, _entity_tag, _parametric, node_count] => node_count,
+            _ => return Err(Error::BadLine(lines.line_no)),
+        };
+        // Do not trust node_count to reserve memory, it might be corrupt.
+        let mut tags = Vec::new();
+        for _ in 0..node_count {
+            tags.push(parse(
+                lines.expect()?.split_whitespace().next(),
+                lines.line_no,
+            )?);
+        }
+        for tag in tags {
+            // Parametric coordinates, if any, follow the first three.
+            let line = lines.expect()?;
+            let mut fields = line.split_whitespace();
+            let x = parse(fields.next(), lines.line_no)?;
+            let y = parse(fields.next(), lines.line_no)?;
+            let z = parse(fields.next(), lines.line_no)?;
+            mesh.add_node(tag, [x, y, z]);
+        }
+    }
+    Ok(())
+}
+
+fn read_elements_v4<R: io::BufRead>(lines: &mut Lines<R>, mesh: &mut Builder) -> Result<()> {
+    let header: Vec<usize> = lines.numbers()?;
+    let block_count = *header.first().ok_or(Error::BadLine(lines.line_no))?;
+    for _ in 0..block_count {
+        let block: Vec<Ref> = lines.numbers()?;
+        let (entity_tag, el_type, element_count) = match block[..] {
+            [_entity_dim, entity_tag, el_type, element_count] => {
+                (entity_tag, el_type, element_count)
+            }
+            _ => return Err(Error::BadLine(lines.line_no)),
+        };
+        let el_type = usize::try_from(el_type).map_err(|_| Error::BadLine(lines.line_no))?;
+        let el_type = ElementType::from_gmsh(el_type)?;
+        for _ in 0..element_count {
+            let line = lines.expect()?;
+            let mut fields = line.split_whitespace();
+            let _tag: usize = parse(fields.next(), lines.line_no)?;
+            mesh.add_element(el_type, fields, entity_tag, lines.line_no)?;
+        }
+    }
+    Ok(())
+}
+
+/// Wrapping `r` in a [`std::io::BufReader`] is recommended.
+pub fn read<R>(r: R) -> Result<Mesh>
+where
+    R: io::BufRead,
+{
+    let mut lines = Lines {
+        lines: r.lines(),
+        line_no: 0,
+    };
+
+    match lines.next()? {
+        Some(line) if line.trim() == "$MeshFormat" => {}
+        _ => return Err(Error::BadHeader),
+    }
+    let format = lines.expect()?;
+    let is_v4 = match format.split_whitespace().collect::<Vec<_>>()[..] {
+        ["4.1", "0", _] => true,
+        [version, "0", _] if version.starts_with("2.") => false,
+        [_, _, _] => return Err(Error::UnsupportedFormat),
+        _ => return Err(Error::BadLine(lines.line_no)),
+    };
+    lines.skip_section("MeshFormat")?;
+
+    let mut mesh = Builder::default();
+    while let Some(line) = lines.next()? {
+        let section = match line.trim().strip_prefix('$') {
+            Some(section) => section.to_owned(),
+            None => return Err(Error::BadLine(lines.line_no)),
+        };
+        match (section.as_str(), is_v4) {
+            ("Nodes", false) => read_nodes_v2(&mut lines, &mut mesh)?,
+            ("Nodes", true) => read_nodes_v4(&mut lines, &mut mesh)?,
+            ("Elements", false) => read_elements_v2(&mut lines, &mut mesh)?,
+            ("Elements", true) => read_elements_v4(&mut lines, &mut mesh)?,
+            _ => {}
+        }
+        lines.skip_section(&section)?;
+    }
+
+    Ok(mesh.build())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_v2() {
+        // Two triangles forming a unit square, in physical group 7.
+        let input = "$MeshFormat
+2.2 0 8
+$EndMeshFormat
+$Nodes
+4
+1 0 0 0
+2 1 0 0
+3 1 1 0
+4 0 1 0
+$EndNodes
+$Elements
+2
+1 2 2 7 1 1 2 3
+2 2 2 7 1 1 3 4
+$EndElements
+";
+        let mesh = read(input.as_bytes()).unwrap();
+        assert_eq!(mesh.dimension(), 2);
+        assert_eq!(mesh.node_count(), 4);
+        assert_eq!(mesh.element_count(), 2);
+        assert_eq!(
+            mesh.topology(),
+            [(ElementType::Triangle, vec![0, 1, 2, 0, 2, 3], vec![7, 7])],
+        );
+    }
+
+    #[test]
+    fn test_read_v4() {
+        // A tetrahedron and a point, with sparse node tags.
+        let input = "$MeshFormat
+4.1 0 8
+$EndMeshFormat
+$Entities
+0 0 0 1
+1 0 0 0 1 1 1 0 0
+$EndEntities
+$Nodes
+1 4 10 40
+3 1 0 4
+10
+20
+30
+40
+0 0 0
+1 0 0
+0 1 0
+0 0 1
+$EndNodes
+$Elements
+2 2 1 2
+3 1 4 1
+1 10 20 30 40
+0 5 15 1
+2 40
+$EndElements
+";
+        let mesh = read(input.as_bytes()).unwrap();
+        assert_eq!(mesh.dimension(), 3);
+        assert_eq!(mesh.node_count(), 4);
+        assert_eq!(mesh.element_count(), 2);
+        assert_eq!(
+            mesh.topology(),
+            [
+                (ElementType::Tetrahedron, vec![0, 1, 2, 3], vec![1]),
+                (ElementType::Vertex, vec![3], vec![5]),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_read_invalid() {
+        let header = "$MeshFormat\n2.2 0 8\n$EndMeshFormat\n";
+        let unknown_node = format!(
+            "{header}$Nodes\n1\n1 0 0 0\n$EndNodes\n$Elements\n1\n1 15 0 2\n$EndElements\n"
+        );
+        assert!(matches!(
+            read(unknown_node.as_bytes()),
+            Err(Error::UnknownNode(10))
+        ));
+        let second_order = format!("{header}$Elements\n1\n1 9 0 1 2 3 4 5 6\n$EndElements\n");
+        assert!(matches!(
+            read(second_order.as_bytes()),
+            Err(Error::UnsupportedElement(9))
+        ));
+        assert!(matches!(
+            read(&b"$MeshFormat\n4.1 1 8\n$EndMeshFormat\n"[..]),
+            Err(Error::UnsupportedFormat)
+        ));
+        assert!(matches!(read(&b"$Nodes\n"[..]), Err(Error::BadHeader)));
+        let huge_block = format!(
+            "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n$Nodes\n1 1 1 1\n0 1 0 {}\n$EndNodes\n",
+            usize::MAX,
+        );
+        assert!(matches!(
+            read(huge_block.as_bytes()),
+            Err(Error::BadLine(7))
+        ));
+    }
+}
@@ -13,10 +13,13 @@ use std::io;
 use std::path::Path;
 use std::str::FromStr;
 
+pub mod gmsh;
 pub mod medit;
+pub mod metis_graph;
 pub mod partition;
 #[cfg(feature = "vtkio")]
 pub mod vtk;
+pub mod vtu;
 pub mod weight;
 
 type Ref = isize;
@@ -73,6 +76,7 @@ pub struct Mesh {
 pub enum Error {
     Io(io::Error),
     UnknownFormat,
+    Gmsh(gmsh::Error),
     Medit(medit::ParseError),
 
     #[cfg(feature = "vtkio")]
@@ -86,6 +90,7 @@ impl fmt::Display for Error {
         match self {
             Self::Io(err) => write!(f, "io: {err}"),
             Self::UnknownFormat => write!(f, "unknown format"),
+            Self::Gmsh(err) => write!(f, "gmsh: {err}"),
             Self::Medit(err) => write!(f, "medit: {err}"),
 
             #[cfg(feature = "vtkio")]
@@ -102,6 +107,15 @@ impl From<io::Error> for Error {
     }
 }
 
+impl From<gmsh::Error> for Error {
+    fn from(err: gmsh::Error) -> Self {
+        match err {
+            gmsh::Error::Io(io_err) => Self::Io(io_err),
+            _ => Self::Gmsh(err),
+        }
+    }
+}
+
 impl From<medit::ParseError> for Error {
     fn from(err: medit::ParseError) -> Self {
         Self::Medit(err)
@@ -133,6 +147,9 @@ impl Mesh {
         if medit::test_format_ascii(buf) {
             return Ok(medit::parse_ascii(r)?);
         }
+        if gmsh::test_format(buf) {
+            return Ok(gmsh::read(r)?);
+        }
         #[cfg(feature = "vtk-legacy")]
         if vtk::test_format_legacy(buf) {
             return Ok(vtk::parse_legacy(r)?);
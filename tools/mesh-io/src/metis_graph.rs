@@ -0,0 +1,290 @@
+//! METIS graph file format encoder/decoder.
+//!
+//! The format is described in the [METIS manual], section 4.1.1. In short:
+//!
+//! - lines starting with `%` are comments,
+//! - the header is `n m [fmt [ncon]]`, where `n` is the number of vertices and
+//!   `m` the number of (undirected) edges,
+//! - then follows one line per vertex, which lists its `ncon` weights if the
+//!   graph has vertex weights, then its neighbors (1-based), each followed by
+//!   the weight of the edge if the graph has edge weights.
+//!
+//! Vertex sizes (`fmt` = `1xx`) are not supported.
+//!
+//! [METIS manual]: https://github.com/KarypisLab/METIS/blob/master/manual/manual.pdf
+
+use std::fmt;
+use std::io;
+
+/// An undirected graph, stored in the compressed sparse row format.
+///
+/// The adjacency matrix can be built with
+/// `CsMat::new((n, n), graph.xadj, graph.adjncy, data)`, where `data` is
+/// either the edge weights or a vector of ones.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Graph {
+    /// Offsets of the neighbor lists in `adjncy`, of length `n + 1`.
+    pub xadj: Vec<usize>,
+
+    /// The concatenated, 0-based neighbor lists of the vertices.
+    pub adjncy: Vec<usize>,
+
+    /// The weights of the vertices, one vector of `ncon` criteria per vertex.
+    pub vertex_weights: Option<Vec<Vec<i64>>>,
+
+    /// The weights of the edges, in the same order as `adjncy`.
+    pub edge_weights: Option<Vec<i64>>,
+}
+
+impl Graph {
+    /// The number of vertices.
+    pub fn vertex_count(&self) -> usize {
+        self.xadj.len().saturating_sub(1)
+    }
+
+    /// The number of undirected edges.
+    pub fn edge_count(&self) -> usize {
+        self.adjncy.len() / 2
+    }
+
+    /// The 0-based neighbors of the given vertex.
+    pub fn neighbors(&self, vertex: usize) -> &[usize] {
+        &self.adjncy[self.xadj[vertex]..self.xadj[vertex + 1]]
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    BadHeader,
+    UnsupportedFormat,
+    /// The given line (1-based) is malformed.
+    BadLine(usize),
+    /// The number of vertices or edges does not match the header.
+    CountMismatch,
+    Io(io::Error),
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Error {
+        Error::Io(err)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::BadHeader => write!(f, "bad file header"),
+            Error::UnsupportedFormat => write!(f, "unsupported graph format"),
+            Error::BadLine(line) => write!(f, "malformed line {line}"),
+            Error::CountMismatch => write!(f, "vertex or edge count does not match the header"),
+            Error::Io(_) => write!(f, "read/write error"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+fn parse_numbers(line: &str, line_no: usize) -> Result<Vec<i64>> {
+    line.split_whitespace()
+        .map(|token| token.parse().map_err(|_| Error::BadLine(line_no)))
+        .collect()
+}
+
+/// Wrapping `r` in a [`std::io::BufReader`] is recommended.
+pub fn read<R>(r: R) -> Result<Graph>
+where
+    R: io::BufRead,
+{
+    let mut lines = r
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line))
+        .filter(|(_, line)| !matches!(line, Ok(line) if line.starts_with('%')));
+
+    let header = match lines.next() {
+        Some((_, line)) => line?,
+        None => return Err(Error::BadHeader),
+    };
+    let header: Vec<usize> = header
+        .split_whitespace()
+        .map(|token| token.parse().map_err(|_| Error::BadHeader))
+        .collect::<Result<_>>()?;
+    let (vertex_count, edge_count, format, ncon) = match header[..] {
+        [n, m] => (n, m, 0, 1),
+        [n, m, fmt] => (n, m, fmt, 1),
+        [n, m, fmt, ncon] => (n, m, fmt, ncon),
+        _ => return Err(Error::BadHeader),
+    };
+    if format > 11 || format % 10 > 1 {
+        return Err(Error::UnsupportedFormat);
+    }
+    let has_vertex_weights = format / 10 == 1;
+    let has_edge_weights = format % 10 == 1;
+
+    // Do not trust the header to reserve memory, it might be corrupt.
+    let mut graph = Graph {
+        xadj: Vec::new(),
+        adjncy: Vec::new(),
+        vertex_weights: has_vertex_weights.then(Vec::new),
+        edge_weights: has_edge_weights.then(Vec::new),
+    };
+    graph.xadj.push(0);
+
+    for (line_no, line) in lines {
+        let line = line?;
+        if graph.vertex_count() == vertex_count {
+            if line.trim().is_empty() {
+                continue;
+            }
+            return Err(Error::CountMismatch);
+        }
+        let mut numbers = parse_numbers(&line, line_no)?.into_iter();
+
+        if let Some(vertex_weights) = &mut graph.vertex_weights {
+            let weights: Vec<i64> = numbers.by_ref().take(ncon).collect();
+            if weights.len() != ncon {
+                return Err(Error::BadLine(line_no));
+            }
+            vertex_weights.push(weights);
+        }
+        while let Some(neighbor) = numbers.next() {
+            if neighbor < 1 || vertex_count < neighbor as usize {
+                return Err(Error::BadLine(line_no));
+            }
+            graph.adjncy.push(neighbor as usize - 1);
+            if let Some(edge_weights) = &mut graph.edge_weights {
+                let weight = numbers.next().ok_or(Error::BadLine(line_no))?;
+                edge_weights.push(weight);
+            }
+        }
+        graph.xadj.push(graph.adjncy.len());
+    }
+
+    if graph.vertex_count() != vertex_count || Some(graph.adjncy.len()) != edge_count.checked_mul(2)
+    {
+        return Err(Error::CountMismatch);
+    }
+
+    Ok(graph)
+}
+
+/// Wrapping `w` in a [`std::io::BufWriter`] is recommended.
+pub fn write<W>(mut w: W, graph: &Graph) -> io::Result<()>
+where
+    W: io::Write,
+{
+    let ncon = match &graph.vertex_weights {
+        Some(vertex_weights) => vertex_weights.first().map_or(1, Vec::len),
+        None => 1,
+    };
+    let format = match (&graph.vertex_weights, &graph.edge_weights) {
+        (None, None) => "",
+        (None, Some(_)) => " 1",
+        (Some(_), None) => " 10",
+        (Some(_), Some(_)) => " 11",
+    };
+    write!(
+        w,
+        "{} {}{}",
+        graph.vertex_count(),
+        graph.edge_count(),
+        format
+    )?;
+    if ncon != 1 {
+        write!(w, " {ncon}")?;
+    }
+    writeln!(w)?;
+
+    for vertex in 0..graph.vertex_count() {
+        let mut separator = "";
+        if let Some(vertex_weights) = &graph.vertex_weights {
+            for weight in &vertex_weights[vertex] {
+                write!(w, "{separator}{weight}")?;
+                separator = " ";
+            }
+        }
+        for i in graph.xadj[vertex]..graph.xadj[vertex + 1] {
+            write!(w, "{separator}{}", graph.adjncy[i] + 1)?;
+            separator = " ";
+            if let Some(edge_weights) = &graph.edge_weights {
+                write!(w, " {}", edge_weights[i])?;
+            }
+        }
+        writeln!(w)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        // 0 - 1
+        // |   |
+        // 3 - 2
+        let comment = "% a weighted square\n";
+        let input = "4 4 11 2
+1 2 2 5 4 3
+3 4 1 5 3 7
+5 6 2 7 4 1
+7 8 1 3 3 1
+";
+        let graph = read(format!("{comment}{input}").as_bytes()).unwrap();
+        assert_eq!(
+            graph,
+            Graph {
+                xadj: vec![0, 2, 4, 6, 8],
+                adjncy: vec![1, 3, 0, 2, 1, 3, 0, 2],
+                vertex_weights: Some(vec![vec![1, 2], vec![3, 4], vec![5, 6], vec![7, 8]]),
+                edge_weights: Some(vec![5, 3, 5, 7, 7, 1, 3, 1]),
+            },
+        );
+        assert_eq!(graph.neighbors(2), &[1, 3]);
+
+        let mut output = Vec::new();
+        write(&mut output, &graph).unwrap();
+        assert_eq!(String::from_utf8(output.clone()).unwrap(), input);
+        assert_eq!(read(&output[..]).unwrap(), graph);
+    }
+
+    #[test]
+    fn test_unweighted() {
+        let input = "3 2\n2\n1 3\n2\n";
+        let graph = read(input.as_bytes()).unwrap();
+        assert_eq!(graph.vertex_weights, None);
+        assert_eq!(graph.edge_weights, None);
+        assert_eq!(graph.edge_count(), 2);
+
+        let mut output = Vec::new();
+        write(&mut output, &graph).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), input);
+    }
+
+    #[test]
+    fn test_count_mismatch() {
+        let input = "3 3\n2\n1 3\n2\n";
+        assert!(matches!(read(input.as_bytes()), Err(Error::CountMismatch)));
+        assert!(matches!(read("".as_bytes()), Err(Error::BadHeader)));
+        assert!(matches!(
+            read("2 1 100\n2\n1\n".as_bytes()),
+            Err(Error::UnsupportedFormat)
+        ));
+        let huge_header = format!("{} {}\n2\n1\n", usize::MAX, usize::MAX);
+        assert!(matches!(
+            read(huge_header.as_bytes()),
+            Err(Error::CountMismatch)
+        ));
+    }
+}
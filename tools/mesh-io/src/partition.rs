@@ -7,6 +7,12 @@ use std::io;
 
 // TODO compile_error when sizeof(usize) < sizeof(u64)
 
+/// Magic bytes of files that only store the number of elements.
+const MAGIC: &[u8; 4] = b"MePe";
+
+/// Magic bytes of files that also store the number of parts.
+const MAGIC_WITH_PART_COUNT: &[u8; 4] = b"MePc";
+
 #[derive(Debug)]
 pub enum Error {
     BadHeader,
@@ -41,29 +47,113 @@ impl std::error::Error for Error {
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+fn read_u64<R>(r: &mut R) -> io::Result<u64>
+where
+    R: io::Read,
+{
+    let mut buf = [0x00; 8];
+    r.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
 /// Wrapping `r` in a [`std::io::BufReader`] is recommended.
-pub fn read<R>(mut r: R) -> Result<Vec<usize>>
+pub fn read<R>(r: R) -> Result<Vec<usize>>
 where
     R: io::Read,
 {
-    let mut header = [0x00; 4];
-    r.read_exact(&mut header)?;
-    if &header != b"MePe" {
-        return Err(Error::BadHeader);
+    let reader = read_streaming(r)?;
+    let mut partition = Vec::with_capacity(reader.element_count());
+    for item in reader {
+        let (_, part) = item?;
+        partition.push(part);
     }
+    Ok(partition)
+}
 
-    let mut count_buf = [0x00; 8];
-    r.read_exact(&mut count_buf)?;
-    let count = u64::from_le_bytes(count_buf) as usize;
+/// Iterator over the `(index, part)` pairs of a partition file.
+///
+/// This type is returned by [`read_streaming`].
+#[derive(Debug)]
+pub struct Reader<R> {
+    r: R,
+    element_count: usize,
+    part_count: Option<usize>,
+    index: usize,
+}
 
-    let mut partition = Vec::with_capacity(count);
-    for _ in 0..count {
-        let mut weight_buf = [0x00; 8];
-        r.read_exact(&mut weight_buf)?;
-        partition.push(u64::from_le_bytes(weight_buf) as usize);
+impl<R> Reader<R> {
+    /// The number of elements in the file.
+    pub fn element_count(&self) -> usize {
+        self.element_count
     }
 
-    Ok(partition)
+    /// The number of parts, if the file has been written by
+    /// [`write_with_header`].
+    pub fn part_count(&self) -> Option<usize> {
+        self.part_count
+    }
+}
+
+impl<R> Iterator for Reader<R>
+where
+    R: io::Read,
+{
+    type Item = Result<(usize, usize)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index == self.element_count {
+            return None;
+        }
+        let index = self.index;
+        self.index += 1;
+        match read_u64(&mut self.r) {
+            Ok(part) => Some(Ok((index, part as usize))),
+            Err(err) => {
+                // Do not yield anything after an error.
+                self.index = self.element_count;
+                Some(Err(Error::Io(err)))
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.element_count - self.index;
+        (len, Some(len))
+    }
+}
+
+/// Reads the header of a partition file, and returns an iterator over its
+/// part IDs, so that they do not have to fit in memory.
+///
+/// Both the files written by [`write`] and [`write_with_header`] are
+/// accepted.
+///
+/// Wrapping `r` in a [`std::io::BufReader`] is recommended.
+pub fn read_streaming<R>(mut r: R) -> Result<Reader<R>>
+where
+    R: io::Read,
+{
+    let mut magic = [0x00; 4];
+    r.read_exact(&mut magic)?;
+    let has_part_count = match &magic {
+        MAGIC => false,
+        MAGIC_WITH_PART_COUNT => true,
+        _ => return Err(Error::BadHeader),
+    };
+
+    let element_count = read_u64(&mut r)? as usize;
+    let part_count = if has_part_count {
+        Some(read_u64(&mut r)? as usize)
+    } else {
+        None
+    };
+
+    Ok(Reader {
+        r,
+        element_count,
+        part_count,
+        index: 0,
+    })
 }
 
 /// Wrapping `w` in a [`std::io::BufWriter`] is recommended.
@@ -75,7 +165,7 @@ where
 {
     let array = array.into_iter();
 
-    write!(w, "MePe")?;
+    w.write_all(MAGIC)?;
     w.write_all(&u64::to_le_bytes(array.len() as u64))?;
 
     for id in array {
@@ -84,3 +174,88 @@ where
 
     Ok(())
 }
+
+/// Same as [`write`], but also stores the number of parts in the header, so
+/// that readers know it before they go through the part IDs.
+///
+/// Part IDs are written as they come, so `array` can be generated on the fly.
+/// They are expected to be lower than `part_count`.
+///
+/// Wrapping `w` in a [`std::io::BufWriter`] is recommended.
+pub fn write_with_header<I, W>(mut w: W, part_count: usize, array: I) -> io::Result<()>
+where
+    I: IntoIterator<Item = usize>,
+    I::IntoIter: ExactSizeIterator,
+    W: io::Write,
+{
+    let array = array.into_iter();
+
+    w.write_all(MAGIC_WITH_PART_COUNT)?;
+    w.write_all(&u64::to_le_bytes(array.len() as u64))?;
+    w.write_all(&u64::to_le_bytes(part_count as u64))?;
+
+    for id in array {
+        debug_assert!(id < part_count);
+        w.write_all(&u64::to_le_bytes(id as u64))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_streaming_round_trip() {
+        let element_count = 1 << 20;
+        let part_count = 37;
+        let partition = || (0..element_count).map(|i| i * 7919 % part_count);
+
+        let mut file = Vec::new();
+        write_with_header(&mut file, part_count, partition()).unwrap();
+        assert_eq!(file.len(), 4 + 8 + 8 + 8 * element_count);
+
+        let reader = read_streaming(&file[..]).unwrap();
+        assert_eq!(reader.element_count(), element_count);
+        assert_eq!(reader.part_count(), Some(part_count));
+        assert_eq!(reader.size_hint(), (element_count, Some(element_count)));
+        let mut expected = partition().enumerate();
+        for item in reader {
+            assert_eq!(item.unwrap(), expected.next().unwrap());
+        }
+        assert!(expected.next().is_none());
+
+        assert!(read(&file[..]).unwrap().into_iter().eq(partition()));
+    }
+
+    #[test]
+    fn test_read_without_part_count() {
+        let mut file = Vec::new();
+        write(&mut file, [1, 0, 2]).unwrap();
+
+        let reader = read_streaming(&file[..]).unwrap();
+        assert_eq!(reader.part_count(), None);
+        let items: Vec<(usize, usize)> = reader.map(Result::unwrap).collect();
+        assert_eq!(items, [(0, 1), (1, 0), (2, 2)]);
+
+        assert_eq!(read(&file[..]).unwrap(), [1, 0, 2]);
+    }
+
+    #[test]
+    fn test_read_truncated() {
+        let mut file = Vec::new();
+        write_with_header(&mut file, 2, [0, 1, 1]).unwrap();
+        file.truncate(file.len() - 4);
+
+        let results: Vec<Result<(usize, usize)>> = read_streaming(&file[..]).unwrap().collect();
+        assert_eq!(results.len(), 3);
+        assert!(matches!(results[2], Err(Error::Io(_))));
+        assert!(read(&file[..]).is_err());
+
+        assert!(matches!(
+            read_streaming(&b"MeP?"[..]),
+            Err(Error::BadHeader)
+        ));
+    }
+}
@@ -0,0 +1,222 @@
+//! This module allows to write VTK XML unstructured grid files (`.vtu`), as
+//! described in [VTK File Formats](https://docs.vtk.org/en/latest/design_documents/VTKFileFormats.html).
+
+use crate::ElementType;
+use crate::Mesh;
+use std::fmt;
+use std::iter;
+
+impl ElementType {
+    fn vtk_cell_type(self) -> u8 {
+        match self {
+            Self::Vertex => 1,
+            Self::Edge => 3,
+            Self::Triangle => 5,
+            Self::Quadrangle | Self::Quadrilateral => 9,
+            Self::Tetrahedron => 10,
+            Self::Hexahedron => 12,
+        }
+    }
+}
+
+/// Serialize a mesh into the ASCII, XML VTK format for unstructured grids.
+///
+/// Node references and element references are written as point data and cell
+/// data respectively.
+///
+/// This type implements [`Display`](fmt::Display).
+#[derive(Debug)]
+pub struct DisplayAscii<'a> {
+    mesh: &'a Mesh,
+    cell_refs_name: &'a str,
+}
+
+impl Mesh {
+    pub fn display_vtu_ascii(&self) -> DisplayAscii<'_> {
+        DisplayAscii {
+            mesh: self,
+            cell_refs_name: "cell_refs",
+        }
+    }
+}
+
+impl<'a> DisplayAscii<'a> {
+    /// Set the name of the cell data array holding element references.
+    ///
+    /// Defaults to `cell_refs`.
+    pub fn cell_refs_name(mut self, name: &'a str) -> Self {
+        self.cell_refs_name = name;
+        self
+    }
+}
+
+impl fmt::Display for DisplayAscii<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mesh = self.mesh;
+        if mesh.dimension() > 3 {
+            return Err(fmt::Error);
+        }
+
+        writeln!(f, r#"<?xml version="1.0"?>"#)?;
+        writeln!(
+            f,
+            r#"<VTKFile type="UnstructuredGrid" version="0.1" byte_order="LittleEndian">"#
+        )?;
+        writeln!(f, "  <UnstructuredGrid>")?;
+        writeln!(
+            f,
+            r#"    <Piece NumberOfPoints="{}" NumberOfCells="{}">"#,
+            mesh.node_count(),
+            mesh.element_count(),
+        )?;
+
+        writeln!(f, "      <Points>")?;
+        writeln!(
+            f,
+            r#"        <DataArray type="Float64" NumberOfComponents="3" format="ascii">"#
+        )?;
+        for (coordinates, _) in mesh.nodes() {
+            write!(f, "         ")?;
+            for coordinate in coordinates.iter().chain(iter::repeat(&0.0)).take(3) {
+                write!(f, " {}", coordinate)?;
+            }
+            writeln!(f)?;
+        }
+        writeln!(f, "        </DataArray>")?;
+        writeln!(f, "      </Points>")?;
+
+        writeln!(f, "      <Cells>")?;
+        writeln!(
+            f,
+            r#"        <DataArray type="Int64" Name="connectivity" format="ascii">"#
+        )?;
+        for (_, nodes, _) in mesh.elements() {
+            write!(f, "         ")?;
+            for node in nodes {
+                write!(f, " {}", node)?;
+            }
+            writeln!(f)?;
+        }
+        writeln!(f, "        </DataArray>")?;
+        writeln!(
+            f,
+            r#"        <DataArray type="Int64" Name="offsets" format="ascii">"#
+        )?;
+        let mut offset = 0;
+        for (element_type, _, _) in mesh.elements() {
+            offset += element_type.node_count();
+            writeln!(f, "          {}", offset)?;
+        }
+        writeln!(f, "        </DataArray>")?;
+        writeln!(
+            f,
+            r#"        <DataArray type="UInt8" Name="types" format="ascii">"#
+        )?;
+        for (element_type, _, _) in mesh.elements() {
+            writeln!(f, "          {}", element_type.vtk_cell_type())?;
+        }
+        writeln!(f, "        </DataArray>")?;
+        writeln!(f, "      </Cells>")?;
+
+        writeln!(f, r#"      <PointData Scalars="node_refs">"#)?;
+        writeln!(
+            f,
+            r#"        <DataArray type="Int64" Name="node_refs" format="ascii">"#
+        )?;
+        for node_ref in mesh.node_refs() {
+            writeln!(f, "          {}", node_ref)?;
+        }
+        writeln!(f, "        </DataArray>")?;
+        writeln!(f, "      </PointData>")?;
+
+        writeln!(f, r#"      <CellData Scalars="{}">"#, self.cell_refs_name)?;
+        writeln!(
+            f,
+            r#"        <DataArray type="Int64" Name="{}" format="ascii">"#,
+            self.cell_refs_name,
+        )?;
+        for (_, _, element_ref) in mesh.elements() {
+            writeln!(f, "          {}", element_ref)?;
+        }
+        writeln!(f, "        </DataArray>")?;
+        writeln!(f, "      </CellData>")?;
+
+        writeln!(f, "    </Piece>")?;
+        writeln!(f, "  </UnstructuredGrid>")?;
+        write!(f, "</VTKFile>")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::medit::parse_ascii;
+
+    #[test]
+    fn test_serialize() {
+        let input = "MeshVersionFormatted 2
+Dimension 2
+
+Vertices
+\t4
+ 0 0 0
+ 1 0 0
+ 1 1 0
+ 0 1 0
+
+Triangles
+\t2
+ 1 2 3 4
+ 1 3 4 7
+
+End";
+        let mesh = parse_ascii(input.as_bytes()).unwrap();
+        let output = mesh
+            .display_vtu_ascii()
+            .cell_refs_name("partition")
+            .to_string();
+        let expected = r#"<?xml version="1.0"?>
+<VTKFile type="UnstructuredGrid" version="0.1" byte_order="LittleEndian">
+  <UnstructuredGrid>
+    <Piece NumberOfPoints="4" NumberOfCells="2">
+      <Points>
+        <DataArray type="Float64" NumberOfComponents="3" format="ascii">
+          0 0 0
+          1 0 0
+          1 1 0
+          0 1 0
+        </DataArray>
+      </Points>
+      <Cells>
+        <DataArray type="Int64" Name="connectivity" format="ascii">
+          0 1 2
+          0 2 3
+        </DataArray>
+        <DataArray type="Int64" Name="offsets" format="ascii">
+          3
+          6
+        </DataArray>
+        <DataArray type="UInt8" Name="types" format="ascii">
+          5
+          5
+        </DataArray>
+      </Cells>
+      <PointData Scalars="node_refs">
+        <DataArray type="Int64" Name="node_refs" format="ascii">
+          0
+          0
+          0
+          0
+        </DataArray>
+      </PointData>
+      <CellData Scalars="partition">
+        <DataArray type="Int64" Name="partition" format="ascii">
+          4
+          7
+        </DataArray>
+      </CellData>
+    </Piece>
+  </UnstructuredGrid>
+</VTKFile>"#;
+        assert_eq!(output, expected);
+    }
+}
@@ -153,11 +153,9 @@ fn main() -> Result<()> {
     let mesh = mesh?;
     let weights = weights?;
 
-    let partition = match mesh.dimension() {
-        2 => main_d::<2>(&matches, edge_weights, mesh, weights)?,
-        3 => main_d::<3>(&matches, edge_weights, mesh, weights)?,
-        n => anyhow::bail!("expected 2D or 3D mesh, got a {n}D mesh"),
-    };
+    let partition = coupe_tools::with_dimension!(mesh, D => {
+        main_d::<D>(&matches, edge_weights, mesh, weights)
+    })??;
 
     let output = coupe_tools::writer(matches.free.get(0))?;
     mesh_io::partition::write(output, partition).context("failed to write partition")?;
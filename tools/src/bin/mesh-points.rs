@@ -66,11 +66,7 @@ fn main() -> Result<()> {
     let mesh = coupe_tools::read_mesh(matches.free.get(0))?;
     let output = coupe_tools::writer(matches.free.get(1))?;
     let with_ids = matches.opt_present("with-ids");
-    match mesh.dimension() {
-        2 => write_points::<2>(output, &mesh, with_ids)?,
-        3 => write_points::<3>(output, &mesh, with_ids)?,
-        n => anyhow::bail!("expected 2D or 3D mesh, got a {n}D mesh"),
-    };
+    coupe_tools::with_dimension!(mesh, D => write_points::<D>(output, &mesh, with_ids))??;
 
     Ok(())
 }
@@ -3,8 +3,6 @@ use coupe::sprs::CsMatView;
 use coupe::Point2D;
 use mesh_io::ElementType;
 use mesh_io::Mesh;
-use rayon::iter::IntoParallelRefIterator as _;
-use rayon::iter::ParallelIterator as _;
 use std::collections::HashSet;
 use std::env;
 use std::io;
@@ -254,17 +252,32 @@ fn paths(mesh: &Mesh) -> impl Iterator<Item = Path> + '_ {
     })
 }
 
-fn write_svg<W>(mut w: W, mesh: &Mesh, optimize: bool) -> Result<()>
+/// The RGB color of the `index`-th out of `count` refs, with hues evenly
+/// spread along the color wheel, so that consecutive refs are easy to tell
+/// apart.
+fn hue_color(index: isize, count: isize) -> isize {
+    let hue = index as f64 / count as f64 * 6.0;
+    let (saturation, value) = (0.6, 0.9);
+    let chroma = value * saturation;
+    let x = chroma * (1.0 - f64::abs(hue % 2.0 - 1.0));
+    let (r, g, b) = match hue as usize {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let channel = |c: f64| ((c + value - chroma) * 255.0).round() as isize;
+    channel(r) << 16 | channel(g) << 8 | channel(b)
+}
+
+fn write_svg<W>(mut w: W, mesh: &Mesh, optimize: bool, hues: bool) -> Result<()>
 where
     W: io::Write,
 {
-    let coordinates = unsafe {
-        std::slice::from_raw_parts(
-            mesh.coordinates().as_ptr() as *const Point2D,
-            mesh.node_count(),
-        )
-    };
-    let bb = match coupe::BoundingBox::<2>::from_points(coordinates.par_iter().cloned()) {
+    let coordinates = coupe::points_from_flat::<2>(mesh.coordinates());
+    let bb = match coupe::BoundingBox::<2>::from_points(coordinates.iter().cloned()) {
         Some(v) => v,
         None => return Ok(()),
     };
@@ -285,6 +298,9 @@ where
             .max()
             .unwrap_or(0);
         move |el_ref| {
+            if hues {
+                return hue_color(el_ref, ref_count);
+            }
             let brightness = (el_ref as f64 / ref_count as f64 * 256.0) as isize;
             brightness << 16 | brightness << 8 | brightness
         }
@@ -325,6 +341,11 @@ fn main() -> Result<()> {
         "no-optimize",
         "do not merge elements of the same ref together",
     );
+    options.optflag(
+        "c",
+        "hues",
+        "color refs with distinct hues instead of shades of gray",
+    );
 
     let matches = options.parse(env::args().skip(1))?;
 
@@ -343,8 +364,9 @@ fn main() -> Result<()> {
     let mesh = coupe_tools::read_mesh(matches.free.get(0))?;
     let output = coupe_tools::writer(matches.free.get(1))?;
     let optimize = !matches.opt_present("o");
+    let hues = matches.opt_present("c");
     match mesh.dimension() {
-        2 => write_svg(output, &mesh, optimize)?,
+        2 => write_svg(output, &mesh, optimize, hues)?,
         n => anyhow::bail!("expected 2D mesh, got a {n}D mesh"),
     };
 
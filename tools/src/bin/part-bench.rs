@@ -7,14 +7,26 @@ use coupe::nalgebra::DefaultAllocator;
 use coupe::nalgebra::DimDiff;
 use coupe::nalgebra::DimSub;
 use coupe::nalgebra::ToTypenum;
+use coupe::num_traits::FromPrimitive;
+use coupe::num_traits::ToPrimitive;
+use coupe::num_traits::Zero;
+use coupe::PointND;
+use coupe::Topology as _;
 use criterion::Criterion;
 use mesh_io::weight;
 use mesh_io::Mesh;
+use rayon::iter::IntoParallelRefIterator as _;
+use rayon::iter::ParallelIterator as _;
 use std::env;
+use std::fmt;
 use std::fs;
 use std::io;
 use std::iter;
+use std::iter::Sum;
+use std::ops::AddAssign;
+use std::ops::Div;
 use std::ops::Range;
+use std::ops::Sub;
 use std::thread::sleep;
 use std::time::Duration;
 
@@ -163,6 +175,119 @@ fn measure_efficiency(
     Ok(())
 }
 
+/// The algorithm specs to benchmark for each of the given part counts.
+///
+/// Algorithms that do not take a part count, like partition improving ones,
+/// are left as-is.
+fn part_count_sweep(
+    algorithm_specs: &[String],
+    part_counts: &str,
+) -> Result<Vec<(usize, Vec<String>)>> {
+    let ranges = parse_ranges(part_counts).context("failed to parse the value of --part-counts")?;
+    ranges
+        .into_iter()
+        .flatten()
+        .map(|part_count| {
+            let mut swept = false;
+            let specs = algorithm_specs
+                .iter()
+                .map(
+                    |spec| match coupe_tools::set_part_count(spec, part_count)? {
+                        Some(spec) => {
+                            swept = true;
+                            Ok(spec)
+                        }
+                        None => Ok(spec.clone()),
+                    },
+                )
+                .collect::<Result<_>>()?;
+            if !swept {
+                anyhow::bail!("none of the given algorithms takes a part count");
+            }
+            Ok((part_count, specs))
+        })
+        .collect()
+}
+
+/// Quality metrics of a partition, printed by `--report-quality`.
+struct Quality {
+    edge_cut: f64,
+    /// The largest imbalance over all weight criteria.
+    imbalance: f64,
+    /// The mean aspect ratio of the parts' bounding boxes, if at least one
+    /// part has a non-flat bounding box.
+    mean_aspect_ratio: Option<f64>,
+}
+
+impl fmt::Display for Quality {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "edge_cut: {}", self.edge_cut)?;
+        writeln!(f, "imbalance_relative_diff: {}", self.imbalance)?;
+        match self.mean_aspect_ratio {
+            Some(ratio) => writeln!(f, "mean_aspect_ratio: {ratio}"),
+            None => writeln!(f, "mean_aspect_ratio: n/a"),
+        }
+    }
+}
+
+fn imbalance<T>(part_count: usize, partition: &[usize], weights: &[Vec<T>]) -> f64
+where
+    T: Copy + Send + Sync,
+    T: PartialOrd + FromPrimitive + ToPrimitive + Zero,
+    T: AddAssign + Div<Output = T> + Sub<Output = T> + Sum,
+{
+    let criterion_count = weights.first().map_or(0, Vec::len);
+    (0..criterion_count)
+        .map(|criterion| {
+            let weights = weights.par_iter().map(|weight| weight[criterion]);
+            coupe::imbalance::imbalance(part_count, partition, weights)
+        })
+        .fold(0.0, f64::max)
+}
+
+fn mean_aspect_ratio<const D: usize>(
+    points: &[PointND<D>],
+    part_count: usize,
+    partition: &[usize],
+) -> Option<f64> {
+    let mut bounding_boxes = vec![None; part_count];
+    for (point, part) in points.iter().zip(partition) {
+        let (min, max) = bounding_boxes[*part].get_or_insert((*point, *point));
+        *min = min.inf(point);
+        *max = max.sup(point);
+    }
+    let (sum, count) = bounding_boxes
+        .into_iter()
+        .flatten()
+        .filter_map(|(min, max)| {
+            let extent = max - min;
+            let shortest = extent.min();
+            if shortest <= 0.0 {
+                return None;
+            }
+            Some(extent.max() / shortest)
+        })
+        .fold((0.0, 0), |(sum, count), ratio| (sum + ratio, count + 1));
+    if count == 0 {
+        None
+    } else {
+        Some(sum / count as f64)
+    }
+}
+
+fn quality<const D: usize>(problem: &coupe_tools::Problem<D>, partition: &[usize]) -> Quality {
+    let part_count = partition.iter().max().map_or(0, |max| max + 1);
+    let imbalance = match problem.weights() {
+        weight::Array::Integers(weights) => imbalance(part_count, partition, weights),
+        weight::Array::Floats(weights) => imbalance(part_count, partition, weights),
+    };
+    Quality {
+        edge_cut: problem.adjacency().edge_cut(partition),
+        imbalance,
+        mean_aspect_ratio: mean_aspect_ratio(problem.points(), part_count, partition),
+    }
+}
+
 fn main_d<const D: usize>(
     matches: getopts::Matches,
     edge_weights: coupe_tools::EdgeWeightDistribution,
@@ -175,11 +300,32 @@ where
         + Allocator<f64, DimDiff<Const<D>, Const<1>>>,
 {
     let algorithm_specs = matches.opt_strs("a");
-    let mut algorithms: Vec<_> = algorithm_specs
-        .iter()
-        .map(|algorithm_spec| {
-            coupe_tools::parse_algorithm::<D>(algorithm_spec)
-                .with_context(|| format!("invalid algorithm {:?}", algorithm_spec))
+
+    // The algorithm chains to benchmark, along with the part count they have
+    // been set to, if any.
+    let chains: Vec<(Option<usize>, Vec<String>)> = match matches.opt_str("part-counts") {
+        Some(part_counts) => {
+            if matches.opt_present("e") {
+                anyhow::bail!("--part-counts and --efficiency cannot be used together");
+            }
+            part_count_sweep(&algorithm_specs, &part_counts)?
+                .into_iter()
+                .map(|(part_count, specs)| (Some(part_count), specs))
+                .collect()
+        }
+        None => vec![(None, algorithm_specs.clone())],
+    };
+    let mut chains: Vec<_> = chains
+        .into_iter()
+        .map(|(part_count, specs)| {
+            let algorithms: Vec<_> = specs
+                .iter()
+                .map(|algorithm_spec| {
+                    coupe_tools::parse_algorithm::<D>(algorithm_spec)
+                        .with_context(|| format!("invalid algorithm {:?}", algorithm_spec))
+                })
+                .collect::<Result<_>>()?;
+            Ok((part_count, specs, algorithms))
         })
         .collect::<Result<_>>()?;
 
@@ -188,20 +334,26 @@ where
 
     let intel_domain = coupe_tools::ittapi::domain("algorithm-chain");
 
-    let mut runners: Vec<_> = algorithms
+    let mut chains: Vec<_> = chains
         .iter_mut()
-        .zip(&algorithm_specs)
-        .map(|(algorithm, algorithm_spec)| {
-            let name = format!("{algorithm_spec}.to_runner");
-            let _task = coupe_tools::ittapi::begin(&intel_domain, &name);
-
-            algorithm.to_runner(&problem)
+        .map(|(part_count, specs, algorithms)| {
+            let runners: Vec<_> = algorithms
+                .iter_mut()
+                .zip(specs.iter())
+                .map(|(algorithm, algorithm_spec)| {
+                    let name = format!("{algorithm_spec}.to_runner");
+                    let _task = coupe_tools::ittapi::begin(&intel_domain, &name);
+
+                    algorithm.to_runner(&problem)
+                })
+                .collect();
+            (*part_count, runners)
         })
         .collect();
-    let mut benchmark = || {
+    let mut benchmark = |runners: &mut [coupe_tools::Runner<'_>]| {
         let _task = coupe_tools::ittapi::begin(&intel_domain, "benchmark-iteration");
 
-        for runner in &mut runners {
+        for runner in runners {
             runner(&mut partition).unwrap();
         }
     };
@@ -231,10 +383,35 @@ where
             format!("{mesh_file};{weight_file};{}", algorithm_specs.join(";"))
         }
     };
-    if matches.opt_present("e") {
-        measure_efficiency(&mut c, benchmark_name, matches.opt_str("e"), benchmark)?;
-    } else {
-        c.bench_function(&benchmark_name, |b| b.iter(&mut benchmark));
+    match &mut chains[..] {
+        [(None, runners)] if matches.opt_present("e") => {
+            measure_efficiency(&mut c, benchmark_name, matches.opt_str("e"), || {
+                benchmark(runners)
+            })?;
+        }
+        [(None, runners)] => {
+            c.bench_function(&benchmark_name, |b| b.iter(|| benchmark(runners)));
+        }
+        chains => {
+            let mut g = c.benchmark_group(benchmark_name);
+            for (part_count, runners) in chains {
+                let benchmark_name = format!("parts={}", part_count.unwrap());
+                g.bench_function(&benchmark_name, |b| b.iter(|| benchmark(runners)));
+            }
+        }
+    }
+
+    if matches.opt_present("report-quality") {
+        for (part_count, runners) in &mut chains {
+            partition.fill(0);
+            for runner in runners.iter_mut() {
+                runner(&mut partition)?;
+            }
+            if let Some(part_count) = part_count {
+                println!("parts={part_count}:");
+            }
+            print!("{}", quality(&problem, &partition));
+        }
     }
 
     Ok(partition)
@@ -254,6 +431,17 @@ fn main() -> Result<()> {
         "NAME",
     );
     options.optflagopt("e", "efficiency", "Benchmark efficiency", "THREADS");
+    options.optopt(
+        "",
+        "part-counts",
+        "Benchmark the algorithms for each part count",
+        "COUNTS",
+    );
+    options.optflag(
+        "",
+        "report-quality",
+        "Print the quality of the final partitions",
+    );
     options.optopt(
         "E",
         "edge-weights",
@@ -308,11 +496,62 @@ fn main() -> Result<()> {
     println!(" -> Number of nodes: {}", mesh.node_count());
     println!(" -> Number of elements: {}", mesh.element_count());
 
-    match mesh.dimension() {
-        2 => main_d::<2>(matches, edge_weights, mesh, weights)?,
-        3 => main_d::<3>(matches, edge_weights, mesh, weights)?,
-        n => anyhow::bail!("expected 2D or 3D mesh, got a {n}D mesh"),
-    };
+    coupe_tools::with_dimension!(mesh, D => main_d::<D>(matches, edge_weights, mesh, weights))??;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_part_count_sweep() {
+        let specs = vec!["rcb,1".to_string(), "fm".to_string()];
+
+        let sweep = part_count_sweep(&specs, "2,4,8,16").unwrap();
+        let part_counts: Vec<usize> = sweep.iter().map(|(part_count, _)| *part_count).collect();
+        assert_eq!(part_counts, [2, 4, 8, 16]);
+        assert_eq!(sweep[2].1, ["rcb,3", "fm"]);
+
+        assert!(part_count_sweep(&specs, "3").is_err());
+        assert!(part_count_sweep(&specs[1..], "2").is_err());
+    }
+
+    #[test]
+    fn test_report_quality_rcb() {
+        // A 4x4 grid of unit squares.
+        let mut coordinates = Vec::new();
+        for y in 0..5 {
+            for x in 0..5 {
+                coordinates.extend([x as f64, y as f64]);
+            }
+        }
+        let mut elements = Vec::new();
+        for y in 0..4 {
+            for x in 0..4 {
+                let node = y * 5 + x;
+                elements.extend([node, node + 1, node + 6, node + 5]);
+            }
+        }
+        let mesh = Mesh::from_raw_parts(
+            2,
+            coordinates,
+            vec![0; 25],
+            vec![(mesh_io::ElementType::Quadrangle, elements, vec![0; 16])],
+        );
+        let weights = weight::Array::Integers(vec![vec![1]; 16]);
+        let problem =
+            coupe_tools::Problem::new(mesh, weights, coupe_tools::EdgeWeightDistribution::Uniform);
+
+        let mut algorithm = coupe_tools::parse_algorithm::<2>("rcb,1").unwrap();
+        let mut partition = vec![0; 16];
+        algorithm.to_runner(&problem)(&mut partition).unwrap();
+
+        let report = quality(&problem, &partition).to_string();
+        assert_eq!(
+            report,
+            "edge_cut: 4\nimbalance_relative_diff: 0\nmean_aspect_ratio: 3\n",
+        );
+    }
+}
@@ -5,6 +5,7 @@ use coupe::num_traits::FromPrimitive;
 use coupe::num_traits::ToPrimitive;
 use coupe::num_traits::Zero;
 use coupe::sprs::CsMatView;
+use coupe::PointND;
 use coupe::Topology;
 use coupe_tools::set_edge_weights;
 use coupe_tools::EdgeWeightDistribution;
@@ -26,6 +27,7 @@ const USAGE: &str = "Usage: part-info [options]";
 struct CriterionStats<T> {
     total_weight: T,
     imbalance: f64,
+    mean_part_weight: f64,
     max_part_weight: T,
     max_part_weight_count: usize,
     min_part_weight: T,
@@ -58,6 +60,7 @@ where
             self.min_part_weight,
             count_str(self.min_part_weight_count),
         )?;
+        writeln!(f, " - Mean part:    {:12.2}", self.mean_part_weight)?;
         Ok(())
     }
 }
@@ -142,6 +145,7 @@ where
             let res: Box<dyn fmt::Display + Send> = Box::new(CriterionStats {
                 total_weight,
                 imbalance,
+                mean_part_weight: ideal_part_weight,
                 max_part_weight,
                 max_part_weight_count,
                 min_part_weight,
@@ -167,6 +171,43 @@ fn empty_part_count(part_ids: &[usize], part_count: usize) -> usize {
             .len()
 }
 
+/// The mean aspect ratio of the parts, or `None` if no part has a
+/// non-degenerate bounding box.
+///
+/// The aspect ratio of a part is the ratio between the longest and the
+/// shortest sides of the axis-aligned bounding box of its elements'
+/// barycentres.
+fn mean_aspect_ratio<const D: usize>(
+    mesh: &Mesh,
+    part_count: usize,
+    parts: &[usize],
+) -> Option<f64> {
+    let points = coupe_tools::barycentres::<D>(mesh);
+    let mut bounding_boxes = vec![None; part_count];
+    for (point, part) in points.iter().zip(parts) {
+        let (min, max) = bounding_boxes[*part].get_or_insert((*point, *point));
+        *min = min.inf(point);
+        *max = max.sup(point);
+    }
+    let (sum, count) = bounding_boxes
+        .into_iter()
+        .flatten()
+        .filter_map(|(min, max)| {
+            let extent = max - min;
+            let shortest = extent.min();
+            if shortest <= 0.0 {
+                return None;
+            }
+            Some(extent.max() / shortest)
+        })
+        .fold((0.0, 0), |(sum, count), ratio| (sum + ratio, count + 1));
+    if count == 0 {
+        None
+    } else {
+        Some(sum / count as f64)
+    }
+}
+
 /// Wrapper around coupe's [coupe::topology::lambda_cut] that applies the edge
 /// weight distribution and sums the criterions.
 fn lambda_cut<T>(
@@ -179,7 +220,9 @@ where
     T: Sum + Mul<Output = T> + AsPrimitive<f64> + Send + Sync + FromPrimitive,
 {
     let weights = weights.par_iter().map(|weight| match edge_weights {
-        EdgeWeightDistribution::Uniform => T::from_usize(weight.len()).unwrap(),
+        EdgeWeightDistribution::Uniform | EdgeWeightDistribution::Distance => {
+            T::from_usize(weight.len()).unwrap()
+        }
         EdgeWeightDistribution::Linear => weight.iter().cloned().sum(),
         EdgeWeightDistribution::Sqrt => {
             let sqrt_sum: f64 = weight
@@ -246,14 +289,21 @@ fn main() -> Result<()> {
 
     let weights = mesh_io::weight::read(weight_file).context("failed to read weight file")?;
 
-    let (adjacency, parts) = rayon::join(
+    let (mesh_and_adjacency, parts) = rayon::join(
         || -> Result<_> {
             let mesh = Mesh::from_reader(mesh_file).context("failed to read mesh file")?;
             let mut adjacency = coupe_tools::dual(&mesh);
             if edge_weights != EdgeWeightDistribution::Uniform {
-                set_edge_weights(&mut adjacency, &weights, edge_weights);
+                // Only distances are computed from the points, so 2D meshes
+                // can use 3D points, whose last coordinate is left to zero.
+                let points: Vec<PointND<3>> = if edge_weights == EdgeWeightDistribution::Distance {
+                    coupe_tools::barycentres(&mesh)
+                } else {
+                    Vec::new()
+                };
+                set_edge_weights(&mut adjacency, &weights, &points, edge_weights);
             }
-            Ok(adjacency)
+            Ok((mesh, adjacency))
         },
         || -> Result<_> {
             let parts = mesh_io::partition::read(partition_file)
@@ -273,11 +323,11 @@ fn main() -> Result<()> {
             for (i, stat) in stats.into_iter().enumerate() {
                 print!("Criterion #{i}:\n{stat}");
             }
-            Ok(parts)
+            Ok((part_count, parts))
         },
     );
-    let adjacency = adjacency?;
-    let parts = parts?;
+    let (mesh, adjacency) = mesh_and_adjacency?;
+    let (part_count, parts) = parts?;
 
     println!("Edge cut size: {}", adjacency.view().edge_cut(&parts));
     let lambda_cut: Box<dyn fmt::Display> = match &weights {
@@ -290,5 +340,13 @@ fn main() -> Result<()> {
     };
     println!("Lambda cut size: {}", lambda_cut);
 
+    let aspect_ratio =
+        coupe_tools::with_dimension!(mesh, D => mean_aspect_ratio::<D>(&mesh, part_count, &parts))
+            .unwrap_or(None);
+    match aspect_ratio {
+        Some(aspect_ratio) => println!("Mean aspect ratio: {aspect_ratio:.2}"),
+        None => println!("Mean aspect ratio: n/a"),
+    }
+
     Ok(())
 }
@@ -0,0 +1,138 @@
+use anyhow::Context as _;
+use anyhow::Result;
+use std::env;
+use std::fs;
+use std::io;
+
+const USAGE: &str = "Usage: split-part [options] [prefix]";
+
+/// The global indices of the elements of each part, in increasing order.
+fn split(partition: &[usize], part_count: usize) -> Vec<Vec<usize>> {
+    let mut parts = vec![Vec::new(); part_count];
+    for (index, part) in partition.iter().enumerate() {
+        parts[*part].push(index);
+    }
+    parts
+}
+
+fn write_indices<W>(mut w: W, indices: &[usize]) -> io::Result<()>
+where
+    W: io::Write,
+{
+    for index in indices {
+        writeln!(w, "{index}")?;
+    }
+    w.flush()
+}
+
+fn main() -> Result<()> {
+    let mut options = getopts::Options::new();
+    options.optflag("h", "help", "print this help menu");
+    options.optflag("", "version", "print version information");
+    options.optopt("n", "parts", "number of files to write", "COUNT");
+    options.optopt("p", "partition", "partition file", "FILE");
+
+    let matches = options.parse(env::args().skip(1))?;
+
+    if matches.opt_present("h") {
+        println!("{}", options.usage(USAGE));
+        return Ok(());
+    }
+    if matches.opt_present("version") {
+        println!("split-part version {}", env!("COUPE_VERSION"));
+        return Ok(());
+    }
+    if matches.free.len() > 1 {
+        anyhow::bail!("too many arguments\n\n{}", options.usage(USAGE));
+    }
+
+    let partition_file = matches
+        .opt_str("p")
+        .context("missing required option 'partition'")?;
+    let partition_file = fs::File::open(partition_file).context("failed to open partition file")?;
+    let partition_file = io::BufReader::new(partition_file);
+    let partition =
+        mesh_io::partition::read(partition_file).context("failed to read partition file")?;
+
+    let min_part_count = partition.iter().max().map_or(0, |max| max + 1);
+    let part_count: usize = matches
+        .opt_get("n")
+        .context("invalid value for option 'parts'")?
+        .unwrap_or(min_part_count);
+    if part_count < min_part_count {
+        anyhow::bail!("partition has at least {min_part_count} parts, got --parts {part_count}");
+    }
+
+    let prefix = matches.free.first().map_or("part", String::as_str);
+    for (part, indices) in split(&partition, part_count).iter().enumerate() {
+        let path = format!("{prefix}.{part}");
+        let file = fs::File::create(&path).with_context(|| format!("failed to create {path:?}"))?;
+        write_indices(io::BufWriter::new(file), indices)
+            .with_context(|| format!("failed to write {path:?}"))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_indices<R>(r: R) -> Result<Vec<usize>>
+    where
+        R: io::BufRead,
+    {
+        r.lines()
+            .enumerate()
+            .filter(|(_, line)| !matches!(line, Ok(line) if line.trim().is_empty()))
+            .map(|(i, line)| {
+                let line = line?;
+                line.trim()
+                    .parse()
+                    .with_context(|| format!("line {}: invalid index {line:?}", i + 1))
+            })
+            .collect()
+    }
+
+    /// The inverse of [`split`].
+    fn gather(parts: &[Vec<usize>]) -> Option<Vec<usize>> {
+        let len = parts.iter().map(Vec::len).sum();
+        let mut partition = vec![usize::MAX; len];
+        for (part, indices) in parts.iter().enumerate() {
+            for index in indices {
+                let slot = partition.get_mut(*index)?;
+                if *slot != usize::MAX {
+                    return None;
+                }
+                *slot = part;
+            }
+        }
+        Some(partition)
+    }
+
+    #[test]
+    fn test_split_gather() {
+        let partition = [2, 0, 0, 3, 2, 0, 3, 2];
+        let parts = split(&partition, 5);
+        assert_eq!(
+            parts,
+            [vec![1, 2, 5], vec![], vec![0, 4, 7], vec![3, 6], vec![]],
+        );
+
+        let parts: Vec<Vec<usize>> = parts
+            .iter()
+            .map(|indices| {
+                let mut file = Vec::new();
+                write_indices(&mut file, indices).unwrap();
+                read_indices(&file[..]).unwrap()
+            })
+            .collect();
+        assert_eq!(gather(&parts).unwrap(), partition);
+    }
+
+    #[test]
+    fn test_read_indices_invalid() {
+        assert!(read_indices(&b"1\n\n3\n"[..]).is_ok());
+        assert!(read_indices(&b"1\nfoo\n"[..]).is_err());
+    }
+}
@@ -223,11 +223,7 @@ fn main() -> Result<()> {
 
     let mesh = coupe_tools::read_mesh(matches.free.get(0))?;
 
-    match mesh.dimension() {
-        2 => weight_gen::<2>(mesh, distributions, matches),
-        3 => weight_gen::<3>(mesh, distributions, matches),
-        n => anyhow::bail!("expected 2D or 3D mesh, got a {n}D mesh"),
-    }
+    coupe_tools::with_dimension!(mesh, D => weight_gen::<D>(mesh, distributions, matches))?
 }
 
 #[cfg(test)]
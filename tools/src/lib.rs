@@ -21,11 +21,8 @@ use mesh_io::weight;
 use mesh_io::ElementType;
 use mesh_io::Mesh;
 use once_cell::sync::OnceCell;
-use rayon::iter::IndexedParallelIterator as _;
-use rayon::iter::IntoParallelIterator as _;
 use rayon::iter::IntoParallelRefIterator as _;
 use rayon::iter::ParallelIterator as _;
-use rayon::slice::ParallelSlice as _;
 use std::any;
 use std::fs::File;
 use std::io;
@@ -79,7 +76,12 @@ impl<const D: usize> Problem<D> {
             .get_or_init(|| {
                 let mut adjacency = dual(&self.mesh);
                 if self.edge_weights != EdgeWeightDistribution::Uniform {
-                    set_edge_weights(&mut adjacency, &self.weights, self.edge_weights);
+                    set_edge_weights(
+                        &mut adjacency,
+                        &self.weights,
+                        self.points(),
+                        self.edge_weights,
+                    );
                 }
                 adjacency
             })
@@ -115,6 +117,15 @@ where
     }
 }
 
+impl<const D: usize> ToRunner<D> for coupe::RoundRobin {
+    fn to_runner<'a>(&'a mut self, _: &'a Problem<D>) -> Runner<'a> {
+        Box::new(move |partition| {
+            self.partition(partition, ())?;
+            Ok(None)
+        })
+    }
+}
+
 impl<const D: usize> ToRunner<D> for coupe::Greedy {
     fn to_runner<'a>(&'a mut self, problem: &'a Problem<D>) -> Runner<'a> {
         use weight::Array::*;
@@ -233,6 +244,31 @@ impl<const D: usize> ToRunner<D> for coupe::Rcb {
     }
 }
 
+impl<const D: usize> ToRunner<D> for coupe::Rib
+where
+    Const<D>: DimSub<Const<1>> + ToTypenum,
+    DefaultAllocator: Allocator<f64, Const<D>, Const<D>, Buffer = ArrayStorage<f64, D, D>>
+        + Allocator<f64, DimDiff<Const<D>, Const<1>>>,
+{
+    fn to_runner<'a>(&'a mut self, problem: &'a Problem<D>) -> Runner<'a> {
+        use weight::Array::*;
+        Box::new(move |partition| {
+            let points = problem.points();
+            match &problem.weights {
+                Integers(is) => {
+                    let weights = is.par_iter().map(|weight| weight[0]);
+                    self.partition(partition, (points, weights))?;
+                }
+                Floats(fs) => {
+                    let weights = fs.par_iter().map(|weight| weight[0]);
+                    self.partition(partition, (points, weights))?;
+                }
+            }
+            Ok(None)
+        })
+    }
+}
+
 impl<const D: usize> ToRunner<D> for coupe::HilbertCurve {
     fn to_runner<'a>(&'a mut self, problem: &'a Problem<D>) -> Runner<'a> {
         use weight::Array::*;
@@ -270,6 +306,50 @@ impl<const D: usize> ToRunner<D> for coupe::HilbertCurve {
     }
 }
 
+impl<const D: usize> ToRunner<D> for coupe::PeanoCurve {
+    fn to_runner<'a>(&'a mut self, problem: &'a Problem<D>) -> Runner<'a> {
+        use weight::Array::*;
+        if D == 2 {
+            // SAFETY: is a noop since D == 2
+            let points =
+                unsafe { mem::transmute::<&[PointND<D>], &[PointND<2>]>(problem.points()) };
+            match &problem.weights {
+                Integers(_) => runner_error("peano is only implemented for floats"),
+                Floats(fs) => {
+                    let weights: Vec<f64> = fs.iter().map(|weight| weight[0]).collect();
+                    Box::new(move |partition| {
+                        self.partition(partition, (points, &weights))?;
+                        Ok(None)
+                    })
+                }
+            }
+        } else {
+            runner_error("peano is only implemented for 2D meshes")
+        }
+    }
+}
+
+impl<const D: usize> ToRunner<D> for coupe::ZCurve
+where
+    Const<D>: DimSub<Const<1>> + ToTypenum,
+    DefaultAllocator: Allocator<f64, Const<D>, Const<D>, Buffer = ArrayStorage<f64, D, D>>
+        + Allocator<f64, DimDiff<Const<D>, Const<1>>>,
+{
+    fn to_runner<'a>(&'a mut self, problem: &'a Problem<D>) -> Runner<'a> {
+        use weight::Array::*;
+        match &problem.weights {
+            Integers(_) => runner_error("zcurve is only implemented for floats"),
+            Floats(fs) => {
+                let weights: Vec<f64> = fs.iter().map(|weight| weight[0]).collect();
+                Box::new(move |partition| {
+                    self.partition(partition, (problem.points(), &weights))?;
+                    Ok(None)
+                })
+            }
+        }
+    }
+}
+
 impl<const D: usize> ToRunner<D> for coupe::KMeans
 where
     Const<D>: DimSub<Const<1>> + ToTypenum,
@@ -364,6 +444,67 @@ impl<const D: usize> ToRunner<D> for coupe::KernighanLin {
     }
 }
 
+impl<const D: usize> ToRunner<D> for coupe::SimulatedAnnealing {
+    fn to_runner<'a>(&'a mut self, problem: &'a Problem<D>) -> Runner<'a> {
+        use weight::Array::*;
+        let adjacency = {
+            let shape = problem.adjacency().shape();
+            let (indptr, indices, f64_data) = problem.adjacency().into_raw_storage();
+            let i64_data = f64_data.iter().map(|f| *f as i64).collect();
+            CsMat::new(shape, indptr.to_vec(), indices.to_vec(), i64_data)
+        };
+        match &problem.weights {
+            Integers(_) => runner_error("sa is only implemented for floats"),
+            Floats(fs) => {
+                let weights: Vec<f64> = fs.iter().map(|weight| weight[0]).collect();
+                Box::new(move |partition| {
+                    let metadata = self.partition(partition, (adjacency.view(), &weights))?;
+                    Ok(Some(Box::new(metadata)))
+                })
+            }
+        }
+    }
+}
+
+impl<const D: usize> ToRunner<D> for coupe::Diffusion {
+    fn to_runner<'a>(&'a mut self, problem: &'a Problem<D>) -> Runner<'a> {
+        use weight::Array::*;
+        let adjacency = {
+            let shape = problem.adjacency().shape();
+            let (indptr, indices, f64_data) = problem.adjacency().into_raw_storage();
+            let i64_data = f64_data.iter().map(|f| *f as i64).collect();
+            CsMat::new(shape, indptr.to_vec(), indices.to_vec(), i64_data)
+        };
+        match &problem.weights {
+            Integers(_) => runner_error("diffusion is only implemented for floats"),
+            Floats(fs) => {
+                let weights: Vec<f64> = fs.iter().map(|weight| weight[0]).collect();
+                Box::new(move |partition| {
+                    let metadata = self.partition(partition, (adjacency.view(), &weights))?;
+                    Ok(Some(Box::new(metadata)))
+                })
+            }
+        }
+    }
+}
+
+impl<const D: usize> ToRunner<D> for coupe::Repartition {
+    fn to_runner<'a>(&'a mut self, problem: &'a Problem<D>) -> Runner<'a> {
+        use weight::Array::*;
+        let points = problem.points();
+        match &problem.weights {
+            Integers(_) => runner_error("repartition is only implemented for floats"),
+            Floats(fs) => {
+                let weights: Vec<f64> = fs.iter().map(|weight| weight[0]).collect();
+                Box::new(move |partition| {
+                    let metadata = self.partition(partition, (points, &weights))?;
+                    Ok(Some(Box::new(metadata)))
+                })
+            }
+        }
+    }
+}
+
 pub fn parse_algorithm<const D: usize>(spec: &str) -> Result<Box<dyn ToRunner<D>>>
 where
     Const<D>: DimSub<Const<1>> + ToTypenum,
@@ -407,6 +548,9 @@ where
             let rng = rand_pcg::Pcg64::from_seed(seed);
             Box::new(coupe::Random { rng, part_count })
         }
+        "round-robin" => Box::new(coupe::RoundRobin {
+            part_count: require(parse(args.next()))?,
+        }),
         "greedy" => Box::new(coupe::Greedy {
             part_count: require(parse(args.next()))?,
         }),
@@ -421,12 +565,42 @@ where
         "rcb" => Box::new(coupe::Rcb {
             iter_count: require(parse(args.next()))?,
             tolerance: optional(parse(args.next()), 0.05)?,
+            ..Default::default()
+        }),
+        "rib" => Box::new(coupe::Rib {
+            iter_count: require(parse(args.next()))?,
+            tolerance: optional(parse(args.next()), 0.05)?,
+            inertia_levels: optional(parse(args.next()), 1)?,
+            ..Default::default()
         }),
         "hilbert" => Box::new(coupe::HilbertCurve {
             part_count: require(parse(args.next()))?,
             order: optional(parse(args.next()), 12)?,
+            tolerance: optional(parse(args.next()), 0.05)?,
+            ..Default::default()
+        }),
+        "peano" => Box::new(coupe::PeanoCurve {
+            part_count: require(parse(args.next()))?,
+            order: optional(parse(args.next()), 8)?,
+            tolerance: optional(parse(args.next()), 0.05)?,
+            ..Default::default()
+        }),
+        "zcurve" => Box::new(coupe::ZCurve {
+            part_count: require(parse(args.next()))?,
+            order: optional(parse(args.next()), 8)?,
+            ..Default::default()
         }),
-        "kmeans" => Box::<coupe::KMeans>::default(),
+        "kmeans" => Box::new(coupe::KMeans {
+            kd_tree: optional(parse(args.next()), false)?,
+            ..Default::default()
+        }),
+        "repartition" => {
+            let default = coupe::Repartition::default();
+            Box::new(coupe::Repartition {
+                tolerance: optional(parse(args.next()), default.tolerance)?,
+                max_iter: optional(parse(args.next()), default.max_iter)?,
+            })
+        }
         "arcswap" => {
             let max_imbalance = parse(args.next()).transpose()?;
             Box::new(coupe::ArcSwap { max_imbalance })
@@ -453,6 +627,22 @@ where
             max_bad_move_in_a_row: optional(parse(args.next()), 1)?,
             ..Default::default()
         }),
+        "sa" => {
+            let default = coupe::SimulatedAnnealing::default();
+            Box::new(coupe::SimulatedAnnealing {
+                initial_temp: optional(parse(args.next()), default.initial_temp)?,
+                cooling_rate: optional(parse(args.next()), default.cooling_rate)?,
+                max_iter: optional(parse(args.next()), default.max_iter)?,
+                ..default
+            })
+        }
+        "diffusion" => {
+            let default = coupe::Diffusion::default();
+            Box::new(coupe::Diffusion {
+                flow_tolerance: optional(parse(args.next()), default.flow_tolerance)?,
+                max_iter: optional(parse(args.next()), default.max_iter)?,
+            })
+        }
 
         #[cfg(feature = "metis")]
         "metis:recursive" => {
@@ -485,14 +675,44 @@ where
         }
 
         #[cfg(feature = "scotch")]
-        "scotch:std" => Box::new(scotch::Standard {
-            part_count: require(parse(args.next()))?,
-        }),
+        "scotch:std" => {
+            let part_count = require(parse(args.next()))?;
+            if part_count == 0 {
+                anyhow::bail!("SCOTCH needs at least one part");
+            }
+            Box::new(coupe::interop::scotch::ScotchStandard { part_count })
+        }
 
         _ => anyhow::bail!("unknown algorithm {:?}", name),
     })
 }
 
+/// Rewrites an algorithm spec so that the algorithm makes `part_count` parts.
+///
+/// Returns `None` for algorithms that do not take a part count, like
+/// partition improving ones.  RCB and RIB take a number of bisections instead,
+/// so `part_count` must be a power of two for them.
+pub fn set_part_count(spec: &str, part_count: usize) -> Result<Option<String>> {
+    let mut args: Vec<&str> = spec.split(',').collect();
+    let value = match args[0] {
+        "random" | "round-robin" | "greedy" | "kk" | "hilbert" | "peano" | "zcurve"
+        | "metis:recursive" | "metis:kway" | "scotch:std" => part_count.to_string(),
+        algorithm @ ("rcb" | "rib") => {
+            if !part_count.is_power_of_two() {
+                anyhow::bail!("{algorithm} can only make a power of two parts, got {part_count}");
+            }
+            part_count.trailing_zeros().to_string()
+        }
+        _ => return Ok(None),
+    };
+    if args.len() == 1 {
+        args.push(&value);
+    } else {
+        args[1] = &value;
+    }
+    Ok(Some(args.join(",")))
+}
+
 /// The number of elements that are taken into account for partitioning.
 pub fn used_element_count(mesh: &Mesh) -> usize {
     let element_dim = match mesh
@@ -521,24 +741,22 @@ pub fn barycentres<const D: usize>(mesh: &Mesh) -> Vec<PointND<D>> {
         Some(v) => v,
         None => return Vec::new(),
     };
-    mesh.elements()
-        .filter_map(|(element_type, nodes, _element_ref)| {
-            if element_type.dimension() != element_dim || element_type == ElementType::Edge {
-                return None;
-            }
-            let mut barycentre = [0.0; D];
-            for node_idx in nodes {
-                let node_coordinates = mesh.node(*node_idx);
-                for (bc_coord, node_coord) in barycentre.iter_mut().zip(node_coordinates) {
-                    *bc_coord += node_coord;
-                }
-            }
-            for bc_coord in &mut barycentre {
-                *bc_coord /= nodes.len() as f64;
+    let nodes: Vec<PointND<D>> = (0..mesh.node_count())
+        .map(|node_idx| {
+            let mut node = PointND::<D>::zeros();
+            for (coord, node_coord) in node.iter_mut().zip(mesh.node(node_idx)) {
+                *coord = *node_coord;
             }
-            Some(PointND::from(barycentre))
+            node
         })
-        .collect()
+        .collect();
+    let elements = mesh
+        .elements()
+        .filter(|(element_type, _nodes, _element_ref)| {
+            element_type.dimension() == element_dim && *element_type != ElementType::Edge
+        })
+        .map(|(_element_type, nodes, _element_ref)| nodes);
+    coupe::mesh::element_barycentres(&nodes, elements)
 }
 
 /// The adjacency matrix that models the dual graph of the given mesh.
@@ -552,119 +770,14 @@ pub fn dual(mesh: &Mesh) -> CsMat<f64> {
         Some(v) => v,
         None => return CsMat::empty(CSR, 0),
     };
-    let ignored_element = |el_type: ElementType| -> bool {
-        el_type.dimension() != dimension || el_type == ElementType::Edge
-    };
-
-    let elements = || {
-        mesh.elements()
-            .filter(|(el_type, _nodes, _ref)| !ignored_element(*el_type))
-            .map(|(_el_type, nodes, _ref)| nodes)
-            .enumerate()
-    };
-
-    // To speed up node lookup, we store topology information in a more
-    // compact array of element chunks.  Chunks store the nodes of elements
-    // of the same type, and their start offset.
-    struct ElementChunk<'a> {
-        start_idx: usize,
-        node_per_element: usize,
-        nodes: &'a [usize],
-    }
-    let topology: Vec<ElementChunk> = mesh
-        .topology()
-        .iter()
-        .filter(|(el_type, _nodes, _refs)| !ignored_element(*el_type))
-        .scan(0, |start_idx, (el_type, nodes, _refs)| {
-            let item = ElementChunk {
-                start_idx: *start_idx,
-                node_per_element: el_type.node_count(),
-                nodes,
-            };
-            *start_idx += nodes.len() / item.node_per_element;
-            Some(item)
+    let elements: Vec<&[usize]> = mesh
+        .elements()
+        .filter(|(el_type, _nodes, _ref)| {
+            el_type.dimension() == dimension && *el_type != ElementType::Edge
         })
+        .map(|(_el_type, nodes, _ref)| nodes)
         .collect();
-    let element_to_nodes = |e: usize| -> &[usize] {
-        for item in &topology {
-            let e = (e - item.start_idx) * item.node_per_element;
-            if e < item.nodes.len() {
-                return &item.nodes[e..e + item.node_per_element];
-            }
-        }
-        unreachable!();
-    };
-
-    let mut node_to_elements = vec![Vec::new(); mesh.node_count()];
-    for (e, nodes) in elements() {
-        for node in nodes {
-            let node_elements = &mut node_to_elements[*node];
-            if node_elements.is_empty() {
-                node_elements.reserve(8);
-            }
-            if let Err(idx) = node_elements.binary_search(&e) {
-                node_elements.insert(idx, e);
-            }
-        }
-    }
-
-    let el_count: usize = topology
-        .iter()
-        .map(|chunk| chunk.nodes.len() / chunk.node_per_element)
-        .sum();
-    let indice_locks = vec![Vec::new(); el_count];
-    topology.par_iter().for_each(|chunk| {
-        let end_idx = chunk.start_idx + chunk.nodes.len() / chunk.node_per_element;
-        chunk
-            .nodes
-            .par_chunks_exact(chunk.node_per_element)
-            .zip(chunk.start_idx..end_idx)
-            .for_each(|(e1_nodes, e1)| {
-                let mut neighbors: Vec<usize> = e1_nodes
-                    .iter()
-                    .flat_map(|node| &node_to_elements[*node])
-                    .cloned()
-                    .filter(|e2| {
-                        e1 != *e2 && {
-                            let e2_nodes = element_to_nodes(*e2);
-                            let nodes_in_common = e1_nodes
-                                .iter()
-                                .filter(|e1_node| e2_nodes.contains(e1_node))
-                                .count();
-                            dimension <= nodes_in_common
-                        }
-                    })
-                    .collect();
-                neighbors.sort_unstable();
-                neighbors.dedup();
-                let ptr = &indice_locks[e1] as *const Vec<usize> as *mut Vec<usize>;
-                unsafe { ptr.write(neighbors) }
-            })
-    });
-
-    let mut indptr: Vec<usize> = Some(0)
-        .into_par_iter()
-        .chain(indice_locks.par_iter().map(|neighbors| neighbors.len()))
-        .collect();
-    for i in 1..indptr.len() {
-        indptr[i] += indptr[i - 1];
-    }
-
-    let size = indptr.len() - 1;
-    let indices = vec![0; indptr[indptr.len() - 1]];
-    indptr
-        .par_iter()
-        .zip(&indptr[1..])
-        .zip(indice_locks)
-        .for_each(|((start, end), neighbors)| {
-            let src = neighbors.as_ptr();
-            let dst = indices[*start..*end].as_ptr() as *mut usize;
-            unsafe { std::ptr::copy_nonoverlapping(src, dst, end - start) }
-        });
-
-    let data = vec![1.0; indices.len()];
-
-    CsMat::new((size, size), indptr, indices, data)
+    coupe::mesh::dual(mesh.node_count(), &elements, dimension)
 }
 
 #[derive(Copy, Clone, PartialEq, Eq)]
@@ -672,6 +785,8 @@ pub enum EdgeWeightDistribution {
     Uniform,
     Linear,
     Sqrt,
+    /// Edge weights are the distance between the barycentres of the elements.
+    Distance,
 }
 
 #[derive(Debug)]
@@ -679,7 +794,7 @@ pub struct EdgeWeightDistError;
 
 impl std::fmt::Display for EdgeWeightDistError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "expected 'uniform', 'linear' or 'sqrt'")
+        write!(f, "expected 'uniform', 'linear', 'sqrt' or 'distance'")
     }
 }
 impl std::error::Error for EdgeWeightDistError {}
@@ -692,14 +807,20 @@ impl std::str::FromStr for EdgeWeightDistribution {
             "uniform" => EdgeWeightDistribution::Uniform,
             "linear" => EdgeWeightDistribution::Linear,
             "sqrt" => EdgeWeightDistribution::Sqrt,
+            "distance" => EdgeWeightDistribution::Distance,
             _ => return Err(EdgeWeightDistError),
         })
     }
 }
 
-pub fn set_edge_weights(
+/// Set the weights of the edges of `adjacency` according to `distribution`.
+///
+/// `points` is only used by [EdgeWeightDistribution::Distance], and must then
+/// hold the barycentres of the elements, as returned by [barycentres].
+pub fn set_edge_weights<const D: usize>(
     adjacency: &mut CsMat<f64>,
     vertex_weights: &weight::Array,
+    points: &[PointND<D>],
     distribution: EdgeWeightDistribution,
 ) {
     let vertex_weights = |vertex: usize| match vertex_weights {
@@ -714,6 +835,7 @@ pub fn set_edge_weights(
                 EdgeWeightDistribution::Uniform => 1.0,
                 EdgeWeightDistribution::Linear => node_weight + neighbor_weight,
                 EdgeWeightDistribution::Sqrt => node_weight.sqrt() + neighbor_weight.sqrt(),
+                EdgeWeightDistribution::Distance => (points[node] - points[neighbor]).norm(),
             };
         }
     }
@@ -724,6 +846,7 @@ pub enum MeshFormat {
     MeditBinary,
     VtkAscii,
     VtkBinary,
+    Vtu,
 }
 
 #[derive(Debug)]
@@ -731,7 +854,10 @@ pub struct MeshFormatError;
 
 impl std::fmt::Display for MeshFormatError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "expected 'mesh', 'meshb', 'vtk-ascii' or 'vtk-binary'")
+        write!(
+            f,
+            "expected 'mesh', 'meshb', 'vtk-ascii', 'vtk-binary' or 'vtu'"
+        )
     }
 }
 impl std::error::Error for MeshFormatError {}
@@ -745,11 +871,45 @@ impl std::str::FromStr for MeshFormat {
             "meshb" => Self::MeditBinary,
             "vtk-ascii" => Self::VtkAscii,
             "vtk-binary" => Self::VtkBinary,
+            "vtu" => Self::Vtu,
             _ => return Err(MeshFormatError),
         })
     }
 }
 
+/// Evaluates an expression with the dimension of the given mesh as a const
+/// generic parameter.
+///
+/// The expression's value is wrapped in `Ok`, and an error is returned instead
+/// for dimensions the tools do not support.
+///
+/// # Example
+///
+/// ```ignore
+/// let partition = coupe_tools::with_dimension!(mesh, D => main_d::<D>(mesh))??;
+/// ```
+#[macro_export]
+macro_rules! with_dimension {
+    ($mesh:expr, $d:ident => $body:expr) => {
+        match $mesh.dimension() {
+            2 => {
+                const $d: usize = 2;
+                Ok($body)
+            }
+            3 => {
+                const $d: usize = 3;
+                Ok($body)
+            }
+            n => Err($crate::unsupported_dimension(n)),
+        }
+    };
+}
+
+#[doc(hidden)]
+pub fn unsupported_dimension(dimension: usize) -> anyhow::Error {
+    anyhow::anyhow!("expected 2D or 3D mesh, got a {dimension}D mesh")
+}
+
 /// Helper to read a mesh either from stdin or from a file.
 pub fn read_mesh(filename: Option<&String>) -> Result<Mesh> {
     Ok(match filename.cloned().as_deref() {
@@ -785,6 +945,17 @@ pub fn write_mesh(
     mesh: &Mesh,
     format: Option<MeshFormat>,
     filename: Option<&String>,
+) -> Result<()> {
+    write_mesh_with_refs_name(mesh, format, filename, "cell_refs")
+}
+
+/// Same as [write_mesh], but element references are named `refs_name` in
+/// formats that support it.
+pub fn write_mesh_with_refs_name(
+    mesh: &Mesh,
+    format: Option<MeshFormat>,
+    filename: Option<&String>,
+    refs_name: &str,
 ) -> Result<()> {
     use std::io::Write;
 
@@ -800,6 +971,8 @@ pub fn write_mesh(
                 Some(MeshFormat::MeditBinary)
             } else if extension.eq_ignore_ascii_case("vtk") {
                 Some(MeshFormat::VtkAscii)
+            } else if extension.eq_ignore_ascii_case("vtu") {
+                Some(MeshFormat::Vtu)
             } else {
                 None
             }
@@ -810,6 +983,7 @@ pub fn write_mesh(
         MeshFormat::MeditBinary => mesh.serialize_medit_binary(w)?,
         MeshFormat::VtkAscii => writeln!(w, "{}", mesh.display_vtk_ascii())?,
         MeshFormat::VtkBinary => mesh.serialize_vtk_binary(w)?,
+        MeshFormat::Vtu => writeln!(w, "{}", mesh.display_vtu_ascii().cell_refs_name(refs_name))?,
     }
     Ok(())
 }
@@ -851,3 +1025,97 @@ where
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distance_edge_weights() {
+        // Three unit squares in a row, the last one being twice as wide:
+        //
+        //     +---+---+-------+
+        //     | 0 | 1 |   2   |
+        //     +---+---+-------+
+        //
+        let coordinates = vec![
+            0.0, 0.0, 1.0, 0.0, 2.0, 0.0, 4.0, 0.0, //
+            0.0, 1.0, 1.0, 1.0, 2.0, 1.0, 4.0, 1.0,
+        ];
+        let mesh = Mesh::from_raw_parts(
+            2,
+            coordinates,
+            vec![0; 8],
+            vec![(
+                ElementType::Quadrangle,
+                vec![0, 1, 5, 4, 1, 2, 6, 5, 2, 3, 7, 6],
+                vec![0; 3],
+            )],
+        );
+        let weights = weight::Array::Floats(vec![vec![1.0]; 3]);
+        let points = barycentres::<2>(&mesh);
+        let mut adjacency = dual(&mesh);
+
+        set_edge_weights(
+            &mut adjacency,
+            &weights,
+            &points,
+            EdgeWeightDistribution::Distance,
+        );
+
+        assert_eq!(adjacency.get(0, 1), Some(&1.0));
+        assert_eq!(adjacency.get(1, 2), Some(&1.5));
+        assert_eq!(adjacency.get(2, 1), Some(&1.5));
+        assert_eq!(adjacency.get(0, 2), None);
+    }
+
+    #[test]
+    fn test_set_part_count() {
+        assert_eq!(
+            set_part_count("hilbert,4,10", 16).unwrap().as_deref(),
+            Some("hilbert,16,10"),
+        );
+        assert_eq!(
+            set_part_count("greedy", 3).unwrap().as_deref(),
+            Some("greedy,3"),
+        );
+        assert_eq!(
+            set_part_count("rcb,1,0.1", 8).unwrap().as_deref(),
+            Some("rcb,3,0.1"),
+        );
+        assert!(set_part_count("rcb,1", 6).is_err());
+        assert_eq!(
+            set_part_count("rib", 4).unwrap().as_deref(),
+            Some("rib,2"),
+        );
+        assert_eq!(set_part_count("fm", 4).unwrap(), None);
+    }
+
+    #[test]
+    fn test_with_dimension() {
+        let square = Mesh::from_raw_parts(
+            2,
+            vec![0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0, 1.0],
+            vec![0; 4],
+            vec![(ElementType::Quadrangle, vec![0, 1, 2, 3], vec![0])],
+        );
+        let tetrahedron = Mesh::from_raw_parts(
+            3,
+            vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0],
+            vec![0; 4],
+            vec![(ElementType::Tetrahedron, vec![0, 1, 2, 3], vec![0])],
+        );
+        let segment = Mesh::from_raw_parts(
+            1,
+            vec![0.0, 1.0],
+            vec![0; 2],
+            vec![(ElementType::Edge, vec![0, 1], vec![0])],
+        );
+
+        let dimension = with_dimension!(square, D => barycentres::<D>(&square)[0].len());
+        assert_eq!(dimension.unwrap(), 2);
+        let dimension = with_dimension!(tetrahedron, D => barycentres::<D>(&tetrahedron)[0].len());
+        assert_eq!(dimension.unwrap(), 3);
+        assert!(with_dimension!(segment, D => D).is_err());
+    }
+}
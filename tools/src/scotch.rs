@@ -1,19 +1,14 @@
 use super::runner_error;
 use super::Problem;
 use super::ToRunner;
-use anyhow::Context as _;
+use coupe::interop::scotch::Num;
+use coupe::interop::scotch::ScotchStandard;
+use coupe::Partition as _;
 use mesh_io::weight;
-use scotch::graph::Data;
-use scotch::Graph;
-use scotch::Num;
 
-pub struct Standard {
-    pub part_count: Num,
-}
-
-impl<const D: usize> ToRunner<D> for Standard {
+impl<const D: usize> ToRunner<D> for ScotchStandard {
     fn to_runner<'a>(&'a mut self, problem: &'a Problem<D>) -> super::Runner<'a> {
-        let weights = match &problem.weights {
+        let weights: Vec<Num> = match &problem.weights {
             weight::Array::Integers(is) => {
                 if is.first().map_or(1, Vec::len) != 1 {
                     return runner_error("SCOTCH cannot do multi-criteria partitioning");
@@ -27,27 +22,10 @@ impl<const D: usize> ToRunner<D> for Standard {
                 crate::zoom_in(fs.iter().map(|v| Some(v[0])))
             }
         };
+        let adjacency = problem.adjacency();
 
-        let (xadj, adjncy, adjwgt) = problem.adjacency().into_raw_storage();
-        let xadj: Vec<_> = xadj.iter().map(|i| *i as Num).collect();
-        let adjncy: Vec<_> = adjncy.iter().map(|i| *i as Num).collect();
-        let adjwgt = crate::zoom_in(adjwgt.iter().map(|v| Some(*v)));
-
-        let mut strat = scotch::Strategy::new();
-        let arch = scotch::Architecture::complete(self.part_count as Num);
-
-        let mut scotch_partition = vec![0; weights.len()];
         Box::new(move |partition| {
-            let graph_data = Data::new(0, &xadj, &[], &weights, &[], &adjncy, &adjwgt);
-            let mut graph = Graph::build(&graph_data).context("failed to build SCOTCH graph")?;
-            graph.check().context("failed to build SCOTCH graph")?;
-            graph
-                .mapping(&arch, &mut scotch_partition)
-                .compute(&mut strat)
-                .context("SCOTCH partitioning failed")?;
-            for (dst, src) in partition.iter_mut().zip(&scotch_partition) {
-                *dst = *src as usize;
-            }
+            self.partition(partition, (adjacency, &weights))?;
             Ok(None)
         })
     }